@@ -0,0 +1,90 @@
+//! Adds a user defined technique to the ones a logical solve may use, and prints every time it
+//! fires. The technique takes part in hints, walkthroughs and grading like the built-in ones.
+
+use sudoku_solver::{
+    board::{cell_name, Board, UnitKind},
+    technique::{CandidatesGrid, LogicalOutcome, TechniqueMove, TechniqueSet},
+};
+
+/// Name the technique's moves are reported under
+const ROW_HIDDEN_SINGLE: &str = "Row hidden single";
+
+/// Places a digit that fits in only one cell of a row, the row only version of a hidden single
+fn find_row_hidden_singles(grid: &CandidatesGrid) -> Vec<TechniqueMove> {
+    let mut moves = Vec::new();
+    for row in 0..9 {
+        for value in 1..=9 {
+            let mut cells = (0..9).filter(|&col| {
+                grid.get(row, col)
+                    .is_some_and(|candidates| candidates.contains(value))
+            });
+            let (Some(col), None) = (cells.next(), cells.next()) else {
+                continue;
+            };
+            moves.push(TechniqueMove {
+                technique: ROW_HIDDEN_SINGLE,
+                position: (row, col),
+                value,
+                unit: Some((UnitKind::Row, row)),
+                reason: format!(
+                    "{value} can only go in {} within row {}",
+                    cell_name((row, col)),
+                    row + 1
+                ),
+                eliminations: Vec::new(),
+            });
+        }
+    }
+    moves
+}
+
+fn main() {
+    let puzzle: Board =
+        "000000000106000050450000610030006140090005308000020000003004009000810002004690080"
+            .parse()
+            .unwrap();
+    let naked_singles = TechniqueSet::up_to("Naked single").unwrap();
+    let techniques = naked_singles
+        .clone()
+        .with_placement_technique((ROW_HIDDEN_SINGLE, find_row_hidden_singles));
+
+    let mut board = puzzle.clone();
+    let outcome = board.solve_logical(&techniques);
+    let fired: Vec<&TechniqueMove> = outcome
+        .moves()
+        .iter()
+        .filter(|found| found.technique == ROW_HIDDEN_SINGLE)
+        .collect();
+    for found in &fired {
+        println!("{ROW_HIDDEN_SINGLE}: {found}");
+    }
+    let hint = puzzle
+        .next_hint_with(&techniques)
+        .expect("the technique has a move to make");
+    println!("First hint: {hint}");
+    let grade = puzzle.grade_with(&techniques);
+    println!(
+        "Grade: {}, hardest technique: {}",
+        grade.difficulty,
+        grade.hardest_technique.unwrap_or("none")
+    );
+
+    // Naked singles alone cannot place a single value on this puzzle
+    let without = puzzle.clone().solve_logical(&naked_singles);
+    assert!(without.moves().is_empty());
+    assert!(
+        matches!(outcome, LogicalOutcome::Solved(_)),
+        "the solve got stuck"
+    );
+    assert!(
+        board.validate_board(),
+        "the techniques produced an invalid board"
+    );
+    assert!(
+        fired.len() > 1,
+        "the technique only fired {} times",
+        fired.len()
+    );
+    assert_eq!(hint.technique, ROW_HIDDEN_SINGLE);
+    assert_eq!(grade.hardest_technique, Some(ROW_HIDDEN_SINGLE));
+}
@@ -0,0 +1,36 @@
+//! Generates a batch of puzzles from a single seed and rates each one by the techniques it needs.
+
+use sudoku_solver::generator::Generator;
+
+fn main() {
+    let puzzles = Generator::new(2024)
+        .with_hint_range(24..=32)
+        .take(10)
+        .map(|generated| generated.graded())
+        .collect::<Vec<_>>();
+
+    for (i, generated) in puzzles.iter().enumerate() {
+        let hints = generated.puzzle.count_filled();
+        println!("{i}: {generated}");
+        println!("   {} hints: {hints}", generated.puzzle);
+        assert_eq!(generated.clue_count, hints);
+        assert!((24..=32).contains(&hints), "puzzle {i} has {hints} hints");
+        assert!(generated.solution.validate_board());
+        assert_eq!(
+            generated.difficulty,
+            Some(generated.puzzle.grade().difficulty),
+            "puzzle {i} was not rated"
+        );
+    }
+
+    assert_eq!(puzzles.len(), 10);
+    assert_eq!(
+        puzzles,
+        Generator::new(2024)
+            .with_hint_range(24..=32)
+            .take(10)
+            .map(|generated| generated.graded())
+            .collect::<Vec<_>>(),
+        "generation is not reproducible from its seed"
+    );
+}
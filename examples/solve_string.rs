@@ -0,0 +1,31 @@
+//! Parses a puzzle from its 81 digit string form, solves it without any terminal output and
+//! prints the solution in the same form.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use sudoku_solver::{board::Board, observer::NoopObserver};
+
+fn main() {
+    let puzzle =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+
+    let mut board = Board::new(puzzle.to_string());
+    board
+        .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut NoopObserver)
+        .expect("the solver failed to complete the board");
+
+    let solution = board.to_string();
+    println!("{solution}");
+
+    assert!(
+        board.validate_board(),
+        "the solution breaks the sudoku rules"
+    );
+    assert!(
+        puzzle
+            .chars()
+            .zip(solution.chars())
+            .all(|(given, solved)| given == '0' || given == solved),
+        "the solver overwrote a given"
+    );
+}
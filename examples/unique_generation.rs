@@ -0,0 +1,48 @@
+//! Digs puzzles with a unique solution out of generated boards, checking uniqueness after every
+//! removal, and compares how many puzzles a second that makes with one thread and with several.
+
+use std::time::Instant;
+
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use sudoku_solver::{generate_board, Board};
+
+/// Removes cells in a random order, putting each one back when the puzzle stops being unique
+fn dig_unique(seed: u64, threads: usize) -> Board {
+    let mut board: Board = generate_board(seed).parse().unwrap();
+    let mut cells: Vec<(usize, usize)> = (0..81).map(|i| (i / 9, i % 9)).collect();
+    cells.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+    for (row, col) in cells {
+        let value = board.get_cell(row, col);
+        board.set_cell(row, col, 0);
+        if board.count_solutions_with_threads(2, threads) != 1 {
+            board.set_cell(row, col, value);
+        }
+    }
+    board
+}
+
+fn main() {
+    let seeds = 0..20;
+    let available = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let mut baseline = Vec::new();
+    for threads in [1, 2, available.max(4)] {
+        let start_time = Instant::now();
+        let puzzles: Vec<Board> = seeds
+            .clone()
+            .map(|seed| dig_unique(seed, threads))
+            .collect();
+        let elapsed = start_time.elapsed();
+
+        if threads == 1 {
+            baseline = puzzles.clone();
+        }
+        assert_eq!(puzzles, baseline, "threads changed the puzzles dug out");
+        let clues: usize = puzzles.iter().map(Board::count_filled).sum();
+        println!(
+            "{threads} threads: {:.1} puzzles/s, {:.1} clues on average",
+            puzzles.len() as f64 / elapsed.as_secs_f64(),
+            clues as f64 / puzzles.len() as f64
+        );
+    }
+}
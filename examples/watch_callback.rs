@@ -0,0 +1,58 @@
+//! Implements the solve observer to print a line for every value the solver places.
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use sudoku_solver::{
+    board::{cell_name, Board, BoardMove},
+    observer::SolveObserver,
+};
+
+/// Prints each placement and counts how many there were, along with the dead ends
+#[derive(Default)]
+struct StepPrinter {
+    steps: usize,
+    backtracks: usize,
+}
+
+impl SolveObserver for StepPrinter {
+    fn on_place(&mut self, _board: &Board, position: (usize, usize), value: u8) {
+        self.steps += 1;
+        println!(
+            "step {}: placed {value} at {}",
+            self.steps,
+            cell_name(position)
+        );
+    }
+
+    fn on_cascade(&mut self, board: &Board, position: (usize, usize), value: u8) {
+        self.on_place(board, position, value);
+    }
+
+    fn on_dead_end(&mut self, _board: &Board, position: (usize, usize)) {
+        println!("dead end at {}", cell_name(position));
+    }
+
+    fn on_backtrack(&mut self, _board: &Board, undone: &BoardMove) {
+        self.backtracks += 1;
+        println!("backtracking over {undone}");
+    }
+}
+
+fn main() {
+    let puzzle =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+    let empty_cells = puzzle.chars().filter(|cell| *cell == '0').count();
+
+    let mut board = Board::new(puzzle.to_string());
+    let mut printer = StepPrinter::default();
+    board
+        .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut printer)
+        .expect("the solver failed to complete the board");
+
+    println!("{} steps, {} backtracks", printer.steps, printer.backtracks);
+    assert!(
+        board.validate_board(),
+        "the solution breaks the sudoku rules"
+    );
+    assert!(printer.steps >= empty_cells);
+}
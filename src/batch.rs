@@ -0,0 +1,616 @@
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    sync::{mpsc, Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+use crate::{
+    board::{Board, SolveError},
+    observer::NoopObserver,
+    report::{SolveLimits, SolveReport},
+    solver::{SudokuSolver, WaveFunctionCollapse},
+    verify::{verify_solution, VerificationFailure},
+};
+
+/// Longest a single puzzle of a batch may take before it is given up on, so one pathological
+/// puzzle cannot hold a worker for the rest of the batch
+pub const PUZZLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// How a single puzzle of a batch went
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchEntry {
+    /// Seed the puzzle's solver was given, which is its position in the batch
+    pub seed: u64,
+    /// The board as the solve left it, filled in when it succeeded
+    pub board: Board,
+    pub result: Result<SolveReport, SolveError>,
+    /// Time spent on this puzzle alone, whatever the result
+    pub elapsed: Duration,
+    /// The puzzle and how its solution fared against [`verify_solution`], when the batch was
+    /// verified and the solve succeeded
+    pub verification: Option<(Board, Result<(), VerificationFailure>)>,
+}
+
+/// The outcome of [`solve_batch_parallel`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BatchReport {
+    /// One entry per puzzle, in the order the puzzles were given
+    pub entries: Vec<BatchEntry>,
+    pub workers: usize,
+    /// Time the whole batch took from start to finish
+    pub wall_time: Duration,
+}
+
+impl BatchEntry {
+    /// Backtracks the solve went through, whether or not it succeeded. `None` when it failed
+    /// before searching
+    pub fn backtracks(&self) -> Option<u64> {
+        match &self.result {
+            Ok(report) => Some(report.backtracks),
+            Err(SolveError::Exhausted { backtracks }) => Some(*backtracks),
+            Err(SolveError::LimitReached { report, .. } | SolveError::Cancelled { report }) => {
+                Some(report.backtracks)
+            }
+            Err(_) => None,
+        }
+    }
+
+    /// Whether the solve succeeded and the solution was then found to be wrong
+    pub fn failed_verification(&self) -> bool {
+        matches!(self.verification, Some((_, Err(_))))
+    }
+}
+
+/// Bucket of the backtrack histogram a count falls into. Bucket 0 holds solves without any
+/// backtrack and bucket `n` the counts from `2^(n - 1)` up to `2^n - 1`
+pub fn backtrack_bucket(backtracks: u64) -> usize {
+    (u64::BITS - backtracks.leading_zeros()) as usize
+}
+
+/// The range of backtrack counts in a bucket, see [`backtrack_bucket`]
+pub fn backtrack_bucket_label(bucket: usize) -> String {
+    match bucket {
+        0 => "0".to_string(),
+        1 => "1".to_string(),
+        bucket => format!("{}-{}", 1u64 << (bucket - 1), (1u64 << bucket) - 1),
+    }
+}
+
+impl BatchReport {
+    /// Time spent solving, summed over every puzzle of the batch
+    pub fn cpu_time(&self) -> Duration {
+        self.entries.iter().map(|entry| entry.elapsed).sum()
+    }
+
+    /// How many times faster the batch went than solving its puzzles one after the other would
+    pub fn speedup(&self) -> f64 {
+        self.cpu_time().as_secs_f64() / self.wall_time.as_secs_f64().max(f64::EPSILON)
+    }
+
+    pub fn solved(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.result.is_ok())
+            .count()
+    }
+
+    /// Solutions checked with [`verify_solution`]
+    pub fn verified(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.verification.is_some())
+            .count()
+    }
+
+    /// Solutions [`verify_solution`] found to be wrong
+    pub fn verification_failures(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| entry.failed_verification())
+            .count()
+    }
+
+    /// Puzzles given up on because they took longer than their timeout
+    pub fn timeouts(&self) -> usize {
+        self.entries
+            .iter()
+            .filter(|entry| matches!(entry.result, Err(SolveError::LimitReached { .. })))
+            .count()
+    }
+
+    /// Time within which the given percentage of the puzzles were done, whatever their result.
+    /// Uses the nearest rank, so the 100th percentile is the slowest puzzle
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        let mut times: Vec<Duration> = self.entries.iter().map(|entry| entry.elapsed).collect();
+        times.sort_unstable();
+        let rank = (percentile / 100.0 * times.len() as f64).ceil() as usize;
+        times
+            .get(rank.clamp(1, times.len().max(1)) - 1)
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Number of solved puzzles in each bucket of backtrack counts, see [`backtrack_bucket`].
+    /// Runs up to the highest bucket any puzzle fell into
+    pub fn backtrack_histogram(&self) -> Vec<usize> {
+        let mut histogram = Vec::new();
+        for entry in &self.entries {
+            let Ok(report) = &entry.result else {
+                continue;
+            };
+            let bucket = backtrack_bucket(report.backtracks);
+            if histogram.len() <= bucket {
+                histogram.resize(bucket + 1, 0);
+            }
+            histogram[bucket] += 1;
+        }
+        histogram
+    }
+
+    /// Number of failed puzzles for each kind of [`SolveError`], sorted by kind
+    pub fn failures_by_kind(&self) -> Vec<(&'static str, usize)> {
+        let mut failures = BTreeMap::new();
+        for entry in &self.entries {
+            if let Err(error) = &entry.result {
+                *failures.entry(error.kind()).or_insert(0) += 1;
+            }
+        }
+        failures.into_iter().collect()
+    }
+
+    /// Compact table of the timing percentiles, the backtrack histogram and the failures, and of
+    /// the verifications if there were any
+    pub fn statistics_table(&self) -> String {
+        let mut table = format!(
+            "{:<12} p50 {}us, p90 {}us, p99 {}us\n",
+            "time",
+            self.percentile(50.0).as_micros(),
+            self.percentile(90.0).as_micros(),
+            self.percentile(99.0).as_micros()
+        );
+        let histogram: Vec<String> = self
+            .backtrack_histogram()
+            .iter()
+            .enumerate()
+            .filter(|(_, count)| **count > 0)
+            .map(|(bucket, count)| format!("{}: {count}", backtrack_bucket_label(bucket)))
+            .collect();
+        table += &format!("{:<12} {}\n", "backtracks", list_or_none(&histogram));
+        let failures: Vec<String> = self
+            .failures_by_kind()
+            .iter()
+            .map(|(kind, count)| format!("{kind}: {count}"))
+            .collect();
+        table += &format!("{:<12} {}\n", "failures", list_or_none(&failures));
+        if self.verified() > 0 {
+            table += &format!(
+                "{:<12} {} solutions, {} wrong\n",
+                "verified",
+                self.verified(),
+                self.verification_failures()
+            );
+        }
+        table
+    }
+
+    /// Lines a benchmark of one solver is summed up in: how many puzzles it completed, the
+    /// [`BatchReport::statistics_table`] and its backtracks
+    pub fn benchmark_table(&self) -> String {
+        let (solved, count) = (self.solved(), self.entries.len());
+        let backtracks: Vec<u64> = self
+            .entries
+            .iter()
+            .filter_map(BatchEntry::backtracks)
+            .collect();
+        format!(
+            "{:<12} {solved}/{count} ({:.1}%)\n{}{:<12} mean {:.1}, max {}\n",
+            "completion",
+            solved as f64 * 100.0 / count.max(1) as f64,
+            self.statistics_table(),
+            "backtracking",
+            backtracks.iter().sum::<u64>() as f64 / backtracks.len().max(1) as f64,
+            backtracks.iter().max().copied().unwrap_or(0)
+        )
+    }
+
+    /// Header of the table comparing solvers, see [`BatchReport::comparison_row`]
+    pub fn comparison_header() -> String {
+        format!(
+            "{:<12} {:>9} {:>8} {:>6} {:>10} {:>10} {:>10} {:>10} {:>10} {:>8}",
+            "solver",
+            "solved",
+            "timeouts",
+            "failed",
+            "p50",
+            "p90",
+            "p99",
+            "placements",
+            "backtracks",
+            "caught"
+        )
+    }
+
+    /// The row of the solver called `name` in the table comparing solvers. Placements,
+    /// backtracks and dead ends caught by propagation are averaged over the solved puzzles
+    pub fn comparison_row(&self, name: &str) -> String {
+        let solved: Vec<&SolveReport> = self
+            .entries
+            .iter()
+            .filter_map(|entry| entry.result.as_ref().ok())
+            .collect();
+        let mean = |total: f64| total / solved.len().max(1) as f64;
+        let time = |percentile| format!("{}us", self.percentile(percentile).as_micros());
+        format!(
+            "{:<12} {:>9} {:>8} {:>6} {:>10} {:>10} {:>10} {:>10.1} {:>10.1} {:>8.1}",
+            name,
+            format!("{}/{}", solved.len(), self.entries.len()),
+            self.timeouts(),
+            self.entries.len() - solved.len() - self.timeouts(),
+            time(50.0),
+            time(90.0),
+            time(99.0),
+            mean(solved.iter().map(|report| report.placements as f64).sum()),
+            mean(solved.iter().map(|report| report.backtracks as f64).sum()),
+            mean(
+                solved
+                    .iter()
+                    .map(|report| report.propagation_failures as f64)
+                    .sum()
+            )
+        )
+    }
+
+    /// Writes one CSV row per puzzle, after a header, with its seed, clues, time in microseconds,
+    /// backtracks and either `solved` or the kind of error it failed with
+    pub fn write_csv<W: Write>(&self, out: &mut W) -> std::io::Result<()> {
+        writeln!(out, "seed,clues,time_us,backtracks,result")?;
+        for entry in &self.entries {
+            let backtracks = entry
+                .backtracks()
+                .map_or(String::new(), |backtracks| backtracks.to_string());
+            let result = match &entry.result {
+                Ok(_) => "solved",
+                Err(error) => error.kind(),
+            };
+            writeln!(
+                out,
+                "{},{},{},{backtracks},{result}",
+                entry.seed,
+                entry.board.clue_count(),
+                entry.elapsed.as_micros()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for BatchReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let solved = self.solved();
+        let timeouts = self.timeouts();
+        write!(
+            f,
+            "Solved {solved}/{} puzzles ({timeouts} timeouts, {} failed) with {} workers in {}ms, \
+             {}ms of solving time, a {:.2}x speedup",
+            self.entries.len(),
+            self.entries.len() - solved - timeouts,
+            self.workers,
+            self.wall_time.as_millis(),
+            self.cpu_time().as_millis(),
+            self.speedup()
+        )
+    }
+}
+
+/// Joins the items of a table line, which reads `none` when there are none
+fn list_or_none(items: &[String]) -> String {
+    if items.is_empty() {
+        "none".to_string()
+    } else {
+        items.join(", ")
+    }
+}
+
+/// Solves every puzzle with the entropy based solver on `workers` threads, each giving up after
+/// [`PUZZLE_TIMEOUT`]
+pub fn solve_batch_parallel(puzzles: Vec<Board>, workers: usize) -> BatchReport {
+    solve_batch_parallel_with_limits(
+        puzzles,
+        workers,
+        &SolveLimits::with_timeout(PUZZLE_TIMEOUT),
+        entropy_solver,
+    )
+}
+
+/// The entropy based solver seeded with `seed`, the one batches are solved with unless told
+/// otherwise
+pub fn entropy_solver(seed: u64) -> Box<dyn SudokuSolver> {
+    Box::new(WaveFunctionCollapse::new(seed))
+}
+
+/// Solves every puzzle on `workers` threads, which take the next puzzle from a shared queue
+/// whenever they finish one. The limits apply to each puzzle on its own. Each puzzle gets a
+/// solver from `new_solver` seeded by its position in the batch, so the results do not depend
+/// on the number of workers or on which one picked the puzzle up
+pub fn solve_batch_parallel_with_limits(
+    puzzles: Vec<Board>,
+    workers: usize,
+    limits: &SolveLimits,
+    new_solver: impl Fn(u64) -> Box<dyn SudokuSolver> + Sync,
+) -> BatchReport {
+    let start_time = Instant::now();
+    let count = puzzles.len();
+    let queue = Mutex::new(puzzles.into_iter().enumerate());
+    let workers = workers.clamp(1, count.max(1));
+
+    let mut solved: Vec<(usize, BatchEntry)> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..workers)
+            .map(|_| {
+                scope.spawn(|| {
+                    let mut entries = Vec::new();
+                    loop {
+                        let next = queue.lock().unwrap().next();
+                        let Some((index, mut board)) = next else {
+                            return entries;
+                        };
+                        let mut solver = new_solver(index as u64);
+                        let solve_start = Instant::now();
+                        let result =
+                            solver.solve_with_limits(&mut board, &mut NoopObserver, limits);
+                        let elapsed = solve_start.elapsed();
+                        entries.push((
+                            index,
+                            BatchEntry {
+                                seed: index as u64,
+                                board,
+                                result,
+                                elapsed,
+                                verification: None,
+                            },
+                        ));
+                    }
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect()
+    });
+    solved.sort_by_key(|(index, _)| *index);
+
+    BatchReport {
+        entries: solved.into_iter().map(|(_, entry)| entry).collect(),
+        workers,
+        wall_time: start_time.elapsed(),
+    }
+}
+
+/// Solves the puzzles one after the other with the same solver, each within `limits`, handing
+/// every entry to `done` along with the puzzle's index as soon as it is solved. Times are the
+/// ones the solves report when they do. Stops at the first error `done` returns
+pub fn solve_batch_sequential<E>(
+    solver: &mut dyn SudokuSolver,
+    puzzles: &[Board],
+    limits: &SolveLimits,
+    mut done: impl FnMut(usize, &BatchEntry) -> Result<(), E>,
+) -> Result<BatchReport, E> {
+    let start_time = Instant::now();
+    let mut entries = Vec::new();
+    for (index, puzzle) in puzzles.iter().enumerate() {
+        let mut board = puzzle.clone();
+        let solve_start = Instant::now();
+        let result = solver.solve_with_limits(&mut board, &mut NoopObserver, limits);
+        let measured = solve_start.elapsed();
+        let report = match &result {
+            Ok(report) => Some(report),
+            Err(error) => error.report(),
+        };
+        let entry = BatchEntry {
+            seed: index as u64,
+            elapsed: report.map_or(measured, |report| report.elapsed),
+            board,
+            result,
+            verification: None,
+        };
+        done(index, &entry)?;
+        entries.push(entry);
+    }
+    Ok(BatchReport {
+        entries,
+        workers: 1,
+        wall_time: start_time.elapsed(),
+    })
+}
+
+/// Solves puzzles as they come out of `puzzles` on `workers` threads, handing each result to
+/// `done` in the order of the input as soon as it and everything before it are ready, so a
+/// stream can be solved without reading all of it first. Every puzzle comes with a tag, like
+/// where it was read from, that is handed back along with its entry. Items that are not
+/// puzzles are passed through in their place without being solved. Seeds are given like
+/// [`solve_batch_parallel_with_limits`] does, by position among the puzzles alone, to the
+/// solvers made by `new_solver`. With `verify`, every solution is also checked with
+/// [`verify_solution`] on the worker that found it. Returns the report of every entry handed
+/// to `done`.
+///
+/// Returning false from `done` stops the stream like it does for [`map_streaming`]
+pub fn solve_batch_streaming<I, T, E>(
+    puzzles: I,
+    workers: usize,
+    limits: &SolveLimits,
+    new_solver: impl Fn(u64) -> Box<dyn SudokuSolver> + Sync,
+    verify: bool,
+    mut done: impl FnMut(Result<(T, &BatchEntry), E>) -> bool,
+) -> BatchReport
+where
+    I: Iterator<Item = Result<(T, Board), E>> + Send,
+    T: Send,
+    E: Send,
+{
+    let solve = |seed: usize, (tag, mut board): (T, Board)| {
+        let seed = seed as u64;
+        let puzzle = verify.then(|| board.clone());
+        let mut solver = new_solver(seed);
+        let solve_start = Instant::now();
+        let result = solver.solve_with_limits(&mut board, &mut NoopObserver, limits);
+        let elapsed = solve_start.elapsed();
+        let verification = puzzle.filter(|_| result.is_ok()).map(|puzzle| {
+            let verified = verify_solution(&puzzle, &board);
+            (puzzle, verified)
+        });
+        let entry = BatchEntry {
+            seed,
+            board,
+            result,
+            elapsed,
+            verification,
+        };
+        (tag, entry)
+    };
+    let start_time = Instant::now();
+    let mut entries = Vec::new();
+    map_streaming(puzzles, workers, solve, |result| match result {
+        Ok((tag, entry)) => {
+            let go_on = done(Ok((tag, &entry)));
+            entries.push(entry);
+            go_on
+        }
+        Err(error) => done(Err(error)),
+    });
+    BatchReport {
+        workers: workers.clamp(1, entries.len().max(1)),
+        entries,
+        wall_time: start_time.elapsed(),
+    }
+}
+
+/// Runs `work` on the items as they come out of `items` on `workers` threads, handing each
+/// result to `done` in the order of the input as soon as it and everything before it are ready.
+/// `work` is also given the item's position among the items that are not errors, while errors
+/// are passed through in their place without being worked on.
+///
+/// Returning false from `done` stops the stream: the items being worked on are finished and
+/// dropped, and no more are taken from `items`
+pub fn map_streaming<I, T, R, E>(
+    items: I,
+    workers: usize,
+    work: impl Fn(usize, T) -> R + Sync,
+    mut done: impl FnMut(Result<R, E>) -> bool,
+) where
+    I: Iterator<Item = Result<T, E>> + Send,
+    T: Send,
+    R: Send,
+    E: Send,
+{
+    let work_on = &work;
+    std::thread::scope(|scope| {
+        // A bounded queue keeps the reader only a little ahead of the workers
+        let (work_sender, queue) = mpsc::sync_channel::<(usize, usize, T)>(workers.max(1));
+        let queue = Arc::new(Mutex::new(queue));
+        let (results_sender, results) = mpsc::channel();
+        for _ in 0..workers.max(1) {
+            let (queue, results_sender) = (Arc::clone(&queue), results_sender.clone());
+            scope.spawn(move || loop {
+                let next = queue.lock().unwrap().recv();
+                let Ok((index, position, item)) = next else {
+                    return;
+                };
+                let result = work_on(position, item);
+                if results_sender.send((index, Ok(result))).is_err() {
+                    return;
+                }
+            });
+        }
+        // The last worker to stop drops the queue, which stops the reader
+        drop(queue);
+        scope.spawn(move || {
+            let mut position = 0;
+            for (index, item) in items.enumerate() {
+                let sent = match item {
+                    Ok(item) => {
+                        position += 1;
+                        work_sender.send((index, position - 1, item)).is_ok()
+                    }
+                    Err(error) => results_sender.send((index, Err(error))).is_ok(),
+                };
+                if !sent {
+                    return;
+                }
+            }
+        });
+
+        let mut pending = BTreeMap::new();
+        let mut next = 0;
+        for (index, result) in &results {
+            pending.insert(index, result);
+            while let Some(result) = pending.remove(&next) {
+                next += 1;
+                if !done(result) {
+                    return;
+                }
+            }
+        }
+    });
+}
+
+/// Writes how each solve went as a CSV, one row per puzzle after a header: its index, where it
+/// came from, its clues, the solver, the result, the milliseconds the solve reported taking,
+/// its placements and its backtracks. Every row is flushed as it is written, so the rows of a
+/// run that crashes partway are still there
+pub struct TimingsWriter<W: Write> {
+    out: W,
+}
+
+impl TimingsWriter<BufWriter<File>> {
+    /// Starts the CSV in a new file at `path`
+    pub fn create(path: &Path) -> io::Result<Self> {
+        File::create(path).map(BufWriter::new).and_then(Self::new)
+    }
+}
+
+impl<W: Write> TimingsWriter<W> {
+    pub const HEADER: &'static str = "index,source,clues,solver,result,ms,placements,backtracks";
+
+    /// Starts the CSV with its header
+    pub fn new(mut out: W) -> io::Result<Self> {
+        writeln!(out, "{}", Self::HEADER)?;
+        out.flush()?;
+        Ok(Self { out })
+    }
+
+    /// Writes the row of a solve of the puzzle at `index` of a run, `source` telling where the
+    /// puzzle came from, like its seeds or its line. The time is the one in the solve's report
+    /// when there is one, so it does not count anything done around the solve, and `measured`
+    /// otherwise
+    pub fn write_row(
+        &mut self,
+        index: usize,
+        source: &str,
+        puzzle: &Board,
+        solver: &str,
+        result: &Result<SolveReport, SolveError>,
+        measured: Duration,
+    ) -> io::Result<()> {
+        let (outcome, report) = match result {
+            Ok(report) => ("solved", Some(report)),
+            Err(error) => (error.kind(), error.report()),
+        };
+        let elapsed = report.map_or(measured, |report| report.elapsed);
+        let placements = report.map_or(String::new(), |report| report.placements.to_string());
+        let backtracks = match result {
+            Err(SolveError::Exhausted { backtracks }) => backtracks.to_string(),
+            _ => report.map_or(String::new(), |report| report.backtracks.to_string()),
+        };
+        writeln!(
+            self.out,
+            "{index},{source},{},{solver},{outcome},{:.3},{placements},{backtracks}",
+            puzzle.clue_count(),
+            elapsed.as_secs_f64() * 1000.0
+        )?;
+        self.out.flush()
+    }
+}
@@ -1,11 +1,39 @@
 use std::{
-    collections::HashSet,
-    fmt::Display,
+    error::Error,
+    fmt::{Debug, Display},
+    hash::{Hash, Hasher},
     io::{Stdout, Write},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
 };
 
 use crossterm::{style, QueueableCommand};
-use rand::{rngs::ThreadRng, seq::IteratorRandom, thread_rng, Rng};
+use rand::{
+    seq::{IteratorRandom, SliceRandom},
+    Rng,
+};
+
+use crate::{
+    candidates::CandidateSet,
+    dimacs::SatAssignmentError,
+    dlx::DancingLinks,
+    generator::MINIMUM_CLUES,
+    grade::GradeReport,
+    history::MoveHistory,
+    observer::{NoopObserver, SolveObserver},
+    palette::Palette,
+    report::{
+        CancellationToken, CellSelection, Limit, PropagationLevel, SolveLimits, SolveOptions,
+        SolveReport, ValueOrdering,
+    },
+    technique::{
+        find_hidden_singles_in, find_naked_singles, CandidatesGrid, ExplainedStep, Hint,
+        HintAction, LogicalOutcome, TechniqueSet,
+    },
+};
 
 pub const VERTICAL_LINE: &str = "│";
 pub const DOWN_T_CONNECTOR: &str = "┬";
@@ -19,385 +47,2948 @@ pub const PLUS_CONNECTOR: &str = "┼";
 pub const RIGHT_T_CONNECTOR: &str = "├";
 pub const LEFT_T_CONNECTOR: &str = "┤";
 
+/// For every cell index, the indices of the 20 other cells sharing its row, column or tile
+static PEERS: [[usize; 20]; 81] = build_peers();
+
+const fn build_peers() -> [[usize; 20]; 81] {
+    let mut peers = [[0; 20]; 81];
+    let mut cell = 0;
+    while cell < 81 {
+        let (row, col) = (cell / 9, cell % 9);
+        let mut count = 0;
+        let mut other = 0;
+        while other < 81 {
+            let (other_row, other_col) = (other / 9, other % 9);
+            let same_tile = row / 3 == other_row / 3 && col / 3 == other_col / 3;
+            if other != cell && (row == other_row || col == other_col || same_tile) {
+                peers[cell][count] = other;
+                count += 1;
+            }
+            other += 1;
+        }
+        cell += 1;
+    }
+    peers
+}
+
+/// Random keys for Zobrist hashing, one per cell and value, generated from a fixed seed so hashes
+/// are stable across runs
+static ZOBRIST_KEYS: [[u64; 9]; 81] = build_zobrist_keys();
+
+const fn build_zobrist_keys() -> [[u64; 9]; 81] {
+    let mut keys = [[0; 9]; 81];
+    // SplitMix64, which is simple enough to run at compile time
+    let mut state: u64 = 0x5d0c_c0de_5eed_2024;
+    let mut i = 0;
+    while i < 81 * 9 {
+        state = state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        keys[i / 9][i % 9] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+}
+
+fn zobrist_of(cells: &[u8; 81]) -> u64 {
+    cells
+        .iter()
+        .enumerate()
+        .filter(|(_, cell)| **cell != 0)
+        .fold(0, |hash, (i, cell)| {
+            hash ^ ZOBRIST_KEYS[i][*cell as usize - 1]
+        })
+}
+
+#[derive(Clone)]
 pub struct Board {
     cells: [u8; 81],
+    /// Cells that were filled in when the puzzle was created and may not be changed by solving
+    givens: [bool; 81],
+    /// Zobrist hash of the cells, kept up to date on every write
+    zobrist: u64,
+    /// Digits present in each row, column and tile, kept up to date on every write so candidates
+    /// and validation never need a scan of the cells
+    occupancy: Occupancy,
+    /// Number of candidates of every empty cell, [`FILLED`] for the others
+    entropy: [u8; 81],
+    /// Empty cells grouped by their number of candidates, bit `i` of `entropy_buckets[n]` being
+    /// set when cell `i` has `n` candidates. Only a cell and its peers move between buckets on a
+    /// write, and the least entropy cell is the lowest bit of the first non-empty bucket
+    entropy_buckets: [u128; 10],
 }
 
-#[derive(Debug)]
-pub struct SudokuRow {
-    pub cells: [u8; 9],
+/// Entropy cache marker for a cell that is filled in
+const FILLED: u8 = u8::MAX;
+
+/// How many times each digit appears in every row, column and tile. A unit can hold the same
+/// digit twice on a board that breaks the rules, so the counts are what lets clearing a cell
+/// update its units without a scan
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Occupancy {
+    /// Indexed by [`UnitKind`], unit index and digit minus one
+    counts: [[[u8; 9]; 9]; 3],
+    /// Digits whose count is not zero, indexed like the counts
+    used: [[CandidateSet; 9]; 3],
+    /// Copies of a digit beyond the first in its unit, summed over every unit
+    duplicates: usize,
 }
 
-#[allow(clippy::format_collect)]
-impl Display for SudokuRow {
+impl Occupancy {
+    /// Counts every digit of the cells from scratch
+    fn from_cells(cells: &[u8; 81]) -> Self {
+        let mut occupancy = Occupancy {
+            counts: [[[0; 9]; 9]; 3],
+            used: [[CandidateSet::empty(); 9]; 3],
+            duplicates: 0,
+        };
+        for (index, &value) in cells.iter().enumerate() {
+            occupancy.add(index, value);
+        }
+        occupancy
+    }
+
+    /// The row, column and tile of a cell, as indices into the counts
+    fn units_of(index: usize) -> [(usize, usize); 3] {
+        let (row, col) = (index / 9, index % 9);
+        [
+            (UnitKind::Row as usize, row),
+            (UnitKind::Column as usize, col),
+            (UnitKind::Tile as usize, (row / 3) * 3 + col / 3),
+        ]
+    }
+
+    /// Counts `value` as placed at the cell, doing nothing for an empty cell
+    fn add(&mut self, index: usize, value: u8) {
+        if value == 0 {
+            return;
+        }
+        for (kind, unit) in Occupancy::units_of(index) {
+            let count = &mut self.counts[kind][unit][value as usize - 1];
+            if *count > 0 {
+                self.duplicates += 1;
+            }
+            *count += 1;
+            self.used[kind][unit].insert(value);
+        }
+    }
+
+    /// Takes back an [`Occupancy::add`] of `value` at the cell
+    fn remove(&mut self, index: usize, value: u8) {
+        if value == 0 {
+            return;
+        }
+        for (kind, unit) in Occupancy::units_of(index) {
+            let count = &mut self.counts[kind][unit][value as usize - 1];
+            *count -= 1;
+            if *count > 0 {
+                self.duplicates -= 1;
+            } else {
+                self.used[kind][unit].remove(value);
+            }
+        }
+    }
+
+    /// Digits present in a unit
+    fn used(&self, unit: UnitKind, index: usize) -> CandidateSet {
+        self.used[unit as usize][index]
+    }
+
+    /// Whether some digit appears more than once in a unit
+    fn has_duplicates_in(&self, unit: UnitKind, index: usize) -> bool {
+        self.counts[unit as usize][index]
+            .iter()
+            .any(|&count| count > 1)
+    }
+}
+
+/// Boards compare and hash by their cells alone, so a solved puzzle equals the same grid built
+/// from scratch regardless of which cells started out as givens
+impl PartialEq for Board {
+    fn eq(&self, other: &Self) -> bool {
+        self.cells == other.cells
+    }
+}
+
+impl Eq for Board {}
+
+impl Hash for Board {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.cells.hash(state);
+    }
+}
+
+/// Prints the board as its nine rows of digits rather than a flat array of 81 numbers
+impl Debug for Board {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list()
+            .entries(self.cells.chunks(9).map(DebugRow))
+            .finish()
+    }
+}
+
+/// Returned when a string cannot be parsed into a board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseBoardError {
+    /// The string does not hold exactly 81 cells
+    WrongLength(usize),
+    /// A character other than a digit or `.` was found at the given index
+    InvalidCharacter { index: usize, character: char },
+}
+
+impl Display for ParseBoardError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseBoardError::WrongLength(length) => {
+                write!(f, "A board needs exactly 81 cells, found {length}")
+            }
+            ParseBoardError::InvalidCharacter { index, character } => {
+                write!(f, "Unexpected character '{character}' at index {index}")
+            }
+        }
+    }
+}
+
+impl Error for ParseBoardError {}
+
+/// Parses the 81 cells left to right, top to bottom. Empty cells may be written as `0` or `.`
+impl FromStr for Board {
+    type Err = ParseBoardError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let cells = s
+            .chars()
+            .enumerate()
+            .map(|(index, character)| match character {
+                '.' => Ok(0),
+                '0'..='9' => Ok(character as u8 - b'0'),
+                _ => Err(ParseBoardError::InvalidCharacter { index, character }),
+            })
+            .collect::<Result<Vec<u8>, _>>()?;
+        let length = cells.len();
+        let cells: [u8; 81] = cells
+            .try_into()
+            .map_err(|_| ParseBoardError::WrongLength(length))?;
+
+        Ok(Board::from_cells(cells))
+    }
+}
+
+/// Prints the board in the same 81 digit form accepted by [`Board::new`]
+impl Display for Board {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let string = self
             .cells
             .iter()
-            .enumerate()
-            .map(|(i, cell)| {
-                let cell = if *cell == 0 {
-                    " ".to_string()
-                } else {
-                    cell.to_string()
-                };
-                let prefix = if i % 3 == 0 {
-                    VERTICAL_LINE.to_string() + " "
+            .map(|cell| (b'0' + cell) as char)
+            .collect::<String>();
+        write!(f, "{string}")
+    }
+}
+
+/// Helper that prints a row of cells as a compact digit string, with empty cells shown as `.`
+struct DebugRow<'a>(&'a [u8]);
+
+impl Debug for DebugRow<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let row = self
+            .0
+            .iter()
+            .map(|cell| {
+                if *cell == 0 {
+                    '.'
                 } else {
-                    String::new()
-                };
-                if i < self.cells.len() - 1 {
-                    return format!("{prefix}{cell} ");
+                    (b'0' + cell) as char
                 }
-                format!("{cell} {VERTICAL_LINE}")
             })
             .collect::<String>();
-        write!(f, "{string}")
+        write!(f, "{row}")
     }
 }
 
 #[derive(Debug)]
-pub struct SudokuColumn {
+pub struct SudokuRow {
     pub cells: [u8; 9],
 }
 
-#[derive(Debug)]
-pub struct SudokuTile {
-    pub cells: [u8; 9],
+impl SudokuRow {
+    /// Iterates over the values of the filled in cells
+    pub fn values(&self) -> impl Iterator<Item = u8> + '_ {
+        self.cells.iter().copied().filter(|cell| *cell != 0)
+    }
+
+    /// Whether any cell holds the given value
+    pub fn contains(&self, value: u8) -> bool {
+        self.cells.contains(&value)
+    }
+}
+
+#[allow(clippy::format_collect)]
+impl Display for SudokuRow {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let cells = self.cells.map(|cell| {
+            if cell == 0 {
+                " ".to_string()
+            } else {
+                cell.to_string()
+            }
+        });
+        write!(f, "{}", lay_out_row(&cells))
+    }
+}
+
+/// Lays out the drawn cells of a row between the lines of its tiles
+fn lay_out_row(cells: &[String; 9]) -> String {
+    cells
+        .iter()
+        .enumerate()
+        .map(|(i, cell)| {
+            let prefix = if i % 3 == 0 {
+                VERTICAL_LINE.to_string() + " "
+            } else {
+                String::new()
+            };
+            if i < cells.len() - 1 {
+                return format!("{prefix}{cell} ");
+            }
+            format!("{cell} {VERTICAL_LINE}")
+        })
+        .collect()
 }
 
 #[derive(Debug)]
-pub struct BoardMove {
-    position: [usize; 2],
-    new_value: u8,
-    cascades: Vec<[usize; 2]>,
+pub struct SudokuColumn {
+    pub cells: [u8; 9],
 }
 
-impl BoardMove {
-    pub fn position(&self) -> [usize; 2] {
-        self.position
+impl SudokuColumn {
+    /// Iterates over the values of the filled in cells
+    pub fn values(&self) -> impl Iterator<Item = u8> + '_ {
+        self.cells.iter().copied().filter(|cell| *cell != 0)
     }
 
-    pub fn new_value(&self) -> u8 {
-        self.new_value
+    /// Whether any cell holds the given value
+    pub fn contains(&self, value: u8) -> bool {
+        self.cells.contains(&value)
     }
 }
 
-impl Board {
-    /// Board constructor
-    pub fn new(string_representation: String) -> Board {
-        let cells: [u8; 81] = string_representation
-            .chars()
-            .map(|char| char.to_string().parse::<u8>().unwrap())
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap();
-
-        Board { cells }
+/// Renders the column vertically, with a separator between each band
+impl Display for SudokuColumn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let horizontal = HORIZONTAL_LINE.repeat(3);
+        writeln!(f, "{TOP_LEFT_CONNECTOR}{horizontal}{TOP_RIGHT_CONNECTOR}")?;
+        for (i, cell) in self.cells.iter().enumerate() {
+            if i % 3 == 0 && i != 0 {
+                writeln!(f, "{RIGHT_T_CONNECTOR}{horizontal}{LEFT_T_CONNECTOR}")?;
+            }
+            let cell = if *cell == 0 {
+                " ".to_string()
+            } else {
+                cell.to_string()
+            };
+            writeln!(f, "{VERTICAL_LINE} {cell} {VERTICAL_LINE}")?;
+        }
+        write!(
+            f,
+            "{BOTTOM_LEFT_CONNECTOR}{horizontal}{BOTTOM_RIGHT_CONNECTOR}"
+        )
     }
+}
 
-    /// Retrieves a single row
-    pub fn get_row(&self, row: usize) -> SudokuRow {
-        SudokuRow {
-            cells: self
-                .cells
-                .iter()
-                .enumerate()
-                .filter(|(i, _)| i / 9 == row)
-                .map(|(_, cell)| *cell)
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap(),
-        }
+#[derive(Debug)]
+pub struct SudokuTile {
+    pub cells: [u8; 9],
+}
+
+impl SudokuTile {
+    /// Iterates over the values of the filled in cells
+    pub fn values(&self) -> impl Iterator<Item = u8> + '_ {
+        self.cells.iter().copied().filter(|cell| *cell != 0)
     }
 
-    /// Retrieves a single column
-    pub fn get_column(&self, column: usize) -> SudokuColumn {
-        SudokuColumn {
-            cells: self
-                .cells
-                .iter()
-                .enumerate()
-                .filter(|(i, _)| i % 9 == column)
-                .map(|(_, cell)| *cell)
-                .collect::<Vec<_>>()
-                .try_into()
-                .unwrap(),
-        }
+    /// Whether any cell holds the given value
+    pub fn contains(&self, value: u8) -> bool {
+        self.cells.contains(&value)
     }
+}
 
-    /// Retrieves a single 3x3 tile
-    pub fn get_tile(&self, tile: (usize, usize)) -> SudokuTile {
-        SudokuTile {
-            cells: self
-                .cells
+/// Renders the tile as a bordered 3x3 grid
+impl Display for SudokuTile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let horizontal = HORIZONTAL_LINE.repeat(7);
+        writeln!(f, "{TOP_LEFT_CONNECTOR}{horizontal}{TOP_RIGHT_CONNECTOR}")?;
+        for row in self.cells.chunks(3) {
+            let row = row
                 .iter()
-                .enumerate()
-                .filter(|(i, _)| {
-                    let tile_row = i / 27;
-                    let tile_column = (i % 9) / 3;
-                    tile_row == tile.0 && tile_column == tile.1
+                .map(|cell| {
+                    if *cell == 0 {
+                        " ".to_string()
+                    } else {
+                        cell.to_string()
+                    }
                 })
-                .map(|(_, cell)| *cell)
                 .collect::<Vec<_>>()
-                .try_into()
-                .unwrap(),
+                .join(" ");
+            writeln!(f, "{VERTICAL_LINE} {row} {VERTICAL_LINE}")?;
         }
+        write!(
+            f,
+            "{BOTTOM_LEFT_CONNECTOR}{horizontal}{BOTTOM_RIGHT_CONNECTOR}"
+        )
     }
+}
 
-    /// Formats the board and prints it out to the console
-    pub fn draw_board(&self, stdout: &mut Stdout) {
-        let temp_row = self.get_row(0).to_string();
-        let (_, temp_row_mid) = temp_row.split_at(VERTICAL_LINE.len());
-        let (temp_row_mid, _) = temp_row_mid.split_at(temp_row_mid.len() - VERTICAL_LINE.len());
+/// The three kinds of units a cell belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitKind {
+    Row,
+    Column,
+    Tile,
+}
 
-        stdout
-            .queue(style::Print(format!(
-                "{TOP_LEFT_CONNECTOR}{}{TOP_RIGHT_CONNECTOR}\n",
-                temp_row_mid
-                    .chars()
-                    .map(|char| {
-                        if char.to_string() == *VERTICAL_LINE {
-                            DOWN_T_CONNECTOR.to_string()
-                        } else {
-                            HORIZONTAL_LINE.to_string()
-                        }
-                    })
-                    .collect::<String>(),
-            )))
-            .unwrap();
-        for i in 0..9 {
-            if i % 3 == 0 && i != 0 && i != 8 {
-                stdout
-                    .queue(style::Print(format!(
-                        "{RIGHT_T_CONNECTOR}{}{LEFT_T_CONNECTOR}\n",
-                        temp_row_mid
-                            .chars()
-                            .map(|char| {
-                                if char.to_string() == *VERTICAL_LINE {
-                                    PLUS_CONNECTOR.to_string()
-                                } else {
-                                    HORIZONTAL_LINE.to_string()
-                                }
-                            })
-                            .collect::<String>(),
-                    )))
-                    .unwrap();
-            }
-            stdout
-                .queue(style::Print(format!("{}\n", self.get_row(i))))
-                .unwrap();
-        }
-        stdout
-            .queue(style::Print(format!(
-                "{BOTTOM_LEFT_CONNECTOR}{}{BOTTOM_RIGHT_CONNECTOR}\n",
-                temp_row_mid
-                    .chars()
-                    .map(|char| {
-                        if char.to_string() == *VERTICAL_LINE {
-                            UP_T_CONNECTOR.to_string()
-                        } else {
-                            HORIZONTAL_LINE.to_string()
-                        }
-                    })
-                    .collect::<String>(),
-            )))
-            .unwrap();
-        stdout.flush().unwrap();
+impl UnitKind {
+    pub const ALL: [UnitKind; 3] = [UnitKind::Row, UnitKind::Column, UnitKind::Tile];
+
+    /// Positions of the nine cells of the unit at `index`. Tiles are indexed left to right, top
+    /// to bottom
+    pub fn positions(&self, index: usize) -> [(usize, usize); 9] {
+        std::array::from_fn(|i| match self {
+            UnitKind::Row => (index, i),
+            UnitKind::Column => (i, index),
+            UnitKind::Tile => ((index / 3) * 3 + i / 3, (index % 3) * 3 + i % 3),
+        })
     }
+}
 
-    /// Entropy is defined as all the states that a cell could be in which it is considered valid.
-    /// The entropy is calculated through a series of hash set differences, an extremely quick
-    /// operation that perfectly fits the rules of sudoku
-    pub fn calculate_entropy_at_cell(&self, row: usize, col: usize) -> Option<Vec<u8>> {
-        let current_index = row * 9 + col;
-        if self.cells[current_index] != 0 {
-            return None;
-        }
+impl Display for UnitKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            UnitKind::Row => "row",
+            UnitKind::Column => "column",
+            UnitKind::Tile => "tile",
+        };
+        write!(f, "{name}")
+    }
+}
 
-        let possible_options: HashSet<u8> = (0..=9).collect();
+/// The rules a board is played under
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum Variant {
+    /// Every row, column and tile holds every digit
+    #[default]
+    Classic,
+    /// X-sudoku, where both main diagonals hold every digit as well
+    X,
+}
 
-        let current_row_set: HashSet<u8> = self.get_row(row).cells.into_iter().collect();
-        let current_column_set: HashSet<u8> = self.get_column(col).cells.into_iter().collect();
-        let current_tile_set: HashSet<u8> = self
-            .get_tile((row / 3, col / 3))
-            .cells
-            .into_iter()
-            .collect();
+impl Variant {
+    pub const ALL: [Variant; 2] = [Variant::Classic, Variant::X];
 
-        let options: Vec<u8> = possible_options
-            .difference(&current_row_set)
-            .cloned()
-            .collect::<HashSet<u8>>()
-            .difference(&current_column_set)
-            .cloned()
-            .collect::<HashSet<u8>>()
-            .difference(&current_tile_set)
-            .cloned()
-            .collect();
-        Some(options)
+    /// Positions of the cells that have to hold every digit on top of the rows, columns and
+    /// tiles, one array for each extra unit
+    pub fn extra_units(&self) -> Vec<[(usize, usize); 9]> {
+        match self {
+            Variant::Classic => Vec::new(),
+            Variant::X => vec![
+                std::array::from_fn(|i| (i, i)),
+                std::array::from_fn(|i| (i, 8 - i)),
+            ],
+        }
     }
+}
 
-    /// Searches for a cell with the least entropy. The lowest entropy equates to the highest confidence
-    pub fn find_least_entropy(&self) -> Option<((usize, usize), Vec<u8>)> {
-        let mut min_pos = (10, 10);
-        let mut min_entropy = (0..=9).collect::<Vec<u8>>();
-        for row in 0..9 {
-            for col in 0..9 {
-                let current_entropy = self.calculate_entropy_at_cell(row, col);
-                if let Some(entropy) = current_entropy {
-                    if entropy.len() < min_entropy.len() {
-                        min_entropy = entropy;
-                        min_pos = (row, col);
-                    }
-                }
-            }
-        }
-        if min_pos == (10, 10) {
-            return None;
-        }
-        Some((min_pos, min_entropy))
+impl Display for Variant {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Variant::Classic => "classic",
+            Variant::X => "x",
+        };
+        write!(f, "{name}")
     }
+}
 
-    /// Solves the sudoku puzzle. Iteratively searches for the cell with least entropy, promptly
-    /// collapsing it to a single possibility. Producing a wrong result is not impossible
-    /// TODO: Implement some form of backtracking to solve cases where wave function colapse gets
-    /// stuck
-    pub fn solve_board(&mut self, stdout: &mut Stdout) -> Result<(), &str> {
-        let mut previous_moves: Vec<BoardMove> = Vec::with_capacity(81);
-
-        let mut least_entropy_result = self.find_least_entropy();
-        let mut rng = thread_rng();
-        while least_entropy_result.is_some() {
-            let ((row, col), min_entropy) = least_entropy_result.as_ref().unwrap();
-
-            if min_entropy.is_empty() {
-                self.draw_board(stdout);
-                least_entropy_result = self.backtrack(&mut previous_moves, &mut rng);
-                continue;
-            } else {
-                let cell_index = row * 9 + col;
-                if min_entropy.len() == 1 {
-                    self.cells[cell_index] = min_entropy[0];
-                    let last_move = previous_moves.last_mut();
-                    if let Some(last) = last_move {
-                        last.cascades.push([*row, *col]);
-                    }
-                } else {
-                    let mut valid_options = Vec::with_capacity(min_entropy.len());
-                    for value in min_entropy {
-                        self.cells[cell_index] = *value;
-                        let next_entropy = self.find_least_entropy();
+/// The name of a cell in messages, its row and column counted from 1 like r4c7
+pub fn cell_name((row, col): (usize, usize)) -> String {
+    format!("r{}c{}", row + 1, col + 1)
+}
 
-                        if let Some(entropy) = next_entropy {
-                            if !entropy.1.is_empty() {
-                                valid_options.push((*value, entropy));
-                            }
-                        } else {
-                            return Ok(());
-                        }
-                    }
-                    let choice = valid_options.into_iter().reduce(|acc, (val, entropy_data)| {
-                        if entropy_data.1.len() < acc.1.1.len() {
-                            return (val, entropy_data);
-                        }
-                        acc
-                    }).unwrap();
-                    previous_moves.push(BoardMove {
-                        position: [*row, *col],
-                        new_value: choice.0,
-                        cascades: Vec::new(),
-                    });
-                };
-                least_entropy_result = self.find_least_entropy();
-            }
-        }
-        Ok(())
+/// Two cells of the same unit holding the same value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+    pub first: (usize, usize),
+    pub second: (usize, usize),
+    pub unit: UnitKind,
+    pub value: u8,
+}
+
+impl Display for Conflict {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} appears at both {} and {} in the same {}",
+            self.value,
+            cell_name(self.first),
+            cell_name(self.second),
+            self.unit
+        )
     }
+}
+
+/// Returned when trying to change a cell that was given as part of the puzzle
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GivenCellError {
+    pub position: (usize, usize),
+}
 
-    /// Validates the resulting board to make sure it follows the sudoku rules
-    pub fn validate_board(&self) {
-        for i in 0..3 {
-            for j in 0..3 {
-                let tile = self.get_tile((i, j));
-                let tile_set: HashSet<u8> = tile.cells.into_iter().collect();
+impl Display for GivenCellError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} is a given and cannot be changed",
+            cell_name(self.position)
+        )
+    }
+}
 
-                let row = self.get_row(i);
-                let row_set: HashSet<u8> = row.cells.into_iter().collect();
+impl Error for GivenCellError {}
 
-                let column = self.get_column(i);
-                let column_set: HashSet<u8> = column.cells.into_iter().collect();
+/// A way a proposed solution fails to solve a puzzle, see [`Board::check_solution`]. Rows,
+/// columns and units are counted from 1 when displayed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// The solution holds another value than the puzzle gives for the cell
+    ChangedGiven {
+        position: (usize, usize),
+        given: u8,
+        value: u8,
+    },
+    /// The value appears more than once in the unit at `index`
+    Duplicate {
+        unit: UnitKind,
+        index: usize,
+        value: u8,
+    },
+    /// The solution leaves the cell empty
+    EmptyCell { position: (usize, usize) },
+}
 
-                if tile.cells.len() != tile_set.len()
-                    || row.cells.len() != row_set.len()
-                    || column.cells.len() != column_set.len()
-                {
-                    println!("The solution is invalid!");
-                    return;
-                }
+impl Display for Discrepancy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Discrepancy::ChangedGiven {
+                position,
+                given,
+                value,
+            } => write!(
+                f,
+                "{} holds {value} instead of the given {given}",
+                cell_name(*position)
+            ),
+            Discrepancy::Duplicate { unit, index, value } => {
+                write!(f, "{value} appears more than once in {unit} {}", index + 1)
+            }
+            Discrepancy::EmptyCell { position } => {
+                write!(f, "{} is empty", cell_name(*position))
             }
         }
-        println!("The board is valid!");
     }
+}
 
-    /// Backtracking moves when a mistake is made. Re-evaluates the entropy at the previous point,
-    /// excluding the value that it took when executing the first time. Filters out values that
-    /// lead to invalid board states.
-    pub fn backtrack(
-        &mut self,
-        previous_moves: &mut Vec<BoardMove>,
-        rng: &mut ThreadRng,
-    ) -> Option<((usize, usize), Vec<u8>)> {
-        assert!(!previous_moves.is_empty());
-        // println!("Here: {}", previous_moves.len());
-
-        let last_move = previous_moves.pop().unwrap();
+/// Reasons a board cannot be dug out along a mask, see [`Board::remove_clues_by_mask`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaskError {
+    /// The mask keeps this many cells, fewer than any puzzle with a unique solution has
+    TooFewCells(usize),
+    /// The mask keeps a cell that is empty on the board
+    EmptyCell { position: (usize, usize) },
+    /// The masked puzzle has several solutions, so these cells outside the mask were kept as
+    /// well to make it unique
+    ExtraCellsKept(Vec<(usize, usize)>),
+    /// Even with every filled in cell kept the board has no unique solution
+    NotUnique,
+}
 
-        for cascade in &last_move.cascades {
-            self.cells[cascade[0] * 9 + cascade[1]] = 0;
+impl Display for MaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaskError::TooFewCells(cells) => write!(
+                f,
+                "The mask keeps {cells} cells, a unique puzzle needs at least {MINIMUM_CLUES}"
+            ),
+            MaskError::EmptyCell { position } => {
+                write!(f, "The mask keeps {}, which is empty", cell_name(*position))
+            }
+            MaskError::ExtraCellsKept(cells) => {
+                let cells: Vec<String> = cells.iter().copied().map(cell_name).collect();
+                write!(
+                    f,
+                    "The mask leaves several solutions, {} had to be kept as well",
+                    cells.join(", ")
+                )
+            }
+            MaskError::NotUnique => write!(f, "The board does not have a unique solution"),
         }
+    }
+}
 
-        let last_move_position = last_move.position;
-        let last_move_position_index = last_move_position[0] * 9 + last_move_position[1];
-        self.cells[last_move_position_index] = 0;
+impl Error for MaskError {}
 
-        let mut last_cell_entropy: HashSet<u8> = self
-            .calculate_entropy_at_cell(last_move_position[0], last_move_position[1])
-            .unwrap()
+/// Reasons a partial solution cannot be laid over a board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum OverlayError {
+    /// The partial solution has a different value in a cell that is a given
+    DisagreesWithGiven {
+        position: (usize, usize),
+        given: u8,
+        value: u8,
+    },
+    /// Laying the partial solution over the board breaks the sudoku rules
+    Conflict(Conflict),
+}
+
+impl Display for OverlayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OverlayError::DisagreesWithGiven {
+                position,
+                given,
+                value,
+            } => write!(
+                f,
+                "{} is given as {given} but the overlay has {value}",
+                cell_name(*position)
+            ),
+            OverlayError::Conflict(conflict) => write!(
+                f,
+                "The overlay puts {} at both {} and {} in the same {}",
+                conflict.value,
+                cell_name(conflict.first),
+                cell_name(conflict.second),
+                conflict.unit
+            ),
+        }
+    }
+}
+
+impl Error for OverlayError {}
+
+/// Rough classification of a puzzle by how many clues it starts with
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClueClass {
+    /// Fewer than [`MINIMUM_CLUES`], which can never have a unique solution
+    BelowMinimum,
+    /// [`MINIMUM_CLUES`] to 25 clues
+    Sparse,
+    /// 26 to 32 clues
+    Typical,
+    /// 33 clues or more
+    Easy,
+}
+
+impl ClueClass {
+    pub fn from_count(clue_count: usize) -> Self {
+        match clue_count {
+            0..MINIMUM_CLUES => ClueClass::BelowMinimum,
+            MINIMUM_CLUES..=25 => ClueClass::Sparse,
+            26..=32 => ClueClass::Typical,
+            _ => ClueClass::Easy,
+        }
+    }
+}
+
+impl Display for ClueClass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ClueClass::BelowMinimum => "below minimum",
+            ClueClass::Sparse => "sparse",
+            ClueClass::Typical => "typical",
+            ClueClass::Easy => "easy",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// How rows and columns are labelled when drawing a board
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CoordinateStyle {
+    /// No labels, the plain grid
+    #[default]
+    None,
+    /// Rows and columns both numbered 1 through 9
+    Numeric,
+    /// Rows lettered A through I and columns numbered 1 through 9
+    Letters,
+}
+
+impl CoordinateStyle {
+    fn row_label(&self, row: usize) -> char {
+        match self {
+            CoordinateStyle::Letters => (b'A' + row as u8) as char,
+            _ => (b'1' + row as u8) as char,
+        }
+    }
+
+    fn column_label(&self, col: usize) -> char {
+        (b'1' + col as u8) as char
+    }
+}
+
+/// Options controlling how [`Board::draw_board_with_options`] lays out the board
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DrawOptions {
+    pub coordinates: CoordinateStyle,
+    /// Styles of the givens, the other filled in cells and the cells in conflict. The default
+    /// plain palette draws them unstyled
+    pub palette: Palette,
+}
+
+/// Symmetries a puzzle's clue pattern can have, see [`Board::clue_symmetry`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SymmetryKind {
+    /// Unchanged by a quarter turn, which implies half turn symmetry as well
+    Rotational90,
+    /// Unchanged by a half turn, the symmetry most published puzzles have
+    Rotational180,
+    /// Unchanged when flipped left to right
+    MirrorHorizontal,
+    /// Unchanged when flipped top to bottom
+    MirrorVertical,
+    /// Unchanged when reflected across the main diagonal
+    Diagonal,
+    /// Unchanged when reflected across the anti-diagonal
+    AntiDiagonal,
+    None,
+}
+
+impl Display for SymmetryKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            SymmetryKind::Rotational90 => "90 degree rotational",
+            SymmetryKind::Rotational180 => "180 degree rotational",
+            SymmetryKind::MirrorHorizontal => "horizontal mirror",
+            SymmetryKind::MirrorVertical => "vertical mirror",
+            SymmetryKind::Diagonal => "diagonal",
+            SymmetryKind::AntiDiagonal => "anti-diagonal",
+            SymmetryKind::None => "none",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Entropy statistics over every empty cell of a board, see [`Board::entropy_summary`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntropySummary {
+    /// Number of empty cells with each amount of candidates, from 0 to 9
+    pub cells_by_entropy: [usize; 10],
+    /// Empty cells without any candidate left, meaning the board cannot be completed
+    pub contradictions: Vec<(usize, usize)>,
+    /// Sum of the candidates of every empty cell
+    pub total_candidates: usize,
+    /// The first cell with the fewest candidates, along with those candidates
+    pub least_entropy: Option<((usize, usize), CandidateSet)>,
+}
+
+/// Prints a single line histogram of the entropy levels, one bar per level from 0 to 9
+impl Display for EntropySummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        const BARS: [char; 9] = [' ', '▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+        let max = self
+            .cells_by_entropy
+            .iter()
+            .copied()
+            .max()
+            .unwrap_or(0)
+            .max(1);
+        let histogram = self
+            .cells_by_entropy
+            .iter()
+            .map(|count| BARS[(count * 8).div_ceil(max)])
+            .collect::<String>();
+        write!(
+            f,
+            "entropy 0-9 {VERTICAL_LINE}{histogram}{VERTICAL_LINE} candidates: {}, contradictions: {}",
+            self.total_candidates,
+            self.contradictions.len()
+        )
+    }
+}
+
+/// Where the solver stands after backtracking
+enum BacktrackOutcome {
+    /// A substitute was found and placed, solving resumes from there
+    Resume,
+    /// Every move in the history was taken back without finding a substitute, so the puzzle
+    /// cannot be solved from this history
+    Exhausted,
+    /// There were no moves to take back in the first place
+    HistoryEmpty,
+    /// The solve was cancelled partway, leaving the board holding the moves still in the history
+    Cancelled,
+}
+
+impl BacktrackOutcome {
+    /// Turns the outcome of backtracking from the dead end at `cell` into the error that ends the
+    /// solve, if there is one
+    fn into_result(self, cell: (usize, usize), backtracks: u64) -> Result<(), SolveError> {
+        match self {
+            BacktrackOutcome::Resume => Ok(()),
+            BacktrackOutcome::Exhausted => Err(SolveError::Exhausted { backtracks }),
+            BacktrackOutcome::HistoryEmpty => Err(SolveError::Contradiction { cell }),
+            BacktrackOutcome::Cancelled => unreachable!("cancellation carries the report"),
+        }
+    }
+}
+
+/// Everything the entropy solver keeps track of between iterations of its main loop
+struct SolveState {
+    /// Moves still on the board, oldest first
+    previous_moves: MoveHistory,
+    report: SolveReport,
+    start_time: std::time::Instant,
+    options: SolveOptions,
+    /// Backtracks the report held at the latest restart
+    restarted_at: u64,
+}
+
+impl SolveState {
+    fn new(previous_moves: MoveHistory, options: SolveOptions) -> Self {
+        Self {
+            previous_moves,
+            report: SolveReport::default(),
+            start_time: std::time::Instant::now(),
+            options,
+            restarted_at: 0,
+        }
+    }
+}
+
+/// Reasons the solver can fail to complete a board
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SolveError {
+    /// The givens already break the sudoku rules, so there is nothing to solve
+    InvalidGivens(Vec<Conflict>),
+    /// The board reached a state that breaks the sudoku rules without any guess left to take
+    /// back, either a cell without any option left or two cells of a unit holding the same value
+    Contradiction { cell: (usize, usize) },
+    /// Every guess was tried and taken back without finding a solution
+    Exhausted { backtracks: u64 },
+    /// The solve was stopped because it went past one of its [`SolveLimits`]. The board is left
+    /// as it was at that point, holding the givens plus the moves in the report's history
+    LimitReached {
+        limit: Limit,
+        report: Box<SolveReport>,
+    },
+    /// The solve was cancelled through its [`CancellationToken`], leaving the board like
+    /// [`SolveError::LimitReached`] does
+    Cancelled { report: Box<SolveReport> },
+    /// A history given to resume from does not match the board at this cell
+    HistoryMismatch { cell: (usize, usize) },
+}
+
+impl SolveError {
+    /// Short name of the variant, to group errors by without their details
+    pub fn kind(&self) -> &'static str {
+        match self {
+            SolveError::InvalidGivens(_) => "invalid givens",
+            SolveError::Contradiction { .. } => "contradiction",
+            SolveError::Exhausted { .. } => "exhausted",
+            SolveError::LimitReached { .. } => "limit reached",
+            SolveError::Cancelled { .. } => "cancelled",
+            SolveError::HistoryMismatch { .. } => "history mismatch",
+        }
+    }
+
+    /// The report of a solve stopped partway, up to the point it stopped
+    pub fn report(&self) -> Option<&SolveReport> {
+        match self {
+            SolveError::LimitReached { report, .. } | SolveError::Cancelled { report } => {
+                Some(report)
+            }
+            _ => None,
+        }
+    }
+
+    /// The error for a solve stopped by `limit`
+    fn stopped(limit: Limit, report: SolveReport) -> Self {
+        let report = Box::new(report);
+        match limit {
+            Limit::Cancelled => SolveError::Cancelled { report },
+            limit => SolveError::LimitReached { limit, report },
+        }
+    }
+}
+
+impl Display for SolveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SolveError::InvalidGivens(conflicts) => {
+                write!(f, "The givens break the sudoku rules")?;
+                if let Some(conflict) = conflicts.first() {
+                    write!(f, ", {conflict}")?;
+                }
+                if conflicts.len() > 1 {
+                    write!(f, " and {} more conflicts", conflicts.len() - 1)?;
+                }
+                Ok(())
+            }
+            SolveError::Contradiction { cell } => {
+                write!(f, "The board has a contradiction at {}", cell_name(*cell))
+            }
+            SolveError::Exhausted { backtracks } => write!(
+                f,
+                "The puzzle has no solution, every guess failed after {backtracks} backtracks"
+            ),
+            SolveError::LimitReached { limit, report } => write!(
+                f,
+                "The solver hit its {limit} limit after placing {} values",
+                report.placements
+            ),
+            SolveError::Cancelled { report } => write!(
+                f,
+                "The solve was cancelled after placing {} values",
+                report.placements
+            ),
+            SolveError::HistoryMismatch { cell } => write!(
+                f,
+                "The history does not match the board at {}",
+                cell_name(*cell)
+            ),
+        }
+    }
+}
+
+impl Error for SolveError {}
+
+/// An empty cell left without any candidate, see [`Board::propagate_singles`], or a digit left
+/// without any place in one of the cell's units, see [`Board::find_contradiction`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Contradiction {
+    pub cell: (usize, usize),
+    /// The digit that has no place left and the unit it is missing from, `None` when the cell
+    /// itself ran out of candidates
+    pub missing: Option<(u8, UnitKind, usize)>,
+}
+
+impl Display for Contradiction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.missing {
+            Some((digit, unit, index)) => {
+                write!(
+                    f,
+                    "The digit {digit} has no place left in {unit} {}",
+                    index + 1
+                )
+            }
+            None => write!(f, "{} has no candidates left", cell_name(self.cell)),
+        }
+    }
+}
+
+impl Error for Contradiction {}
+
+/// How many solutions a board has, see [`Board::has_unique_solution`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum UniquenessResult {
+    /// The board has exactly this one solution
+    Unique(Box<Board>),
+    /// The board has at least two solutions, the first two found are kept for comparison
+    Multiple {
+        first: Box<Board>,
+        second: Box<Board>,
+    },
+    /// The board has no solution at all
+    None,
+}
+
+impl UniquenessResult {
+    /// Cells whose value differs between the two solutions of an ambiguous board. Empty unless
+    /// there are multiple solutions
+    pub fn ambiguous_cells(&self) -> Vec<(usize, usize)> {
+        match self {
+            UniquenessResult::Multiple { first, second } => first
+                .diff(second)
+                .into_iter()
+                .map(|cell| cell.position)
+                .collect(),
+            UniquenessResult::Unique(_) | UniquenessResult::None => Vec::new(),
+        }
+    }
+}
+
+impl Display for UniquenessResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            UniquenessResult::Unique(_) => write!(f, "The puzzle has a unique solution"),
+            UniquenessResult::Multiple { .. } => {
+                let cells = self
+                    .ambiguous_cells()
+                    .iter()
+                    .map(|&position| cell_name(position))
+                    .collect::<Vec<_>>()
+                    .join("/");
+                write!(
+                    f,
+                    "The puzzle has multiple solutions, cells {cells} form an unavoidable set"
+                )
+            }
+            UniquenessResult::None => write!(f, "The puzzle has no solution"),
+        }
+    }
+}
+
+/// A single cell that differs between two boards
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellDiff {
+    pub position: (usize, usize),
+    pub old_value: u8,
+    pub new_value: u8,
+}
+
+/// A cell that was filled in as a consequence of a move, because it had a single option left
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cascade {
+    position: [usize; 2],
+    value: u8,
+}
+
+impl Cascade {
+    pub fn new(position: [usize; 2], value: u8) -> Self {
+        Self { position, value }
+    }
+
+    pub fn position(&self) -> [usize; 2] {
+        self.position
+    }
+
+    pub fn value(&self) -> u8 {
+        self.value
+    }
+}
+
+/// Written as `r#c#=v` with 1 based coordinates, e.g. `r1c3=4`
+impl Display for Cascade {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let [row, col] = self.position;
+        write!(f, "{}={}", cell_name((row, col)), self.value)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct BoardMove {
+    position: [usize; 2],
+    new_value: u8,
+    cascades: Vec<Cascade>,
+    /// Values to try in this cell instead when backtracking, in order. Each is tried at most
+    /// once, as the substitute move only keeps the ones after it
+    remaining_candidates: Vec<u8>,
+}
+
+/// Moves compare by what they place on the board, regardless of the search that produced them
+impl PartialEq for BoardMove {
+    fn eq(&self, other: &Self) -> bool {
+        self.position == other.position
+            && self.new_value == other.new_value
+            && self.cascades == other.cascades
+    }
+}
+
+impl Eq for BoardMove {}
+
+impl BoardMove {
+    /// Creates a move that places `new_value` at `position` without any cascades. Backtracking
+    /// may replace it with any other digit that fits the cell
+    pub fn new(position: [usize; 2], new_value: u8) -> Self {
+        Self::guess(
+            position,
+            new_value,
+            (1..=9).filter(|value| *value != new_value).collect(),
+        )
+    }
+
+    /// Creates a guess that backtracking replaces with the remaining candidates, in order
+    fn guess(position: [usize; 2], new_value: u8, remaining_candidates: Vec<u8>) -> Self {
+        Self {
+            position,
+            new_value,
+            cascades: Vec::new(),
+            remaining_candidates,
+        }
+    }
+
+    /// Creates the root move holding the single that was forced before any guess. Nothing is
+    /// left to try, so backtracking takes it off the board without looking for a substitute
+    fn forced(position: [usize; 2], new_value: u8) -> Self {
+        Self::guess(position, new_value, Vec::new())
+    }
+
+    pub fn position(&self) -> [usize; 2] {
+        self.position
+    }
+
+    pub fn new_value(&self) -> u8 {
+        self.new_value
+    }
+
+    pub fn cascades(&self) -> &[Cascade] {
+        &self.cascades
+    }
+
+    /// Values backtracking would try in this cell instead, in order
+    pub fn remaining_candidates(&self) -> &[u8] {
+        &self.remaining_candidates
+    }
+
+    /// Records a cell that was filled in as a consequence of this move
+    pub fn push_cascade(&mut self, cascade: Cascade) {
+        self.cascades.push(cascade);
+    }
+}
+
+/// Written as the move itself followed by its cascades, all in `r#c#=v` notation and separated
+/// by spaces
+impl Display for BoardMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", Cascade::new(self.position, self.new_value))?;
+        for cascade in &self.cascades {
+            write!(f, " {cascade}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Board {
+    /// Board constructor. Panics if the string is not a valid board, use [`str::parse`] to handle
+    /// the error instead
+    pub fn new(string_representation: String) -> Board {
+        string_representation.parse().unwrap()
+    }
+
+    /// Builds a board directly from its cells, left to right, top to bottom. Every filled in cell
+    /// is considered a given
+    pub fn from_cells(cells: [u8; 81]) -> Board {
+        Board::with_givens(cells, cells.map(|cell| cell != 0))
+    }
+
+    /// Builds a board from its cells and givens mask, deriving everything else from the cells
+    fn with_givens(cells: [u8; 81], givens: [bool; 81]) -> Board {
+        let mut board = Board {
+            cells,
+            givens,
+            zobrist: zobrist_of(&cells),
+            occupancy: Occupancy::from_cells(&cells),
+            entropy: [FILLED; 81],
+            entropy_buckets: [0; 10],
+        };
+        board.recompute_units();
+        board
+    }
+
+    /// Recounts the digits of every unit from scratch, after the cells changed wholesale
+    fn recompute_units(&mut self) {
+        self.occupancy = Occupancy::from_cells(&self.cells);
+        for index in 0..81 {
+            self.refresh_entropy(index);
+        }
+    }
+
+    /// Moves a cell into the bucket matching its current number of candidates
+    fn refresh_entropy(&mut self, index: usize) {
+        let old = self.entropy[index];
+        if old != FILLED {
+            self.entropy_buckets[old as usize] &= !(1 << index);
+        }
+        self.entropy[index] = match self.candidates_at(index / 9, index % 9) {
+            Some(candidates) => {
+                self.entropy_buckets[candidates.len()] |= 1 << index;
+                candidates.len() as u8
+            }
+            None => FILLED,
+        };
+    }
+
+    /// Retrieves the value of a single cell, 0 meaning the cell is empty
+    pub fn get_cell(&self, row: usize, col: usize) -> u8 {
+        self.cells[row * 9 + col]
+    }
+
+    /// Overwrites the value of a single cell, 0 clearing it
+    pub fn set_cell(&mut self, row: usize, col: usize, value: u8) {
+        self.write_cell(row * 9 + col, value);
+    }
+
+    /// Every change to the cells goes through here so the Zobrist hash stays in sync
+    fn write_cell(&mut self, index: usize, value: u8) {
+        let old = self.cells[index];
+        if old != 0 {
+            self.zobrist ^= ZOBRIST_KEYS[index][old as usize - 1];
+        }
+        if value != 0 {
+            self.zobrist ^= ZOBRIST_KEYS[index][value as usize - 1];
+        }
+        self.cells[index] = value;
+        if old != value {
+            self.occupancy.remove(index, old);
+            self.occupancy.add(index, value);
+            debug_assert_eq!(
+                self.occupancy,
+                Occupancy::from_cells(&self.cells),
+                "unit occupancy drifted from the cells"
+            );
+            self.refresh_entropy(index);
+            for peer in PEERS[index] {
+                self.refresh_entropy(peer);
+            }
+        }
+    }
+
+    /// Candidates of a single cell, `None` if it is already filled in. Reads the digits used by
+    /// its units, so it costs a few bit operations rather than a scan of the peers
+    pub fn candidates_at(&self, row: usize, col: usize) -> Option<CandidateSet> {
+        if self.cells[row * 9 + col] != 0 {
+            return None;
+        }
+        let used = self
+            .occupancy
+            .used(UnitKind::Row, row)
+            .union(self.occupancy.used(UnitKind::Column, col))
+            .union(self.occupancy.used(UnitKind::Tile, (row / 3) * 3 + col / 3));
+        Some(CandidateSet::all().difference(used))
+    }
+
+    /// Zobrist hash of the cells, identical for any two boards with the same cells. It is
+    /// updated incrementally whenever a cell changes, so it is cheap enough to track visited
+    /// states during a search
+    pub fn zobrist_hash(&self) -> u64 {
+        self.zobrist
+    }
+
+    /// The key a cell holding `value` contributes to the Zobrist hash, for callers that want to
+    /// update a hash of their own
+    pub fn zobrist_key(row: usize, col: usize, value: u8) -> u64 {
+        if value == 0 {
+            return 0;
+        }
+        ZOBRIST_KEYS[row * 9 + col][value as usize - 1]
+    }
+
+    /// Whether the cell was filled in as part of the original puzzle
+    pub fn is_given(&self, row: usize, col: usize) -> bool {
+        self.givens[row * 9 + col]
+    }
+
+    /// The puzzle the board started from, holding its givens alone
+    pub fn givens(&self) -> Board {
+        let mut cells = self.cells;
+        for (cell, given) in cells.iter_mut().zip(self.givens) {
+            if !given {
+                *cell = 0;
+            }
+        }
+        Board::with_givens(cells, self.givens)
+    }
+
+    /// Number of clues the puzzle started with, counted from the givens so it stays the same
+    /// while the board is being solved
+    pub fn clue_count(&self) -> usize {
+        self.givens.iter().filter(|given| **given).count()
+    }
+
+    /// Classifies the puzzle by its number of clues
+    pub fn clue_class(&self) -> ClueClass {
+        ClueClass::from_count(self.clue_count())
+    }
+
+    /// Clears filled in cells in a random order until `target_clues` are left, returning how
+    /// many were cleared. Cleared cells stop being givens. Nothing is cleared when the board
+    /// already has no more than `target_clues` filled in
+    pub fn remove_clues(&mut self, rng: &mut impl Rng, target_clues: usize) -> usize {
+        let mut filled: Vec<usize> = (0..81).filter(|&index| self.cells[index] != 0).collect();
+        filled.shuffle(rng);
+        let removals = filled.len().saturating_sub(target_clues);
+        for &index in &filled[..removals] {
+            self.write_cell(index, 0);
+            self.givens[index] = false;
+        }
+        removals
+    }
+
+    /// Keeps exactly the cells where `mask` is true, clearing the rest, so the clues form the
+    /// mask's pattern. With `enforce_unique`, cells outside the mask are put back one at a time
+    /// until the puzzle has a unique solution, each one where two of its solutions differ, then
+    /// the ones that turn out not to be needed are cleared again. [`MaskError::ExtraCellsKept`]
+    /// lists the rest with the board holding the unique puzzle. The board is left untouched on
+    /// every other error
+    pub fn remove_clues_by_mask(
+        &mut self,
+        mask: &[bool; 81],
+        enforce_unique: bool,
+    ) -> Result<(), MaskError> {
+        let kept = mask.iter().filter(|keep| **keep).count();
+        if enforce_unique && kept < MINIMUM_CLUES {
+            return Err(MaskError::TooFewCells(kept));
+        }
+        if let Some(index) = (0..81).find(|&index| mask[index] && self.cells[index] == 0) {
+            return Err(MaskError::EmptyCell {
+                position: (index / 9, index % 9),
+            });
+        }
+
+        let original = self.clone();
+        for index in (0..81).filter(|&index| !mask[index]) {
+            self.write_cell(index, 0);
+            self.givens[index] = false;
+        }
+        if !enforce_unique {
+            return Ok(());
+        }
+        let mut extra = Vec::new();
+        loop {
+            match self.has_unique_solution() {
+                UniquenessResult::Unique(_) => break,
+                UniquenessResult::Multiple { first, second } => {
+                    let index = (0..81).find(|&index| {
+                        self.cells[index] == 0
+                            && original.cells[index] != 0
+                            && first.cells[index] != second.cells[index]
+                    });
+                    // Only cells the board started out without tell the solutions apart
+                    let Some(index) = index else {
+                        *self = original;
+                        return Err(MaskError::NotUnique);
+                    };
+                    self.write_cell(index, original.cells[index]);
+                    self.givens[index] = true;
+                    extra.push((index / 9, index % 9));
+                }
+                UniquenessResult::None => {
+                    *self = original;
+                    return Err(MaskError::NotUnique);
+                }
+            }
+        }
+        // Cells put back early can turn out not to be needed once later ones are in
+        extra.retain(|&(row, col)| {
+            let index = row * 9 + col;
+            self.write_cell(index, 0);
+            if self.count_solutions(2) == 1 {
+                self.givens[index] = false;
+                false
+            } else {
+                self.write_cell(index, original.cells[index]);
+                true
+            }
+        });
+        if extra.is_empty() {
+            Ok(())
+        } else {
+            Err(MaskError::ExtraCellsKept(extra))
+        }
+    }
+
+    /// Empties a cell, refusing to do so if the cell is a given
+    pub fn clear_cell(&mut self, row: usize, col: usize) -> Result<(), GivenCellError> {
+        if self.is_given(row, col) {
+            return Err(GivenCellError {
+                position: (row, col),
+            });
+        }
+        self.write_cell(row * 9 + col, 0);
+        Ok(())
+    }
+
+    /// Copies every filled in cell of `partial` into the empty cells of this board, for example to
+    /// restore a saved game onto a fresh puzzle. Cells this board already has a value for are
+    /// kept. The board is left untouched if the partial disagrees with a given or the result
+    /// breaks the sudoku rules
+    pub fn overlay(&mut self, partial: &Board) -> Result<(), OverlayError> {
+        let mut overlaid = self.clone();
+        for ((row, col), value) in partial.filled_cells() {
+            let current = self.get_cell(row, col);
+            if self.is_given(row, col) && current != value {
+                return Err(OverlayError::DisagreesWithGiven {
+                    position: (row, col),
+                    given: current,
+                    value,
+                });
+            }
+            if current == 0 {
+                overlaid.set_cell(row, col, value);
+            }
+        }
+
+        if let Some(conflict) = overlaid.conflicts().into_iter().next() {
+            return Err(OverlayError::Conflict(conflict));
+        }
+        *self = overlaid;
+        Ok(())
+    }
+
+    /// The 20 cells that share a row, column or tile with the given cell, excluding the cell itself
+    pub fn peers(row: usize, col: usize) -> [(usize, usize); 20] {
+        PEERS[row * 9 + col].map(|peer| (peer / 9, peer % 9))
+    }
+
+    /// Whether two different cells share a row, column or tile
+    pub fn sees(a: (usize, usize), b: (usize, usize)) -> bool {
+        a != b && (a.0 == b.0 || a.1 == b.1 || (a.0 / 3 == b.0 / 3 && a.1 / 3 == b.1 / 3))
+    }
+
+    /// Iterates over the positions of every empty cell, left to right, top to bottom
+    pub fn empty_cells(&self) -> impl Iterator<Item = (usize, usize)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| **cell == 0)
+            .map(|(i, _)| (i / 9, i % 9))
+    }
+
+    /// Iterates over the positions and values of every filled in cell, left to right, top to
+    /// bottom
+    pub fn filled_cells(&self) -> impl Iterator<Item = ((usize, usize), u8)> + '_ {
+        self.cells
+            .iter()
+            .enumerate()
+            .filter(|(_, cell)| **cell != 0)
+            .map(|(i, cell)| ((i / 9, i % 9), *cell))
+    }
+
+    /// Number of cells that have not been filled in yet
+    pub fn count_empty(&self) -> usize {
+        self.empty_cells().count()
+    }
+
+    /// Number of cells that have been filled in
+    pub fn count_filled(&self) -> usize {
+        self.filled_cells().count()
+    }
+
+    /// Retrieves a single row
+    pub fn get_row(&self, row: usize) -> SudokuRow {
+        SudokuRow {
+            cells: self
+                .cells
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| i / 9 == row)
+                .map(|(_, cell)| *cell)
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+        }
+    }
+
+    /// Retrieves a single column
+    pub fn get_column(&self, column: usize) -> SudokuColumn {
+        SudokuColumn {
+            cells: self
+                .cells
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| i % 9 == column)
+                .map(|(_, cell)| *cell)
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+        }
+    }
+
+    /// Retrieves a single 3x3 tile
+    pub fn get_tile(&self, tile: (usize, usize)) -> SudokuTile {
+        SudokuTile {
+            cells: self
+                .cells
+                .iter()
+                .enumerate()
+                .filter(|(i, _)| {
+                    let tile_row = i / 27;
+                    let tile_column = (i % 9) / 3;
+                    tile_row == tile.0 && tile_column == tile.1
+                })
+                .map(|(_, cell)| *cell)
+                .collect::<Vec<_>>()
+                .try_into()
+                .unwrap(),
+        }
+    }
+
+    /// Builds a new board where each cell is taken from the position `source` maps it to.
+    /// `source` must be a permutation of the grid for the result to be meaningful
+    fn permuted(&self, source: impl Fn(usize, usize) -> (usize, usize)) -> Board {
+        let mut cells = [0; 81];
+        let mut givens = [false; 81];
+        for i in 0..81 {
+            let (row, col) = source(i / 9, i % 9);
+            cells[i] = self.cells[row * 9 + col];
+            givens[i] = self.givens[row * 9 + col];
+        }
+        Board::with_givens(cells, givens)
+    }
+
+    /// Reflects the board across its main diagonal, turning rows into columns
+    pub fn transposed(&self) -> Board {
+        self.permuted(|row, col| (col, row))
+    }
+
+    /// Rotates the board a quarter turn clockwise
+    pub fn rotated_90(&self) -> Board {
+        self.permuted(|row, col| (8 - col, row))
+    }
+
+    /// Rotates the board a half turn
+    pub fn rotated_180(&self) -> Board {
+        self.permuted(|row, col| (8 - row, 8 - col))
+    }
+
+    /// Rotates the board a quarter turn counterclockwise
+    pub fn rotated_270(&self) -> Board {
+        self.permuted(|row, col| (col, 8 - row))
+    }
+
+    /// Flips the board left to right, so the first column becomes the last
+    pub fn mirrored_horizontal(&self) -> Board {
+        self.permuted(|row, col| (row, 8 - col))
+    }
+
+    /// Flips the board top to bottom, so the first row becomes the last
+    pub fn mirrored_vertical(&self) -> Board {
+        self.permuted(|row, col| (8 - row, col))
+    }
+
+    /// Finds the symmetry of the pattern formed by the filled in cells, ignoring their values.
+    /// When several symmetries hold, the first one listed in [`SymmetryKind`] is returned
+    pub fn clue_symmetry(&self) -> SymmetryKind {
+        let pattern = Board::from_cells(self.cells.map(|cell| (cell != 0) as u8));
+        let candidates = [
+            (SymmetryKind::Rotational90, pattern.rotated_90()),
+            (SymmetryKind::Rotational180, pattern.rotated_180()),
+            (
+                SymmetryKind::MirrorHorizontal,
+                pattern.mirrored_horizontal(),
+            ),
+            (SymmetryKind::MirrorVertical, pattern.mirrored_vertical()),
+            (SymmetryKind::Diagonal, pattern.transposed()),
+            (
+                SymmetryKind::AntiDiagonal,
+                pattern.transposed().rotated_180(),
+            ),
+        ];
+        candidates
             .into_iter()
+            .find(|(_, transformed)| *transformed == pattern)
+            .map_or(SymmetryKind::None, |(kind, _)| kind)
+    }
+
+    /// Renames every digit on the board, `permutation[d - 1]` being the new name of digit `d`.
+    /// Empty cells are left untouched
+    pub fn relabel_digits(&mut self, permutation: [u8; 9]) {
+        let mut sorted = permutation;
+        sorted.sort_unstable();
+        assert_eq!(
+            sorted,
+            [1, 2, 3, 4, 5, 6, 7, 8, 9],
+            "Digit relabeling must be a permutation of 1 through 9"
+        );
+
+        for cell in self.cells.iter_mut().filter(|cell| **cell != 0) {
+            *cell = permutation[*cell as usize - 1];
+        }
+        self.zobrist = zobrist_of(&self.cells);
+        self.recompute_units();
+    }
+
+    /// Retrieves the three rows of a band, a band being a horizontal group of three tiles
+    pub fn get_band(&self, band: usize) -> [SudokuRow; 3] {
+        assert!(band < 3, "Band index must be below 3");
+        std::array::from_fn(|i| self.get_row(band * 3 + i))
+    }
+
+    /// Retrieves the three columns of a stack, a stack being a vertical group of three tiles
+    pub fn get_stack(&self, stack: usize) -> [SudokuColumn; 3] {
+        assert!(stack < 3, "Stack index must be below 3");
+        std::array::from_fn(|i| self.get_column(stack * 3 + i))
+    }
+
+    /// Overwrites the three rows of a band. Which cells are givens is left unchanged
+    pub fn set_band(&mut self, band: usize, rows: &[SudokuRow]) {
+        assert!(band < 3, "Band index must be below 3");
+        assert_eq!(rows.len(), 3, "A band is made up of exactly 3 rows");
+        for (i, row) in rows.iter().enumerate() {
+            for (col, cell) in row.cells.iter().enumerate() {
+                self.write_cell((band * 3 + i) * 9 + col, *cell);
+            }
+        }
+    }
+
+    /// Overwrites the three columns of a stack. Which cells are givens is left unchanged
+    pub fn set_stack(&mut self, stack: usize, columns: &[SudokuColumn]) {
+        assert!(stack < 3, "Stack index must be below 3");
+        assert_eq!(columns.len(), 3, "A stack is made up of exactly 3 columns");
+        for (i, column) in columns.iter().enumerate() {
+            for (row, cell) in column.cells.iter().enumerate() {
+                self.write_cell(row * 9 + stack * 3 + i, *cell);
+            }
+        }
+    }
+
+    /// Swaps two rows `a` and `b` within the same band. Rows are indexed from the top of the band
+    pub fn swap_rows_in_band(&mut self, band: usize, a: usize, b: usize) {
+        assert!(a < 3 && b < 3, "Row indices must be below 3");
+        let mut rows = self.get_band(band);
+        rows.swap(a, b);
+        self.set_band(band, &rows);
+        self.swap_given_rows(band * 3 + a, band * 3 + b);
+    }
+
+    /// Swaps two columns `a` and `b` within the same stack. Columns are indexed from the left of
+    /// the stack
+    pub fn swap_columns_in_stack(&mut self, stack: usize, a: usize, b: usize) {
+        assert!(a < 3 && b < 3, "Column indices must be below 3");
+        let mut columns = self.get_stack(stack);
+        columns.swap(a, b);
+        self.set_stack(stack, &columns);
+        self.swap_given_columns(stack * 3 + a, stack * 3 + b);
+    }
+
+    /// Swaps two bands, each one being a horizontal group of three tiles
+    pub fn swap_bands(&mut self, a: usize, b: usize) {
+        let band_a = self.get_band(a);
+        let band_b = self.get_band(b);
+        self.set_band(a, &band_b);
+        self.set_band(b, &band_a);
+        for offset in 0..3 {
+            self.swap_given_rows(a * 3 + offset, b * 3 + offset);
+        }
+    }
+
+    /// Swaps two stacks, each one being a vertical group of three tiles
+    pub fn swap_stacks(&mut self, a: usize, b: usize) {
+        let stack_a = self.get_stack(a);
+        let stack_b = self.get_stack(b);
+        self.set_stack(a, &stack_b);
+        self.set_stack(b, &stack_a);
+        for offset in 0..3 {
+            self.swap_given_columns(a * 3 + offset, b * 3 + offset);
+        }
+    }
+
+    fn swap_given_rows(&mut self, a: usize, b: usize) {
+        for col in 0..9 {
+            self.givens.swap(a * 9 + col, b * 9 + col);
+        }
+    }
+
+    fn swap_given_columns(&mut self, a: usize, b: usize) {
+        for row in 0..9 {
+            self.givens.swap(row * 9 + a, row * 9 + b);
+        }
+    }
+
+    /// Formats the board and prints it out to the console
+    pub fn draw_board(&self, stdout: &mut Stdout) {
+        self.draw_board_with_options(stdout, &DrawOptions::default());
+    }
+
+    /// Formats the board according to the options and prints it out to any writer
+    pub fn draw_board_with_options<W: Write>(&self, out: &mut W, options: &DrawOptions) {
+        out.queue(style::Print(self.render_board(options))).unwrap();
+        out.flush().unwrap();
+    }
+
+    /// Formats the board into a plain string, one line per row of the drawing, suitable for
+    /// output that does not go to a terminal
+    pub fn render_board(&self, options: &DrawOptions) -> String {
+        let temp_row = self.get_row(0).to_string();
+        let (_, temp_row_mid) = temp_row.split_at(VERTICAL_LINE.len());
+        let (temp_row_mid, _) = temp_row_mid.split_at(temp_row_mid.len() - VERTICAL_LINE.len());
+        let separator = |connector: &str| {
+            temp_row_mid
+                .chars()
+                .map(|char| {
+                    if char.to_string() == *VERTICAL_LINE {
+                        connector.to_string()
+                    } else {
+                        HORIZONTAL_LINE.to_string()
+                    }
+                })
+                .collect::<String>()
+        };
+
+        // Row labels take up two characters on the left, so every line without one is padded
+        let padding = if options.coordinates == CoordinateStyle::None {
+            ""
+        } else {
+            "  "
+        };
+        let mut output = String::new();
+        if options.coordinates != CoordinateStyle::None {
+            let column_labels = (0..9)
+                .map(|i| {
+                    let prefix = if i % 3 == 0 { "  " } else { "" };
+                    format!("{prefix}{} ", options.coordinates.column_label(i))
+                })
+                .collect::<String>();
+            output += &format!("{padding}{}\n", column_labels.trim_end());
+        }
+        output += &format!(
+            "{padding}{TOP_LEFT_CONNECTOR}{}{TOP_RIGHT_CONNECTOR}\n",
+            separator(DOWN_T_CONNECTOR)
+        );
+        for i in 0..9 {
+            if i % 3 == 0 && i != 0 && i != 8 {
+                output += &format!(
+                    "{padding}{RIGHT_T_CONNECTOR}{}{LEFT_T_CONNECTOR}\n",
+                    separator(PLUS_CONNECTOR)
+                );
+            }
+            let label = match options.coordinates {
+                CoordinateStyle::None => String::new(),
+                coordinates => format!("{} ", coordinates.row_label(i)),
+            };
+            output += &format!("{label}{}\n", self.render_row(i, &options.palette));
+        }
+        output += &format!(
+            "{padding}{BOTTOM_LEFT_CONNECTOR}{}{BOTTOM_RIGHT_CONNECTOR}\n",
+            separator(UP_T_CONNECTOR)
+        );
+        output
+    }
+
+    /// Draws a row of the board like [`SudokuRow`] does, each digit styled by the role of its cell
+    fn render_row(&self, row: usize, palette: &Palette) -> String {
+        if palette.is_plain() {
+            return self.get_row(row).to_string();
+        }
+        let conflicting: Vec<(usize, usize)> = self
+            .conflicts()
+            .iter()
+            .flat_map(|conflict| [conflict.first, conflict.second])
+            .collect();
+        let cells: [String; 9] = std::array::from_fn(|col| {
+            let value = self.get_cell(row, col);
+            if value == 0 {
+                return " ".to_string();
+            }
+            let style = if conflicting.contains(&(row, col)) {
+                palette.conflict
+            } else if self.is_given(row, col) {
+                palette.given
+            } else {
+                palette.solved
+            };
+            style.apply(value).to_string()
+        });
+        lay_out_row(&cells)
+    }
+
+    /// Entropy is defined as all the states that a cell could be in which it is considered valid.
+    /// The entropy is calculated by filtering out every value already present in one of the cell's
+    /// peers, which perfectly fits the rules of sudoku
+    pub fn calculate_entropy_at_cell(&self, row: usize, col: usize) -> Option<Vec<u8>> {
+        Some(self.candidates_at(row, col)?.iter().collect())
+    }
+
+    /// Lists every pair of cells that currently break the sudoku rules. A pair that shares more
+    /// than one unit is reported once for each of them. Empty cells never conflict
+    pub fn conflicts(&self) -> Vec<Conflict> {
+        let mut conflicts = Vec::new();
+        if self.occupancy.duplicates == 0 {
+            return conflicts;
+        }
+        for unit in UnitKind::ALL {
+            for index in 0..9 {
+                if !self.occupancy.has_duplicates_in(unit, index) {
+                    continue;
+                }
+                let positions = unit.positions(index);
+                for (i, first) in positions.iter().enumerate() {
+                    let value = self.get_cell(first.0, first.1);
+                    if value == 0 {
+                        continue;
+                    }
+                    for second in &positions[i + 1..] {
+                        if self.get_cell(second.0, second.1) == value {
+                            conflicts.push(Conflict {
+                                first: *first,
+                                second: *second,
+                                unit,
+                                value,
+                            });
+                        }
+                    }
+                }
+            }
+        }
+        conflicts
+    }
+
+    /// Checks a proposed solution to this puzzle, listing every given it changes to another
+    /// value, then every value it repeats within a unit, then every cell it leaves empty. An
+    /// empty list means the solution is complete, follows the sudoku rules and keeps the givens
+    pub fn check_solution(&self, solution: &Board) -> Vec<Discrepancy> {
+        let mut discrepancies: Vec<Discrepancy> = self
+            .filled_cells()
+            .filter(|((row, col), _)| self.is_given(*row, *col))
+            .filter_map(|((row, col), given)| {
+                let value = solution.get_cell(row, col);
+                (value != 0 && value != given).then_some(Discrepancy::ChangedGiven {
+                    position: (row, col),
+                    given,
+                    value,
+                })
+            })
             .collect();
+        for unit in UnitKind::ALL {
+            for index in 0..9 {
+                let mut counts = [0; 10];
+                for (row, col) in unit.positions(index) {
+                    counts[solution.get_cell(row, col) as usize] += 1;
+                }
+                discrepancies.extend(
+                    (1..=9)
+                        .filter(|value| counts[*value as usize] > 1)
+                        .map(|value| Discrepancy::Duplicate { unit, index, value }),
+                );
+            }
+        }
+        discrepancies.extend(
+            solution
+                .empty_cells()
+                .map(|position| Discrepancy::EmptyCell { position }),
+        );
+        discrepancies
+    }
 
-        last_cell_entropy.remove(&last_move.new_value);
+    /// Whether the board is completely filled in and every cell agrees with the given solution
+    pub fn matches_solution(&self, solution: &Board) -> bool {
+        self.count_empty() == 0 && self.differs_from_solution(solution).is_empty()
+    }
 
-        let cell_subsitute_opt = last_cell_entropy
+    /// Lists every cell whose value differs in `other`, treating `self` as the old board
+    pub fn diff(&self, other: &Board) -> Vec<CellDiff> {
+        self.cells
             .iter()
-            .map(|possible_value| {
-                self.cells[last_move_position_index] = *possible_value;
-                (possible_value, self.find_least_entropy())
+            .zip(other.cells.iter())
+            .enumerate()
+            .filter(|(_, (old, new))| old != new)
+            .map(|(i, (old, new))| CellDiff {
+                position: (i / 9, i % 9),
+                old_value: *old,
+                new_value: *new,
             })
-            .filter(|(_, x)| {
-                if x.is_none() {
-                    return false;
-                }
+            .collect()
+    }
 
-                !x.as_ref().unwrap().1.is_empty()
-            })
-            .map(|(val, next_data)| (*val, next_data.unwrap()))
-            .choose(rng);
-
-        if let Some((substitute_val, next_data)) = cell_subsitute_opt {
-            println!("Substitute for {last_move:?}: {substitute_val}, next pos and entropy: {next_data:?}");
-            let new_move = BoardMove {
-                position: last_move_position,
-                new_value: substitute_val,
-                cascades: Vec::new(),
+    /// Positions of the filled in cells that disagree with the given solution. Empty cells are
+    /// not reported
+    pub fn differs_from_solution(&self, solution: &Board) -> Vec<(usize, usize)> {
+        self.filled_cells()
+            .filter(|((row, col), value)| solution.get_cell(*row, *col) != *value)
+            .map(|(position, _)| position)
+            .collect()
+    }
+
+    /// Calculates the candidates of every cell at once, `None` marking cells that are already
+    /// filled in. Built from the digits kept for every unit, so no cells are scanned
+    pub fn candidates_grid(&self) -> [[Option<CandidateSet>; 9]; 9] {
+        let mut grid = [[None; 9]; 9];
+        for (row, col) in self.empty_cells() {
+            grid[row][col] = self.candidates_at(row, col);
+        }
+        grid
+    }
+
+    /// Gathers entropy statistics over every empty cell of the board in a single scan
+    pub fn entropy_summary(&self) -> EntropySummary {
+        let mut summary = EntropySummary {
+            cells_by_entropy: [0; 10],
+            contradictions: Vec::new(),
+            total_candidates: 0,
+            least_entropy: None,
+        };
+        let grid = self.candidates_grid();
+        for (row, col) in self.empty_cells() {
+            let candidates = grid[row][col].unwrap();
+            summary.cells_by_entropy[candidates.len()] += 1;
+            summary.total_candidates += candidates.len();
+            if candidates.is_empty() {
+                summary.contradictions.push((row, col));
+            }
+            match summary.least_entropy {
+                Some((_, least)) if least.len() <= candidates.len() => {}
+                _ => summary.least_entropy = Some(((row, col), candidates)),
+            }
+        }
+        summary
+    }
+
+    /// Searches for a cell with the least entropy. The lowest entropy equates to the highest confidence
+    pub fn find_least_entropy(&self) -> Option<((usize, usize), Vec<u8>)> {
+        let (position, candidates) = self.least_entropy_cell()?;
+        Some((position, candidates.iter().collect()))
+    }
+
+    /// The first cell with the fewest candidates along with them, read off the cached buckets
+    fn least_entropy_cell(&self) -> Option<((usize, usize), CandidateSet)> {
+        let bucket = self.entropy_buckets.iter().find(|bucket| **bucket != 0)?;
+        let index = bucket.trailing_zeros() as usize;
+        let position = (index / 9, index % 9);
+        let candidates = self.candidates_at(position.0, position.1).unwrap();
+        debug_assert_eq!(
+            Some((position, candidates)),
+            self.scan_least_entropy_fresh(),
+            "the entropy buckets are out of sync with the cells"
+        );
+        Some((position, candidates))
+    }
+
+    /// Finds the least entropy cell by looking at every empty cell, which the cached buckets are
+    /// checked against in debug builds
+    fn scan_least_entropy_fresh(&self) -> Option<((usize, usize), CandidateSet)> {
+        let mut least: Option<((usize, usize), CandidateSet)> = None;
+        for (row, col) in self.empty_cells() {
+            let candidates = self.candidates_at(row, col).unwrap();
+            match least {
+                Some((_, current)) if current.len() <= candidates.len() => {}
+                _ => least = Some(((row, col), candidates)),
+            }
+            // Nothing beats a cell without any options
+            if candidates.is_empty() {
+                break;
+            }
+        }
+        least
+    }
+
+    /// Same as [`Board::find_least_entropy`], counting the scan towards the report
+    fn scan_least_entropy(&self, report: &mut SolveReport) -> Option<((usize, usize), Vec<u8>)> {
+        report.entropy_scans += 1;
+        self.find_least_entropy()
+    }
+
+    /// Same as [`Board::scan_least_entropy`], picking between the cells tied for the fewest
+    /// candidates as `selection` says
+    fn select_least_entropy(
+        &self,
+        selection: CellSelection,
+        rng: &mut (impl Rng + ?Sized),
+        report: &mut SolveReport,
+    ) -> Option<((usize, usize), Vec<u8>)> {
+        if selection == CellSelection::First {
+            return self.scan_least_entropy(report);
+        }
+        report.entropy_scans += 1;
+        let bucket = *self.entropy_buckets.iter().find(|bucket| **bucket != 0)?;
+        let tied = (0..81).filter(|index| bucket & (1 << index) != 0);
+        let index = if selection == CellSelection::Degree {
+            let degree = |index: &usize| {
+                PEERS[*index]
+                    .iter()
+                    .filter(|peer| self.cells[**peer] == 0)
+                    .count()
             };
-            previous_moves.push(new_move);
-            return Some(next_data);
+            let most = tied.clone().map(|index| degree(&index)).max()?;
+            tied.filter(|index| degree(index) == most).choose(rng)?
         } else {
-            println!("Recursing");
-            self.backtrack(previous_moves, rng);
+            tied.choose(rng)?
+        };
+        let position = (index / 9, index % 9);
+        let candidates = self.candidates_at(position.0, position.1).unwrap();
+        Some((position, candidates.iter().collect()))
+    }
+
+    /// Orders the values of the empty cell at `position` by how many candidates of its peers
+    /// each one rules out, fewest first. Values that rule out as many keep their order, or are
+    /// shuffled with `rng` when `shuffle_ties` is set
+    fn order_least_constraining(
+        &self,
+        (row, col): (usize, usize),
+        mut values: Vec<u8>,
+        shuffle_ties: bool,
+        rng: &mut (impl Rng + ?Sized),
+    ) -> Vec<u8> {
+        if shuffle_ties {
+            values.shuffle(rng);
+        }
+        let peers = Board::peers(row, col);
+        values.sort_by_key(|value| {
+            peers
+                .iter()
+                .filter(|(row, col)| {
+                    self.candidates_at(*row, *col)
+                        .is_some_and(|candidates| candidates.contains(*value))
+                })
+                .count()
+        });
+        values
+    }
+
+    /// Solves the sudoku puzzle. Iteratively searches for the cell with least entropy, promptly
+    /// collapsing it to a single possibility, and backtracks whenever that runs into a dead end.
+    /// The report holds the moves made along the way, so they can be stored or replayed, along
+    /// with counts of how much work the solve took. Substitutes picked while backtracking are
+    /// drawn from `rng`, so a seeded generator makes the solve reproducible
+    pub fn solve_board(
+        &mut self,
+        rng: &mut (impl Rng + ?Sized),
+        observer: &mut (impl SolveObserver + ?Sized),
+    ) -> Result<SolveReport, SolveError> {
+        self.solve_board_with_limits(rng, observer, &SolveLimits::default())
+    }
+
+    /// Same as [`Board::solve_board`], giving up with [`SolveError::LimitReached`] once the solve
+    /// goes past any of the limits. They are checked before every guess and every backtrack
+    pub fn solve_board_with_limits(
+        &mut self,
+        rng: &mut (impl Rng + ?Sized),
+        observer: &mut (impl SolveObserver + ?Sized),
+        limits: &SolveLimits,
+    ) -> Result<SolveReport, SolveError> {
+        self.solve_board_with_options(rng, observer, limits, SolveOptions::default())
+    }
+
+    /// Same as [`Board::solve_board_with_limits`], making its choices as the options say.
+    ///
+    /// Every restart takes back all the moves made so far, and from then on ties between equally
+    /// good values are broken with `rng` so the solver heads down a different path. Once out of
+    /// restarts the board is solved with [`Board::solve_dfs`] instead, whose report is added to
+    /// this one. Ties between cells are broken as [`CellSelection`] describes
+    pub fn solve_board_with_options(
+        &mut self,
+        rng: &mut (impl Rng + ?Sized),
+        observer: &mut (impl SolveObserver + ?Sized),
+        limits: &SolveLimits,
+        options: SolveOptions,
+    ) -> Result<SolveReport, SolveError> {
+        let state = SolveState::new(MoveHistory::new(), options);
+        let conflicts = self.conflicts();
+        if !conflicts.is_empty() {
+            return Err(SolveError::InvalidGivens(conflicts));
+        }
+        if let Some(Contradiction { cell, .. }) = self.find_contradiction() {
+            return Err(SolveError::Contradiction { cell });
+        }
+        self.run_solve(state, rng, observer, limits)
+    }
+
+    /// Picks a solve back up from the moves it had made, as kept in the report of a solve that
+    /// was stopped by its limits or cancelled. The board has to hold every value of the history,
+    /// none of them on a given, otherwise [`SolveError::HistoryMismatch`] names the first cell
+    /// that differs. Values tried before are not tried again when backtracking, as long as the
+    /// history is the one from the report. Moves parsed back from text have no record of it, so
+    /// backtracking tries every other digit that fits
+    pub fn resume_solve(
+        &mut self,
+        history: MoveHistory,
+        rng: &mut (impl Rng + ?Sized),
+        observer: &mut (impl SolveObserver + ?Sized),
+        limits: &SolveLimits,
+    ) -> Result<SolveReport, SolveError> {
+        let conflicts = self.conflicts();
+        if !conflicts.is_empty() {
+            return Err(SolveError::InvalidGivens(conflicts));
+        }
+        for board_move in history.iter() {
+            let placements = std::iter::once((board_move.position, board_move.new_value)).chain(
+                board_move
+                    .cascades
+                    .iter()
+                    .map(|cascade| (cascade.position, cascade.value)),
+            );
+            for ([row, col], value) in placements {
+                if self.get_cell(row, col) != value || self.is_given(row, col) {
+                    return Err(SolveError::HistoryMismatch { cell: (row, col) });
+                }
+            }
+        }
+        let state = SolveState::new(history, SolveOptions::default());
+        self.run_solve(state, rng, observer, limits)
+    }
+
+    /// The main loop of the entropy solver, carrying on from whatever state it is given
+    fn run_solve(
+        &mut self,
+        state: SolveState,
+        rng: &mut (impl Rng + ?Sized),
+        observer: &mut (impl SolveObserver + ?Sized),
+        limits: &SolveLimits,
+    ) -> Result<SolveReport, SolveError> {
+        let SolveState {
+            mut previous_moves,
+            mut report,
+            start_time,
+            options,
+            mut restarted_at,
+        } = state;
+        let (policy, selection, ordering, propagation) = (
+            options.restarts,
+            options.cell_selection,
+            options.value_ordering,
+            options.propagation,
+        );
+        let mut propagated =
+            self.propagate_into_history(&mut previous_moves, &mut report, propagation, observer);
+        'solve: loop {
+            let limit = limits.exceeded(&report, start_time.elapsed());
+            if let Some(limit) = limit {
+                report.history = previous_moves;
+                report.elapsed = start_time.elapsed();
+                return Err(SolveError::stopped(limit, report));
+            }
+            if let Err(Contradiction { cell, .. }) = propagated {
+                observer.on_dead_end(self, cell);
+                report.backtracks += 1;
+                if report.backtracks - restarted_at > policy.backtrack_threshold {
+                    while let Some(board_move) = previous_moves.pop() {
+                        self.undo_move(&board_move);
+                        observer.on_backtrack(self, &board_move);
+                    }
+                    if report.restarts >= policy.max_restarts {
+                        return self.finish_with_dfs(report, start_time, observer, limits);
+                    }
+                    report.restarts += 1;
+                    restarted_at = report.backtracks;
+                    propagated = self.propagate_into_history(
+                        &mut previous_moves,
+                        &mut report,
+                        propagation,
+                        observer,
+                    );
+                    continue;
+                }
+                let cancellation = limits.cancellation.as_ref();
+                match self.backtrack(&mut previous_moves, &mut report, cancellation, observer) {
+                    BacktrackOutcome::Cancelled => {
+                        report.history = previous_moves;
+                        report.elapsed = start_time.elapsed();
+                        return Err(SolveError::stopped(Limit::Cancelled, report));
+                    }
+                    outcome => outcome.into_result(cell, report.backtracks)?,
+                }
+                propagated = self.propagate_into_history(
+                    &mut previous_moves,
+                    &mut report,
+                    propagation,
+                    observer,
+                );
+                continue;
+            }
+
+            // Singles were all filled in by the propagation, so every cell left has several
+            // options or none at all
+            let least_entropy = self.select_least_entropy(selection, rng, &mut report);
+            let Some(((row, col), options)) = least_entropy else {
+                break;
+            };
+            let cell_index = row * 9 + col;
+            let ordered = match ordering {
+                ValueOrdering::FewestOptions => {
+                    let mut valid_options = Vec::with_capacity(options.len());
+                    for value in options {
+                        self.write_cell(cell_index, value);
+                        let next_entropy = self.scan_least_entropy(&mut report);
+                        // Every value is probed against the same clean state
+                        self.write_cell(cell_index, 0);
+
+                        if let Some(entropy) = next_entropy {
+                            if !entropy.1.is_empty() {
+                                valid_options.push((value, entropy.1.len()));
+                            }
+                        } else {
+                            self.write_cell(cell_index, value);
+                            report.placements += 1;
+                            report.guesses += 1;
+                            observer.on_place(self, (row, col), value);
+                            previous_moves.push(BoardMove::new([row, col], value));
+                            // This value completed the board
+                            break 'solve;
+                        }
+                    }
+                    // The value leaving the fewest options at the next cell goes first, the
+                    // others are kept as substitutes in random order
+                    let fewest = valid_options.iter().map(|(_, next)| *next).min();
+                    let mut tied = valid_options
+                        .iter()
+                        .filter(|(_, next)| Some(*next) == fewest)
+                        .map(|(value, _)| *value);
+                    let first = if report.restarts == 0 {
+                        tied.next()
+                    } else {
+                        tied.choose(rng)
+                    };
+                    let mut substitutes: Vec<u8> = valid_options
+                        .into_iter()
+                        .map(|(value, _)| value)
+                        .filter(|value| Some(*value) != first)
+                        .collect();
+                    substitutes.shuffle(rng);
+                    first.into_iter().chain(substitutes).collect()
+                }
+                ValueOrdering::LeastConstraining => {
+                    self.order_least_constraining((row, col), options, report.restarts > 0, rng)
+                }
+                ValueOrdering::Random => {
+                    let mut options = options;
+                    options.shuffle(rng);
+                    options
+                }
+            };
+            let mut ordered = ordered.into_iter();
+            let Some(value) = ordered.next() else {
+                // Every option leads straight into a dead end
+                propagated = Err(Contradiction {
+                    cell: (row, col),
+                    missing: None,
+                });
+                continue;
+            };
+            self.write_cell(cell_index, value);
+            report.placements += 1;
+            report.guesses += 1;
+            observer.on_place(self, (row, col), value);
+            previous_moves.push(BoardMove::guess([row, col], value, ordered.collect()));
+            propagated = self.propagate_into_history(
+                &mut previous_moves,
+                &mut report,
+                propagation,
+                observer,
+            );
+        }
+        self.verify_solved()?;
+        report.history = previous_moves;
+        report.elapsed = start_time.elapsed();
+        Ok(report)
+    }
+
+    /// Hands a board the entropy solver gave up on over to the exhaustive solver, with whatever
+    /// is left of the time limit, adding the work it does to `report`
+    fn finish_with_dfs(
+        &mut self,
+        mut report: SolveReport,
+        start_time: std::time::Instant,
+        observer: &mut (impl SolveObserver + ?Sized),
+        limits: &SolveLimits,
+    ) -> Result<SolveReport, SolveError> {
+        let remaining = SolveLimits {
+            max_duration: limits
+                .max_duration
+                .map(|max| max.saturating_sub(start_time.elapsed())),
+            ..limits.clone()
+        };
+        let exhaustive = self.solve_dfs_with_limits(observer, &remaining)?;
+        report.history = exhaustive.history;
+        report.placements += exhaustive.placements;
+        report.forced_singles += exhaustive.forced_singles;
+        report.guesses += exhaustive.guesses;
+        report.backtracks += exhaustive.backtracks;
+        report.max_backtrack_depth = report
+            .max_backtrack_depth
+            .max(exhaustive.max_backtrack_depth);
+        report.propagation_failures += exhaustive.propagation_failures;
+        report.entropy_scans += exhaustive.entropy_scans;
+        report.elapsed = start_time.elapsed();
+        Ok(report)
+    }
+
+    /// Repeatedly fills in every cell that has a single candidate left, until none remain. The
+    /// placements are returned in order, so they can be recorded as the cascades of a move. When
+    /// a cell runs out of candidates along the way, the placements are taken back and the cell is
+    /// returned instead
+    pub fn propagate_singles(&mut self) -> Result<Vec<Cascade>, Contradiction> {
+        let mut placed = Vec::new();
+        match self.fill_singles(&mut placed, &mut NoopObserver) {
+            Ok(()) => Ok(placed),
+            Err(contradiction) => {
+                for cascade in placed.iter().rev() {
+                    let [row, col] = cascade.position;
+                    self.write_cell(row * 9 + col, 0);
+                }
+                Err(contradiction)
+            }
+        }
+    }
+
+    /// Fills in singles like [`Board::propagate_singles`], pushing each placement onto `placed`
+    /// as it goes. Placements are left on the board when a contradiction is found
+    fn fill_singles(
+        &mut self,
+        placed: &mut Vec<Cascade>,
+        observer: &mut (impl SolveObserver + ?Sized),
+    ) -> Result<(), Contradiction> {
+        loop {
+            if self.entropy_buckets[0] != 0 {
+                let index = self.entropy_buckets[0].trailing_zeros() as usize;
+                return Err(Contradiction {
+                    cell: (index / 9, index % 9),
+                    missing: None,
+                });
+            }
+            if self.entropy_buckets[1] == 0 {
+                return Ok(());
+            }
+            let index = self.entropy_buckets[1].trailing_zeros() as usize;
+            let (row, col) = (index / 9, index % 9);
+            let value = self.candidates_at(row, col).unwrap().iter().next().unwrap();
+            self.write_cell(index, value);
+            observer.on_cascade(self, (row, col), value);
+            placed.push(Cascade::new([row, col], value));
+        }
+    }
+
+    /// Fills in singles like [`Board::fill_singles`], along with every digit that has a single
+    /// place left in one of its units, until neither is left. A digit without any place left in
+    /// a unit is a contradiction as well
+    fn fill_singles_and_homes(
+        &mut self,
+        placed: &mut Vec<Cascade>,
+        observer: &mut (impl SolveObserver + ?Sized),
+    ) -> Result<(), Contradiction> {
+        loop {
+            self.fill_singles(placed, observer)?;
+            let Some(home) = self.find_single_home()? else {
+                return Ok(());
+            };
+            let [row, col] = home.position;
+            self.write_cell(row * 9 + col, home.value);
+            observer.on_cascade(self, (row, col), home.value);
+            placed.push(home);
+        }
+    }
+
+    /// The first digit left with a single place in one of its units, along with that place. A
+    /// digit left without any place is returned as a contradiction instead
+    fn find_single_home(&self) -> Result<Option<Cascade>, Contradiction> {
+        for unit in UnitKind::ALL {
+            for index in 0..9 {
+                let positions = unit.positions(index);
+                let missing = CandidateSet::all().difference(self.occupancy.used(unit, index));
+                for digit in missing.iter() {
+                    let mut homes = positions.iter().filter(|(row, col)| {
+                        self.candidates_at(*row, *col)
+                            .is_some_and(|candidates| candidates.contains(digit))
+                    });
+                    match (homes.next(), homes.next()) {
+                        (None, _) => {
+                            let cell = positions
+                                .into_iter()
+                                .find(|(row, col)| self.get_cell(*row, *col) == 0)
+                                .unwrap_or(positions[0]);
+                            return Err(Contradiction {
+                                cell,
+                                missing: Some((digit, unit, index)),
+                            });
+                        }
+                        (Some(&(row, col)), None) => {
+                            return Ok(Some(Cascade::new([row, col], digit)))
+                        }
+                        _ => {}
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Propagates as far as `level` goes during a solve, recording the placements as cascades of
+    /// the latest move. Placements forced before the first guess hang off a root move, so every
+    /// placement is owned by a move that can be undone and replayed. Placements are recorded even
+    /// when propagation runs into a contradiction, so backtracking takes them back off along with
+    /// their move
+    fn propagate_into_history(
+        &mut self,
+        previous_moves: &mut MoveHistory,
+        report: &mut SolveReport,
+        level: PropagationLevel,
+        observer: &mut (impl SolveObserver + ?Sized),
+    ) -> Result<(), Contradiction> {
+        let mut placed = Vec::new();
+        let result = match level {
+            PropagationLevel::None => Ok(()),
+            PropagationLevel::ForwardCheck => self.fill_singles(&mut placed, observer),
+            PropagationLevel::Ac3 => self.fill_singles_and_homes(&mut placed, observer),
+        };
+        if result.is_err() {
+            report.propagation_failures += 1;
+        }
+        report.placements += placed.len();
+        report.forced_singles += placed.len();
+        for cascade in placed {
+            match previous_moves.last_mut() {
+                Some(last) => last.push_cascade(cascade),
+                None => previous_moves.push(BoardMove::forced(cascade.position, cascade.value)),
+            }
+        }
+        result
+    }
+
+    /// Looks for a quick proof that the board has no solution. Singles, locked candidates and
+    /// pairs are propagated on a copy of the board until a cell runs out of candidates or a unit
+    /// has no place left for one of its digits. `None` does not mean the board can be solved,
+    /// only that propagation alone did not rule it out
+    pub fn find_contradiction(&self) -> Option<Contradiction> {
+        let techniques = TechniqueSet::up_to("Hidden pair").expect("hidden pairs are a technique");
+        let mut board = self.clone();
+        let mut grid = CandidatesGrid::from_board(self);
+        loop {
+            if let Some(contradiction) = board.find_unplaceable(&grid) {
+                return Some(contradiction);
+            }
+            let single = find_naked_singles(&grid)
+                .into_iter()
+                .next()
+                .or_else(|| find_hidden_singles_in(&grid).into_iter().next());
+            if let Some(single) = single {
+                let (row, col) = single.position;
+                board.write_cell(row * 9 + col, single.value);
+                grid.place(row, col, single.value);
+            } else if let Some((_, elimination)) = techniques.find_next_elimination(&grid) {
+                grid.apply_elimination(&elimination);
+            } else {
+                return None;
+            }
+        }
+    }
+
+    /// The first cell of `grid` without any candidate, or else the first digit that is neither
+    /// placed in a unit nor a candidate of any of its cells
+    fn find_unplaceable(&self, grid: &CandidatesGrid) -> Option<Contradiction> {
+        if let Some((cell, _)) = grid
+            .open_cells()
+            .find(|(_, candidates)| candidates.is_empty())
+        {
+            return Some(Contradiction {
+                cell,
+                missing: None,
+            });
+        }
+        for unit in UnitKind::ALL {
+            for index in 0..9 {
+                let positions = unit.positions(index);
+                let homes = positions
+                    .iter()
+                    .filter_map(|&(row, col)| grid.get(row, col))
+                    .fold(self.occupancy.used(unit, index), |homes, candidates| {
+                        homes.union(candidates)
+                    });
+                if let Some(digit) = CandidateSet::all().difference(homes).iter().next() {
+                    let cell = positions
+                        .into_iter()
+                        .find(|&(row, col)| grid.get(row, col).is_some())
+                        .unwrap_or(positions[0]);
+                    return Some(Contradiction {
+                        cell,
+                        missing: Some((digit, unit, index)),
+                    });
+                }
+            }
         }
         None
     }
+
+    /// Checks that the solver really completed the board, explaining what is wrong otherwise
+    fn verify_solved(&self) -> Result<(), SolveError> {
+        if let Some(cell) = self.empty_cells().next() {
+            let cell = self
+                .entropy_summary()
+                .contradictions
+                .first()
+                .copied()
+                .unwrap_or(cell);
+            return Err(SolveError::Contradiction { cell });
+        }
+        if let Some(conflict) = self.conflicts().first() {
+            return Err(SolveError::Contradiction {
+                cell: conflict.first,
+            });
+        }
+        Ok(())
+    }
+
+    /// Solves the board with plain depth first search. Each guess goes on the cell with the least
+    /// entropy, the singles it forces are filled in as its cascades, and a guess that leads to a
+    /// dead end is taken back before trying the next candidate. Unlike [`Board::solve_board`] the
+    /// search is guaranteed to find a solution when one exists, and to prove there is none
+    /// otherwise, in which case the board is left as it was
+    pub fn solve_dfs(
+        &mut self,
+        observer: &mut (impl SolveObserver + ?Sized),
+    ) -> Result<SolveReport, SolveError> {
+        self.solve_dfs_with_limits(observer, &SolveLimits::default())
+    }
+
+    /// Same as [`Board::solve_dfs`], giving up with [`SolveError::LimitReached`] once the search
+    /// goes past any of the limits. They are checked before every value tried
+    pub fn solve_dfs_with_limits(
+        &mut self,
+        observer: &mut (impl SolveObserver + ?Sized),
+        limits: &SolveLimits,
+    ) -> Result<SolveReport, SolveError> {
+        let start_time = std::time::Instant::now();
+        let conflicts = self.conflicts();
+        if !conflicts.is_empty() {
+            return Err(SolveError::InvalidGivens(conflicts));
+        }
+        if let Some(Contradiction { cell, .. }) = self.find_contradiction() {
+            return Err(SolveError::Contradiction { cell });
+        }
+
+        let mut report = SolveReport::default();
+        let mut unwound = 0;
+        let first = self.scan_least_entropy(&mut report);
+        let limited = (limits, start_time);
+        match self.solve_dfs_from(first, &mut report, &mut unwound, limited, observer) {
+            Ok(true) => {}
+            Ok(false) => {
+                return Err(SolveError::Exhausted {
+                    backtracks: report.backtracks,
+                })
+            }
+            Err(limit) => {
+                report.elapsed = start_time.elapsed();
+                return Err(SolveError::stopped(limit, report));
+            }
+        }
+        self.verify_solved()?;
+        report.elapsed = start_time.elapsed();
+        Ok(report)
+    }
+
+    /// Tries every candidate of the least entropy cell in turn, recursing after each one. Returns
+    /// whether the board was solved, every move made being taken back otherwise. `unwound` counts
+    /// the moves taken back since the last placement, for the report's backtrack depth. Going
+    /// past a limit stops the search where it is, leaving its moves on the board
+    fn solve_dfs_from(
+        &mut self,
+        least_entropy: Option<((usize, usize), Vec<u8>)>,
+        report: &mut SolveReport,
+        unwound: &mut usize,
+        (limits, start_time): (&SolveLimits, std::time::Instant),
+        observer: &mut (impl SolveObserver + ?Sized),
+    ) -> Result<bool, Limit> {
+        let Some(((row, col), candidates)) = least_entropy else {
+            return Ok(true);
+        };
+        if candidates.is_empty() {
+            observer.on_dead_end(self, (row, col));
+            report.backtracks += 1;
+            return Ok(false);
+        }
+
+        for value in candidates.iter().copied() {
+            if let Some(limit) = limits.exceeded(report, start_time.elapsed()) {
+                return Err(limit);
+            }
+            let mut board_move = BoardMove::new([row, col], value);
+            self.write_cell(row * 9 + col, value);
+            report.placements += 1;
+            *unwound = 0;
+            if candidates.len() == 1 {
+                report.forced_singles += 1;
+                observer.on_cascade(self, (row, col), value);
+            } else {
+                report.guesses += 1;
+                observer.on_place(self, (row, col), value);
+            }
+
+            // Fill in the singles this value forces, stopping at the next real choice
+            let mut next = self.scan_least_entropy(report);
+            while let Some(((row, col), single)) = next
+                .as_ref()
+                .filter(|(_, candidates)| candidates.len() == 1)
+                .map(|(position, candidates)| (*position, candidates[0]))
+            {
+                self.write_cell(row * 9 + col, single);
+                board_move.push_cascade(Cascade::new([row, col], single));
+                report.placements += 1;
+                report.forced_singles += 1;
+                observer.on_cascade(self, (row, col), single);
+                next = self.scan_least_entropy(report);
+            }
+
+            report.history.push(board_move);
+            if self.solve_dfs_from(next, report, unwound, (limits, start_time), observer)? {
+                return Ok(true);
+            }
+            let board_move = report.history.pop().unwrap();
+            self.undo_move(&board_move);
+            *unwound += 1;
+            report.max_backtrack_depth = report.max_backtrack_depth.max(*unwound);
+            observer.on_backtrack(self, &board_move);
+        }
+        Ok(false)
+    }
+
+    /// Solves the board as an exact cover problem with dancing links, see [`DancingLinks`]. The
+    /// history holds one move per filled in cell, in the order the search chose them. The board
+    /// is left as it was when there is no solution
+    pub fn solve_dlx(&mut self) -> Result<SolveReport, SolveError> {
+        let start_time = std::time::Instant::now();
+        let conflicts = self.conflicts();
+        if !conflicts.is_empty() {
+            return Err(SolveError::InvalidGivens(conflicts));
+        }
+        let mut links =
+            DancingLinks::from_board(self).expect("givens without conflicts form a partial cover");
+
+        let mut report = SolveReport::default();
+        let mut solution = None;
+        links.search(1, &mut report, &mut |chosen| {
+            solution = Some(chosen.to_vec())
+        });
+        let Some(chosen) = solution else {
+            return Err(SolveError::Exhausted {
+                backtracks: report.backtracks,
+            });
+        };
+        for index in chosen {
+            let ((row, col), value) = crate::dlx::placement(index);
+            self.write_cell(row * 9 + col, value);
+            report.history.push(BoardMove::new([row, col], value));
+        }
+        self.verify_solved()?;
+        report.elapsed = start_time.elapsed();
+        Ok(report)
+    }
+
+    /// Encodes the board as a DIMACS CNF formula over 729 variables, one for every digit in every
+    /// cell, so it can be handed to an off the shelf SAT solver. See [`crate::dimacs::clauses`]
+    pub fn to_dimacs(&self) -> String {
+        crate::dimacs::encode(&self.cells)
+    }
+
+    /// Reads a SAT solver's satisfying assignment of [`Board::to_dimacs`] back into a board.
+    /// Every cell of the result is a given, and nothing checks it against the sudoku rules
+    pub fn from_sat_assignment(assignment: &[i32]) -> Result<Board, SatAssignmentError> {
+        crate::dimacs::decode(assignment).map(Board::from_cells)
+    }
+
+    /// Fills in the board using only the given techniques, never guessing. Eliminations are kept
+    /// in a [`CandidatesGrid`] and made one at a time with the cheapest technique that finds one,
+    /// going back to singles after each. Stops when the board is filled in or nothing applies,
+    /// leaving every placement made so far on the board
+    pub fn solve_logical(&mut self, techniques: &TechniqueSet) -> LogicalOutcome {
+        let mut grid = CandidatesGrid::from_board(self);
+        let mut moves = Vec::new();
+        let mut eliminations = Vec::new();
+        while self.empty_cells().next().is_some() {
+            if let Some(mut found) = techniques.find_next_placement(&grid) {
+                let (row, col) = found.position;
+                self.set_cell(row, col, found.value);
+                grid.place(row, col, found.value);
+                found.eliminations = std::mem::take(&mut eliminations);
+                moves.push(found);
+                continue;
+            }
+            let Some((name, elimination)) = techniques.find_next_elimination(&grid) else {
+                return LogicalOutcome::Stuck {
+                    moves,
+                    board_state: Box::new(self.clone()),
+                    remaining_candidates: Box::new(grid),
+                };
+            };
+            grid.apply_elimination(&elimination);
+            eliminations.push((name, elimination));
+        }
+        LogicalOutcome::Solved(moves)
+    }
+
+    /// Finds the simplest move a human could make next, see [`Hint::find`]. Only the candidates
+    /// the board itself allows are considered, so eliminations from earlier hints are not
+    /// remembered. Returns `None` when the board is filled in or no technique applies
+    pub fn next_hint(&self) -> Option<Hint> {
+        self.next_hint_with(&TechniqueSet::all())
+    }
+
+    /// Same as [`Board::next_hint`], using only the techniques of `techniques`
+    pub fn next_hint_with(&self, techniques: &TechniqueSet) -> Option<Hint> {
+        Hint::find_with(&CandidatesGrid::from_board(self), techniques)
+    }
+
+    /// Walks through a whole solve of the board as a human could, one [`Hint`] at a time. When no
+    /// technique applies, the cell with the fewest candidates is filled in from the solution and
+    /// labelled as trial and error. The board itself is left untouched
+    pub fn explain_solution(&self) -> Result<Vec<ExplainedStep>, SolveError> {
+        self.explain_solution_with(&TechniqueSet::all())
+    }
+
+    /// Same as [`Board::explain_solution`], using only the techniques of `techniques`
+    pub fn explain_solution_with(
+        &self,
+        techniques: &TechniqueSet,
+    ) -> Result<Vec<ExplainedStep>, SolveError> {
+        let mut solution = self.clone();
+        solution.solve_dlx()?;
+
+        let mut board = self.clone();
+        let mut grid = CandidatesGrid::from_board(self);
+        let mut steps = Vec::new();
+        while board.empty_cells().next().is_some() {
+            let Some(hint) = Hint::find_with(&grid, techniques) else {
+                let (position, _) = grid
+                    .open_cells()
+                    .min_by_key(|(_, candidates)| candidates.len())
+                    .expect("an unfinished board has open cells");
+                let value = solution.get_cell(position.0, position.1);
+                board.set_cell(position.0, position.1, value);
+                grid.place(position.0, position.1, value);
+                steps.push(ExplainedStep::TrialAndError { position, value });
+                continue;
+            };
+            match &hint.action {
+                HintAction::Place(found) => {
+                    let (row, col) = found.position;
+                    board.set_cell(row, col, found.value);
+                    grid.place(row, col, found.value);
+                }
+                HintAction::Eliminate(elimination) => {
+                    grid.apply_elimination(elimination);
+                }
+            }
+            steps.push(ExplainedStep::Logical(hint));
+        }
+        Ok(steps)
+    }
+
+    /// Grades how hard the puzzle is for a human by solving a copy of it with every technique,
+    /// see [`GradeReport::from_outcome`]
+    pub fn grade(&self) -> GradeReport {
+        self.grade_with(&TechniqueSet::all())
+    }
+
+    /// Same as [`Board::grade`], solving with the techniques of `techniques`
+    pub fn grade_with(&self, techniques: &TechniqueSet) -> GradeReport {
+        GradeReport::from_outcome(&self.clone().solve_logical(techniques))
+    }
+
+    /// Same as [`Board::count_solutions`], searching with dancing links instead
+    pub fn count_solutions_dlx(&self, limit: usize) -> usize {
+        self.count_variant_solutions(Variant::Classic, limit)
+    }
+
+    /// Same as [`Board::count_solutions_dlx`], counting the solutions that follow the rules of
+    /// `variant`
+    pub fn count_variant_solutions(&self, variant: Variant, limit: usize) -> usize {
+        let Some(mut links) = DancingLinks::for_variant(self, variant) else {
+            return 0;
+        };
+        let mut found = 0;
+        links.search(limit, &mut SolveReport::default(), &mut |_| found += 1);
+        found
+    }
+
+    /// Counts the solutions of the board with an exhaustive search, stopping as soon as `limit`
+    /// of them have been found. Boards whose givens break the rules have no solutions. The board
+    /// itself is left untouched
+    pub fn count_solutions(&self, limit: usize) -> usize {
+        let mut found = 0;
+        self.search_solutions(limit, &mut |_| found += 1);
+        found
+    }
+
+    /// Lists up to `limit` solutions of the board, found by an exhaustive search that always
+    /// branches on the first cell with the fewest candidates and tries them in increasing order,
+    /// so the same board always lists the same solutions in the same order. The board itself is
+    /// left untouched
+    pub fn solutions(&self, limit: usize) -> Vec<Board> {
+        let mut solutions = Vec::new();
+        self.search_solutions(limit, &mut |solution| solutions.push(solution.clone()));
+        solutions
+    }
+
+    /// Same as [`Board::count_solutions`], searching on up to `threads` threads. Forced cells are
+    /// filled in until the first cell with a choice, and each of its candidates is searched on a
+    /// worker of its own. Every worker stops once `limit` solutions have been found between them
+    pub fn count_solutions_with_threads(&self, limit: usize, threads: usize) -> usize {
+        if threads <= 1 {
+            return self.count_solutions(limit);
+        }
+        if limit == 0 || !self.conflicts().is_empty() {
+            return 0;
+        }
+        let mut board = self.clone();
+        let ((row, col), candidates) = loop {
+            match board.least_entropy_cell() {
+                None => return 1,
+                Some(((row, col), candidates)) if candidates.len() == 1 => {
+                    board.write_cell(row * 9 + col, candidates.iter().next().unwrap());
+                }
+                Some(branch) => break branch,
+            }
+        };
+
+        let found = AtomicUsize::new(0);
+        let queue = Mutex::new(candidates.iter());
+        std::thread::scope(|scope| {
+            for _ in 0..threads.min(candidates.len()) {
+                scope.spawn(|| {
+                    let mut board = board.clone();
+                    loop {
+                        let next = queue.lock().unwrap().next();
+                        let Some(value) = next else {
+                            return;
+                        };
+                        board.write_cell(row * 9 + col, value);
+                        board.count_solutions_shared(&found, limit);
+                    }
+                });
+            }
+        });
+        found.into_inner().min(limit)
+    }
+
+    /// Counts solutions into a counter shared between threads, giving up as soon as it reaches
+    /// `limit`. Every value is taken back off before returning
+    fn count_solutions_shared(&mut self, found: &AtomicUsize, limit: usize) {
+        let Some(((row, col), candidates)) = self.least_entropy_cell() else {
+            found.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+        for value in candidates.iter() {
+            if found.load(Ordering::Relaxed) >= limit {
+                break;
+            }
+            self.write_cell(row * 9 + col, value);
+            self.count_solutions_shared(found, limit);
+        }
+        self.write_cell(row * 9 + col, 0);
+    }
+
+    /// Searches for up to two solutions of the board, keeping them so an ambiguous puzzle can be
+    /// inspected
+    pub fn has_unique_solution(&self) -> UniquenessResult {
+        let mut solutions = Vec::with_capacity(2);
+        self.search_solutions(2, &mut |solution| solutions.push(solution.clone()));
+        let mut solutions = solutions.into_iter();
+        match (solutions.next(), solutions.next()) {
+            (Some(first), Some(second)) => UniquenessResult::Multiple {
+                first: Box::new(first),
+                second: Box::new(second),
+            },
+            (Some(solution), None) => UniquenessResult::Unique(Box::new(solution)),
+            _ => UniquenessResult::None,
+        }
+    }
+
+    /// Runs an exhaustive search on a copy of the board, passing each solution to `on_solution`
+    /// until `limit` of them have been found
+    fn search_solutions(&self, limit: usize, on_solution: &mut dyn FnMut(&Board)) {
+        if limit == 0 || !self.conflicts().is_empty() {
+            return;
+        }
+        let mut remaining = limit;
+        self.clone()
+            .search_solutions_from(&mut remaining, on_solution);
+    }
+
+    /// Fills in the cell with the least entropy with each of its candidates in turn, recursing
+    /// until the board is full or a cell runs out of options. Every value is taken back off
+    /// before returning
+    fn search_solutions_from(
+        &mut self,
+        remaining: &mut usize,
+        on_solution: &mut dyn FnMut(&Board),
+    ) {
+        let Some(((row, col), candidates)) = self.least_entropy_cell() else {
+            on_solution(self);
+            *remaining -= 1;
+            return;
+        };
+        for value in candidates.iter() {
+            self.write_cell(row * 9 + col, value);
+            self.search_solutions_from(remaining, on_solution);
+            self.write_cell(row * 9 + col, 0);
+            if *remaining == 0 {
+                return;
+            }
+        }
+    }
+
+    /// Validates the resulting board to make sure it is completely filled in and follows the
+    /// sudoku rules
+    pub fn validate_board(&self) -> bool {
+        !self.cells.contains(&0) && self.occupancy.duplicates == 0
+    }
+
+    /// Same as [`Board::validate_board`], also checking the extra units of `variant`
+    pub fn validate_variant(&self, variant: Variant) -> bool {
+        self.validate_board()
+            && variant.extra_units().iter().all(|unit| {
+                let mut seen = [false; 10];
+                unit.iter().all(|&(row, col)| {
+                    !std::mem::replace(&mut seen[self.get_cell(row, col) as usize], true)
+                })
+            })
+    }
+
+    /// Places the value of a move on the board, along with every cell that cascaded from it
+    pub fn apply_move(&mut self, board_move: &BoardMove) {
+        let [row, col] = board_move.position;
+        self.write_cell(row * 9 + col, board_move.new_value);
+        for cascade in &board_move.cascades {
+            let [row, col] = cascade.position;
+            self.write_cell(row * 9 + col, cascade.value);
+        }
+    }
+
+    /// Reverts a move, clearing its cell along with every cell that cascaded from it
+    pub fn undo_move(&mut self, board_move: &BoardMove) {
+        for cascade in &board_move.cascades {
+            let [row, col] = cascade.position;
+            self.write_cell(row * 9 + col, 0);
+        }
+        let [row, col] = board_move.position;
+        self.write_cell(row * 9 + col, 0);
+    }
+
+    /// Backtracking moves when a mistake is made. The latest move is taken back and replaced by
+    /// the next of its remaining candidates that still fits. When none is left, the move before
+    /// it is taken back as well, and so on until a substitute is found or the history runs out.
+    /// Cancelling the token stops it between moves
+    fn backtrack(
+        &mut self,
+        previous_moves: &mut MoveHistory,
+        report: &mut SolveReport,
+        cancellation: Option<&CancellationToken>,
+        observer: &mut (impl SolveObserver + ?Sized),
+    ) -> BacktrackOutcome {
+        if previous_moves.is_empty() {
+            return BacktrackOutcome::HistoryEmpty;
+        }
+
+        let mut depth = 0;
+        loop {
+            if cancellation.is_some_and(CancellationToken::is_cancelled) {
+                return BacktrackOutcome::Cancelled;
+            }
+            let Some(mut last_move) = previous_moves.pop() else {
+                return BacktrackOutcome::Exhausted;
+            };
+            self.undo_move(&last_move);
+            depth += 1;
+            report.max_backtrack_depth = report.max_backtrack_depth.max(depth);
+            observer.on_backtrack(self, &last_move);
+
+            // The board is back to how it was when the move was made, so the remaining
+            // candidates of a move the solver made all fit. Moves parsed from text list every
+            // other digit instead
+            let [row, col] = last_move.position;
+            let candidates = self.candidates_at(row, col).unwrap();
+            let remaining = &mut last_move.remaining_candidates;
+            let Some(next) = remaining
+                .iter()
+                .position(|value| candidates.contains(*value))
+            else {
+                continue;
+            };
+            let later = remaining.split_off(next + 1);
+            let substitute = remaining[next];
+            self.write_cell(row * 9 + col, substitute);
+            report.placements += 1;
+            report.guesses += 1;
+            observer.on_place(self, (row, col), substitute);
+            previous_moves.push(BoardMove::guess([row, col], substitute, later));
+            return BacktrackOutcome::Resume;
+        }
+    }
 }
@@ -0,0 +1,92 @@
+use std::fmt::Debug;
+
+/// A set of the digits 1 through 9, stored as a bitmask where bit `d` marks digit `d` as present
+#[derive(Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct CandidateSet(u16);
+
+impl CandidateSet {
+    const ALL_BITS: u16 = 0b11_1111_1110;
+
+    /// Creates a set with no digits in it
+    pub fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Creates a set containing every digit from 1 to 9
+    pub fn all() -> Self {
+        Self(Self::ALL_BITS)
+    }
+
+    /// Creates a set from a raw bitmask, ignoring any bit outside of 1 through 9
+    pub fn from_bits(bits: u16) -> Self {
+        Self(bits & Self::ALL_BITS)
+    }
+
+    /// Raw bitmask of the set, bit `d` being set when digit `d` is present
+    pub fn bits(&self) -> u16 {
+        self.0
+    }
+
+    /// Whether the digit is in the set
+    pub fn contains(&self, value: u8) -> bool {
+        (1..=9).contains(&value) && self.0 & (1 << value) != 0
+    }
+
+    /// Number of digits in the set
+    pub fn len(&self) -> usize {
+        self.0.count_ones() as usize
+    }
+
+    /// Whether the set has no digits in it
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Adds a digit to the set. Zero stands for an empty cell and is ignored
+    pub fn insert(&mut self, value: u8) {
+        if (1..=9).contains(&value) {
+            self.0 |= 1 << value;
+        }
+    }
+
+    /// Removes a digit from the set, returning whether it was present
+    pub fn remove(&mut self, value: u8) -> bool {
+        let present = self.contains(value);
+        if present {
+            self.0 &= !(1 << value);
+        }
+        present
+    }
+
+    /// Digits in this set that are not in `other`
+    pub fn difference(&self, other: CandidateSet) -> CandidateSet {
+        Self(self.0 & !other.0)
+    }
+
+    /// Digits in either set
+    pub fn union(&self, other: CandidateSet) -> CandidateSet {
+        Self(self.0 | other.0)
+    }
+
+    /// Iterates over the digits in the set in ascending order
+    pub fn iter(&self) -> impl Iterator<Item = u8> {
+        let bits = self.0;
+        (1..=9).filter(move |value| bits & (1 << value) != 0)
+    }
+}
+
+impl Debug for CandidateSet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_set().entries(self.iter()).finish()
+    }
+}
+
+impl FromIterator<u8> for CandidateSet {
+    fn from_iter<T: IntoIterator<Item = u8>>(iter: T) -> Self {
+        let mut set = Self::empty();
+        for value in iter {
+            set.insert(value);
+        }
+        set
+    }
+}
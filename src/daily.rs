@@ -0,0 +1,163 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    str::FromStr,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use crate::{
+    generator::{generate_with_difficulty, GeneratedPuzzle},
+    grade::Difficulty,
+};
+
+/// Hashed along with the date, so the daily seeds differ from anything else derived from dates.
+/// Changing it changes every daily puzzle
+pub const DAILY_SALT: &str = "sudoku_solver daily puzzle";
+
+/// Grade every daily puzzle aims for
+pub const DAILY_DIFFICULTY: Difficulty = Difficulty::Medium;
+
+/// Puzzles generated in search of the daily difficulty before settling for the closest
+pub const DAILY_ATTEMPTS: usize = 50;
+
+/// A day of the Gregorian calendar
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: i32,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    /// Date constructor. Returns `None` for a day its month does not have
+    pub fn new(year: i32, month: u8, day: u8) -> Option<Date> {
+        let valid = (1..=12).contains(&month) && (1..=days_in_month(year, month)).contains(&day);
+        valid.then_some(Date { year, month, day })
+    }
+
+    /// Today's date in UTC, going by the system clock
+    pub fn today() -> Date {
+        let seconds = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        Date::from_days_since_epoch((seconds / 86_400) as i64)
+    }
+
+    /// The date `days` after 1970-01-01, converted as in Howard Hinnant's `civil_from_days`
+    pub fn from_days_since_epoch(days: i64) -> Date {
+        let days = days + 719_468;
+        let era = days.div_euclid(146_097);
+        let day_of_era = days.rem_euclid(146_097);
+        let year_of_era =
+            (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+        let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+        // Months are counted from March, so the leap day comes last
+        let shifted_month = (5 * day_of_year + 2) / 153;
+        let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u8;
+        let month = if shifted_month < 10 {
+            shifted_month + 3
+        } else {
+            shifted_month - 9
+        } as u8;
+        let year = (year_of_era + era * 400 + i64::from(month <= 2)) as i32;
+        Date { year, month, day }
+    }
+
+    /// Days from 1970-01-01 to the date, the inverse of [`Date::from_days_since_epoch`], as in
+    /// Howard Hinnant's `days_from_civil`
+    pub fn days_since_epoch(&self) -> i64 {
+        let year = i64::from(self.year) - i64::from(self.month <= 2);
+        let era = year.div_euclid(400);
+        let year_of_era = year.rem_euclid(400);
+        let shifted_month = (i64::from(self.month) + 9) % 12;
+        let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(self.day) - 1;
+        let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+        era * 146_097 + day_of_era - 719_468
+    }
+}
+
+fn days_in_month(year: i32, month: u8) -> u8 {
+    let leap = (year % 4 == 0 && year % 100 != 0) || year % 400 == 0;
+    match month {
+        2 if leap => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+/// Prints the date in the ISO 8601 form, `YYYY-MM-DD`
+impl Display for Date {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+/// Returned when a string cannot be parsed into a date
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseDateError {
+    /// The string is not written as `YYYY-MM-DD`
+    Format(String),
+    /// The month does not have that day, or there is no such month
+    NoSuchDay(String),
+}
+
+impl Display for ParseDateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseDateError::Format(date) => {
+                write!(f, "Expected a date written as YYYY-MM-DD, found {date}")
+            }
+            ParseDateError::NoSuchDay(date) => write!(f, "There is no day {date}"),
+        }
+    }
+}
+
+impl Error for ParseDateError {}
+
+/// Parses a date written as `YYYY-MM-DD`
+impl FromStr for Date {
+    type Err = ParseDateError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let format_error = || ParseDateError::Format(s.to_string());
+        let parts: Vec<&str> = s.split('-').collect();
+        let [year, month, day] = parts[..] else {
+            return Err(format_error());
+        };
+        let digits = |part: &str, length: usize| {
+            part.len() == length && part.bytes().all(|byte| byte.is_ascii_digit())
+        };
+        if !(digits(year, 4) && digits(month, 2) && digits(day, 2)) {
+            return Err(format_error());
+        }
+        let (year, month, day) = (
+            year.parse().map_err(|_| format_error())?,
+            month.parse().map_err(|_| format_error())?,
+            day.parse().map_err(|_| format_error())?,
+        );
+        Date::new(year, month, day).ok_or_else(|| ParseDateError::NoSuchDay(s.to_string()))
+    }
+}
+
+/// Seed of the puzzle of the day, the 64 bit FNV-1a hash of [`DAILY_SALT`], a space and the date
+/// in its `YYYY-MM-DD` form. Unlike the standard library's hashers, FNV-1a is fixed, so the seed
+/// is the same on every platform and build
+pub fn daily_seed(date: Date) -> u64 {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+    format!("{DAILY_SALT} {date}")
+        .bytes()
+        .fold(OFFSET_BASIS, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(PRIME)
+        })
+}
+
+/// The puzzle of the day, generated from its [`daily_seed`] with [`generate_with_difficulty`]
+/// aiming for [`DAILY_DIFFICULTY`], settling for the closest attempt if none grades as that. The
+/// puzzle comes graded
+pub fn generate_daily(date: Date) -> GeneratedPuzzle {
+    generate_with_difficulty(daily_seed(date), DAILY_DIFFICULTY, DAILY_ATTEMPTS)
+        .unwrap_or_else(|error| *error.closest)
+        .graded()
+}
@@ -0,0 +1,140 @@
+use std::{error::Error, fmt::Display};
+
+use crate::board::{cell_name, UnitKind};
+
+/// Number of boolean variables, one for every digit in every cell
+pub const VARIABLES: i32 = 729;
+
+/// The variable that is true when `value` is placed at `row`, `col`, numbered from 1 as DIMACS
+/// requires
+pub fn variable(row: usize, col: usize, value: u8) -> i32 {
+    ((row * 9 + col) * 9) as i32 + value as i32
+}
+
+/// The cell and value a variable stands for
+pub fn placement(variable: i32) -> ((usize, usize), u8) {
+    let index = (variable - 1) as usize;
+    let cell = index / 9;
+    ((cell / 9, cell % 9), (index % 9) as u8 + 1)
+}
+
+/// Clauses that allow at most one of the variables to be true, one for every pair of them
+fn at_most_one(variables: [i32; 9], clauses: &mut Vec<Vec<i32>>) {
+    for (i, first) in variables.iter().enumerate() {
+        for second in &variables[i + 1..] {
+            clauses.push(vec![-first, -second]);
+        }
+    }
+}
+
+/// The clauses of the sudoku rules: every cell holds at least one digit and at most one, every
+/// row, column and tile holds each digit at most once, and every given is fixed by a unit clause
+pub fn clauses(cells: &[u8; 81]) -> Vec<Vec<i32>> {
+    let mut clauses = Vec::new();
+    for row in 0..9 {
+        for col in 0..9 {
+            let variables = std::array::from_fn(|digit| variable(row, col, digit as u8 + 1));
+            clauses.push(variables.to_vec());
+            at_most_one(variables, &mut clauses);
+        }
+    }
+    for kind in UnitKind::ALL {
+        for index in 0..9 {
+            let positions = kind.positions(index);
+            for value in 1..=9 {
+                let variables = positions.map(|(row, col)| variable(row, col, value));
+                at_most_one(variables, &mut clauses);
+            }
+        }
+    }
+    for (cell, &value) in cells.iter().enumerate() {
+        if value != 0 {
+            clauses.push(vec![variable(cell / 9, cell % 9, value)]);
+        }
+    }
+    clauses
+}
+
+/// Writes the clauses in the DIMACS CNF format, after a comment holding the board
+pub fn encode(cells: &[u8; 81]) -> String {
+    let clauses = clauses(cells);
+    let board: String = cells.iter().map(|cell| (b'0' + cell) as char).collect();
+    let mut cnf = format!("c sudoku {board}\np cnf {VARIABLES} {}\n", clauses.len());
+    for clause in clauses {
+        for literal in clause {
+            cnf.push_str(&literal.to_string());
+            cnf.push(' ');
+        }
+        cnf.push_str("0\n");
+    }
+    cnf
+}
+
+/// Returned when a SAT solver's assignment cannot be read back into a solved board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SatAssignmentError {
+    /// A literal names a variable outside of 1 through 729
+    VariableOutOfRange(i32),
+    /// None of the cell's digits are true
+    Unassigned { cell: (usize, usize) },
+    /// More than one of the cell's digits are true
+    Ambiguous {
+        cell: (usize, usize),
+        values: (u8, u8),
+    },
+}
+
+impl Display for SatAssignmentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SatAssignmentError::VariableOutOfRange(literal) => {
+                write!(
+                    f,
+                    "The literal {literal} is not one of the {VARIABLES} variables"
+                )
+            }
+            SatAssignmentError::Unassigned { cell } => {
+                write!(f, "No digit is assigned to {}", cell_name(*cell))
+            }
+            SatAssignmentError::Ambiguous {
+                cell,
+                values: (first, second),
+            } => write!(
+                f,
+                "Both {first} and {second} are assigned to {}",
+                cell_name(*cell)
+            ),
+        }
+    }
+}
+
+impl Error for SatAssignmentError {}
+
+/// Reads the cells from the true literals of an assignment, as listed on a SAT solver's `v`
+/// lines. Negative literals and the terminating `0` are skipped
+pub fn decode(assignment: &[i32]) -> Result<[u8; 81], SatAssignmentError> {
+    let mut cells = [0; 81];
+    for &literal in assignment {
+        if literal.abs() > VARIABLES {
+            return Err(SatAssignmentError::VariableOutOfRange(literal));
+        }
+        if literal <= 0 {
+            continue;
+        }
+        let ((row, col), value) = placement(literal);
+        let cell = &mut cells[row * 9 + col];
+        if *cell != 0 && *cell != value {
+            return Err(SatAssignmentError::Ambiguous {
+                cell: (row, col),
+                values: (*cell, value),
+            });
+        }
+        *cell = value;
+    }
+    match cells.iter().position(|&cell| cell == 0) {
+        Some(cell) => Err(SatAssignmentError::Unassigned {
+            cell: (cell / 9, cell % 9),
+        }),
+        None => Ok(cells),
+    }
+}
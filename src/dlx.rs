@@ -0,0 +1,277 @@
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{
+    board::{Board, Variant},
+    report::SolveReport,
+};
+
+/// Number of constraints, which are the columns of the matrix
+const CONSTRAINTS: usize = 4 * 81;
+/// Constraints added by [`Variant::X`], one for every digit on each of the two main diagonals
+const DIAGONAL_CONSTRAINTS: usize = 2 * 9;
+/// Number of candidate placements, which are the rows of the matrix
+const PLACEMENTS: usize = 9 * 81;
+/// Index of the root header that links the uncovered column headers together
+const ROOT: usize = 0;
+
+/// The matrix row that places `value` at `row`, `col`
+fn placement_index(row: usize, col: usize, value: u8) -> usize {
+    (row * 9 + col) * 9 + value as usize - 1
+}
+
+/// The cell and value a matrix row places
+pub fn placement(index: usize) -> ((usize, usize), u8) {
+    let cell = index / 9;
+    ((cell / 9, cell % 9), (index % 9) as u8 + 1)
+}
+
+/// Column headers of the constraints a placement meets under `variant`, the four of a classic
+/// sudoku followed by those of the diagonals the cell lies on
+fn constraints(index: usize, variant: Variant) -> Vec<usize> {
+    let ((row, col), value) = placement(index);
+    let digit = value as usize - 1;
+    let tile = (row / 3) * 3 + col / 3;
+    let mut headers = vec![
+        1 + row * 9 + col,
+        1 + 81 + row * 9 + digit,
+        1 + 2 * 81 + col * 9 + digit,
+        1 + 3 * 81 + tile * 9 + digit,
+    ];
+    if variant == Variant::X {
+        if row == col {
+            headers.push(1 + CONSTRAINTS + digit);
+        }
+        if row + col == 8 {
+            headers.push(1 + CONSTRAINTS + 9 + digit);
+        }
+    }
+    headers
+}
+
+/// Sudoku as an exact cover problem, solved with Knuth's Algorithm X on dancing links. Each of the
+/// 729 matrix rows places one digit in one cell, and each of the 324 columns is a constraint that
+/// has to be met exactly once: every cell holds a digit, and every row, column and tile holds
+/// every digit. [`Variant::X`] adds 18 columns asking the same of both main diagonals.
+///
+/// The matrix is stored as a toroidal doubly linked list. Node 0 is the root, nodes 1 through
+/// 324, or 342 with the diagonals, are the column headers and every node after them belongs to a
+/// placement
+pub struct DancingLinks {
+    left: Vec<usize>,
+    right: Vec<usize>,
+    up: Vec<usize>,
+    down: Vec<usize>,
+    column: Vec<usize>,
+    /// Placement each node belongs to, unused for the root and headers
+    placement: Vec<usize>,
+    /// Nodes left in each column, indexed by header
+    size: Vec<usize>,
+    /// Placements chosen and taken back since the last new choice, for the report
+    unwound: usize,
+}
+
+impl DancingLinks {
+    /// Builds the matrix for the board, with the givens already chosen. Returns `None` when two
+    /// givens meet the same constraint, in which case there is nothing to search
+    pub fn from_board(board: &Board) -> Option<Self> {
+        Self::for_variant(board, Variant::Classic)
+    }
+
+    /// Same as [`DancingLinks::from_board`], meeting the constraints of `variant`
+    pub fn for_variant(board: &Board, variant: Variant) -> Option<Self> {
+        Self::build(board, variant, 0..PLACEMENTS)
+    }
+
+    /// Same as [`DancingLinks::for_variant`], with the placements of every constraint tried in
+    /// an order drawn from `rng` instead of increasing order, so solving an empty board gives a
+    /// different grid for every seed
+    pub fn shuffled(board: &Board, variant: Variant, rng: &mut impl Rng) -> Option<Self> {
+        let mut order: Vec<usize> = (0..PLACEMENTS).collect();
+        order.shuffle(rng);
+        Self::build(board, variant, order)
+    }
+
+    /// Builds the matrix, linking the placements into their columns in `order`
+    fn build(
+        board: &Board,
+        variant: Variant,
+        order: impl IntoIterator<Item = usize>,
+    ) -> Option<Self> {
+        let headers = match variant {
+            Variant::Classic => CONSTRAINTS,
+            Variant::X => CONSTRAINTS + DIAGONAL_CONSTRAINTS,
+        };
+        let nodes = 1 + headers + 4 * PLACEMENTS + 2 * 9 * 9;
+        let mut links = Self {
+            left: Vec::with_capacity(nodes),
+            right: Vec::with_capacity(nodes),
+            up: Vec::with_capacity(nodes),
+            down: Vec::with_capacity(nodes),
+            column: Vec::with_capacity(nodes),
+            placement: Vec::with_capacity(nodes),
+            size: vec![0; 1 + headers],
+            unwound: 0,
+        };
+
+        for header in 0..=headers {
+            links.left.push((header + headers) % (headers + 1));
+            links.right.push((header + 1) % (headers + 1));
+            links.up.push(header);
+            links.down.push(header);
+            links.column.push(header);
+            links.placement.push(0);
+        }
+
+        let mut first_nodes = vec![0; PLACEMENTS];
+        for index in order {
+            let first = links.left.len();
+            first_nodes[index] = first;
+            let constraints = constraints(index, variant);
+            let count = constraints.len();
+            for (i, header) in constraints.into_iter().enumerate() {
+                let node = first + i;
+                links.left.push(first + (i + count - 1) % count);
+                links.right.push(first + (i + 1) % count);
+                links.up.push(links.up[header]);
+                links.down.push(header);
+                links.column.push(header);
+                links.placement.push(index);
+
+                let above = links.up[header];
+                links.down[above] = node;
+                links.up[header] = node;
+                links.size[header] += 1;
+            }
+        }
+
+        let mut covered = vec![false; 1 + headers];
+        for ((row, col), value) in board.filled_cells() {
+            let index = placement_index(row, col, value);
+            let first = first_nodes[index];
+            for node in first..first + constraints(index, variant).len() {
+                let header = links.column[node];
+                if covered[header] {
+                    return None;
+                }
+                covered[header] = true;
+                links.cover(header);
+            }
+        }
+        Some(links)
+    }
+
+    /// Unlinks a column header and every placement that meets its constraint
+    fn cover(&mut self, header: usize) {
+        self.right[self.left[header]] = self.right[header];
+        self.left[self.right[header]] = self.left[header];
+        let mut row = self.down[header];
+        while row != header {
+            let mut node = self.right[row];
+            while node != row {
+                self.down[self.up[node]] = self.down[node];
+                self.up[self.down[node]] = self.up[node];
+                self.size[self.column[node]] -= 1;
+                node = self.right[node];
+            }
+            row = self.down[row];
+        }
+    }
+
+    /// Links a covered column back in, in exactly the reverse order of [`DancingLinks::cover`]
+    fn uncover(&mut self, header: usize) {
+        let mut row = self.up[header];
+        while row != header {
+            let mut node = self.left[row];
+            while node != row {
+                self.size[self.column[node]] += 1;
+                self.down[self.up[node]] = node;
+                self.up[self.down[node]] = node;
+                node = self.left[node];
+            }
+            row = self.up[row];
+        }
+        self.right[self.left[header]] = header;
+        self.left[self.right[header]] = header;
+    }
+
+    /// Runs Algorithm X, passing the placements of each solution found to `on_solution` until
+    /// `limit` of them have been found. Choices made along the way are counted in `report`,
+    /// picking the constraint with the fewest placements left counting as one scan. The matrix
+    /// is restored to its starting state before returning
+    pub fn search(
+        &mut self,
+        limit: usize,
+        report: &mut SolveReport,
+        on_solution: &mut dyn FnMut(&[usize]),
+    ) {
+        if limit == 0 {
+            return;
+        }
+        let mut remaining = limit;
+        let mut chosen = Vec::with_capacity(81);
+        self.search_from(&mut chosen, &mut remaining, report, on_solution);
+    }
+
+    fn search_from(
+        &mut self,
+        chosen: &mut Vec<usize>,
+        remaining: &mut usize,
+        report: &mut SolveReport,
+        on_solution: &mut dyn FnMut(&[usize]),
+    ) {
+        if self.right[ROOT] == ROOT {
+            on_solution(chosen);
+            *remaining -= 1;
+            return;
+        }
+
+        report.entropy_scans += 1;
+        let mut header = self.right[ROOT];
+        let mut best = header;
+        while header != ROOT {
+            if self.size[header] < self.size[best] {
+                best = header;
+            }
+            header = self.right[header];
+        }
+        let options = self.size[best];
+        if options == 0 {
+            report.backtracks += 1;
+            return;
+        }
+
+        self.cover(best);
+        let mut row = self.down[best];
+        while row != best {
+            chosen.push(self.placement[row]);
+            report.placements += 1;
+            if options == 1 {
+                report.forced_singles += 1;
+            } else {
+                report.guesses += 1;
+            }
+            self.unwound = 0;
+            let mut node = self.right[row];
+            while node != row {
+                self.cover(self.column[node]);
+                node = self.right[node];
+            }
+
+            self.search_from(chosen, remaining, report, on_solution);
+
+            let mut node = self.left[row];
+            while node != row {
+                self.uncover(self.column[node]);
+                node = self.left[node];
+            }
+            chosen.pop();
+            if *remaining == 0 {
+                break;
+            }
+            self.unwound += 1;
+            report.max_backtrack_depth = report.max_backtrack_depth.max(self.unwound);
+            row = self.down[row];
+        }
+        self.uncover(best);
+    }
+}
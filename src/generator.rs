@@ -0,0 +1,1123 @@
+use std::{error::Error, fmt::Display, ops::RangeInclusive, str::FromStr, time::Duration};
+
+use rand::prelude::*;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    board::{Board, MaskError, SymmetryKind, Variant},
+    dlx::{placement, DancingLinks},
+    grade::Difficulty,
+    observer::NoopObserver,
+    report::{CellSelection, SolveLimits, SolveOptions, ValueOrdering},
+};
+
+/// Bumped whenever [`generate_board`] starts producing different boards for the same seeds, so
+/// seeds written down under an older version are known not to reproduce their boards
+pub const PATTERN_SEED_VERSION: u32 = 2;
+
+/// Base generation derived from https://gamedev.stackexchange.com/a/138228
+/// Uses various shifting techniques from https://pi.math.cornell.edu/~mec/Summer2009/Mahmood/Symmetry.html
+/// The bands and stacks are then reordered and the board is transposed half of the time
+pub fn generate_board(seed: u64) -> String {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+    // Original board generation
+    let mut rows = [[0_u8; 9]; 9];
+    let mut base_row: [u8; 9] = (1..=9).collect::<Vec<_>>().try_into().unwrap();
+    base_row.shuffle(&mut rng);
+    rows[0] = base_row;
+    for i in 1..9 {
+        let mut rotated_previous_row = rows[i - 1];
+        if i % 3 == 0 {
+            rotated_previous_row.rotate_left(1);
+            rows[i] = rotated_previous_row;
+        } else {
+            rotated_previous_row.rotate_left(3);
+            rows[i] = rotated_previous_row;
+        }
+    }
+    let mut board = Board::from_cells(rows.concat().try_into().unwrap());
+
+    // Shuffles the columns within the stack that they exist in in order to preserve sudoku rules
+    for stack in 0..3 {
+        let mut order = [0, 1, 2];
+        order.shuffle(&mut rng);
+        for (a, b) in order_to_swaps(order) {
+            board.swap_columns_in_stack(stack, a, b);
+        }
+    }
+
+    // Shuffles the numbers themselves, e.g. 1->5, 2->3, 9->1. This preserves the sudoku rules
+    let mut shuffled_numbers = [1, 2, 3, 4, 5, 6, 7, 8, 9];
+    shuffled_numbers.shuffle(&mut rng);
+    board.relabel_digits(shuffled_numbers);
+
+    // Shuffles the rows within the band that they exist in in order to preserve sudoku rules
+    for band in 0..3 {
+        let mut order = [0, 1, 2];
+        order.shuffle(&mut rng);
+        for (a, b) in order_to_swaps(order) {
+            board.swap_rows_in_band(band, a, b);
+        }
+    }
+
+    // Reorders the bands and the stacks as wholes, which preserves the sudoku rules as well
+    let mut order = [0, 1, 2];
+    order.shuffle(&mut rng);
+    for (a, b) in order_to_swaps(order) {
+        board.swap_bands(a, b);
+    }
+    let mut order = [0, 1, 2];
+    order.shuffle(&mut rng);
+    for (a, b) in order_to_swaps(order) {
+        board.swap_stacks(a, b);
+    }
+
+    if rng.gen_bool(0.5) {
+        board = board.transposed();
+    }
+
+    board.to_string()
+}
+
+/// Generates a solved board by solving an empty one, picking cells and values in an order drawn
+/// from `seed`. Unlike [`generate_board`], whose bands only ever hold three different sets of
+/// digits in their three-cell rows, any valid grid can come out of it
+pub fn generate_full_grid(seed: u64) -> Board {
+    let mut board = Board::from_cells([0; 81]);
+    let options = SolveOptions {
+        cell_selection: CellSelection::Random,
+        value_ordering: ValueOrdering::Random,
+        ..SolveOptions::default()
+    };
+    board
+        .solve_board_with_options(
+            &mut ChaCha8Rng::seed_from_u64(seed),
+            &mut NoopObserver,
+            &SolveLimits::default(),
+            options,
+        )
+        .expect("an empty board always has a solution");
+    Board::new(board.to_string())
+}
+
+/// Generates a solved board following the rules of `variant`, which is the one of
+/// [`generate_full_grid`] for a classic sudoku. Other variants solve an empty board with dancing
+/// links, trying placements in an order drawn from `seed`, since shuffling a pattern like
+/// [`generate_board`] does would not keep their extra units filled
+pub fn generate_variant_grid(variant: Variant, seed: u64) -> Board {
+    if variant == Variant::Classic {
+        return generate_full_grid(seed);
+    }
+    let empty = Board::from_cells([0; 81]);
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let mut links = DancingLinks::shuffled(&empty, variant, &mut rng)
+        .expect("an empty board has no conflicting givens");
+    let mut cells = [0; 81];
+    links.search(1, &mut Default::default(), &mut |chosen| {
+        for &index in chosen {
+            let ((row, col), value) = placement(index);
+            cells[row * 9 + col] = value;
+        }
+    });
+    assert!(
+        !cells.contains(&0),
+        "an empty {variant} board always has a solution"
+    );
+    Board::new(Board::from_cells(cells).to_string())
+}
+
+/// How the solved board a puzzle is dug out of gets generated
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum GridMethod {
+    /// Solving an empty board, see [`generate_full_grid`]
+    #[default]
+    Solved,
+    /// Shuffling a fixed pattern, which is quicker but only reaches a small family of grids, see
+    /// [`generate_board`]
+    Pattern,
+}
+
+impl GridMethod {
+    pub const ALL: [GridMethod; 2] = [GridMethod::Solved, GridMethod::Pattern];
+
+    /// The solved board generated from `seed`
+    pub fn grid(&self, seed: u64) -> Board {
+        match self {
+            GridMethod::Solved => generate_full_grid(seed),
+            GridMethod::Pattern => Board::new(generate_board(seed)),
+        }
+    }
+}
+
+impl Display for GridMethod {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            GridMethod::Solved => "solved",
+            GridMethod::Pattern => "pattern",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Breaks down a reordering of three lines, where line `order[i]` should end up at position `i`,
+/// into the swaps that carry it out
+fn order_to_swaps(order: [usize; 3]) -> Vec<(usize, usize)> {
+    let mut current = [0, 1, 2];
+    let mut swaps = Vec::with_capacity(2);
+    for (target, line) in order.into_iter().enumerate() {
+        let source = current.iter().position(|current| *current == line).unwrap();
+        if source != target {
+            current.swap(source, target);
+            swaps.push((source, target));
+        }
+    }
+    swaps
+}
+
+/// Fewest clues a sudoku with a unique solution can have
+pub const MINIMUM_CLUES: usize = 17;
+
+/// Clues a unique puzzle is dug down to unless told otherwise, which it does not always get to
+pub const UNIQUE_PUZZLE_CLUES: usize = 24;
+
+/// Returned when a range of clues to leave on a puzzle cannot be used
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ClueRangeError {
+    /// The range holds no clue count at all
+    Empty(RangeInclusive<usize>),
+    /// The range reaches below [`MINIMUM_CLUES`] or above 81
+    OutOfBounds(RangeInclusive<usize>),
+}
+
+impl Display for ClueRangeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClueRangeError::Empty(range) => write!(
+                f,
+                "The clue range {}..={} is empty, its start must not be above its end",
+                range.start(),
+                range.end()
+            ),
+            ClueRangeError::OutOfBounds(range) => write!(
+                f,
+                "The clue range {}..={} must lie within {MINIMUM_CLUES}..=81",
+                range.start(),
+                range.end()
+            ),
+        }
+    }
+}
+
+impl Error for ClueRangeError {}
+
+/// Checks that a range of clues is not empty and only holds counts a puzzle can have
+pub fn validate_clue_range(clues: &RangeInclusive<usize>) -> Result<(), ClueRangeError> {
+    if clues.is_empty() {
+        Err(ClueRangeError::Empty(clues.clone()))
+    } else if *clues.start() < MINIMUM_CLUES || *clues.end() > 81 {
+        Err(ClueRangeError::OutOfBounds(clues.clone()))
+    } else {
+        Ok(())
+    }
+}
+
+/// Takes a completed board and randomly removes cells from it, leaving a number of clues drawn
+/// from `clues`. Returns the number of clues left
+#[deprecated(note = "use `Board::remove_clues`, which takes an exact clue count")]
+pub fn remove_board_cells(
+    board_string_representation: &mut String,
+    seed: u64,
+    clues: RangeInclusive<usize>,
+) -> Result<usize, ClueRangeError> {
+    validate_clue_range(&clues)?;
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+
+    let mut board = Board::new(board_string_representation.clone());
+    let target_clues = rng.gen_range(clues);
+    board.remove_clues(&mut rng, target_clues);
+    *board_string_representation = board.to_string();
+
+    Ok(board.count_filled())
+}
+
+/// Returned when a string cannot be parsed into a mask of clues
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseMaskError {
+    /// The mask does not hold exactly 81 cells
+    WrongLength(usize),
+    /// A character other than `#`, `.` or whitespace was found at the given index
+    InvalidCharacter { index: usize, character: char },
+}
+
+impl Display for ParseMaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseMaskError::WrongLength(length) => {
+                write!(f, "A mask needs exactly 81 cells, found {length}")
+            }
+            ParseMaskError::InvalidCharacter { index, character } => write!(
+                f,
+                "Unexpected character '{character}' at index {index}, expected '#' or '.'"
+            ),
+        }
+    }
+}
+
+impl Error for ParseMaskError {}
+
+/// Parses a mask of the cells to keep as clues, `#` for a clue and `.` for an empty cell, left
+/// to right, top to bottom. Whitespace is skipped, so the mask can be written on one line or as
+/// a grid of nine
+pub fn parse_mask(mask: &str) -> Result<[bool; 81], ParseMaskError> {
+    let cells = mask
+        .chars()
+        .enumerate()
+        .filter(|(_, character)| !character.is_whitespace())
+        .map(|(index, character)| match character {
+            '#' => Ok(true),
+            '.' => Ok(false),
+            _ => Err(ParseMaskError::InvalidCharacter { index, character }),
+        })
+        .collect::<Result<Vec<bool>, _>>()?;
+    let length = cells.len();
+    cells
+        .try_into()
+        .map_err(|_| ParseMaskError::WrongLength(length))
+}
+
+/// Every seed a run takes, for the solved board, the clues dug out of it and the solve. Written
+/// as `B<board>-R<removal>-S<solver>`, so a run can be noted down and reproduced exactly
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SeedBundle {
+    pub board: u64,
+    pub removal: u64,
+    pub solver: u64,
+}
+
+impl Display for SeedBundle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "B{}-R{}-S{}", self.board, self.removal, self.solver)
+    }
+}
+
+/// Returned when a string cannot be parsed into a [`SeedBundle`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseSeedBundleError(pub String);
+
+impl Display for ParseSeedBundleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Expected seeds written as B<board>-R<removal>-S<solver>, found {}",
+            self.0
+        )
+    }
+}
+
+impl Error for ParseSeedBundleError {}
+
+impl FromStr for SeedBundle {
+    type Err = ParseSeedBundleError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let parts: Vec<&str> = s.split('-').collect();
+        let seeds: Option<Vec<u64>> = match parts[..] {
+            [board, removal, solver] => [(board, 'B'), (removal, 'R'), (solver, 'S')]
+                .into_iter()
+                .map(|(part, prefix)| part.strip_prefix(prefix)?.parse().ok())
+                .collect(),
+            _ => None,
+        };
+        match seeds.as_deref() {
+            Some(&[board, removal, solver]) => Ok(SeedBundle {
+                board,
+                removal,
+                solver,
+            }),
+            _ => Err(ParseSeedBundleError(s.to_string())),
+        }
+    }
+}
+
+/// A generated puzzle along with its solution, the seeds that reproduce it and what is known
+/// about it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GeneratedPuzzle {
+    pub puzzle: Board,
+    pub solution: Board,
+    pub board_seed: u64,
+    pub removal_seed: u64,
+    /// Seed the puzzle is solved with, so the solve can be replayed too. Generating sets it to
+    /// the removal seed, see [`GeneratedPuzzle::with_solver_seed`]
+    pub solver_seed: u64,
+    pub clue_count: usize,
+    /// The puzzle's grade, once it has been graded
+    pub difficulty: Option<Difficulty>,
+    /// Hardest technique the logical solve of the puzzle needed, once it has been graded
+    pub hardest_technique: Option<&'static str>,
+    /// How the cells were dug out of the solution
+    pub symmetry: RemovalSymmetry,
+    /// The work the reference solver took on the puzzle, once it has been measured
+    pub effort: Option<SolveEffort>,
+    /// Solution counts run to keep the puzzle unique while digging it out, none when it was dug
+    /// out without them
+    pub uniqueness_checks: usize,
+    /// Rules the puzzle and its solution follow
+    pub variant: Variant,
+}
+
+/// Seed the reference solve of [`GeneratedPuzzle::with_effort`] draws its choices from, fixed so
+/// the backtracks it counts are the same on every run
+pub const EFFORT_SOLVER_SEED: u64 = 0;
+
+/// How much work the reference solver took on a puzzle, a machine's measure of how hard it is to
+/// go along with the human grade
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SolveEffort {
+    pub backtracks: u64,
+    pub elapsed: Duration,
+}
+
+impl GeneratedPuzzle {
+    /// Columns of the rows written by [`GeneratedPuzzle::csv_row`]
+    pub const CSV_HEADER: &'static str =
+        "board_seed,removal_seed,solver_seed,clues,grade,backtracks,time_us";
+    fn new(
+        puzzle: Board,
+        solution: Board,
+        (board_seed, removal_seed): (u64, u64),
+        symmetry: RemovalSymmetry,
+    ) -> Self {
+        Self {
+            clue_count: puzzle.clue_count(),
+            puzzle,
+            solution,
+            board_seed,
+            removal_seed,
+            solver_seed: removal_seed,
+            difficulty: None,
+            hardest_technique: None,
+            symmetry,
+            effort: None,
+            uniqueness_checks: 0,
+            variant: Variant::Classic,
+        }
+    }
+
+    /// The seeds of the puzzle and its solve
+    pub fn seeds(&self) -> SeedBundle {
+        SeedBundle {
+            board: self.board_seed,
+            removal: self.removal_seed,
+            solver: self.solver_seed,
+        }
+    }
+
+    /// Sets the seed the puzzle is solved with
+    pub fn with_solver_seed(mut self, solver_seed: u64) -> Self {
+        self.solver_seed = solver_seed;
+        self
+    }
+
+    /// Grades the puzzle unless it already was, see [`Board::grade`]
+    pub fn graded(mut self) -> Self {
+        if self.difficulty.is_none() {
+            let report = self.puzzle.grade();
+            self.difficulty = Some(report.difficulty);
+            self.hardest_technique = report.hardest_technique;
+        }
+        self
+    }
+
+    /// Solves the puzzle with [`Board::solve_board`] seeded with [`EFFORT_SOLVER_SEED`] to
+    /// measure its effort, unless it already was. The effort stays unknown if the solve fails
+    pub fn with_effort(mut self) -> Self {
+        if self.effort.is_none() {
+            let mut board = self.puzzle.clone();
+            let mut rng = ChaCha8Rng::seed_from_u64(EFFORT_SOLVER_SEED);
+            self.effort = board
+                .solve_board(&mut rng, &mut NoopObserver)
+                .ok()
+                .map(|report| SolveEffort {
+                    backtracks: report.backtracks,
+                    elapsed: report.elapsed,
+                });
+        }
+        self
+    }
+
+    /// The seeds, clue count, grade and effort as a row under [`GeneratedPuzzle::CSV_HEADER`].
+    /// What is not known yet is left empty
+    pub fn csv_row(&self) -> String {
+        let difficulty = self
+            .difficulty
+            .map_or(String::new(), |difficulty| difficulty.to_string());
+        let (backtracks, time) = match self.effort {
+            Some(effort) => (
+                effort.backtracks.to_string(),
+                effort.elapsed.as_micros().to_string(),
+            ),
+            None => (String::new(), String::new()),
+        };
+        format!(
+            "{},{},{},{},{difficulty},{backtracks},{time}",
+            self.board_seed, self.removal_seed, self.solver_seed, self.clue_count
+        )
+    }
+
+    /// Digs the puzzle out of the solution again, keeping the clues where `mask` is true, see
+    /// [`Board::remove_clues_by_mask`]. The puzzle is left as it was on errors other than
+    /// [`MaskError::ExtraCellsKept`]
+    pub fn apply_mask(&mut self, mask: &[bool; 81], enforce_unique: bool) -> Result<(), MaskError> {
+        let mut puzzle = self.solution.clone();
+        let result = puzzle.remove_clues_by_mask(mask, enforce_unique);
+        if matches!(result, Ok(()) | Err(MaskError::ExtraCellsKept(_))) {
+            self.clue_count = puzzle.clue_count();
+            self.puzzle = puzzle;
+            self.difficulty = None;
+        }
+        result
+    }
+
+    /// The puzzle in the .sdk format, one row of digits per line with `.` for empty cells, after
+    /// comment lines holding the seeds, the solution and everything else known about it, the
+    /// effort only once it has been measured
+    pub fn to_sdk(&self) -> String {
+        let difficulty = self
+            .difficulty
+            .map_or("ungraded".to_string(), |difficulty| difficulty.to_string());
+        let mut sdk = format!(
+            "#C board seed: {}\n#C removal seed: {}\n#C solver seed: {}\n#C clues: {}\n\
+             #C grade: {difficulty}\n#C symmetry: {}\n#C variant: {}\n#C solution: {}\n",
+            self.board_seed,
+            self.removal_seed,
+            self.solver_seed,
+            self.clue_count,
+            self.symmetry,
+            self.variant,
+            self.solution
+        );
+        if let Some(effort) = self.effort {
+            sdk.push_str(&format!(
+                "#C effort: {} backtracks in {}us\n",
+                effort.backtracks,
+                effort.elapsed.as_micros()
+            ));
+        }
+        let cells = self.puzzle.to_string().replace('0', ".");
+        for row in cells.as_bytes().chunks(9) {
+            sdk.push_str(std::str::from_utf8(row).unwrap());
+            sdk.push('\n');
+        }
+        sdk
+    }
+}
+
+/// One line holding the seeds, the clue count, the grade and the symmetry
+impl Display for GeneratedPuzzle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Board seed: {}, removal seed: {}, solve seed: {}, clues: {}, grade: ",
+            self.board_seed, self.removal_seed, self.solver_seed, self.clue_count
+        )?;
+        match self.difficulty {
+            Some(difficulty) => write!(f, "{difficulty}")?,
+            None => write!(f, "ungraded")?,
+        }
+        write!(f, ", symmetry: {}", self.symmetry)
+    }
+}
+
+/// Generates a solved board from `board_seed`, then removes cells from it using `removal_seed`
+/// until a number of clues drawn from `clues` is left, keeping the solved board around as ground
+/// truth. Panics if the range is not valid, see [`validate_clue_range`]
+pub fn generate_puzzle(
+    board_seed: u64,
+    removal_seed: u64,
+    clues: RangeInclusive<usize>,
+) -> GeneratedPuzzle {
+    generate_puzzle_with_grid(GridMethod::default(), board_seed, removal_seed, clues)
+}
+
+/// Same as [`generate_puzzle`], generating the solved board with `grid`
+pub fn generate_puzzle_with_grid(
+    grid: GridMethod,
+    board_seed: u64,
+    removal_seed: u64,
+    clues: RangeInclusive<usize>,
+) -> GeneratedPuzzle {
+    if let Err(error) = validate_clue_range(&clues) {
+        panic!("{error}");
+    }
+    let solution = grid.grid(board_seed);
+    let mut puzzle = solution.clone();
+    let mut rng = ChaCha8Rng::seed_from_u64(removal_seed);
+    let target_clues = rng.gen_range(clues);
+    puzzle.remove_clues(&mut rng, target_clues);
+
+    GeneratedPuzzle::new(
+        puzzle,
+        solution,
+        (board_seed, removal_seed),
+        RemovalSymmetry::None,
+    )
+}
+
+/// Groups of cells dug out of a puzzle together so its clues keep a symmetric pattern, see
+/// [`Board::clue_symmetry`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum RemovalSymmetry {
+    /// Cells are dug out one at a time
+    #[default]
+    None,
+    /// Cells are dug out along with the cell a half turn away
+    Rotational180,
+    /// Cells are dug out along with the cell mirrored left to right
+    MirrorHorizontal,
+    /// Cells are dug out along with the cell mirrored top to bottom
+    MirrorVertical,
+    /// Cells are dug out along with the cell reflected across the main diagonal
+    Diagonal,
+}
+
+impl RemovalSymmetry {
+    pub const ALL: [RemovalSymmetry; 5] = [
+        RemovalSymmetry::None,
+        RemovalSymmetry::Rotational180,
+        RemovalSymmetry::MirrorHorizontal,
+        RemovalSymmetry::MirrorVertical,
+        RemovalSymmetry::Diagonal,
+    ];
+
+    /// The cell a cell is paired with, which is itself on the axis or center of the symmetry
+    pub fn partner(&self, row: usize, col: usize) -> (usize, usize) {
+        match self {
+            RemovalSymmetry::None => (row, col),
+            RemovalSymmetry::Rotational180 => (8 - row, 8 - col),
+            RemovalSymmetry::MirrorHorizontal => (row, 8 - col),
+            RemovalSymmetry::MirrorVertical => (8 - row, col),
+            RemovalSymmetry::Diagonal => (col, row),
+        }
+    }
+
+    /// The symmetry a puzzle dug out this way has
+    pub fn kind(&self) -> SymmetryKind {
+        match self {
+            RemovalSymmetry::None => SymmetryKind::None,
+            RemovalSymmetry::Rotational180 => SymmetryKind::Rotational180,
+            RemovalSymmetry::MirrorHorizontal => SymmetryKind::MirrorHorizontal,
+            RemovalSymmetry::MirrorVertical => SymmetryKind::MirrorVertical,
+            RemovalSymmetry::Diagonal => SymmetryKind::Diagonal,
+        }
+    }
+}
+
+impl Display for RemovalSymmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            RemovalSymmetry::None => "none",
+            RemovalSymmetry::Rotational180 => "rotational",
+            RemovalSymmetry::MirrorHorizontal => "horizontal",
+            RemovalSymmetry::MirrorVertical => "vertical",
+            RemovalSymmetry::Diagonal => "diagonal",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Generates a solved board from `seed` with [`generate_full_grid`], then digs cells out of it in
+/// an order drawn from the same seed. A removal is put back whenever the puzzle stops having a
+/// unique solution, and digging stops once `target_clues` are left or every remaining cell has
+/// been tried, so the puzzle may keep more clues than asked for. Both seeds of the result are
+/// `seed`
+pub fn generate_unique_puzzle(seed: u64, target_clues: usize) -> GeneratedPuzzle {
+    generate_symmetric_puzzle(seed, target_clues, RemovalSymmetry::None)
+}
+
+/// Same as [`generate_unique_puzzle`], digging out each cell together with its partner under
+/// `symmetry`. A pair that would take the puzzle below `target_clues` is skipped, so a cell on
+/// the axis can still be dug out to get there
+pub fn generate_symmetric_puzzle(
+    seed: u64,
+    target_clues: usize,
+    symmetry: RemovalSymmetry,
+) -> GeneratedPuzzle {
+    let solution = generate_full_grid(seed);
+    let (puzzle, checks) = dig_unique(&solution, seed, target_clues, symmetry, Variant::Classic);
+    let mut generated = GeneratedPuzzle::new(puzzle, solution, (seed, seed), symmetry);
+    generated.uniqueness_checks = checks;
+    generated
+}
+
+/// Digs cells out of `solution` in an order drawn from `order_seed` for as long as the puzzle
+/// keeps a unique solution. The result is minimal: taking out any one of its clues would give it
+/// several solutions
+pub fn minimize_puzzle(solution: &Board, order_seed: u64) -> Board {
+    dig_unique(
+        solution,
+        order_seed,
+        0,
+        RemovalSymmetry::None,
+        Variant::Classic,
+    )
+    .0
+}
+
+/// Digs groups of cells out of `solution` in an order drawn from `order_seed`, putting back the
+/// ones that cost the puzzle its unique solution, until `target_clues` are left. Every pass goes
+/// through the cells in a fresh order once, skipping the groups a check already proved cannot
+/// go since removals only ever add solutions, and digging stops after a pass that removes
+/// nothing. Uniqueness is checked under the rules of `variant`. Returns the puzzle along with
+/// the number of uniqueness checks made
+fn dig_unique(
+    solution: &Board,
+    order_seed: u64,
+    target_clues: usize,
+    symmetry: RemovalSymmetry,
+    variant: Variant,
+) -> (Board, usize) {
+    let mut puzzle = solution.clone();
+    let mut rng = ChaCha8Rng::seed_from_u64(order_seed);
+    let mut cells: Vec<usize> = (0..81).collect();
+    let mut unremovable = [false; 81];
+    let mut checks = 0;
+
+    let mut clues = 81;
+    loop {
+        cells.shuffle(&mut rng);
+        let mut removed = false;
+        for &index in &cells {
+            if clues <= target_clues {
+                break;
+            }
+            let (row, col) = (index / 9, index % 9);
+            if puzzle.get_cell(row, col) == 0 || unremovable[index] {
+                continue;
+            }
+            let partner = symmetry.partner(row, col);
+            let group = if partner == (row, col) {
+                vec![(row, col)]
+            } else {
+                vec![(row, col), partner]
+            };
+            if clues - group.len() < target_clues {
+                continue;
+            }
+            for &(row, col) in &group {
+                puzzle.set_cell(row, col, 0);
+            }
+            checks += 1;
+            let solutions = match variant {
+                Variant::Classic => puzzle.count_solutions(2),
+                variant => puzzle.count_variant_solutions(variant, 2),
+            };
+            if solutions == 1 {
+                clues -= group.len();
+                removed = true;
+            } else {
+                for &(row, col) in &group {
+                    puzzle.set_cell(row, col, solution.get_cell(row, col));
+                    unremovable[row * 9 + col] = true;
+                }
+            }
+        }
+        if !removed || clues <= target_clues {
+            break;
+        }
+    }
+
+    (Board::new(puzzle.to_string()), checks)
+}
+
+/// Seed of the puzzle at `index` in a pack generated from `master_seed`. The seeds follow one
+/// another, so any puzzle of a pack can be regenerated from the master seed and its position
+pub fn pack_seed(master_seed: u64, index: u64) -> u64 {
+    master_seed.wrapping_add(index)
+}
+
+/// Generates the puzzle at `index` of a pack, from its seed as given by [`pack_seed`]. Unique
+/// puzzles are dug down to a clue count drawn from `clues` with that seed, and like any from
+/// [`generate_unique_puzzle`] may keep more clues than the range allows. Others are generated
+/// by [`generate_puzzle`]. Panics if the range is not valid, see [`validate_clue_range`]
+pub fn generate_pack_puzzle(
+    master_seed: u64,
+    index: u64,
+    clues: RangeInclusive<usize>,
+    unique: bool,
+) -> GeneratedPuzzle {
+    if let Err(error) = validate_clue_range(&clues) {
+        panic!("{error}");
+    }
+    let seed = pack_seed(master_seed, index);
+    if unique {
+        let target_clues = ChaCha8Rng::seed_from_u64(seed).gen_range(clues);
+        generate_unique_puzzle(seed, target_clues)
+    } else {
+        generate_puzzle(seed, seed, clues)
+    }
+}
+
+/// Same as [`generate_pack_puzzle`], generating a puzzle that follows the rules of `variant`
+/// from a grid of [`generate_variant_grid`]. Unique puzzles are dug out keeping a single
+/// solution under those rules, which may not be unique as a classic sudoku
+pub fn generate_pack_variant_puzzle(
+    variant: Variant,
+    master_seed: u64,
+    index: u64,
+    clues: RangeInclusive<usize>,
+    unique: bool,
+) -> GeneratedPuzzle {
+    if variant == Variant::Classic {
+        return generate_pack_puzzle(master_seed, index, clues, unique);
+    }
+    if let Err(error) = validate_clue_range(&clues) {
+        panic!("{error}");
+    }
+    let seed = pack_seed(master_seed, index);
+    let solution = generate_variant_grid(variant, seed);
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let target_clues = rng.gen_range(clues);
+    let (puzzle, checks) = if unique {
+        dig_unique(
+            &solution,
+            seed,
+            target_clues,
+            RemovalSymmetry::None,
+            variant,
+        )
+    } else {
+        let mut puzzle = solution.clone();
+        puzzle.remove_clues(&mut rng, target_clues);
+        (puzzle, 0)
+    };
+    let mut generated = GeneratedPuzzle::new(puzzle, solution, (seed, seed), RemovalSymmetry::None);
+    generated.uniqueness_checks = checks;
+    generated.variant = variant;
+    generated
+}
+
+/// Candidates generated in a row without keeping one before a [`PackGenerator`] gives up
+pub const PACK_CANDIDATES: usize = 200;
+
+/// Iterator over the puzzles of a pack, generated by [`generate_pack_variant_puzzle`] from one
+/// master seed. Classic puzzles come graded with their effort measured. Candidates not graded as
+/// the difficulty asked for, or that the reference solver backtracks more than the most effort
+/// allowed on, are discarded for the next seed, and after [`PACK_CANDIDATES`] of them in a row
+/// the pack ends with a [`PackExhausted`]
+pub struct PackGenerator {
+    master_seed: u64,
+    clues: RangeInclusive<usize>,
+    unique: bool,
+    variant: Variant,
+    difficulty: Option<Difficulty>,
+    max_effort: Option<u64>,
+    /// Index of the next candidate's seed, see [`pack_seed`]
+    index: u64,
+    discarded: u64,
+    /// Candidates discarded since the last one kept, and how many of those graded too easy
+    missed: usize,
+    too_easy: usize,
+}
+
+impl PackGenerator {
+    /// Generates classic puzzles with clues in range, without making sure they are unique unless
+    /// configured otherwise. Panics if the range is not valid
+    pub fn new(master_seed: u64, clues: RangeInclusive<usize>) -> Self {
+        if let Err(error) = validate_clue_range(&clues) {
+            panic!("{error}");
+        }
+        Self {
+            master_seed,
+            clues,
+            unique: false,
+            variant: Variant::Classic,
+            difficulty: None,
+            max_effort: None,
+            index: 0,
+            discarded: 0,
+            missed: 0,
+            too_easy: 0,
+        }
+    }
+
+    /// Digs the puzzles out keeping a single solution
+    pub fn with_unique(mut self) -> Self {
+        self.unique = true;
+        self
+    }
+
+    /// Sets the rules the puzzles follow, which leaves them ungraded unless they are classic
+    pub fn with_variant(mut self, variant: Variant) -> Self {
+        self.variant = variant;
+        self
+    }
+
+    /// Only keeps unique puzzles graded as `difficulty`, see [`Board::grade`]
+    pub fn with_difficulty(mut self, difficulty: Difficulty) -> Self {
+        self.difficulty = Some(difficulty);
+        self.with_unique()
+    }
+
+    /// Only keeps puzzles the reference solver needs at most `max_effort` backtracks on, see
+    /// [`GeneratedPuzzle::with_effort`]
+    pub fn with_max_effort(mut self, max_effort: u64) -> Self {
+        self.max_effort = Some(max_effort);
+        self
+    }
+
+    /// Candidates discarded so far
+    pub fn discarded(&self) -> u64 {
+        self.discarded
+    }
+}
+
+impl Iterator for PackGenerator {
+    type Item = Result<GeneratedPuzzle, PackExhausted>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while self.missed < PACK_CANDIDATES {
+            let generated = generate_pack_variant_puzzle(
+                self.variant,
+                self.master_seed,
+                self.index,
+                self.clues.clone(),
+                self.unique,
+            );
+            self.index += 1;
+            let generated = match self.variant {
+                Variant::Classic => generated.graded().with_effort(),
+                _ => generated,
+            };
+            let backtracks = generated.effort.map_or(0, |effort| effort.backtracks);
+            let too_hard = self
+                .max_effort
+                .is_some_and(|max_effort| backtracks > max_effort);
+            let wrong_grade = self
+                .difficulty
+                .is_some_and(|difficulty| generated.difficulty != Some(difficulty));
+            if !too_hard && !wrong_grade {
+                (self.missed, self.too_easy) = (0, 0);
+                return Some(Ok(generated));
+            }
+            self.discarded += 1;
+            self.missed += 1;
+            if self
+                .difficulty
+                .is_some_and(|difficulty| generated.difficulty < Some(difficulty))
+            {
+                self.too_easy += 1;
+            }
+        }
+        // Ends the pack once the error has been handed out
+        if self.missed > PACK_CANDIDATES {
+            return None;
+        }
+        self.missed += 1;
+        Some(Err(PackExhausted {
+            candidates: PACK_CANDIDATES,
+            difficulty: self.difficulty,
+            max_effort: self.max_effort,
+            too_easy: self.too_easy,
+        }))
+    }
+}
+
+/// Returned by a [`PackGenerator`] that generated [`PACK_CANDIDATES`] candidates in a row without
+/// keeping any of them
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PackExhausted {
+    pub candidates: usize,
+    pub difficulty: Option<Difficulty>,
+    pub max_effort: Option<u64>,
+    /// Candidates that graded easier than the difficulty asked for
+    pub too_easy: usize,
+}
+
+impl Display for PackExhausted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let wanted = match (self.difficulty, self.max_effort) {
+            (Some(difficulty), _) => format!("graded {difficulty}"),
+            (None, max_effort) => format!(
+                "solved within {} backtracks",
+                max_effort.unwrap_or_default()
+            ),
+        };
+        // Digging deeper makes puzzles harder, leaving more clues makes them easier
+        let hint = if 2 * self.too_easy > self.candidates {
+            "fewer"
+        } else {
+            "more"
+        };
+        write!(
+            f,
+            "Generated {} candidates in a row, none {wanted}; try {hint} clues",
+            self.candidates
+        )
+    }
+}
+
+impl Error for PackExhausted {}
+
+/// A minimal puzzle dug out while hunting for puzzles with few clues, along with the seeds that
+/// lead to it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HuntFind {
+    pub puzzle: Board,
+    pub clues: usize,
+    /// Seed of the solved grid, see [`generate_full_grid`]
+    pub grid_seed: u64,
+    /// Seed of the order the clues were dug out in, see [`minimize_puzzle`]
+    pub order_seed: u64,
+}
+
+/// One line holding the puzzle, its clue count and its seeds
+impl Display for HuntFind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} clues: {}, grid seed: {}, order seed: {}",
+            self.puzzle, self.clues, self.grid_seed, self.order_seed
+        )
+    }
+}
+
+/// Minimizes the grid generated from `grid_seed` once for every restart, each time digging in
+/// an order drawn from a generator seeded with the grid seed. Returns every puzzle dug out
+pub fn hunt_grid(grid_seed: u64, restarts: usize) -> Vec<HuntFind> {
+    let solution = generate_full_grid(grid_seed);
+    let mut rng = ChaCha8Rng::seed_from_u64(grid_seed);
+    (0..restarts.max(1))
+        .map(|_| {
+            let order_seed = rng.gen();
+            let puzzle = minimize_puzzle(&solution, order_seed);
+            HuntFind {
+                clues: puzzle.clue_count(),
+                puzzle,
+                grid_seed,
+                order_seed,
+            }
+        })
+        .collect()
+}
+
+/// Clues a puzzle aimed at a difficulty is dug down to. Fewer clues make the harder grades
+/// likelier, while the easiest ones only turn up reliably with plenty of clues left
+fn clues_for(difficulty: Difficulty) -> usize {
+    match difficulty {
+        Difficulty::Trivial => 36,
+        Difficulty::Easy => 28,
+        Difficulty::Medium => 24,
+        Difficulty::Hard | Difficulty::Expert => 22,
+        Difficulty::Diabolical => 17,
+    }
+}
+
+/// Attempts made in search of a difficulty before settling for the closest one, unless told
+/// otherwise
+pub const DIFFICULTY_ATTEMPTS: usize = 50;
+
+/// Returned by [`generate_with_difficulty`] when none of its attempts had the target difficulty
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DifficultyNotReached {
+    pub target: Difficulty,
+    pub attempts: usize,
+    /// The attempt whose grade was nearest the target, the earliest one on a tie
+    pub closest: Box<GeneratedPuzzle>,
+    pub closest_difficulty: Difficulty,
+}
+
+impl Display for DifficultyNotReached {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "No {} puzzle in {} attempts, the closest was {}",
+            self.target, self.attempts, self.closest_difficulty
+        )
+    }
+}
+
+impl Error for DifficultyNotReached {}
+
+/// Generates unique puzzles until one grades as `target`, see [`Board::grade`]. Each attempt
+/// draws its seed from a generator seeded with `seed`, and is dug down to fewer clues the
+/// harder the target. At least one attempt is made
+pub fn generate_with_difficulty(
+    seed: u64,
+    target: Difficulty,
+    max_attempts: usize,
+) -> Result<GeneratedPuzzle, DifficultyNotReached> {
+    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+    let distance = |difficulty: Difficulty| (difficulty as i32 - target as i32).abs();
+    let mut closest: Option<(GeneratedPuzzle, Difficulty)> = None;
+    for _ in 0..max_attempts.max(1) {
+        let generated = generate_unique_puzzle(rng.gen(), clues_for(target)).graded();
+        let difficulty = generated.difficulty.expect("the puzzle was just graded");
+        if difficulty == target {
+            return Ok(generated);
+        }
+        if closest
+            .as_ref()
+            .is_none_or(|(_, closest)| distance(difficulty) < distance(*closest))
+        {
+            closest = Some((generated, difficulty));
+        }
+    }
+    let (closest, closest_difficulty) = closest.expect("at least one attempt was made");
+    Err(DifficultyNotReached {
+        target,
+        attempts: max_attempts.max(1),
+        closest: Box::new(closest),
+        closest_difficulty,
+    })
+}
+
+/// Endless iterator of freshly generated puzzles. Every puzzle draws its board, removal and solver
+/// seeds from the generator's own seeded RNG, so the whole sequence is reproducible from one seed
+pub struct Generator {
+    rng: ChaCha8Rng,
+    clues: RangeInclusive<usize>,
+    grid: GridMethod,
+}
+
+impl Generator {
+    /// Generator constructor. Puzzles keep between 20 and 30 hints unless configured otherwise
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            clues: 20..=30,
+            grid: GridMethod::default(),
+        }
+    }
+
+    /// Sets the range of hints left on the board, see [`generate_puzzle`]. Panics if the range
+    /// is not valid
+    pub fn with_hint_range(mut self, clues: RangeInclusive<usize>) -> Self {
+        if let Err(error) = validate_clue_range(&clues) {
+            panic!("{error}");
+        }
+        self.clues = clues;
+        self
+    }
+
+    /// Sets how the solved boards are generated, see [`GridMethod`]
+    pub fn with_grid_method(mut self, grid: GridMethod) -> Self {
+        self.grid = grid;
+        self
+    }
+}
+
+impl Iterator for Generator {
+    type Item = GeneratedPuzzle;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let generated = generate_puzzle_with_grid(
+            self.grid,
+            self.rng.gen(),
+            self.rng.gen(),
+            self.clues.clone(),
+        );
+        Some(generated.with_solver_seed(self.rng.gen()))
+    }
+}
@@ -0,0 +1,195 @@
+use std::fmt::Display;
+
+use crate::technique::{technique_names, LogicalOutcome};
+
+/// How hard a puzzle is for a human, from the hardest technique it needs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Difficulty {
+    Trivial,
+    Easy,
+    Medium,
+    Hard,
+    Expert,
+    /// No technique known to the logical solver finishes the puzzle, so it takes guessing
+    Diabolical,
+}
+
+impl Difficulty {
+    /// Every difficulty, easiest first
+    pub const ALL: [Difficulty; 6] = [
+        Difficulty::Trivial,
+        Difficulty::Easy,
+        Difficulty::Medium,
+        Difficulty::Hard,
+        Difficulty::Expert,
+        Difficulty::Diabolical,
+    ];
+
+    /// Difficulty of a puzzle needing the named technique once. Unknown names rate as
+    /// diabolical
+    pub fn of_technique(name: &str) -> Difficulty {
+        match name {
+            "Naked single" => Difficulty::Trivial,
+            "Hidden single" => Difficulty::Easy,
+            "Locked candidates" | "Naked pair" => Difficulty::Medium,
+            "Hidden pair" | "Naked triple" | "Hidden triple" | "X-Wing" | "Swordfish" => {
+                Difficulty::Hard
+            }
+            "XY-Wing" | "XYZ-Wing" | "Simple coloring" => Difficulty::Expert,
+            _ => Difficulty::Diabolical,
+        }
+    }
+
+    /// The next difficulty up, staying at the same one for the hardest
+    fn harder(self) -> Difficulty {
+        let index = Self::ALL.iter().position(|&known| known == self).unwrap();
+        Self::ALL[(index + 1).min(Self::ALL.len() - 1)]
+    }
+}
+
+impl Display for Difficulty {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{self:?}")
+    }
+}
+
+/// An elimination technique needed at least this many times rates the puzzle one level higher
+const REPEATS_FOR_HARDER: usize = 3;
+
+/// A difficulty along with what it was based on
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GradeReport {
+    pub difficulty: Difficulty,
+    /// Latest technique in the runner's order that the logical solve needed
+    pub hardest_technique: Option<&'static str>,
+    /// Times each technique was used, in the runner's order, leaving out unused ones and
+    /// followed by techniques of the caller's own in the order they were first used. Placements
+    /// and eliminations count once each
+    pub technique_counts: Vec<(&'static str, usize)>,
+    /// Whether the logical solve got stuck before finishing
+    pub requires_guessing: bool,
+}
+
+impl GradeReport {
+    /// Grades the result of a logical solve. A stuck solve is diabolical. Otherwise the hardest
+    /// technique decides, moved up a level when it is an elimination that was needed three times
+    /// or more, but never up to diabolical. Techniques of the caller's own rate as diabolical,
+    /// see [`Difficulty::of_technique`]
+    pub fn from_outcome(outcome: &LogicalOutcome) -> Self {
+        let mut technique_counts: Vec<(&'static str, usize)> =
+            technique_names().map(|name| (name, 0)).collect();
+        for name in outcome.techniques_used() {
+            match technique_counts
+                .iter_mut()
+                .find(|(known, _)| *known == name)
+            {
+                Some((_, count)) => *count += 1,
+                None => technique_counts.push((name, 1)),
+            }
+        }
+        technique_counts.retain(|(_, count)| *count > 0);
+
+        let hardest_technique = outcome.hardest_technique();
+        let requires_guessing = matches!(outcome, LogicalOutcome::Stuck { .. });
+        let difficulty = if requires_guessing {
+            Difficulty::Diabolical
+        } else {
+            let hardest = hardest_technique.unwrap_or("Naked single");
+            let base = Difficulty::of_technique(hardest);
+            let repeats = technique_counts
+                .iter()
+                .find(|(name, _)| *name == hardest)
+                .map_or(0, |(_, count)| *count);
+            let is_elimination = base >= Difficulty::Medium;
+            if is_elimination && repeats >= REPEATS_FOR_HARDER {
+                base.harder().min(Difficulty::Expert).max(base)
+            } else {
+                base
+            }
+        };
+        Self {
+            difficulty,
+            hardest_technique,
+            technique_counts,
+            requires_guessing,
+        }
+    }
+}
+
+impl Display for GradeReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.difficulty)?;
+        if self.requires_guessing {
+            write!(f, ", requires guessing")?;
+        }
+        let counts: Vec<String> = self
+            .technique_counts
+            .iter()
+            .map(|(name, count)| format!("{name} x{count}"))
+            .collect();
+        if !counts.is_empty() {
+            write!(f, " ({})", counts.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// How many puzzles of a pack got each grade, to audit a whole pack at once
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GradeDistribution {
+    /// Puzzles of each grade, in the order of [`Difficulty::ALL`]
+    counts: [usize; Difficulty::ALL.len()],
+}
+
+impl GradeDistribution {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts one more puzzle of the report's grade
+    pub fn add(&mut self, report: &GradeReport) {
+        let index = Difficulty::ALL
+            .iter()
+            .position(|&known| known == report.difficulty)
+            .unwrap();
+        self.counts[index] += 1;
+    }
+
+    /// Puzzles counted with the grade
+    pub fn count(&self, difficulty: Difficulty) -> usize {
+        Difficulty::ALL
+            .iter()
+            .zip(self.counts)
+            .find_map(|(&known, count)| (known == difficulty).then_some(count))
+            .unwrap_or_default()
+    }
+
+    /// Puzzles counted with any grade
+    pub fn total(&self) -> usize {
+        self.counts.iter().sum()
+    }
+}
+
+impl FromIterator<GradeReport> for GradeDistribution {
+    fn from_iter<T: IntoIterator<Item = GradeReport>>(reports: T) -> Self {
+        let mut distribution = Self::new();
+        for report in reports {
+            distribution.add(&report);
+        }
+        distribution
+    }
+}
+
+/// A table of every grade with its count and share of the puzzles, the grades nothing got
+/// included, ending with the total
+impl Display for GradeDistribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:<12} {:>8} {:>7}", "grade", "puzzles", "share")?;
+        let total = self.total();
+        for (difficulty, count) in Difficulty::ALL.iter().zip(self.counts) {
+            let share = 100.0 * count as f64 / total.max(1) as f64;
+            writeln!(f, "{:<12} {count:>8} {share:>6.1}%", difficulty.to_string())?;
+        }
+        writeln!(f, "{:<12} {total:>8}", "total")
+    }
+}
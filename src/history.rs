@@ -0,0 +1,172 @@
+use std::{error::Error, fmt::Display, str::FromStr};
+
+use crate::board::{cell_name, Board, BoardMove, Cascade};
+
+/// The moves made while solving a board, oldest first. Serializes to one move per line, each
+/// line holding the move followed by its cascades in `r#c#=v` notation
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MoveHistory {
+    moves: Vec<BoardMove>,
+}
+
+/// Reasons a history cannot be replayed onto a board
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplayError {
+    /// A move places a value on a cell that is a given
+    CellIsGiven { position: (usize, usize) },
+    /// A move places a value on a cell that already holds a different one
+    CellOccupied { position: (usize, usize), value: u8 },
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::CellIsGiven { position } => {
+                write!(f, "{} is a given", cell_name(*position))
+            }
+            ReplayError::CellOccupied { position, value } => {
+                write!(f, "{} already holds {value}", cell_name(*position))
+            }
+        }
+    }
+}
+
+impl Error for ReplayError {}
+
+/// Returned when a serialized history cannot be parsed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseHistoryError {
+    /// 1 based line number of the offending move
+    pub line: usize,
+    pub token: String,
+}
+
+impl Display for ParseHistoryError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Expected a placement like r1c2=3 on line {}, found \"{}\"",
+            self.line, self.token
+        )
+    }
+}
+
+impl Error for ParseHistoryError {}
+
+impl MoveHistory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn push(&mut self, board_move: BoardMove) {
+        self.moves.push(board_move);
+    }
+
+    pub fn pop(&mut self) -> Option<BoardMove> {
+        self.moves.pop()
+    }
+
+    /// The most recent move, which cascades get attached to while solving
+    pub fn last_mut(&mut self) -> Option<&mut BoardMove> {
+        self.moves.last_mut()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &BoardMove> {
+        self.moves.iter()
+    }
+
+    pub fn len(&self) -> usize {
+        self.moves.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.moves.is_empty()
+    }
+
+    /// Applies every move in order onto the board. Each placement must land on an empty cell that
+    /// is not a given, or on a cell already holding the same value. The board is left untouched
+    /// if any placement fails
+    pub fn replay_onto(&self, board: &mut Board) -> Result<(), ReplayError> {
+        let mut replayed = board.clone();
+        for board_move in &self.moves {
+            let placements =
+                std::iter::once(Cascade::new(board_move.position(), board_move.new_value()))
+                    .chain(board_move.cascades().iter().copied());
+            for placement in placements {
+                let [row, col] = placement.position();
+                let current = replayed.get_cell(row, col);
+                if current == placement.value() {
+                    continue;
+                }
+                if replayed.is_given(row, col) {
+                    return Err(ReplayError::CellIsGiven {
+                        position: (row, col),
+                    });
+                }
+                if current != 0 {
+                    return Err(ReplayError::CellOccupied {
+                        position: (row, col),
+                        value: current,
+                    });
+                }
+            }
+            replayed.apply_move(board_move);
+        }
+        *board = replayed;
+        Ok(())
+    }
+}
+
+impl From<Vec<BoardMove>> for MoveHistory {
+    fn from(moves: Vec<BoardMove>) -> Self {
+        Self { moves }
+    }
+}
+
+impl Display for MoveHistory {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for board_move in &self.moves {
+            writeln!(f, "{board_move}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a single `r#c#=v` placement with 1 based coordinates
+fn parse_placement(token: &str) -> Option<Cascade> {
+    let (cell, value) = token.strip_prefix('r')?.split_once('=')?;
+    let (row, col) = cell.split_once('c')?;
+    let row = row.parse::<usize>().ok()?;
+    let col = col.parse::<usize>().ok()?;
+    let value = value.parse::<u8>().ok()?;
+    if !(1..=9).contains(&row) || !(1..=9).contains(&col) || !(1..=9).contains(&value) {
+        return None;
+    }
+    Some(Cascade::new([row - 1, col - 1], value))
+}
+
+impl FromStr for MoveHistory {
+    type Err = ParseHistoryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut history = MoveHistory::new();
+        for (i, line) in s.lines().enumerate() {
+            let mut placements = line.split_whitespace().map(|token| {
+                parse_placement(token).ok_or_else(|| ParseHistoryError {
+                    line: i + 1,
+                    token: token.to_string(),
+                })
+            });
+            let Some(first) = placements.next() else {
+                continue;
+            };
+            let first = first?;
+            let mut board_move = BoardMove::new(first.position(), first.value());
+            for cascade in placements {
+                board_move.push_cascade(cascade?);
+            }
+            history.push(board_move);
+        }
+        Ok(history)
+    }
+}
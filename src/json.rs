@@ -0,0 +1,366 @@
+use std::{error::Error, fmt::Display, str::FromStr};
+
+/// Arrays and objects nested deeper than this are refused, so a hostile line cannot overflow the
+/// stack of the parser
+pub const MAX_DEPTH: usize = 64;
+
+/// A JSON value. Numbers keep the text they were written as, so integers too large for a float,
+/// like seeds, survive being read and written back
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(String),
+    String(String),
+    Array(Vec<JsonValue>),
+    /// Members in the order they were written
+    Object(Vec<(String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// An object made of the members, in their order
+    pub fn object<const N: usize>(members: [(&str, JsonValue); N]) -> Self {
+        JsonValue::Object(
+            members
+                .into_iter()
+                .map(|(key, value)| (key.to_string(), value))
+                .collect(),
+        )
+    }
+
+    /// The value of the member of an object, the first one if the key is repeated. `None` for
+    /// values that are not objects
+    pub fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            JsonValue::String(string) => Some(string),
+            _ => None,
+        }
+    }
+
+    /// The number if it is a non negative integer that fits a `u64`
+    pub fn as_u64(&self) -> Option<u64> {
+        match self {
+            JsonValue::Number(number) => number.parse().ok(),
+            _ => None,
+        }
+    }
+}
+
+impl From<&str> for JsonValue {
+    fn from(value: &str) -> Self {
+        JsonValue::String(value.to_string())
+    }
+}
+
+impl From<String> for JsonValue {
+    fn from(value: String) -> Self {
+        JsonValue::String(value)
+    }
+}
+
+impl From<bool> for JsonValue {
+    fn from(value: bool) -> Self {
+        JsonValue::Bool(value)
+    }
+}
+
+impl From<usize> for JsonValue {
+    fn from(value: usize) -> Self {
+        JsonValue::Number(value.to_string())
+    }
+}
+
+impl From<u64> for JsonValue {
+    fn from(value: u64) -> Self {
+        JsonValue::Number(value.to_string())
+    }
+}
+
+impl<T: Into<JsonValue>> From<Option<T>> for JsonValue {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(JsonValue::Null, Into::into)
+    }
+}
+
+/// Written compactly, without any whitespace, so a value always fits on one line
+impl Display for JsonValue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JsonValue::Null => write!(f, "null"),
+            JsonValue::Bool(value) => write!(f, "{value}"),
+            JsonValue::Number(number) => write!(f, "{number}"),
+            JsonValue::String(string) => write!(f, "{}", json_string(string)),
+            JsonValue::Array(values) => {
+                write!(f, "[")?;
+                for (i, value) in values.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { "," };
+                    write!(f, "{separator}{value}")?;
+                }
+                write!(f, "]")
+            }
+            JsonValue::Object(members) => {
+                write!(f, "{{")?;
+                for (i, (key, value)) in members.iter().enumerate() {
+                    let separator = if i == 0 { "" } else { "," };
+                    write!(f, "{separator}{}:{value}", json_string(key))?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+/// The string as a JSON string literal, quotes included
+pub fn json_string(value: &str) -> String {
+    let mut escaped = String::from("\"");
+    for char in value.chars() {
+        match char {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            char if char.is_control() => escaped.push_str(&format!("\\u{:04x}", char as u32)),
+            char => escaped.push(char),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+/// Returned when text is not a single JSON value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseJsonError {
+    /// Byte offset the parser gave up at
+    pub position: usize,
+    /// What the parser was looking for there
+    pub expected: &'static str,
+}
+
+impl Display for ParseJsonError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Expected {} at byte {} of the JSON",
+            self.expected, self.position
+        )
+    }
+}
+
+impl Error for ParseJsonError {}
+
+impl FromStr for JsonValue {
+    type Err = ParseJsonError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser {
+            text: s,
+            position: 0,
+        };
+        let value = parser.value(0)?;
+        parser.skip_whitespace();
+        if parser.position < s.len() {
+            return Err(parser.error("the end of the input"));
+        }
+        Ok(value)
+    }
+}
+
+/// Recursive descent over the text of a single value
+struct Parser<'a> {
+    text: &'a str,
+    /// Byte offset of the next character to read
+    position: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, expected: &'static str) -> ParseJsonError {
+        ParseJsonError {
+            position: self.position,
+            expected,
+        }
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.text[self.position..].chars().next()
+    }
+
+    fn next(&mut self) -> Option<char> {
+        let char = self.peek()?;
+        self.position += char.len_utf8();
+        Some(char)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(' ' | '\t' | '\n' | '\r')) {
+            self.position += 1;
+        }
+    }
+
+    /// Consumes `literal` if the text continues with it
+    fn eat(&mut self, literal: &str) -> bool {
+        let matched = self.text[self.position..].starts_with(literal);
+        if matched {
+            self.position += literal.len();
+        }
+        matched
+    }
+
+    fn value(&mut self, depth: usize) -> Result<JsonValue, ParseJsonError> {
+        self.skip_whitespace();
+        if depth > MAX_DEPTH {
+            return Err(self.error("values nested less deeply"));
+        }
+        match self.peek() {
+            Some('{') => self.object(depth),
+            Some('[') => self.array(depth),
+            Some('"') => self.string().map(JsonValue::String),
+            Some('-' | '0'..='9') => self.number(),
+            _ if self.eat("null") => Ok(JsonValue::Null),
+            _ if self.eat("true") => Ok(JsonValue::Bool(true)),
+            _ if self.eat("false") => Ok(JsonValue::Bool(false)),
+            _ => Err(self.error("a value")),
+        }
+    }
+
+    fn object(&mut self, depth: usize) -> Result<JsonValue, ParseJsonError> {
+        self.position += 1;
+        let mut members = Vec::new();
+        self.skip_whitespace();
+        if self.eat("}") {
+            return Ok(JsonValue::Object(members));
+        }
+        loop {
+            self.skip_whitespace();
+            if self.peek() != Some('"') {
+                return Err(self.error("a member name"));
+            }
+            let key = self.string()?;
+            self.skip_whitespace();
+            if !self.eat(":") {
+                return Err(self.error("':'"));
+            }
+            members.push((key, self.value(depth + 1)?));
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some('}') => {
+                    self.position += 1;
+                    return Ok(JsonValue::Object(members));
+                }
+                _ => return Err(self.error("',' or '}'")),
+            }
+        }
+    }
+
+    fn array(&mut self, depth: usize) -> Result<JsonValue, ParseJsonError> {
+        self.position += 1;
+        let mut values = Vec::new();
+        self.skip_whitespace();
+        if self.eat("]") {
+            return Ok(JsonValue::Array(values));
+        }
+        loop {
+            values.push(self.value(depth + 1)?);
+            self.skip_whitespace();
+            match self.peek() {
+                Some(',') => self.position += 1,
+                Some(']') => {
+                    self.position += 1;
+                    return Ok(JsonValue::Array(values));
+                }
+                _ => return Err(self.error("',' or ']'")),
+            }
+        }
+    }
+
+    fn string(&mut self) -> Result<String, ParseJsonError> {
+        self.position += 1;
+        let mut string = String::new();
+        loop {
+            match self.next() {
+                Some('"') => return Ok(string),
+                Some('\\') => {
+                    let escaped = match self.next() {
+                        Some(char @ ('"' | '\\' | '/')) => char,
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => self.unicode_escape()?,
+                        _ => return Err(self.error("an escape sequence")),
+                    };
+                    string.push(escaped);
+                }
+                Some(char) if !char.is_control() => string.push(char),
+                _ => return Err(self.error("the closing '\"' of the string")),
+            }
+        }
+    }
+
+    /// The character of a `\u` escape, the `\u` already read, joining surrogate pairs
+    fn unicode_escape(&mut self) -> Result<char, ParseJsonError> {
+        let high = self.hex_code_unit()?;
+        let code = if (0xd800..0xdc00).contains(&high) {
+            if !self.eat("\\u") {
+                return Err(self.error("the low half of a surrogate pair"));
+            }
+            let low = self.hex_code_unit()?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(self.error("the low half of a surrogate pair"));
+            }
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("a valid character code"))
+    }
+
+    fn hex_code_unit(&mut self) -> Result<u32, ParseJsonError> {
+        let digits = self.text.get(self.position..self.position + 4);
+        let code = digits
+            .filter(|digits| digits.chars().all(|char| char.is_ascii_hexdigit()))
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("four hex digits"))?;
+        self.position += 4;
+        Ok(code)
+    }
+
+    fn number(&mut self) -> Result<JsonValue, ParseJsonError> {
+        let start = self.position;
+        self.eat("-");
+        let digits = |parser: &mut Self| {
+            let first = parser.position;
+            while matches!(parser.peek(), Some('0'..='9')) {
+                parser.position += 1;
+            }
+            parser.position > first
+        };
+        if !self.eat("0") && !digits(self) {
+            return Err(self.error("a digit"));
+        }
+        if self.eat(".") && !digits(self) {
+            return Err(self.error("a digit after the decimal point"));
+        }
+        if self.eat("e") || self.eat("E") {
+            let _ = self.eat("+") || self.eat("-");
+            if !digits(self) {
+                return Err(self.error("a digit in the exponent"));
+            }
+        }
+        Ok(JsonValue::Number(
+            self.text[start..self.position].to_string(),
+        ))
+    }
+}
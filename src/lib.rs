@@ -0,0 +1,77 @@
+//! Sudoku solving and generation.
+//!
+//! Puzzles are [`Board`]s, parsed from their 81 digit string form. They are solved with
+//! [`Board::solve_board`], which collapses the cell with the least entropy until the board is
+//! complete, backtracking when it runs into a dead end. Progress can be followed through a
+//! [`SolveObserver`], and the random choices made while backtracking come from a caller supplied
+//! generator so a solve can be reproduced. New puzzles come from the [`generator`] module.
+//!
+//! ```
+//! use rand::SeedableRng;
+//! use rand_chacha::ChaCha8Rng;
+//! use sudoku_solver::{Board, NoopObserver};
+//!
+//! let mut board: Board =
+//!     "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+//!         .parse()
+//!         .unwrap();
+//! board
+//!     .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut NoopObserver)
+//!     .unwrap();
+//! assert!(board.validate_board());
+//! ```
+
+pub mod batch;
+pub mod board;
+pub mod candidates;
+pub mod daily;
+pub mod dimacs;
+pub mod dlx;
+pub mod generator;
+pub mod grade;
+pub mod history;
+pub mod json;
+pub mod observer;
+pub mod output;
+pub mod palette;
+pub mod play;
+pub mod protocol;
+pub mod repl;
+pub mod report;
+pub mod screen;
+pub mod solver;
+pub mod stats;
+pub mod technique;
+pub mod verify;
+
+pub use batch::{solve_batch_parallel, BatchReport};
+pub use board::{
+    Board, BoardMove, Cascade, Contradiction, Discrepancy, MaskError, ParseBoardError, SolveError,
+    UniquenessResult,
+};
+pub use candidates::CandidateSet;
+pub use daily::{generate_daily, Date};
+pub use dimacs::SatAssignmentError;
+#[allow(deprecated)]
+pub use generator::remove_board_cells;
+pub use generator::{
+    generate_board, generate_full_grid, generate_pack_puzzle, generate_puzzle,
+    generate_puzzle_with_grid, generate_symmetric_puzzle, generate_unique_puzzle,
+    generate_with_difficulty, hunt_grid, minimize_puzzle, parse_mask, ClueRangeError,
+    DifficultyNotReached, GeneratedPuzzle, Generator, GridMethod, HuntFind, PackExhausted,
+    PackGenerator, ParseMaskError, RemovalSymmetry, SeedBundle, SolveEffort,
+};
+pub use grade::{Difficulty, GradeDistribution, GradeReport};
+pub use history::MoveHistory;
+pub use observer::{AnimatingObserver, NoopObserver, SolveObserver, TerminalObserver};
+pub use output::{OutputFormat, Record, ResultWriter};
+pub use palette::{ColorChoice, Palette};
+pub use play::Game;
+pub use repl::{ReplCommand, ReplSession};
+pub use report::{
+    CancellationToken, CellSelection, PropagationLevel, RestartPolicy, SolveLimits, SolveOptions,
+    SolveReport, ValueOrdering,
+};
+pub use screen::TerminalGuard;
+pub use solver::SudokuSolver;
+pub use stats::{GameRecord, Stats};
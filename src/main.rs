@@ -1,151 +1,2088 @@
-use std::{io::stdout, time::Instant};
+use std::{
+    fmt::Display,
+    fs::{File, OpenOptions},
+    io::{stderr, stdin, stdout, BufRead, BufReader, BufWriter, ErrorKind, Read, Write},
+    ops::RangeInclusive,
+    path::{Path, PathBuf},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU64, Ordering},
+        mpsc, Arc,
+    },
+    time::{Duration, Instant},
+};
 
 use crossterm::{
-    cursor, style::Print, terminal::{self, Clear}, QueueableCommand
+    cursor,
+    event::{self, Event, KeyCode, KeyEventKind, KeyModifiers},
+    style::{Print, PrintStyledContent},
+    terminal::{self, Clear},
+    tty::IsTty,
+    QueueableCommand,
 };
 use rand::prelude::*;
+use signal_hook::consts::SIGINT;
+
+use sudoku_solver::{
+    batch::{
+        map_streaming, solve_batch_sequential, solve_batch_streaming, BatchReport, TimingsWriter,
+        PUZZLE_TIMEOUT,
+    },
+    board::{Board, DrawOptions, MaskError, ParseBoardError, SolveError, Variant},
+    daily::{generate_daily, Date, ParseDateError},
+    generator::{
+        generate_puzzle, generate_puzzle_with_grid, generate_symmetric_puzzle,
+        generate_with_difficulty, hunt_grid, pack_seed, parse_mask, validate_clue_range,
+        GeneratedPuzzle, GridMethod, PackGenerator, ParseSeedBundleError, RemovalSymmetry,
+        SeedBundle, DIFFICULTY_ATTEMPTS, MINIMUM_CLUES, UNIQUE_PUZZLE_CLUES,
+    },
+    grade::{Difficulty, GradeDistribution},
+    history::{MoveHistory, ParseHistoryError},
+    observer::{AnimatingObserver, NoopObserver},
+    output::{OutputFormat, PackWriter, Record, RecordError, ResultWriter},
+    palette::{ColorChoice, Palette},
+    play::Game,
+    protocol,
+    repl::ReplSession,
+    report::{CancellationToken, SolveLimits, SolveReport},
+    screen::{self, TerminalGuard},
+    solver::{comparison_solvers, registered_solvers, solver_named, SudokuSolver},
+    stats::{clock_time, default_stats_path, GameRecord, Stats},
+    technique::{HintAction, LogicalOutcome, TechniqueSet},
+    verify::{bug_report, verify_solution, VerificationFailure},
+};
+
+/// Printed by `help` and whenever no subcommand is given
+const USAGE: &str = "\
+Usage: sudoku_solver <COMMAND> [OPTIONS]
+
+Commands:
+  solve        Solve puzzles from files or stdin, or a generated puzzle
+  generate     Generate a pack of puzzles
+  play         Solve a generated puzzle by hand in the terminal
+  grade        Print how hard puzzles are for a human
+  bench        Compare the solvers on generated puzzles
+  daily        Print the puzzle of the day
+  check        Check solutions against their puzzles
+  hint         Print the next logical move on a puzzle
+  explain      Walk through a logical solve of a puzzle
+  repl         Poke at a puzzle one command at a time, read from stdin
+  serve-stdio  Answer JSON requests read from stdin, one per line, for other programs
+  stats        Print the solving statistics kept by play
+  hunt17       Dig minimal puzzles in search of ones with few clues
+  help         Print this message, or the help of a command
+
+Options shared by the commands:
+  --seeds B1-R2-S3  Seeds of an earlier run, to reproduce its puzzle exactly
+  --format NAME     How results are printed: line, grid, json or csv
+  --color WHEN      When to color boards: auto, always or never [default: auto]. Auto colors
+                    them on a terminal unless NO_COLOR is set
+  -q, --quiet       Print only the results
+  -h, --help        Print the help of the command
+
+Exit codes:
+  0  Every puzzle was solved, or the command succeeded
+  1  A puzzle has no solution
+  2  A puzzle's givens break the sudoku rules
+  3  An argument or an input could not be parsed
+  4  A solve or a search stopped at one of its limits
+  5  Anything else, like a file that could not be written
+
+Run `sudoku_solver help <COMMAND>` for the options of a command";
+
+const SOLVE_HELP: &str = "\
+Usage: sudoku_solver solve [FILE]... [OPTIONS]
+
+Solves every puzzle of the files, one 81 digit puzzle per line with 0 or . for empty cells and
+lines starting with # skipped. A FILE of - reads stdin, as does giving no files while puzzles
+are piped in. Otherwise a puzzle is generated and solved, its solve animated with --animate.
+
+Puzzles are read and solved as they come, each answered as soon as the ones before it are, so
+the command works as a filter. With --quiet, or when stdout is not a terminal, nothing is drawn
+and each puzzle prints exactly one line: its solution, UNSOLVABLE, INVALID followed by the
+reason when the line is not a puzzle, or TIMEOUT when its solve ran out of time, with the
+details of failures going to stderr. A solve that went wrong inside the solver prints
+UNSOLVABLE as well, the exit code of 5 telling it apart.
+
+With --verify, every solution is checked again without trusting the solver: the givens must be
+kept, every unit must hold the digits 1 to 9, and a puzzle with a single solution must get the
+one dancing links finds. A solution that fails prints UNSOLVABLE, exits with 5 and is written to
+the dump file along with its seeds and the solution dancing links finds, for a bug report.
+
+Options:
+  --jobs N            Threads the puzzles of the files are solved on
+  --keep-going        Exit with 0 even if some puzzles of the files failed
+  --timings FILE      Write how each puzzle of the files was solved as a CSV, row by row
+  --timeout-ms N      Milliseconds each puzzle of the files may take [default: 5000]
+  --verify            Check every solution with an independent method
+  --verify-dump FILE  File failed verifications are appended to [default: verify-failures.txt]
+  --solver NAME       Solver for the puzzles: wfc, dfs or dlx [default: wfc]
+  --solutions N       List up to N solutions of each puzzle and whether there are more, 0
+                      only counting them
+  --animate           Watch the generated puzzle being solved, Ctrl-C or q stopping it
+  --delay-ms N        Pause N milliseconds after every placement of the animation [default: 0]
+  --seeds B1-R2-S3    Seeds of the generated puzzle, random unless given
+  --difficulty NAME   Generate a puzzle of this grade: easy, medium, hard, expert or diabolical
+  --symmetry NAME     Dig the clues out in symmetric pairs, see RemovalSymmetry for the names
+  --grid NAME         How the solved grid is generated with --fast: solved or pattern
+  --fast              Dig without keeping the solution unique
+  --clues A..=B       Clues --fast leaves, or a single count [default: 20..=30]
+  --pattern FILE      Keep the clues where the mask in the file has a #
+  --save FILE         Write the generated puzzle in the .sdk format
+  --export-cnf FILE   Write the generated puzzle as a DIMACS CNF formula instead of solving it
+  --hint              Print the first logical move on the generated puzzle
+  --explain           Walk through a logical solve, --steps N stopping it after N steps
+  --logical           Tell whether the generated puzzle needs guessing
+  --format NAME       How results are printed: line, grid, json or csv
+  -q, --quiet         Print one line per puzzle and nothing else";
+
+const GENERATE_HELP: &str = "\
+Usage: sudoku_solver generate [OPTIONS]
+
+Generates puzzles from the seeds following a master seed, so the whole pack can be generated
+again from it, and prints them one per line.
+
+Options:
+  -n, --count N          Puzzles to generate [default: 100]
+  --seed N               Master seed, random unless given
+  --clues A..=B          Range of clues the puzzles are dug down to [default: 20..=30]
+  --unique               Only dig puzzles with a single solution
+  --variant NAME         Rules the puzzles follow: classic, or x where both main diagonals hold
+                         every digit too [default: classic]
+  --difficulty NAME      Only keep unique puzzles of this grade: easy, medium, hard or expert
+  --print-grade          Add each puzzle's grade and the hardest technique it needs to its line,
+                         or to its row of the CSV with --out
+  --max-effort N         Discard puzzles the reference solver backtracks more than N times on
+  --out FILE             Write the puzzles to FILE, with their seeds and grades in a CSV next to it
+  --with-solutions       Also write the solutions next to FILE
+  --progress K           Report progress on stderr every K puzzles [default: 50]
+  --format NAME          How puzzles are printed without --out: line, grid, json or csv
+  -q, --quiet            Do not report progress";
+
+const PLAY_HELP: &str = "\
+Usage: sudoku_solver play [OPTIONS]
+
+Generates a puzzle and lets you solve it in the terminal. Move with the arrow keys, type 1-9 to
+fill in the selected cell and 0, backspace or delete to clear it. u takes back the last entry,
+? shows a hint, m counts the mistakes so far and q quits.
+
+Every game is recorded in the stats file when it ends, solved or not, see the stats command.
+
+Options:
+  --seeds B1-R2-S3    Seeds of the puzzle, random unless given
+  --difficulty NAME   Generate a puzzle of this grade
+  --symmetry NAME     Dig the clues out in symmetric pairs
+  --daily             Play today's puzzle of the day, counting toward the daily streak
+  --stats-file FILE   Where the stats are kept [default: sudoku_solver/stats.txt in the data
+                      directory, $XDG_DATA_HOME or ~/.local/share]";
+
+const STATS_HELP: &str = "\
+Usage: sudoku_solver stats [OPTIONS]
+
+Prints the games played and solved at each grade, with the best and average solve times, and
+the streak of puzzles of the day solved on consecutive days. A stats file that cannot be read
+is reported and treated as empty.
+
+Options:
+  --stats-file FILE   Where the stats are kept [default: sudoku_solver/stats.txt in the data
+                      directory, $XDG_DATA_HOME or ~/.local/share]";
+
+const GRADE_HELP: &str = "\
+Usage: sudoku_solver grade [FILE]... [OPTIONS]
+
+Grades every puzzle of the files as they are read and prints how many got each grade. A FILE
+of - reads stdin.
+
+Options:
+  --details       Also print a line per puzzle: its clues, grade, hardest technique and whether
+                  techniques alone solve it
+  --jobs N        Grade on N threads [default: all cores]
+  --keep-going    Exit with 0 even if some lines are not puzzles
+  --format NAME   Print a record per puzzle instead, as line, grid, json or csv";
+
+const BENCH_HELP: &str = "\
+Usage: sudoku_solver bench [OPTIONS]
+
+Runs a solver over generated puzzles without drawing anything and prints how many it solved,
+the distribution of its timings and its backtracks. The same seed always gives the same puzzles.
+
+Options:
+  -n, --puzzles N  Puzzles to generate [default: 100]
+  --seed N         Master seed of the puzzles [default: 0]
+  --clues A..=B    Range of clues the puzzles keep [default: 20..=30]
+  --solver NAME    Solver to run: wfc, dfs or dlx [default: wfc]
+  --compare        Run every solver, and the entropy solver with each of its options, over the
+                   same puzzles and print a table comparing them
+  --csv FILE       Write how the chosen solver did on each puzzle
+  --timings FILE   Write how every solver run did on each puzzle as a CSV, row by row
+  -q, --quiet      Do not report progress";
+
+const DAILY_HELP: &str = "\
+Usage: sudoku_solver daily [YYYY-MM-DD] [OPTIONS]
+
+Prints the puzzle of the day, today's in UTC unless a date is given.
+
+Options:
+  --format NAME   How the puzzle is printed: line, grid, json or csv
+  -q, --quiet     Print only the puzzle";
+
+const HUNT_HELP: &str = "\
+Usage: sudoku_solver hunt17 [OPTIONS]
+
+Digs minimal puzzles out of one grid after another until interrupted with Ctrl-C, logging the
+ones with few clues along with the seeds that lead to them.
+
+Options:
+  --threshold N  Log puzzles with at most N clues [default: 17]
+  --restarts N   Removal orders tried per grid [default: 8]
+  --seed N       Master seed of the grids, random unless given
+  --jobs N       Threads to hunt on
+  --log FILE     File the finds are appended to [default: hunt17.log]
+  -q, --quiet    Do not keep a running tally";
+
+const CHECK_HELP: &str = "\
+Usage: sudoku_solver check --puzzle FILE --solution FILE
+       sudoku_solver check [FILE]...
+
+Checks that each solution is complete, follows the sudoku rules and keeps every given of its
+puzzle, printing OK or each thing wrong with it. The puzzles and solutions are paired line by
+line, or read as `puzzle,solution` lines from the FILEs, - reading stdin.
 
-pub mod board;
-use board::Board;
-use rand_chacha::ChaCha8Rng;
+Options:
+  --puzzle FILE    Puzzles, one per line
+  --solution FILE  Solutions, one per line in the order of the puzzles
+  --keep-going     Exit with 0 even if some solutions are wrong";
+
+const HINT_HELP: &str = "\
+Usage: sudoku_solver hint [OPTIONS] FILE
+
+Prints the next move a human could make on the puzzle in FILE, - reading stdin, with the
+technique behind it, and the grid once it is made.
+
+Options:
+  --given-moves MOVES  Moves to make first, like r1c2=3,r4c5=7, to pick up a puzzle part way
+  -q, --quiet          Print the grid on one line rather than drawing it";
+
+const EXPLAIN_HELP: &str = "\
+Usage: sudoku_solver explain [OPTIONS] FILE
+
+Walks through a solve of the puzzle in FILE, - reading stdin, one logical move per line. Where
+no technique applies, a cell is filled in from the solution as trial and error.
+
+Options:
+  --max-steps N        Stop the walkthrough after N moves
+  --given-moves MOVES  Moves to make first, like r1c2=3,r4c5=7, to pick up a puzzle part way
+  -q, --quiet          Print only the moves";
+
+const REPL_HELP: &str = "\
+Usage: sudoku_solver repl [OPTIONS] FILE
+
+Loads the puzzle in FILE and runs the commands read from stdin on it, one per line, so a
+session can be scripted with a heredoc. Blank lines and lines starting with # are skipped, and
+a command that fails says why on stderr without ending the session.
+
+Commands:
+  show             Draw the board
+  set r4c7 5       Fill in a cell
+  clear r4c7       Empty a cell
+  candidates r4c7  List the values a cell can still take
+  hint             Explain the next move a human could make
+  solve            Fill in the rest of the board
+  undo             Take back the last set, clear or solve
+  count-solutions  Count the solutions the board has left
+  save out.txt     Write the board to a file, on one line
+  help             List the commands
+  quit             Stop, as the end of the input does
+
+Options:
+  --given-moves MOVES  Moves to make first, like r1c2=3,r4c5=7, to pick up a puzzle part way
+
+Exits with 3 if any command read from a pipe or file failed.";
+
+const SERVE_HELP: &str = "\
+Usage: sudoku_solver serve-stdio
+
+Answers requests read from stdin, one JSON object per line, with one JSON object per line on
+stdout, so the solver can run as a child process of a program written in any language. Nothing
+else is printed, and the terminal is left alone.
+
+Requests:
+  {\"cmd\":\"solve\",\"puzzle\":\"530070000...\"}  The solution, and whether it is unique
+  {\"cmd\":\"generate\",\"clues\":26}            A unique puzzle, \"difficulty\" picking a grade
+                                        instead and \"seed\" reproducing one
+  {\"cmd\":\"hint\",\"puzzle\":\"...\"}             The next move a human could make
+  {\"cmd\":\"grade\",\"puzzle\":\"...\"}            How hard the puzzle is
+
+Every response echoes the request's \"id\", then holds either \"ok\":true and a \"result\", or
+\"ok\":false and an \"error\" with a \"code\" and a \"message\". Runs until stdin ends.";
+
+/// Every command with its help text
+const COMMANDS: [(&str, &str); 13] = [
+    ("solve", SOLVE_HELP),
+    ("generate", GENERATE_HELP),
+    ("play", PLAY_HELP),
+    ("grade", GRADE_HELP),
+    ("bench", BENCH_HELP),
+    ("daily", DAILY_HELP),
+    ("check", CHECK_HELP),
+    ("hint", HINT_HELP),
+    ("explain", EXPLAIN_HELP),
+    ("repl", REPL_HELP),
+    ("serve-stdio", SERVE_HELP),
+    ("stats", STATS_HELP),
+    ("hunt17", HUNT_HELP),
+];
+
+/// Flags that take a value, so the value is not mistaken for a positional argument
+const VALUE_FLAGS: [&str; 35] = [
+    "--seeds",
+    "--format",
+    "--jobs",
+    "--solver",
+    "--difficulty",
+    "--symmetry",
+    "--grid",
+    "--pattern",
+    "--save",
+    "--export-cnf",
+    "--steps",
+    "-n",
+    "--count",
+    "--puzzles",
+    "--seed",
+    "--clues",
+    "--max-effort",
+    "--out",
+    "--progress",
+    "--csv",
+    "--threshold",
+    "--restarts",
+    "--log",
+    "--puzzle",
+    "--solution",
+    "--delay-ms",
+    "--solutions",
+    "--timings",
+    "--timeout-ms",
+    "--given-moves",
+    "--max-steps",
+    "--color",
+    "--verify-dump",
+    "--stats-file",
+    "--variant",
+];
 
 fn main() {
-    // String representation of a sudoku board. The numbers in the string correspond to cells in
-    // the board, going left to right, top to bottom.
-    /* let initial_board_string =
-    "530070000600195000098000060800060003400803001700020006060000280000419005000080079"; */
-
-    // Problem seeds:
-    // Board seed: 12499731774094038275, removal seed: 8137985501619016255
-    let board_seed =thread_rng().gen();
-    let remove_cell_seed = thread_rng().gen();
-    let mut initial_board_string = generate_board(board_seed);
-    let cells_removed = remove_board_cells(&mut initial_board_string, remove_cell_seed, 20, 30);
-
-    let mut board = Board::new(initial_board_string);
-    terminal::enable_raw_mode().unwrap();
-
-    let mut stdout = stdout();
-    stdout.queue(Clear(terminal::ClearType::All)).unwrap();
-    stdout.queue(Print(format!("Board seed: {board_seed}, removal seed: {remove_cell_seed}"))).unwrap();
-    stdout.queue(cursor::MoveTo(0, 1)).unwrap();
-    board.draw_board(&mut stdout);
-
-    let start_time = std::time::Instant::now();
-    if board.solve_board(&mut stdout).is_err() {
-        println!("The solver was unable to complete the board.");
-    }
-    let end_time = std::time::Instant::now();
-
-    board.draw_board(&mut stdout);
-    terminal::disable_raw_mode().unwrap();
-
-    board.validate_board();
-    let duration = end_time - start_time;
-    println!("Duration: {}ms", duration.as_millis());
-    println!("hints: {}", 81-cells_removed);
-
-    /* let mut total_completed = 0.0;
-    let mut total_average_time = 0.0;
-
-    for i in 0..1 {
-        let (completed_this_round, average_time) = benchmark(i * 200);
-        total_completed += completed_this_round;
-        total_average_time += average_time;
-    }
-
-    println!("Total completed: {total_completed} / 1000, average time: {}ms", total_average_time / 4.0); */
-}
-
-/// Base generation derived from https://gamedev.stackexchange.com/a/138228
-/// Uses various shifting techniques from https://pi.math.cornell.edu/~mec/Summer2009/Mahmood/Symmetry.html
-fn generate_board(seed: u64) -> String {
-    let mut rng = ChaCha8Rng::seed_from_u64(seed);
-
-    // Original board generation
-    let mut rows = [[0_u8; 9]; 9];
-    let mut base_row: [u8; 9] = (0..9).collect::<Vec<_>>().try_into().unwrap();
-    base_row.shuffle(&mut rng);
-    rows[0] = base_row;
-    for i in 1..9 {
-        let mut rotated_previous_row = rows[i - 1];
-        if i % 3 == 0 {
-            rotated_previous_row.rotate_left(1);
-            rows[i] = rotated_previous_row;
-        } else {
-            rotated_previous_row.rotate_left(3);
-            rows[i] = rotated_previous_row;
+    // A panic has already restored the terminal and printed its message by the time it gets
+    // caught here
+    screen::install_panic_hook();
+    let code = match std::panic::catch_unwind(run) {
+        Ok(Ok(())) => 0,
+        Ok(Err(error)) => {
+            eprintln!("{error}");
+            error.exit_code()
+        }
+        Err(_) => AppError::Internal(String::new()).exit_code(),
+    };
+    std::process::exit(code);
+}
+
+/// Runs the command given on the command line. Every way a run can fail ends up as the error
+/// returned here, which decides the exit code
+fn run() -> Result<(), AppError> {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    // Without a command, puzzles piped in are solved as if `solve` had been given
+    let Some((command, rest)) = args.split_first() else {
+        if stdin().is_tty() {
+            return Err(AppError::Parse(USAGE.to_string()));
+        }
+        return solve(&Flags { args: &[] });
+    };
+    let flags = Flags { args: rest };
+    if command == "help" || command == "-h" || command == "--help" {
+        let help = rest
+            .first()
+            .and_then(|name| COMMANDS.iter().find(|(command, _)| command == name));
+        println!("{}", help.map_or(USAGE, |(_, help)| help));
+        return Ok(());
+    }
+    let Some((_, help)) = COMMANDS.iter().find(|(name, _)| name == command) else {
+        return Err(AppError::Parse(format!(
+            "Unknown command {command}\n\n{USAGE}"
+        )));
+    };
+    if flags.has(&["-h", "--help"]) {
+        println!("{help}");
+        return Ok(());
+    }
+    match command.as_str() {
+        "solve" => solve(&flags),
+        "generate" => generate_pack(&flags),
+        "play" => play(&flags),
+        "grade" => grade(&flags),
+        "bench" => benchmark(&flags),
+        "daily" => daily(&flags),
+        "check" => check(&flags),
+        "hint" => hint(&flags),
+        "explain" => explain(&flags),
+        "repl" => repl(&flags),
+        "serve-stdio" => serve_stdio(),
+        "stats" => stats(&flags),
+        _ => hunt(&flags),
+    }
+}
+
+/// Everything that can end a run early. Each kind has its own exit code, so scripts can tell
+/// them apart without reading the message
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AppError {
+    /// A puzzle has no solution, exit code 1
+    Unsolvable(String),
+    /// A puzzle's givens break the sudoku rules, exit code 2
+    InvalidPuzzle(String),
+    /// An argument or an input could not be parsed, exit code 3
+    Parse(String),
+    /// A solve or a search stopped at one of its limits before finishing, exit code 4
+    LimitReached(String),
+    /// Anything else, like a file that could not be written or a panic, exit code 5
+    Internal(String),
+}
+
+impl AppError {
+    fn exit_code(&self) -> i32 {
+        match self {
+            AppError::Unsolvable(_) => 1,
+            AppError::InvalidPuzzle(_) => 2,
+            AppError::Parse(_) => 3,
+            AppError::LimitReached(_) => 4,
+            AppError::Internal(_) => 5,
         }
     }
 
-    // Shuffles the indices for the columns within the stack that they exist in in order to
-    // preserve sudoku rules
-    let shuffled_column_order = (0..3)
-        .flat_map(|i| {
-            let mut order = [3 * i, 3 * i + 1, 3 * i + 2];
-            order.shuffle(&mut rng);
-            order
+    /// What a plain solve prints in place of the solution when it failed. Scripts only need to
+    /// tell broken puzzles from ones without a solution or time to find one, the details go to
+    /// stderr. Internal errors leave the puzzle unsolved too, only the exit code tells them apart
+    fn token(&self) -> &'static str {
+        match self {
+            AppError::InvalidPuzzle(_) | AppError::Parse(_) => "INVALID",
+            AppError::LimitReached(_) => "TIMEOUT",
+            AppError::Unsolvable(_) | AppError::Internal(_) => "UNSOLVABLE",
+        }
+    }
+
+    /// The error as a record prints it in place of a result
+    fn record_error(&self) -> RecordError {
+        RecordError {
+            token: self.token(),
+            message: self.to_string(),
+        }
+    }
+
+    /// The same kind of error with another message
+    fn with_message(&self, message: String) -> Self {
+        match self {
+            AppError::Unsolvable(_) => AppError::Unsolvable(message),
+            AppError::InvalidPuzzle(_) => AppError::InvalidPuzzle(message),
+            AppError::Parse(_) => AppError::Parse(message),
+            AppError::LimitReached(_) => AppError::LimitReached(message),
+            AppError::Internal(_) => AppError::Internal(message),
+        }
+    }
+}
+
+impl Display for AppError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AppError::Unsolvable(message)
+            | AppError::InvalidPuzzle(message)
+            | AppError::Parse(message)
+            | AppError::LimitReached(message)
+            | AppError::Internal(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl From<SolveError> for AppError {
+    fn from(error: SolveError) -> Self {
+        let message = error.to_string();
+        match error {
+            SolveError::InvalidGivens(_) => AppError::InvalidPuzzle(message),
+            SolveError::Contradiction { .. } | SolveError::Exhausted { .. } => {
+                AppError::Unsolvable(message)
+            }
+            SolveError::LimitReached { .. } | SolveError::Cancelled { .. } => {
+                AppError::LimitReached(message)
+            }
+            SolveError::HistoryMismatch { .. } => AppError::Internal(message),
+        }
+    }
+}
+
+/// Terminal errors, which is all that is left to go wrong while drawing
+impl From<std::io::Error> for AppError {
+    fn from(error: std::io::Error) -> Self {
+        AppError::Internal(error.to_string())
+    }
+}
+
+/// The arguments following a command, looked up by flag name
+struct Flags<'a> {
+    args: &'a [String],
+}
+
+impl<'a> Flags<'a> {
+    /// Whether any of the names is given
+    fn has(&self, names: &[&str]) -> bool {
+        self.args.iter().any(|arg| names.contains(&arg.as_str()))
+    }
+
+    /// The argument following the first of the names that is given
+    fn value(&self, names: &[&str]) -> Option<&'a str> {
+        self.args
+            .iter()
+            .position(|arg| names.contains(&arg.as_str()))
+            .and_then(|i| self.args.get(i + 1))
+            .map(String::as_str)
+    }
+
+    /// The number following any of the names, `default` unless given
+    fn number<T: FromStr>(&self, names: &[&str], default: T) -> Result<T, AppError> {
+        self.value(names).map_or(Ok(default), |number| {
+            number.parse().map_err(|_| {
+                let flag = names[names.len() - 1];
+                AppError::Parse(format!("{flag} expects a number, found {number}"))
+            })
         })
-        .collect::<Vec<usize>>();
-    rows.iter_mut().for_each(|row| {
-        *row = (0..9)
-            .map(|i| row[shuffled_column_order[i]])
-            .collect::<Vec<_>>()
-            .try_into()
-            .unwrap()
-    });
+    }
+
+    /// The value of `flag` looked up among `all` by its displayed name, ignoring case
+    fn named<T: Copy + Display>(&self, flag: &str, all: &[T]) -> Result<Option<T>, AppError> {
+        let Some(name) = self.value(&[flag]) else {
+            return Ok(None);
+        };
+        let found = all
+            .iter()
+            .find(|value| value.to_string().eq_ignore_ascii_case(name));
+        found.copied().map(Some).ok_or_else(|| {
+            let names: Vec<String> = all.iter().map(ToString::to_string).collect();
+            AppError::Parse(format!(
+                "Unknown {flag} {name}, expected one of {}",
+                names.join(", ")
+            ))
+        })
+    }
+
+    /// Arguments that are neither flags nor the values of flags. A lone `-` counts, as it stands
+    /// for stdin
+    fn positional(&self) -> Vec<&'a str> {
+        let mut positional = Vec::new();
+        let mut args = self.args.iter();
+        while let Some(arg) = args.next() {
+            if VALUE_FLAGS.contains(&arg.as_str()) {
+                args.next();
+            } else if arg == "-" || !arg.starts_with('-') {
+                positional.push(arg.as_str());
+            }
+        }
+        positional
+    }
+
+    fn quiet(&self) -> bool {
+        self.has(&["-q", "--quiet"])
+    }
 
-    // Shuffles the numbers themselves, e.g. 1->5, 2->3, 9->1. This preserves the sudoku rules
-    let mut shuffled_numbers = (1..=9).collect::<Vec<_>>();
-    shuffled_numbers.shuffle(&mut rng);
+    /// Whether solves print plain results without drawing anything, as they do with `--quiet`,
+    /// when stdout is not a terminal or when the format is meant for programs
+    fn plain(&self) -> bool {
+        self.quiet()
+            || !stdout().is_tty()
+            || self.format().is_ok_and(|format| format.is_structured())
+    }
 
-    // Shuffles the indices for the rows within the band that they exist in in order to
-    // preserve sudoku rules
-    let shuffled_row_order = (0..3).flat_map(|i| {
-        let mut order = [3 * i, 3 * i + 1, 3 * i + 2];
-        order.shuffle(&mut rng);
-        order
+    /// The palette boards printed to stdout are drawn with, as `--color` chooses
+    fn palette(&self) -> Result<Palette, AppError> {
+        let choice = self
+            .named("--color", &ColorChoice::ALL)?
+            .unwrap_or_default();
+        Ok(Palette::for_output(choice, stdout().is_tty()))
+    }
+
+    /// How boards printed to stdout are drawn
+    fn draw_options(&self) -> Result<DrawOptions, AppError> {
+        Ok(DrawOptions {
+            palette: self.palette()?,
+            ..DrawOptions::default()
+        })
+    }
+
+    fn format(&self) -> Result<OutputFormat, AppError> {
+        Ok(self
+            .named("--format", &OutputFormat::ALL)?
+            .unwrap_or_default())
+    }
+}
+
+/// Reads all of a file, `-` reading stdin
+fn read_input(path: &str) -> Result<String, AppError> {
+    let contents = if path == "-" {
+        let mut contents = String::new();
+        stdin().read_to_string(&mut contents).map(|_| contents)
+    } else {
+        std::fs::read_to_string(path)
+    };
+    contents.map_err(|error| AppError::Internal(format!("Could not read {path}: {error}")))
+}
+
+/// The lines of an input worth reading along with their line numbers, skipping blank lines and
+/// lines starting with `#`
+fn input_lines(contents: &str) -> impl Iterator<Item = (usize, &str)> {
+    contents
+        .lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+}
+
+/// Reads the puzzles of every file, one per line, `-` reading stdin. Blank lines and lines
+/// starting with `#` are skipped, and lines that are not a board are kept as the reason why
+fn read_puzzles(paths: &[&str]) -> Result<Vec<Result<Board, String>>, AppError> {
+    let mut puzzles = Vec::new();
+    for path in paths {
+        let contents = read_input(path)?;
+        for (number, line) in input_lines(&contents) {
+            puzzles.push(
+                line.parse().map_err(|error| {
+                    format!("Line {number} of {path} is not a valid board: {error}")
+                }),
+            );
+        }
+    }
+    Ok(puzzles)
+}
+
+/// A puzzle and the solution proposed for it, as `check` reads them
+type Pair = (Board, Board);
+
+/// Reads `puzzle,solution` lines from every file like [`read_puzzles`] does, skipping a header
+fn read_pairs(paths: &[&str]) -> Result<Vec<Result<Pair, String>>, AppError> {
+    let mut pairs = Vec::new();
+    for path in paths {
+        let contents = read_input(path)?;
+        for (number, line) in input_lines(&contents) {
+            if line.eq_ignore_ascii_case("puzzle,solution") {
+                continue;
+            }
+            let pair = line
+                .split_once(',')
+                .ok_or_else(|| "there is no comma between the puzzle and solution".to_string())
+                .and_then(|(puzzle, solution)| {
+                    let puzzle = puzzle.trim().parse().map_err(|e| format!("puzzle: {e}"))?;
+                    let solution = solution
+                        .trim()
+                        .parse()
+                        .map_err(|e| format!("solution: {e}"))?;
+                    Ok((puzzle, solution))
+                });
+            pairs.push(
+                pair.map_err(|error| format!("Line {number} of {path} is not valid, {error}")),
+            );
+        }
+    }
+    Ok(pairs)
+}
+
+/// A line of input that is not a puzzle
+struct BadLine {
+    /// Why it is not, printed after INVALID
+    reason: String,
+    /// The error naming the line, for stderr and the exit code
+    error: AppError,
+}
+
+/// Reads the puzzles of every file like [`read_puzzles`], but lazily: lines are only read from
+/// the buffered files or stdin as the puzzles are taken. Each puzzle comes with the file and
+/// line it was read from. Every file is opened up front, so one
+/// that cannot be opened fails before anything is read, while one failing partway gives an
+/// internal error, after which nothing more should be taken
+fn stream_puzzles<'a>(
+    paths: &[&'a str],
+) -> Result<impl Iterator<Item = Result<(String, Board), BadLine>> + Send + 'a, AppError> {
+    let mut readers: Vec<(&str, Box<dyn BufRead + Send>)> = Vec::new();
+    for &path in paths {
+        let reader: Box<dyn BufRead + Send> = if path == "-" {
+            Box::new(BufReader::new(stdin()))
+        } else {
+            let file = File::open(path)
+                .map_err(|error| AppError::Internal(format!("Could not read {path}: {error}")))?;
+            Box::new(BufReader::new(file))
+        };
+        readers.push((path, reader));
+    }
+    let lines = readers.into_iter().flat_map(|(path, reader)| {
+        reader
+            .lines()
+            .enumerate()
+            .map(move |(i, line)| (path, i + 1, line))
     });
+    Ok(lines.filter_map(|(path, number, line)| {
+        let line = match line {
+            Ok(line) => line,
+            Err(error) => {
+                let error = AppError::Internal(format!("Could not read {path}: {error}"));
+                let reason = error.to_string();
+                return Some(Err(BadLine { reason, error }));
+            }
+        };
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let source = match path {
+            "-" => format!("stdin:{number}"),
+            path => format!("{path}:{number}"),
+        };
+        Some(
+            line.parse()
+                .map(|board| (source, board))
+                .map_err(|error: ParseBoardError| BadLine {
+                    reason: error.to_string(),
+                    error: AppError::Parse(format!(
+                        "Line {number} of {path} is not a valid board: {error}"
+                    )),
+                }),
+        )
+    }))
+}
+
+/// Generates the puzzle `solve` and `play` work on from the seeds following `--seeds`, random
+/// unless given, honouring the flags that shape it. Returns the seeds along with it, so the run
+/// can be repeated
+fn generated_puzzle(flags: &Flags) -> Result<(GeneratedPuzzle, SeedBundle), AppError> {
+    // `--seeds B1-R2-S3` takes the seeds of an earlier run, which along with the same flags
+    // reproduces it exactly
+    let seeds = match flags.value(&["--seeds"]) {
+        Some(seeds) => seeds
+            .parse()
+            .map_err(|error: ParseSeedBundleError| AppError::Parse(error.to_string()))?,
+        None => SeedBundle {
+            board: thread_rng().gen(),
+            removal: thread_rng().gen(),
+            solver: thread_rng().gen(),
+        },
+    };
+    let (board_seed, remove_cell_seed, solve_seed) = (seeds.board, seeds.removal, seeds.solver);
+    // `--symmetry rotational` digs cells out in symmetric pairs, see `RemovalSymmetry` for the
+    // other names
+    let symmetry = flags
+        .named("--symmetry", &RemovalSymmetry::ALL)?
+        .unwrap_or(RemovalSymmetry::None);
+    // `--difficulty hard` keeps generating until a puzzle grades as hard, see `Difficulty` for
+    // the other names
+    let difficulty = flags.named("--difficulty", &Difficulty::ALL)?;
+    // `--grid pattern` generates the solved board by shuffling a fixed pattern instead of
+    // solving an empty board, see `GridMethod`
+    let grid = flags.named("--grid", &GridMethod::ALL)?.unwrap_or_default();
+    // `--fast` digs cells out without checking the puzzle stays unique, which is quicker but
+    // usually leaves it with several solutions
+    let generated = if flags.has(&["--fast"]) {
+        generate_puzzle_with_grid(grid, board_seed, remove_cell_seed, clue_range(flags)?)
+    } else if let Some(difficulty) = difficulty {
+        generate_with_difficulty(board_seed, difficulty, DIFFICULTY_ATTEMPTS).unwrap_or_else(
+            |error| {
+                eprintln!("{error}");
+                *error.closest
+            },
+        )
+    } else {
+        generate_symmetric_puzzle(board_seed, UNIQUE_PUZZLE_CLUES, symmetry)
+    };
+    let mut generated = generated;
+    // `--pattern FILE` keeps the clues where the mask in the file has a `#` instead, putting back
+    // as few other cells as it takes to keep the solution unique
+    if let Some(path) = flags.value(&["--pattern"]) {
+        let mask = std::fs::read_to_string(path).map_err(|error| {
+            AppError::Internal(format!("Could not read the mask in {path}: {error}"))
+        })?;
+        let mask = parse_mask(&mask).map_err(|error| {
+            AppError::Parse(format!("Could not read the mask in {path}: {error}"))
+        })?;
+        match generated.apply_mask(&mask, true) {
+            Ok(()) => {}
+            Err(error @ MaskError::ExtraCellsKept(_)) => eprintln!("{error}"),
+            Err(error) => return Err(AppError::Parse(error.to_string())),
+        }
+    }
+    Ok((generated.with_solver_seed(solve_seed).graded(), seeds))
+}
+
+/// File solutions that fail `--verify` are appended to unless `--verify-dump` names another
+const VERIFY_DUMP: &str = "verify-failures.txt";
+
+/// The error a solution that failed verification ends the run with, after appending a bug
+/// report on it to `dump`. `origin` says where the puzzle came from, like its seeds
+fn verification_failed(
+    label: &str,
+    failure: &VerificationFailure,
+    origin: &str,
+    (puzzle, solved): (&Board, &Board),
+    dump: &str,
+) -> AppError {
+    let report = bug_report(failure, origin, puzzle, solved);
+    let written = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(dump)
+        .and_then(|mut file| file.write_all(report.as_bytes()));
+    let dumped = match written {
+        Ok(()) => format!("the boards were written to {dump}"),
+        Err(error) => format!("could not write the boards to {dump}: {error}"),
+    };
+    AppError::Internal(format!(
+        "VERIFICATION FAILED for {label}: {failure}, {dumped}"
+    ))
+}
 
-    shuffled_row_order
-        .flat_map(|i| rows[i].map(|cell| shuffled_numbers[cell as usize].to_string()))
-        .collect()
+/// Solves the puzzles of the files given, or a generated one when there are none
+fn solve(flags: &Flags) -> Result<(), AppError> {
+    let mut paths = flags.positional();
+    // Puzzles piped in are solved rather than a generated one, unless its seeds are given
+    if paths.is_empty() && !stdin().is_tty() && !flags.has(&["--seeds"]) {
+        paths.push("-");
+    }
+    // `--solutions N` lists the solutions of the puzzles instead of solving them once
+    if let Some(limit) = flags.value(&["--solutions"]) {
+        let limit = limit
+            .parse()
+            .map_err(|_| AppError::Parse(format!("--solutions expects a number, not {limit}")))?;
+        let puzzles = if paths.is_empty() {
+            vec![Ok(generated_puzzle(flags)?.0.puzzle)]
+        } else {
+            read_puzzles(&paths)?
+        };
+        return list_solutions(&puzzles, limit, flags);
+    }
+    if paths.is_empty() {
+        return solve_generated(flags);
+    }
+    let available = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let jobs = flags.number(&["--jobs"], available)?.max(1);
+    solve_batch(&paths, jobs, flags)
 }
 
-/// Takes a completed board and randomly removes cells from it
-fn remove_board_cells(board_string_representation: &mut String, seed: u64, minimum_hints: i32, maximum_hints: i32) -> i32 {
-    assert!(minimum_hints < maximum_hints, "User specified minimum hints is greater than or equal to maximum hints");
-    let mut rng = ChaCha8Rng::seed_from_u64(seed);
+/// Most solutions counted by `--solutions 0` before it settles for saying there are more
+const SOLUTION_COUNT_CAP: usize = 10_000;
 
-    let test = 81 - rng.gen_range(minimum_hints..maximum_hints);
-    for _ in 0..test {
-        let mut rand_index = rng.gen_range(0..81);
-        while board_string_representation.get(rand_index..rand_index+1) == Some("0") {
-            rand_index = rng.gen_range(0..81);
+/// Writes up to `limit` solutions of every puzzle in the order the exhaustive search finds them,
+/// which is the same on every run, followed by a line saying whether there are more. A limit
+/// of 0 only counts the solutions, up to [`SOLUTION_COUNT_CAP`]. Fails with the kind of the
+/// first puzzle that is invalid or has no solution, unless `--keep-going` is given
+fn list_solutions(
+    puzzles: &[Result<Board, String>],
+    limit: usize,
+    flags: &Flags,
+) -> Result<(), AppError> {
+    let format = flags.format()?;
+    let mut writer = ResultWriter::new(stdout(), format, flags.palette()?);
+    let (mut first_failure, mut failed) = (None, 0);
+    for (i, puzzle) in puzzles.iter().enumerate() {
+        let puzzle = match puzzle {
+            Ok(puzzle) if puzzle.conflicts().is_empty() => puzzle,
+            Ok(_) => {
+                let error = AppError::InvalidPuzzle(format!(
+                    "Puzzle {}: the givens break the sudoku rules",
+                    i + 1
+                ));
+                writer.write(&Record {
+                    error: Some(error.record_error()),
+                    ..Record::default()
+                })?;
+                eprintln!("{error}");
+                failed += 1;
+                first_failure.get_or_insert(error);
+                continue;
+            }
+            Err(error) => {
+                let error = AppError::Parse(error.clone());
+                writer.write(&Record {
+                    error: Some(error.record_error()),
+                    ..Record::default()
+                })?;
+                eprintln!("{error}");
+                failed += 1;
+                first_failure.get_or_insert(error);
+                continue;
+            }
+        };
+        // One solution more than shown tells whether there are more
+        let cap = if limit == 0 {
+            SOLUTION_COUNT_CAP
+        } else {
+            limit
+        };
+        let solutions = puzzle.solutions(cap + 1);
+        for solution in solutions.iter().take(limit) {
+            writer.write(&Record {
+                puzzle: Some(puzzle),
+                solution: Some(solution),
+                ..Record::default()
+            })?;
+        }
+        let summary = match (solutions.len(), limit) {
+            (0, _) => "The puzzle has no solution".to_string(),
+            (found, 0) if found > cap => format!("More than {cap} solutions"),
+            (1, 0) => "1 solution".to_string(),
+            (found, 0) => format!("{found} solutions"),
+            (found, _) if found > limit => format!("Showing {limit}, more solutions exist"),
+            (1, _) => "The only solution".to_string(),
+            (found, _) => format!("All {found} solutions"),
+        };
+        if !format.is_structured() {
+            println!("{summary}");
+        }
+        if solutions.is_empty() {
+            let error = AppError::Unsolvable(format!("Puzzle {}: it has no solution", i + 1));
+            failed += 1;
+            first_failure.get_or_insert(error);
         }
-        board_string_representation.replace_range(rand_index..rand_index+1, "0");
     }
+    match first_failure {
+        Some(failure) if !flags.has(&["--keep-going"]) => Err(failure.with_message(format!(
+            "{failed} of {} puzzles have no solutions to list",
+            puzzles.len()
+        ))),
+        _ => Ok(()),
+    }
+}
 
-    test
+/// Generates a puzzle and solves it, animating the solve unless `--quiet` is given or stdout is
+/// not a terminal
+fn solve_generated(flags: &Flags) -> Result<(), AppError> {
+    let format = flags.format()?;
+    let (generated, seeds) = generated_puzzle(flags)?;
+
+    // `--export-cnf FILE` writes the puzzle as a DIMACS CNF formula for a SAT solver instead
+    if let Some(path) = flags.value(&["--export-cnf"]) {
+        std::fs::write(path, generated.puzzle.to_dimacs())
+            .map_err(|error| AppError::Internal(format!("Could not write {path}: {error}")))?;
+        println!("{generated}");
+        println!("Wrote {} to {path}", generated.puzzle);
+        return Ok(());
+    }
+
+    // `--solver dfs` or `--solver dlx` swap the wave function collapse solver for one of the
+    // exhaustive ones
+    let mut solver = solver_named(solver_name(flags)?, generated.solver_seed)
+        .expect("solver_name only returns registered solvers");
+    let mut board = generated.puzzle.clone();
+    let result = if flags.plain() || !flags.has(&["--animate"]) {
+        solver.solve(&mut board, &mut NoopObserver)
+    } else {
+        let delay = Duration::from_millis(flags.number(&["--delay-ms"], 0)?);
+        let palette = flags.palette()?;
+        animate_solve(&generated, solver.as_mut(), &mut board, delay, palette)?
+    };
+
+    let mut result = result.map_err(AppError::from);
+    // `--verify` checks the solution again without trusting the solver that found it
+    let verify = flags.has(&["--verify"]);
+    if let (true, Ok(_)) = (verify, &result) {
+        if let Err(failure) = verify_solution(&generated.puzzle, &board) {
+            result = Err(verification_failed(
+                "the generated puzzle",
+                &failure,
+                &format!("seeds {seeds}, solver {}", solver.name()),
+                (&generated.puzzle, &board),
+                flags.value(&["--verify-dump"]).unwrap_or(VERIFY_DUMP),
+            ));
+        }
+    }
+    if flags.plain() {
+        ResultWriter::new(stdout(), format, flags.palette()?).write(&Record {
+            puzzle: Some(&generated.puzzle),
+            solution: result.is_ok().then_some(&board),
+            seeds: Some(seeds),
+            elapsed: result.as_ref().ok().map(|report| report.elapsed),
+            difficulty: generated.difficulty,
+            error: result.as_ref().err().map(AppError::record_error),
+            ..Record::default()
+        })?;
+        return result.map(|_| ());
+    }
+    let report = result?;
+
+    println!("{generated}");
+    print!("{}", board.render_board(&flags.draw_options()?));
+    if board.validate_board() {
+        println!("The board is valid!");
+    } else {
+        println!("The solution is invalid!");
+    }
+    if board.matches_solution(&generated.solution) {
+        println!("The board matches the generated solution!");
+    } else {
+        println!("The board does not match the generated solution!");
+    }
+    if verify {
+        println!("The solution passed verification!");
+    }
+    println!("{report}");
+    println!("hints: {} ({})", board.clue_count(), board.clue_class());
+    println!("Run again with --seeds {seeds}");
+    println!("clue symmetry: {}", generated.puzzle.clue_symmetry());
+
+    // `--save FILE` writes the puzzle in the .sdk format, with its seeds and grade in comments
+    if let Some(path) = flags.value(&["--save"]) {
+        std::fs::write(path, generated.to_sdk())
+            .map_err(|error| AppError::Internal(format!("Could not write {path}: {error}")))?;
+        println!("Saved the puzzle to {path}");
+    }
+
+    // `--hint` explains the first move a human could make on the puzzle
+    if flags.has(&["--hint"]) {
+        match generated.puzzle.next_hint() {
+            Some(hint) => println!("Hint: {hint}"),
+            None => println!("No technique applies to the puzzle"),
+        }
+    }
+
+    // `--explain` walks through a solve of the puzzle, `--steps N` stopping it after N steps
+    if flags.has(&["--explain"]) {
+        let limit = flags.number(&["--steps"], usize::MAX)?;
+        match generated.puzzle.explain_solution() {
+            Ok(steps) => {
+                for (number, step) in steps.iter().take(limit).enumerate() {
+                    println!("{}. {step}", number + 1);
+                }
+            }
+            Err(error) => println!("Could not explain the solution: {error}"),
+        }
+    }
+
+    // `--logical` also tries the puzzle with techniques alone, to tell whether it needs guessing
+    if flags.has(&["--logical"]) {
+        let outcome = generated.puzzle.clone().solve_logical(&TechniqueSet::all());
+        match (&outcome, outcome.hardest_technique()) {
+            (LogicalOutcome::Solved(_), Some(hardest)) => {
+                println!("Solvable with techniques up to {hardest}")
+            }
+            (LogicalOutcome::Solved(_), None) => println!("Solvable without any techniques"),
+            (LogicalOutcome::Stuck { moves, .. }, _) => {
+                println!("Requires guessing after {} logical placements", moves.len())
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Solves the board while animating it in raw mode with `palette`, pausing `delay` after every
+/// placement.
+/// Ctrl-C, q or Esc cancel the solve, which then ends with [`SolveError::Cancelled`] and the
+/// terminal back in cooked mode
+fn animate_solve(
+    generated: &GeneratedPuzzle,
+    solver: &mut dyn SudokuSolver,
+    board: &mut Board,
+    delay: Duration,
+    palette: Palette,
+) -> Result<Result<SolveReport, SolveError>, AppError> {
+    let mut screen = TerminalGuard::new()?;
+    screen.queue(cursor::MoveTo(0, 0))?;
+    screen.queue(Print(generated))?;
+    let token = CancellationToken::new();
+    let done = Arc::new(AtomicBool::new(false));
+    // Raw mode turns Ctrl-C into a key press, so a thread watches the keys while the solve runs.
+    // It is only started once the terminal is set up, since nothing can fail after that and
+    // stop it from being told to finish
+    let keys = {
+        let (token, done) = (token.clone(), Arc::clone(&done));
+        std::thread::spawn(move || {
+            while !done.load(Ordering::Relaxed) {
+                if !event::poll(Duration::from_millis(50)).unwrap_or(false) {
+                    continue;
+                }
+                let Ok(Event::Key(key)) = event::read() else {
+                    continue;
+                };
+                let ctrl_c =
+                    key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL);
+                if ctrl_c || matches!(key.code, KeyCode::Char('q') | KeyCode::Esc) {
+                    token.cancel();
+                }
+            }
+        })
+    };
+    let mut observer = AnimatingObserver::new(&mut screen, (0, 1))
+        .with_delay(delay)
+        .with_cancellation(token.clone())
+        .with_palette(palette);
+    let result =
+        solver.solve_with_limits(board, &mut observer, &SolveLimits::with_cancellation(token));
+    observer.finish(board);
+    done.store(true, Ordering::Relaxed);
+    let _ = keys.join();
+    Ok(result)
+}
+
+/// Lets the player solve a generated puzzle in the terminal until it is solved or they quit
+fn play(flags: &Flags) -> Result<(), AppError> {
+    // `--daily` plays today's puzzle of the day, which extends the daily streak once solved
+    let daily = flags.has(&["--daily"]).then(Date::today);
+    let (generated, seeds) = match daily {
+        Some(date) => {
+            let generated = generate_daily(date);
+            let seeds = generated.seeds();
+            (generated, seeds)
+        }
+        None => generated_puzzle(flags)?,
+    };
+    let stats_path = stats_path(flags)?;
+    let mut game = Game::new(generated.puzzle.clone(), generated.solution.clone());
+    let palette = flags.palette()?;
+    let mut message = String::new();
+    // The cell the last hint places a value in
+    let mut hinted = None;
+    let mut screen = TerminalGuard::new()?;
+    let start_time = Instant::now();
+    // The cursor marks the selected cell
+    screen.queue(cursor::Show)?;
+    loop {
+        draw_game(&mut screen, &generated, &game, &message, &palette, hinted)?;
+        if game.is_solved() {
+            break;
+        }
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        message.clear();
+        hinted = None;
+        match key.code {
+            KeyCode::Up => game.move_cursor(-1, 0),
+            KeyCode::Down => game.move_cursor(1, 0),
+            KeyCode::Left => game.move_cursor(0, -1),
+            KeyCode::Right => game.move_cursor(0, 1),
+            KeyCode::Char(digit @ '0'..='9') => {
+                if let Err(error) = game.enter(digit as u8 - b'0') {
+                    message = error.to_string();
+                }
+            }
+            KeyCode::Backspace | KeyCode::Delete => {
+                if let Err(error) = game.enter(0) {
+                    message = error.to_string();
+                }
+            }
+            KeyCode::Char('u') if !game.undo() => {
+                message = "Nothing to take back".to_string();
+            }
+            KeyCode::Char('?') => {
+                let hint = game.hint();
+                if let Some(HintAction::Place(found)) = hint.as_ref().map(|hint| &hint.action) {
+                    hinted = Some(found.position);
+                }
+                message = hint.map_or(
+                    "No technique applies, some entry must be wrong".to_string(),
+                    |hint| hint.to_string(),
+                );
+            }
+            KeyCode::Char('m') => message = format!("{} mistakes so far", game.mistakes().len()),
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => break,
+            KeyCode::Char('q') | KeyCode::Esc => break,
+            _ => {}
+        }
+    }
+    let elapsed = start_time.elapsed();
+    drop(screen);
+    if game.is_solved() {
+        println!("Solved in {}!", clock_time(elapsed));
+    } else {
+        println!("The solution was {}", generated.solution);
+    }
+    println!("Play it again with --seeds {seeds}");
+    let game = GameRecord {
+        difficulty: generated.difficulty.expect("played puzzles come graded"),
+        solved: game.is_solved(),
+        elapsed,
+        daily,
+    };
+    // The game is over either way, so stats that cannot be saved only get a warning
+    match Stats::record_in_file(&stats_path, &game) {
+        Ok((_, warning)) => {
+            if let Some(warning) = warning {
+                eprintln!("Warning: {warning}");
+            }
+        }
+        Err(error) => eprintln!(
+            "Warning: could not save the stats to {}: {error}",
+            stats_path.display()
+        ),
+    }
+    Ok(())
 }
 
-/* fn benchmark(seed: u64) -> (f32, f32){
-    let (completed_count, completed_times): (Vec<_>, Vec<_>) = (0..1000).map(|i| {
-        let mut initial_board_string = generate_board(i + seed);
-        remove_board_cells(&mut initial_board_string, i, 20, 40);
+/// The stats file `--stats-file` names, or the default one in the user's data directory
+fn stats_path(flags: &Flags) -> Result<PathBuf, AppError> {
+    flags
+        .value(&["--stats-file"])
+        .map(PathBuf::from)
+        .or_else(default_stats_path)
+        .ok_or_else(|| {
+            AppError::Parse("No data directory to keep the stats in, pass --stats-file".to_string())
+        })
+}
 
-        let start_time = Instant::now();
-        let mut board = Board::new(initial_board_string);
-        let solve = board.solve_board(None);
-        println!("{i}");
-        if solve.is_ok() {
-            (1.0, (Instant::now() - start_time).as_millis() as f32)
+fn stats(flags: &Flags) -> Result<(), AppError> {
+    let path = stats_path(flags)?;
+    let (stats, warning) = Stats::load(&path);
+    if let Some(warning) = warning {
+        eprintln!("Warning: {warning}");
+    }
+    if stats.played() == 0 && !flags.quiet() {
+        println!("No games played yet, stats are kept in {}", path.display());
+        return Ok(());
+    }
+    println!("{}", stats.table(Date::today()));
+    Ok(())
+}
+
+/// Draws the puzzle header, the board, a line of controls and `message` below it, then places
+/// the terminal cursor on the selected cell. The palette styles the board, the selected cell
+/// and the cell of the last hint, if any
+fn draw_game(
+    stdout: &mut impl Write,
+    generated: &GeneratedPuzzle,
+    game: &Game,
+    message: &str,
+    palette: &Palette,
+    hinted: Option<(usize, usize)>,
+) -> std::io::Result<()> {
+    stdout
+        .queue(Clear(terminal::ClearType::All))?
+        .queue(cursor::MoveTo(0, 0))?
+        .queue(Print(generated))?;
+    let options = DrawOptions {
+        palette: *palette,
+        ..DrawOptions::default()
+    };
+    let lines = game.board().render_board(&options);
+    for (i, line) in lines.lines().enumerate() {
+        stdout
+            .queue(cursor::MoveTo(0, 1 + i as u16))?
+            .queue(Print(line))?;
+    }
+    let status = if game.is_solved() {
+        "Solved!"
+    } else {
+        "arrows move, 1-9 fill in, 0 clears, u undoes, ? hints, m counts mistakes, q quits"
+    };
+    stdout
+        .queue(cursor::MoveTo(0, 15))?
+        .queue(Print(status))?
+        .queue(cursor::MoveTo(0, 16))?
+        .queue(Print(message))?;
+    // Matches the layout of `Board::render_board` without coordinates
+    let position = |(row, col): (usize, usize)| {
+        cursor::MoveTo(
+            (2 + 2 * col + 2 * (col / 3)) as u16,
+            (2 + row + row / 3) as u16,
+        )
+    };
+    let digit = |(row, col): (usize, usize)| match game.board().get_cell(row, col) {
+        0 => ' ',
+        value => (b'0' + value) as char,
+    };
+    let restyled = hinted
+        .map(|cell| (cell, palette.highlight))
+        .into_iter()
+        .chain([(game.cursor(), palette.cursor)]);
+    for (cell, style) in restyled {
+        stdout
+            .queue(position(cell))?
+            .queue(PrintStyledContent(style.apply(digit(cell))))?;
+    }
+    stdout.queue(position(game.cursor()))?;
+    stdout.flush()
+}
+
+/// Grades every puzzle of the files given on `--jobs N` threads as the lines are read, and
+/// prints how many puzzles got each grade. `--details` also prints a line per puzzle as it is
+/// graded, with its clues, grade, hardest technique and whether techniques alone solve it. The
+/// json and csv formats print a record per puzzle instead. Fails if any line is not a puzzle,
+/// unless `--keep-going` is given
+fn grade(flags: &Flags) -> Result<(), AppError> {
+    let paths = flags.positional();
+    if paths.is_empty() {
+        return Err(AppError::Parse(
+            "grade expects a file of puzzles, or - for stdin".to_string(),
+        ));
+    }
+    let available = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let jobs = flags.number(&["--jobs"], available)?.max(1);
+    let format = flags.format()?;
+    let details = flags.has(&["--details"]) && !format.is_structured();
+    let mut writer = ResultWriter::new(stdout(), format, flags.palette()?);
+    let puzzles = stream_puzzles(&paths)?;
+    if details {
+        println!(
+            "{:>5}  {:>5}  {:<10}  {:<18}  logical",
+            "#", "clues", "grade", "hardest technique"
+        );
+    }
+    let mut distribution = GradeDistribution::new();
+    let (mut count, mut invalid, mut fatal) = (0, 0, None);
+    let grade_puzzle = |_, (_, puzzle): (String, Board)| {
+        let report = puzzle.grade();
+        (puzzle, report)
+    };
+    map_streaming(puzzles, jobs, grade_puzzle, |result| {
+        count += 1;
+        let written = match result {
+            Ok((puzzle, report)) => {
+                distribution.add(&report);
+                if details {
+                    writeln!(
+                        stdout(),
+                        "{count:>5}  {:>5}  {:<10}  {:<18}  {}",
+                        puzzle.clue_count(),
+                        report.difficulty.to_string(),
+                        report.hardest_technique.unwrap_or("none"),
+                        if report.requires_guessing {
+                            "no"
+                        } else {
+                            "yes"
+                        }
+                    )
+                } else if format.is_structured() {
+                    writer.write(&Record {
+                        puzzle: Some(&puzzle),
+                        difficulty: Some(report.difficulty),
+                        note: Some(report.to_string()),
+                        ..Record::default()
+                    })
+                } else {
+                    Ok(())
+                }
+            }
+            Err(BadLine {
+                error: error @ AppError::Internal(_),
+                ..
+            }) => {
+                fatal = Some(error);
+                return false;
+            }
+            Err(BadLine { reason, error }) => {
+                invalid += 1;
+                eprintln!("{error}");
+                if details {
+                    writeln!(stdout(), "{count:>5}  INVALID {reason}")
+                } else if format.is_structured() {
+                    writer.write(&Record {
+                        error: Some(error.record_error()),
+                        ..Record::default()
+                    })
+                } else {
+                    Ok(())
+                }
+            }
+        };
+        match written {
+            Ok(()) => true,
+            Err(error) if error.kind() == ErrorKind::BrokenPipe => false,
+            Err(error) => {
+                fatal = Some(error.into());
+                false
+            }
+        }
+    });
+    if let Some(error) = fatal {
+        return Err(error);
+    }
+    if !format.is_structured() {
+        if details {
+            println!();
+        }
+        print!("{distribution}");
+    }
+    if invalid > 0 {
+        let message = format!("{invalid} of {count} lines are not puzzles");
+        if !flags.has(&["--keep-going"]) {
+            return Err(AppError::Parse(message));
+        }
+        if !format.is_structured() {
+            println!("{message}");
+        }
+    }
+    Ok(())
+}
+
+/// Checks solutions against their puzzles, printing OK or every discrepancy for each. Fails if
+/// any solution is wrong or any line cannot be read, unless `--keep-going` is given
+fn check(flags: &Flags) -> Result<(), AppError> {
+    let pairs =
+        match (flags.value(&["--puzzle"]), flags.value(&["--solution"])) {
+            (Some(puzzles), Some(solutions)) => {
+                let (puzzles, solutions) = (read_puzzles(&[puzzles])?, read_puzzles(&[solutions])?);
+                if puzzles.len() != solutions.len() {
+                    return Err(AppError::Parse(format!(
+                        "{} puzzles but {} solutions were given",
+                        puzzles.len(),
+                        solutions.len()
+                    )));
+                }
+                puzzles
+                    .into_iter()
+                    .zip(solutions)
+                    .map(|(puzzle, solution)| Ok((puzzle?, solution?)))
+                    .collect()
+            }
+            (None, None) if !flags.positional().is_empty() => read_pairs(&flags.positional())?,
+            _ => return Err(AppError::Parse(
+                "check expects --puzzle FILE --solution FILE, or a file of puzzle,solution lines"
+                    .to_string(),
+            )),
+        };
+    let mut first_failure = None;
+    let mut failed = 0;
+    for (i, pair) in pairs.iter().enumerate() {
+        let prefix = if pairs.len() > 1 {
+            format!("{}: ", i + 1)
         } else {
-            (0.0,0.0)
+            String::new()
+        };
+        let failure = match pair {
+            Err(error) => Some(AppError::Parse(error.clone())),
+            Ok((puzzle, _)) if !puzzle.conflicts().is_empty() => Some(AppError::InvalidPuzzle(
+                format!("Puzzle {} breaks the sudoku rules", i + 1),
+            )),
+            Ok((puzzle, solution)) => {
+                let discrepancies = puzzle.check_solution(solution);
+                if discrepancies.is_empty() {
+                    println!("{prefix}OK");
+                    None
+                } else {
+                    for discrepancy in &discrepancies {
+                        println!("{prefix}{discrepancy}");
+                    }
+                    Some(AppError::Unsolvable(String::new()))
+                }
+            }
+        };
+        if let Some(failure) = failure {
+            if !matches!(failure, AppError::Unsolvable(_)) {
+                eprintln!("{failure}");
+                println!("{prefix}{}", failure.token());
+            }
+            failed += 1;
+            first_failure.get_or_insert(failure);
+        }
+    }
+    match first_failure {
+        Some(failure) if !flags.has(&["--keep-going"]) => Err(failure.with_message(format!(
+            "{failed} of {} solutions do not check out",
+            pairs.len()
+        ))),
+        _ => Ok(()),
+    }
+}
+
+/// Reads the single puzzle `hint` and `explain` work on and makes the `--given-moves` on it.
+/// Fails if the moves overwrite a given, break the rules or leave the puzzle without a solution
+fn read_position(flags: &Flags, command: &str) -> Result<Board, AppError> {
+    let [path] = flags.positional()[..] else {
+        return Err(AppError::Parse(format!(
+            "{command} expects a single file holding a puzzle, or - for stdin"
+        )));
+    };
+    let mut puzzles = read_puzzles(&[path])?.into_iter();
+    let mut board = match (puzzles.next(), puzzles.next()) {
+        (Some(puzzle), None) => puzzle.map_err(AppError::Parse)?,
+        (None, _) => return Err(AppError::Parse(format!("{path} holds no puzzle"))),
+        (Some(_), Some(_)) => {
+            return Err(AppError::Parse(format!(
+                "{command} works on one puzzle, but {path} holds more"
+            )))
+        }
+    };
+
+    if let Some(moves) = flags.value(&["--given-moves"]) {
+        let history: MoveHistory =
+            moves
+                .replace(',', "\n")
+                .parse()
+                .map_err(|error: ParseHistoryError| {
+                    AppError::Parse(format!(
+                        "--given-moves expects moves like r1c2=3,r4c5=7, found \"{}\"",
+                        error.token
+                    ))
+                })?;
+        history
+            .replay_onto(&mut board)
+            .map_err(|error| AppError::Parse(error.to_string()))?;
+        if !board.conflicts().is_empty() {
+            return Err(AppError::InvalidPuzzle(
+                "The given moves break the sudoku rules".to_string(),
+            ));
+        }
+        if board.clone().solve_dlx().is_err() {
+            return Err(AppError::Unsolvable(
+                "The puzzle has no solution left after the given moves".to_string(),
+            ));
+        }
+    }
+    Ok(board)
+}
+
+/// Prints the next logical move on the puzzle and the grid once it is made, drawn unless the
+/// output is plain
+fn hint(flags: &Flags) -> Result<(), AppError> {
+    let mut board = read_position(flags, "hint")?;
+    let Some(hint) = board.next_hint() else {
+        if board.empty_cells().next().is_none() {
+            println!("The puzzle is already solved");
+            return Ok(());
+        }
+        return Err(AppError::LimitReached(
+            "No technique applies to the puzzle, it takes guessing from here".to_string(),
+        ));
+    };
+    if let HintAction::Place(found) = &hint.action {
+        board.set_cell(found.position.0, found.position.1, found.value);
+    }
+    println!("{hint}");
+    if flags.plain() {
+        println!("{board}");
+    } else {
+        print!("{}", board.render_board(&flags.draw_options()?));
+    }
+    Ok(())
+}
+
+/// Prints a walkthrough of a solve of the puzzle, one numbered move per line, stopping after
+/// `--max-steps N` moves if given
+fn explain(flags: &Flags) -> Result<(), AppError> {
+    let board = read_position(flags, "explain")?;
+    let limit = flags.number(&["--max-steps"], usize::MAX)?;
+    let steps = board.explain_solution()?;
+    for (number, step) in steps.iter().take(limit).enumerate() {
+        println!("{}. {step}", number + 1);
+    }
+    if steps.len() > limit && !flags.plain() {
+        println!("Stopped after {limit} of {} moves", steps.len());
+    }
+    Ok(())
+}
+
+/// Runs the commands read from stdin on the puzzle, printing what each one answers and, on
+/// stderr, why any that failed did. The prompt is only shown when stdin is a terminal
+fn repl(flags: &Flags) -> Result<(), AppError> {
+    if flags.positional() == ["-"] {
+        return Err(AppError::Parse(
+            "repl reads its commands from stdin, so the puzzle has to come from a file".to_string(),
+        ));
+    }
+    let board = read_position(flags, "repl")?;
+    let mut session = ReplSession::new(board, flags.draw_options()?);
+    let interactive = stdin().is_tty();
+    let failed = session.run(stdin().lock(), stdout(), stderr(), interactive)?;
+    if failed > 0 && !interactive {
+        let commands = if failed == 1 { "command" } else { "commands" };
+        return Err(AppError::Parse(format!("{failed} {commands} failed")));
+    }
+    Ok(())
+}
+
+/// Answers the request lines read from stdin on stdout, see [`protocol::serve`]
+fn serve_stdio() -> Result<(), AppError> {
+    Ok(protocol::serve(stdin().lock(), stdout().lock())?)
+}
+
+/// Prints the puzzle of the day, today's unless a date is given
+fn daily(flags: &Flags) -> Result<(), AppError> {
+    let date = flags
+        .positional()
+        .first()
+        .map_or(Ok(Date::today()), |date| date.parse())
+        .map_err(|error: ParseDateError| AppError::Parse(error.to_string()))?;
+    let format = flags.format()?;
+    let generated = generate_daily(date);
+    if !flags.quiet() && !format.is_structured() {
+        let grade = generated.difficulty.expect("daily puzzles come graded");
+        println!("Puzzle of {date}, grade: {grade}");
+    }
+    ResultWriter::new(stdout(), format, flags.palette()?).write(&Record {
+        puzzle: Some(&generated.puzzle),
+        seeds: Some(generated.seeds()),
+        difficulty: generated.difficulty,
+        ..Record::default()
+    })?;
+    Ok(())
+}
+
+/// Longest a single puzzle may take in the benchmark before it counts as a timeout
+const BENCHMARK_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Runs a solver headless over `--puzzles N` puzzles generated from `--seed S` with `--clues`
+/// in range, and prints how often it solved them, the distribution of its timings and its
+/// backtracks. `--compare` runs every registered solver, followed by the entropy solver with
+/// each of its options, over the same puzzles instead and prints a table comparing them.
+/// Progress goes to stderr when it is a terminal, unless `--quiet` is given
+fn benchmark(flags: &Flags) -> Result<(), AppError> {
+    let count = flags.number(&["-n", "--puzzles"], 100)?;
+    let master_seed = flags.number(&["--seed"], 0)?;
+    let clues = clue_range(flags)?;
+    // The counts are redrawn in place, which only makes sense on a terminal
+    let quiet = flags.quiet() || !std::io::stderr().is_tty();
+    let solver_name = solver_name(flags)?;
+    let mut timings = flags
+        .value(&["--timings"])
+        .map(timings_writer)
+        .transpose()?;
+
+    let mut generated = Vec::new();
+    for index in 0..count {
+        let seed = pack_seed(master_seed, index);
+        generated.push(generate_puzzle(seed, seed, clues.clone()));
+        if !quiet {
+            eprint!("\rGenerating puzzles: {}/{count}", index + 1);
         }
-    }).unzip();
+    }
+    if !quiet {
+        eprintln!();
+    }
+    let puzzles: Vec<Board> = generated
+        .iter()
+        .map(|generated| generated.puzzle.clone())
+        .collect();
 
-    let total_completed = completed_count.iter().sum::<f32>();
-    let total_time = completed_times.iter().sum::<f32>();
-    (total_completed, total_time / total_completed)
-} */
+    let mut solvers = if flags.has(&["--compare"]) {
+        comparison_solvers(0)
+    } else {
+        let solver =
+            solver_named(solver_name, 0).expect("solver_name only returns registered solvers");
+        vec![(solver_name.to_string(), solver)]
+    };
+    let limits = SolveLimits::with_timeout(BENCHMARK_TIMEOUT);
+    let mut reports = Vec::new();
+    for (name, solver) in &mut solvers {
+        let report = solve_batch_sequential(solver.as_mut(), &puzzles, &limits, |index, entry| {
+            if let Some((path, timings)) = &mut timings {
+                let seeds = generated[index].seeds().to_string();
+                timings
+                    .write_row(
+                        index,
+                        &seeds,
+                        &puzzles[index],
+                        name,
+                        &entry.result,
+                        entry.elapsed,
+                    )
+                    .map_err(|error| {
+                        AppError::Internal(format!("Could not write {path}: {error}"))
+                    })?;
+            }
+            if !quiet {
+                eprint!("\r{name}: {}/{} puzzles", index + 1, puzzles.len());
+            }
+            Ok::<_, AppError>(())
+        })?;
+        if !quiet {
+            eprintln!();
+        }
+        reports.push((name.clone(), report));
+    }
+
+    if flags.has(&["--compare"]) {
+        println!("{}", BatchReport::comparison_header());
+        for (name, report) in &reports {
+            println!("{}", report.comparison_row(name));
+        }
+        println!();
+    }
+    let (_, report) = reports
+        .iter()
+        .find(|(name, _)| name == solver_name)
+        .expect("the chosen solver is always benchmarked");
+    println!("{solver_name}: {report}");
+    print!("{}", report.benchmark_table());
+    if let Some(path) = flags.value(&["--csv"]) {
+        File::create(path)
+            .and_then(|mut file| report.write_csv(&mut file))
+            .map_err(|error| AppError::Internal(format!("Could not write {path}: {error}")))?;
+    }
+    Ok(())
+}
+
+/// Puzzles between two progress reports of `generate` unless `--progress K` says otherwise
+const PACK_PROGRESS_INTERVAL: u64 = 50;
+
+/// Generates `--count N` puzzles, digging `--unique` ones if asked, with between `--clues A..=B`
+/// clues, and prints them one per line, or writes them to `--out FILE`. The puzzles are
+/// generated from the seeds following `--seed N`, a random one unless given, so the whole pack
+/// can be generated again from it. Puzzles not graded as `--difficulty NAME`, or that the
+/// reference solver backtracks more than `--max-effort N` times on, are discarded for the next
+/// seed, giving up once [`PackGenerator`] has discarded too many in a row. With `--out`, each puzzle's seeds, grade
+/// and solve effort go to a CSV next to the pack, and `--with-solutions` also writes the solved
+/// grids there, see [`PackWriter`]. `--print-grade` adds the hardest technique each puzzle needs to that CSV, or its
+/// grade and hardest technique to its line without `--out`. Progress is reported on stderr every
+/// `--progress K` puzzles unless `--quiet` is given
+fn generate_pack(flags: &Flags) -> Result<(), AppError> {
+    let count = flags.number(&["-n", "--count"], 100)?;
+    let master_seed = flags.number(&["--seed"], thread_rng().gen())?;
+    let interval = flags
+        .number(&["--progress"], PACK_PROGRESS_INTERVAL)?
+        .max(1);
+    let clues = clue_range(flags)?;
+    let difficulty = flags.named("--difficulty", &Difficulty::ALL)?;
+    let max_effort = match flags.value(&["--max-effort"]) {
+        Some(_) => Some(flags.number(&["--max-effort"], 0)?),
+        None => None,
+    };
+    let (quiet, format) = (flags.quiet(), flags.format()?);
+    let print_grade = flags.has(&["--print-grade"]);
+    // `--variant x` generates X-sudoku puzzles, which the grader and the reference solver know
+    // nothing about since they only follow the classic rules
+    let variant = flags.named("--variant", &Variant::ALL)?.unwrap_or_default();
+    if variant != Variant::Classic {
+        if let Some(flag) = ["--difficulty", "--max-effort", "--print-grade"]
+            .into_iter()
+            .find(|flag| flags.has(&[flag]))
+        {
+            return Err(AppError::Parse(format!(
+                "{flag} grades puzzles by the classic rules, so it cannot be used with --variant \
+                 {variant}"
+            )));
+        }
+    }
+    let out = flags.value(&["--out"]).map(Path::new);
+    let with_solutions = flags.has(&["--with-solutions"]);
+    if out.is_none() && with_solutions {
+        return Err(AppError::Parse(
+            "--with-solutions writes the solutions next to the pack, so it needs --out".to_string(),
+        ));
+    }
+
+    let pack_error =
+        |error: std::io::Error| AppError::Internal(format!("Could not write the pack: {error}"));
+    let mut files = out
+        .map(|out| PackWriter::create(out, with_solutions, print_grade))
+        .transpose()
+        .map_err(|error| AppError::Internal(format!("Could not write {error}")))?;
+    let mut writer = ResultWriter::new(stdout(), format, flags.palette()?);
+    let mut pack = PackGenerator::new(master_seed, clues).with_variant(variant);
+    if let Some(difficulty) = difficulty {
+        pack = pack.with_difficulty(difficulty);
+    } else if flags.has(&["--unique"]) {
+        pack = pack.with_unique();
+    }
+    if let Some(max_effort) = max_effort {
+        pack = pack.with_max_effort(max_effort);
+    }
+
+    if !quiet {
+        eprintln!("Generating {count} puzzles from master seed {master_seed}");
+    }
+    let start_time = Instant::now();
+    for done in 1..=count {
+        let Some(generated) = pack.next() else {
+            break;
+        };
+        let generated = generated.map_err(|error| AppError::LimitReached(error.to_string()))?;
+        match &mut files {
+            Some(files) => files.write(&generated).map_err(pack_error)?,
+            None => writer.write(&Record {
+                puzzle: Some(&generated.puzzle),
+                seeds: Some(generated.seeds()),
+                difficulty: generated.difficulty,
+                note: generated
+                    .difficulty
+                    .filter(|_| print_grade)
+                    .map(|difficulty| {
+                        format!(
+                            "{difficulty} {}",
+                            generated.hardest_technique.unwrap_or("none")
+                        )
+                    }),
+                ..Record::default()
+            })?,
+        }
+        if !quiet && (done % interval == 0 || done == count) {
+            let rate = done as f64 / start_time.elapsed().as_secs_f64();
+            let eta = (count - done) as f64 / rate;
+            eprintln!(
+                "{done}/{count} puzzles, {} discarded, {rate:.1} puzzles/s, ETA {eta:.0}s",
+                pack.discarded()
+            );
+        }
+    }
+    if let (Some(out), Some(files)) = (out, &mut files) {
+        files.finish().map_err(pack_error)?;
+        if !quiet {
+            println!("Wrote {count} puzzles to {}", out.display());
+            println!(
+                "Wrote their seeds, grades and effort to {}",
+                files.metadata_path().display()
+            );
+            if let Some(solutions_path) = files.solutions_path() {
+                println!("Wrote their solutions to {}", solutions_path.display());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Restarts of the minimizer per grid in `hunt17` unless `--restarts N` says otherwise
+const HUNT_RESTARTS: usize = 8;
+
+/// Digs minimal puzzles out of one grid after another on `--jobs N` threads until interrupted,
+/// trying `--restarts N` removal orders per grid. Every puzzle with at most `--threshold N`
+/// clues, 17 unless given, is appended to `--log FILE` with the seeds that lead to it. The
+/// grids come from the seeds following `--seed N`, a random one unless given, and a running
+/// tally of the grids examined and the fewest clues found is kept on stderr unless `--quiet` is
+/// given
+fn hunt(flags: &Flags) -> Result<(), AppError> {
+    let threshold = flags.number(&["--threshold"], MINIMUM_CLUES)?;
+    let restarts = flags.number(&["--restarts"], HUNT_RESTARTS)?;
+    let master_seed = flags.number(&["--seed"], thread_rng().gen())?;
+    let available = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let jobs = flags.number(&["--jobs"], available)?.max(1);
+    let path = flags.value(&["--log"]).unwrap_or("hunt17.log");
+    let quiet = flags.quiet();
+    let log_error =
+        |error: std::io::Error| AppError::Internal(format!("Could not write {path}: {error}"));
+    let mut log = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(path)
+        .map(BufWriter::new)
+        .map_err(log_error)?;
+
+    // Ctrl-C only raises the flag, so the workers finish their grids and the log gets flushed
+    let interrupted = Arc::new(AtomicBool::new(false));
+    signal_hook::flag::register(SIGINT, Arc::clone(&interrupted))
+        .map_err(|error| AppError::Internal(format!("Could not handle Ctrl-C: {error}")))?;
+    eprintln!(
+        "Hunting for puzzles with at most {threshold} clues from master seed {master_seed} on \
+         {jobs} threads, press Ctrl-C to stop"
+    );
+
+    let next_grid = AtomicU64::new(0);
+    let start_time = Instant::now();
+    let (mut grids, mut found) = (0_u64, 0_u64);
+    let mut best: Option<usize> = None;
+    let mut failed_write = None;
+    std::thread::scope(|scope| {
+        let (sender, receiver) = mpsc::channel();
+        for _ in 0..jobs {
+            let sender = sender.clone();
+            let (interrupted, next_grid) = (&interrupted, &next_grid);
+            scope.spawn(move || {
+                while !interrupted.load(Ordering::Relaxed) {
+                    let index = next_grid.fetch_add(1, Ordering::Relaxed);
+                    let finds = hunt_grid(pack_seed(master_seed, index), restarts);
+                    if sender.send(finds).is_err() {
+                        break;
+                    }
+                }
+            });
+        }
+        drop(sender);
+
+        // Runs until every worker has stopped and hung up
+        for finds in receiver {
+            grids += 1;
+            for find in finds {
+                best = Some(best.map_or(find.clues, |best| best.min(find.clues)));
+                if find.clues <= threshold {
+                    found += 1;
+                    let written = writeln!(log, "{find}").and_then(|()| log.flush());
+                    if let Err(error) = written {
+                        failed_write.get_or_insert(error);
+                        interrupted.store(true, Ordering::Relaxed);
+                    }
+                }
+            }
+            if quiet {
+                continue;
+            }
+            eprint!(
+                "\r{grids} grids examined in {:.0}s, fewest clues {}, {found} logged",
+                start_time.elapsed().as_secs_f64(),
+                best.map_or("-".to_string(), |best| best.to_string())
+            );
+        }
+    });
+    if !quiet {
+        eprintln!();
+    }
+    if let Some(error) = failed_write {
+        return Err(log_error(error));
+    }
+    log.flush().map_err(log_error)?;
+    println!("Examined {grids} grids, logged {found} puzzles to {path}");
+    Ok(())
+}
+
+/// The range of clues given by `--clues`, 20 to 30 unless given
+fn clue_range(flags: &Flags) -> Result<RangeInclusive<usize>, AppError> {
+    let clues = match flags.value(&["--clues"]) {
+        None => 20..=30,
+        Some(range) => parse_clue_range(range).ok_or_else(|| {
+            AppError::Parse(format!(
+                "--clues expects a range like 24..=30, found {range}"
+            ))
+        })?,
+    };
+    validate_clue_range(&clues).map_err(|error| AppError::Parse(error.to_string()))?;
+    Ok(clues)
+}
+
+/// Solver used unless `--solver` names another one
+const DEFAULT_SOLVER: &str = "wfc";
+
+/// The solver `--solver NAME` picks, which has to be one of the registered ones
+fn solver_name<'a>(flags: &Flags<'a>) -> Result<&'a str, AppError> {
+    let name = flags.value(&["--solver"]).unwrap_or(DEFAULT_SOLVER);
+    let names: Vec<String> = registered_solvers(0)
+        .iter()
+        .map(|solver| solver.name().to_string())
+        .collect();
+    if !names.iter().any(|registered| registered == name) {
+        return Err(AppError::Parse(format!(
+            "Unknown solver {name}, expected one of {}",
+            names.join(", ")
+        )));
+    }
+    Ok(name)
+}
+
+/// Reads a range of clues written as `A..=B`, or as a single count
+fn parse_clue_range(range: &str) -> Option<RangeInclusive<usize>> {
+    match range.split_once("..=") {
+        Some((start, end)) => Some(start.trim().parse().ok()?..=end.trim().parse().ok()?),
+        None => {
+            let clues = range.trim().parse().ok()?;
+            Some(clues..=clues)
+        }
+    }
+}
+
+/// Starts the timings CSV at `path`, keeping the path along with it for the errors
+fn timings_writer(path: &str) -> Result<(&str, TimingsWriter<BufWriter<File>>), AppError> {
+    TimingsWriter::create(Path::new(path))
+        .map(|timings| (path, timings))
+        .map_err(|error| AppError::Internal(format!("Could not write {path}: {error}")))
+}
+
+/// Solves the puzzles of the files on `jobs` threads as they are read, writing a record for
+/// each as soon as the ones before it are written, with the reasons for failures going to
+/// stderr. A summary of the batch follows unless in plain mode. Fails with the kind of the
+/// first failure if any puzzle failed, unless `--keep-going` is given. Whoever reads the
+/// output going away ends the batch quietly. `--timings FILE` writes how each solve went as a
+/// CSV, see [`TimingsWriter`]
+fn solve_batch(paths: &[&str], jobs: usize, flags: &Flags) -> Result<(), AppError> {
+    let mut writer = ResultWriter::new(stdout(), flags.format()?, flags.palette()?);
+    let mut timings = flags
+        .value(&["--timings"])
+        .map(timings_writer)
+        .transpose()?;
+    let puzzles = stream_puzzles(paths)?;
+    let (mut count, mut failed) = (0, 0);
+    let (mut first_failure, mut fatal) = (None, None);
+    let timeout = flags.number(&["--timeout-ms"], PUZZLE_TIMEOUT.as_millis() as u64)?;
+    let limits = SolveLimits::with_timeout(Duration::from_millis(timeout));
+    let verify = flags.has(&["--verify"]);
+    let dump = flags.value(&["--verify-dump"]).unwrap_or(VERIFY_DUMP);
+    // `--solver dfs` or `--solver dlx` solve every puzzle with one of the exhaustive solvers
+    let solver_name = solver_name(flags)?;
+    let new_solver = |seed| {
+        solver_named(solver_name, seed).expect("solver_name only returns registered solvers")
+    };
+    let report = solve_batch_streaming(puzzles, jobs, &limits, new_solver, verify, |result| {
+        count += 1;
+        let (written, failure) = match &result {
+            Ok((source, entry)) => {
+                let failure = match (&entry.result, &entry.verification) {
+                    (Err(error), _) => Some(
+                        AppError::from(error.clone())
+                            .with_message(format!("Puzzle {count}: {error}")),
+                    ),
+                    (Ok(_), Some((puzzle, Err(failure)))) => Some(verification_failed(
+                        &format!("Puzzle {count}"),
+                        failure,
+                        &format!("{source}, solver seed {}", entry.seed),
+                        (puzzle, &entry.board),
+                        dump,
+                    )),
+                    (Ok(_), _) => None,
+                };
+                let puzzle = entry.board.givens();
+                if let Some((path, timings)) = &mut timings {
+                    let row = timings.write_row(
+                        entry.seed as usize,
+                        source,
+                        &puzzle,
+                        solver_name,
+                        &entry.result,
+                        entry.elapsed,
+                    );
+                    if let Err(error) = row {
+                        fatal = Some(AppError::Internal(format!(
+                            "Could not write {path}: {error}"
+                        )));
+                        return false;
+                    }
+                }
+                let written = writer.write(&Record {
+                    puzzle: Some(&puzzle),
+                    solution: failure.is_none().then_some(&entry.board),
+                    elapsed: Some(entry.elapsed),
+                    error: failure.as_ref().map(AppError::record_error),
+                    ..Record::default()
+                });
+                (written, failure)
+            }
+            Err(BadLine {
+                error: error @ AppError::Internal(_),
+                ..
+            }) => {
+                fatal = Some(error.clone());
+                return false;
+            }
+            Err(BadLine { reason, error }) => {
+                let written = writer.write(&Record {
+                    error: Some(error.record_error()),
+                    note: Some(reason.clone()),
+                    ..Record::default()
+                });
+                (written, Some(error.clone()))
+            }
+        };
+        if let Some(failure) = failure {
+            eprintln!("{failure}");
+            failed += 1;
+            first_failure.get_or_insert(failure);
+        }
+        match written {
+            Ok(()) => true,
+            Err(error) if error.kind() == ErrorKind::BrokenPipe => false,
+            Err(error) => {
+                fatal = Some(error.into());
+                false
+            }
+        }
+    });
+    if let Some(error) = fatal {
+        return Err(error);
+    }
+    if !flags.plain() {
+        println!("{report}");
+        print!("{}", report.statistics_table());
+    }
+    match first_failure {
+        Some(failure) if !flags.has(&["--keep-going"]) => {
+            Err(failure.with_message(format!("{failed} of {count} puzzles could not be solved")))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,277 @@
+use std::{
+    io::{stdout, Stdout, Write},
+    time::{Duration, Instant},
+};
+
+use crossterm::{
+    cursor,
+    style::{Print, PrintStyledContent},
+    terminal::{Clear, ClearType},
+    QueueableCommand,
+};
+
+use crate::{
+    board::{Board, BoardMove, DrawOptions},
+    palette::Palette,
+    report::CancellationToken,
+};
+
+/// Receives notifications while a board is being solved. Every method has an empty default so
+/// implementors only need to override the events they care about
+pub trait SolveObserver {
+    /// Called after the solver picks a value for a cell that had several options, either as a
+    /// fresh guess or as a substitute while backtracking
+    fn on_place(&mut self, _board: &Board, _position: (usize, usize), _value: u8) {}
+
+    /// Called after the solver fills in a cell that only had a single option left
+    fn on_cascade(&mut self, _board: &Board, _position: (usize, usize), _value: u8) {}
+
+    /// Called when the solver finds a cell without any option left and has to backtrack
+    fn on_dead_end(&mut self, _board: &Board, _position: (usize, usize)) {}
+
+    /// Called after a move and its cascades have been taken back off the board
+    fn on_backtrack(&mut self, _board: &Board, _undone: &BoardMove) {}
+}
+
+/// Observer that ignores every event, used to solve a board without any output
+pub struct NoopObserver;
+
+impl SolveObserver for NoopObserver {}
+
+/// Observer that redraws the board to the terminal whenever the solver hits a dead end
+pub struct TerminalObserver {
+    stdout: Stdout,
+}
+
+impl TerminalObserver {
+    pub fn new() -> Self {
+        Self { stdout: stdout() }
+    }
+}
+
+impl Default for TerminalObserver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SolveObserver for TerminalObserver {
+    fn on_dead_end(&mut self, board: &Board, _position: (usize, usize)) {
+        board.draw_board(&mut self.stdout);
+    }
+}
+
+/// Frames drawn per second by an [`AnimatingObserver`] unless configured otherwise
+pub const DEFAULT_FPS: u32 = 30;
+
+/// Longest an [`AnimatingObserver`] sleeps at once while pausing, so a cancellation is noticed
+/// quickly however long the delay
+const PAUSE_SLICE: Duration = Duration::from_millis(10);
+
+/// Observer that animates a solve on a terminal. The board is drawn whole on the first event,
+/// then only the cells that changed since the last frame are repainted, with a status line of
+/// counts below the grid. Placements are drawn at most `fps` times a second, while a backtrack
+/// always gets a frame of its own so the search can be seen giving up on a guess, with the
+/// values it takes back highlighted until the next frame. A delay pauses after every frame.
+/// Cells are styled by the palette, the colored one unless told otherwise
+pub struct AnimatingObserver<W: Write> {
+    out: W,
+    /// Terminal column and row of the top left corner of the grid
+    origin: (u16, u16),
+    frame_interval: Duration,
+    last_frame: Option<Instant>,
+    delay: Duration,
+    /// Cuts the pauses short once cancelled
+    cancellation: Option<CancellationToken>,
+    palette: Palette,
+    /// Cells as they were on the last frame, `None` before the first
+    drawn: Option<[u8; 81]>,
+    /// Indices of the cells drawn highlighted on the last frame
+    highlighted: Vec<usize>,
+    placements: u64,
+    backtracks: u64,
+    start_time: Instant,
+}
+
+impl<W: Write> AnimatingObserver<W> {
+    /// Animates to `out` with the grid's top left corner at `origin`, at [`DEFAULT_FPS`]
+    pub fn new(out: W, origin: (u16, u16)) -> Self {
+        Self {
+            out,
+            origin,
+            frame_interval: Duration::from_secs(1) / DEFAULT_FPS,
+            last_frame: None,
+            delay: Duration::ZERO,
+            cancellation: None,
+            palette: Palette::colored(),
+            drawn: None,
+            highlighted: Vec::new(),
+            placements: 0,
+            backtracks: 0,
+            start_time: Instant::now(),
+        }
+    }
+
+    /// Sets the most frames drawn per second, 0 drawing every placement
+    pub fn with_fps(mut self, fps: u32) -> Self {
+        self.frame_interval = match fps {
+            0 => Duration::ZERO,
+            fps => Duration::from_secs(1) / fps,
+        };
+        self
+    }
+
+    /// Pauses for `delay` after every frame, which then draws every placement whatever the fps
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Stops pausing once `token` is cancelled, so the solve holding the same token ends quickly
+    pub fn with_cancellation(mut self, token: CancellationToken) -> Self {
+        self.cancellation = Some(token);
+        self
+    }
+
+    /// Styles the cells with `palette`, the values taken back in its conflict style
+    pub fn with_palette(mut self, palette: Palette) -> Self {
+        self.palette = palette;
+        self
+    }
+
+    /// Terminal position of a cell's digit, matching [`Board::render_board`] without coordinates
+    fn cell_position(&self, row: usize, col: usize) -> (u16, u16) {
+        (
+            self.origin.0 + (2 + 2 * col + 2 * (col / 3)) as u16,
+            self.origin.1 + (1 + row + row / 3) as u16,
+        )
+    }
+
+    /// Draws a frame and pauses after it, unless the last one was drawn too recently and there
+    /// is no delay. Backtracks are drawn regardless
+    fn frame(&mut self, board: &Board, backtrack: bool) {
+        if !backtrack
+            && self.delay.is_zero()
+            && self
+                .last_frame
+                .is_some_and(|last| last.elapsed() < self.frame_interval)
+        {
+            return;
+        }
+        self.draw(board, backtrack);
+        self.last_frame = Some(Instant::now());
+        self.pause();
+    }
+
+    /// Sleeps for the delay, or until the cancellation token is cancelled
+    fn pause(&self) {
+        let end = Instant::now() + self.delay;
+        while !self
+            .cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+        {
+            let now = Instant::now();
+            if now >= end {
+                break;
+            }
+            std::thread::sleep((end - now).min(PAUSE_SLICE));
+        }
+    }
+
+    /// Repaints the cells that changed since the last frame, or the whole board on the first,
+    /// followed by the status line. On a backtrack the values taken back stay on screen
+    /// highlighted, to be cleared by the next frame
+    fn draw(&mut self, board: &Board, backtrack: bool) {
+        let cells: [u8; 81] = std::array::from_fn(|i| board.get_cell(i / 9, i % 9));
+        match self.drawn {
+            None => {
+                self.out
+                    .queue(cursor::MoveTo(self.origin.0, self.origin.1))
+                    .unwrap();
+                let options = DrawOptions {
+                    palette: self.palette,
+                    ..DrawOptions::default()
+                };
+                for line in board.render_board(&options).lines() {
+                    self.out
+                        .queue(Print(line))
+                        .unwrap()
+                        .queue(cursor::MoveToNextLine(1))
+                        .unwrap()
+                        .queue(cursor::MoveToColumn(self.origin.0))
+                        .unwrap();
+                }
+            }
+            Some(drawn) => {
+                let highlighted = std::mem::take(&mut self.highlighted);
+                for (i, (&old, &new)) in drawn.iter().zip(cells.iter()).enumerate() {
+                    if old == new && !highlighted.contains(&i) {
+                        continue;
+                    }
+                    let (x, y) = self.cell_position(i / 9, i % 9);
+                    self.out.queue(cursor::MoveTo(x, y)).unwrap();
+                    if backtrack && old != 0 && new == 0 {
+                        let digit = (b'0' + old) as char;
+                        self.out
+                            .queue(PrintStyledContent(self.palette.conflict.apply(digit)))
+                            .unwrap();
+                        self.highlighted.push(i);
+                    } else {
+                        let digit = if new == 0 { ' ' } else { (b'0' + new) as char };
+                        self.out
+                            .queue(PrintStyledContent(self.palette.solved.apply(digit)))
+                            .unwrap();
+                    }
+                }
+            }
+        }
+        self.drawn = Some(cells);
+
+        self.out
+            .queue(cursor::MoveTo(self.origin.0, self.status_row()))
+            .unwrap()
+            .queue(Print(format!(
+                "{} placements, {} backtracks, {}ms",
+                self.placements,
+                self.backtracks,
+                self.start_time.elapsed().as_millis()
+            )))
+            .unwrap()
+            .queue(Clear(ClearType::UntilNewLine))
+            .unwrap();
+        self.out.flush().unwrap();
+    }
+
+    /// Terminal row of the status line, just below the grid
+    fn status_row(&self) -> u16 {
+        self.origin.1 + 13
+    }
+
+    /// Draws the final state of the board, however recently the last frame was, and leaves the
+    /// cursor at the start of the line below the status line
+    pub fn finish(&mut self, board: &Board) {
+        self.draw(board, false);
+        self.out
+            .queue(cursor::MoveTo(self.origin.0, self.status_row() + 1))
+            .unwrap();
+        self.out.flush().unwrap();
+    }
+}
+
+impl<W: Write> SolveObserver for AnimatingObserver<W> {
+    fn on_place(&mut self, board: &Board, _position: (usize, usize), _value: u8) {
+        self.placements += 1;
+        self.frame(board, false);
+    }
+
+    fn on_cascade(&mut self, board: &Board, _position: (usize, usize), _value: u8) {
+        self.placements += 1;
+        self.frame(board, false);
+    }
+
+    fn on_backtrack(&mut self, board: &Board, _undone: &BoardMove) {
+        self.backtracks += 1;
+        self.frame(board, true);
+    }
+}
@@ -0,0 +1,271 @@
+use std::{
+    fmt::Display,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use crate::{
+    board::{Board, DrawOptions},
+    generator::{GeneratedPuzzle, SeedBundle},
+    grade::Difficulty,
+    json::JsonValue,
+    palette::Palette,
+};
+
+/// How results are printed, picked with `--format NAME`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// The 81 digit string on a single line
+    #[default]
+    Line,
+    /// The box drawing grid as plain text
+    Grid,
+    /// One JSON object per line
+    Json,
+    /// Comma separated values under a header row
+    Csv,
+}
+
+impl OutputFormat {
+    pub const ALL: [OutputFormat; 4] = [
+        OutputFormat::Line,
+        OutputFormat::Grid,
+        OutputFormat::Json,
+        OutputFormat::Csv,
+    ];
+
+    /// Whether the output is meant for programs, so nothing but the results may go to stdout
+    pub fn is_structured(&self) -> bool {
+        matches!(self, OutputFormat::Json | OutputFormat::Csv)
+    }
+}
+
+impl Display for OutputFormat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OutputFormat::Line => write!(f, "line"),
+            OutputFormat::Grid => write!(f, "grid"),
+            OutputFormat::Json => write!(f, "json"),
+            OutputFormat::Csv => write!(f, "csv"),
+        }
+    }
+}
+
+/// Why a record holds no result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordError {
+    /// Printed in place of the board by the line and grid formats, like INVALID or TIMEOUT
+    pub token: &'static str,
+    /// Written out in full by the JSON and CSV formats
+    pub message: String,
+}
+
+/// One result of a command, with whatever is known about it
+#[derive(Debug, Default)]
+pub struct Record<'a> {
+    pub puzzle: Option<&'a Board>,
+    pub solution: Option<&'a Board>,
+    pub seeds: Option<SeedBundle>,
+    pub elapsed: Option<Duration>,
+    pub difficulty: Option<Difficulty>,
+    /// Printed after the board by the line and grid formats, for readers rather than programs
+    pub note: Option<String>,
+    pub error: Option<RecordError>,
+}
+
+impl Record<'_> {
+    /// The record as a JSON object, what is not known being null
+    pub fn to_json(&self) -> JsonValue {
+        JsonValue::object([
+            ("puzzle", self.puzzle.map(Board::to_string).into()),
+            ("solution", self.solution.map(Board::to_string).into()),
+            ("seeds", self.seeds.map(|seeds| seeds.to_string()).into()),
+            (
+                "time_us",
+                self.elapsed
+                    .map(|elapsed| elapsed.as_micros() as u64)
+                    .into(),
+            ),
+            (
+                "difficulty",
+                self.difficulty
+                    .map(|difficulty| difficulty.to_string())
+                    .into(),
+            ),
+            (
+                "error",
+                self.error
+                    .as_ref()
+                    .map(|error| error.message.clone())
+                    .into(),
+            ),
+        ])
+    }
+
+    /// The record as a row under [`ResultWriter::CSV_HEADER`], what is not known being empty
+    pub fn csv_row(&self) -> String {
+        let fields = [
+            self.puzzle.map(Board::to_string),
+            self.solution.map(Board::to_string),
+            self.seeds.map(|seeds| seeds.to_string()),
+            self.elapsed.map(|elapsed| elapsed.as_micros().to_string()),
+            self.difficulty.map(|difficulty| difficulty.to_string()),
+            self.error.as_ref().map(|error| csv_field(&error.message)),
+        ];
+        let fields: Vec<String> = fields.into_iter().map(Option::unwrap_or_default).collect();
+        fields.join(",")
+    }
+}
+
+/// Writes records in one format, so every command prints its results the same way
+pub struct ResultWriter<W: Write> {
+    out: W,
+    format: OutputFormat,
+    /// Styles the boards of the grid format
+    palette: Palette,
+    header_written: bool,
+}
+
+impl<W: Write> ResultWriter<W> {
+    /// Names of the CSV columns, in the order of the values in every row
+    pub const CSV_HEADER: &'static str = "puzzle,solution,seeds,time_us,difficulty,error";
+
+    pub fn new(out: W, format: OutputFormat, palette: Palette) -> Self {
+        Self {
+            out,
+            format,
+            palette,
+            header_written: false,
+        }
+    }
+
+    /// Writes the record as a line of JSON or CSV, the CSV header going before the first one, or
+    /// for the line and grid formats as the solution if there is one and the puzzle otherwise.
+    /// Those print a failed record as its [`RecordError::token`], leaving the message to whoever
+    /// reports errors. Every record is flushed right away, so whoever reads the output gets it as
+    /// it is written
+    pub fn write(&mut self, record: &Record) -> io::Result<()> {
+        let line = match self.format {
+            OutputFormat::Line | OutputFormat::Grid => {
+                let board = match (&record.error, record.solution.or(record.puzzle)) {
+                    (Some(error), _) => error.token.to_string(),
+                    (None, Some(board)) if self.format == OutputFormat::Grid => board
+                        .render_board(&DrawOptions {
+                            palette: self.palette,
+                            ..DrawOptions::default()
+                        })
+                        .trim_end()
+                        .to_string(),
+                    (None, Some(board)) => board.to_string(),
+                    (None, None) => String::new(),
+                };
+                match &record.note {
+                    Some(note) if self.format == OutputFormat::Grid => format!("{board}\n{note}"),
+                    Some(note) => format!("{board} {note}"),
+                    None => board,
+                }
+            }
+            OutputFormat::Json => record.to_json().to_string(),
+            OutputFormat::Csv if self.header_written => record.csv_row(),
+            OutputFormat::Csv => {
+                self.header_written = true;
+                format!("{}\n{}", Self::CSV_HEADER, record.csv_row())
+            }
+        };
+        writeln!(self.out, "{line}")?;
+        self.out.flush()
+    }
+}
+
+/// The value as a CSV field, quoted when it holds a comma, a quote or a line break
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+/// The files a pack is written to: the puzzles one per line, a CSV of their seeds, grades and
+/// effort next to it, and if asked for their solutions
+pub struct PackWriter {
+    pack: BufWriter<File>,
+    metadata: BufWriter<File>,
+    metadata_path: PathBuf,
+    solutions: Option<(PathBuf, BufWriter<File>)>,
+    /// Whether the CSV also holds the hardest technique each puzzle needs
+    with_technique: bool,
+}
+
+impl PackWriter {
+    /// Creates the pack at `path`, the CSV beside it with the `csv` extension and, when
+    /// `with_solutions`, the solutions beside it with the `solutions.sdm` extension. Errors
+    /// start with the path of the file that could not be created
+    pub fn create(path: &Path, with_solutions: bool, with_technique: bool) -> io::Result<Self> {
+        let create = |path: &Path| {
+            File::create(path).map(BufWriter::new).map_err(|error| {
+                io::Error::new(error.kind(), format!("{}: {error}", path.display()))
+            })
+        };
+        let metadata_path = path.with_extension("csv");
+        let solutions_path = path.with_extension("solutions.sdm");
+        let mut writer = Self {
+            pack: create(path)?,
+            metadata: create(&metadata_path)?,
+            metadata_path,
+            solutions: if with_solutions {
+                Some((solutions_path.clone(), create(&solutions_path)?))
+            } else {
+                None
+            },
+            with_technique,
+        };
+        let technique_column = if with_technique {
+            ",hardest_technique"
+        } else {
+            ""
+        };
+        writeln!(
+            writer.metadata,
+            "{}{technique_column}",
+            GeneratedPuzzle::CSV_HEADER
+        )?;
+        Ok(writer)
+    }
+
+    pub fn metadata_path(&self) -> &Path {
+        &self.metadata_path
+    }
+
+    /// Where the solutions go, `None` unless they were asked for
+    pub fn solutions_path(&self) -> Option<&Path> {
+        self.solutions.as_ref().map(|(path, _)| path.as_path())
+    }
+
+    /// Adds the puzzle to the pack, its row to the CSV and its solution if they are written
+    pub fn write(&mut self, generated: &GeneratedPuzzle) -> io::Result<()> {
+        writeln!(self.pack, "{}", generated.puzzle)?;
+        if self.with_technique {
+            let technique = generated.hardest_technique.unwrap_or("none");
+            writeln!(self.metadata, "{},{technique}", generated.csv_row())?;
+        } else {
+            writeln!(self.metadata, "{}", generated.csv_row())?;
+        }
+        if let Some((_, solutions)) = &mut self.solutions {
+            writeln!(solutions, "{}", generated.solution)?;
+        }
+        Ok(())
+    }
+
+    /// Flushes every file, which is when writing them can still fail
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.pack.flush()?;
+        self.metadata.flush()?;
+        match &mut self.solutions {
+            Some((_, solutions)) => solutions.flush(),
+            None => Ok(()),
+        }
+    }
+}
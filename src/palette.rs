@@ -0,0 +1,97 @@
+use std::{ffi::OsStr, fmt::Display};
+
+use crossterm::style::{self, Attribute, Color, ContentStyle, Stylize};
+
+/// Whether output gets colored, as chosen with `--color`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub enum ColorChoice {
+    /// Color output going to a terminal, unless `NO_COLOR` is set
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub const ALL: [ColorChoice; 3] = [ColorChoice::Auto, ColorChoice::Always, ColorChoice::Never];
+
+    /// Whether to color output going to a terminal when `is_terminal` is true, `no_color` being
+    /// the value of the `NO_COLOR` variable. Following <https://no-color.org>, any value but an
+    /// empty one turns colors off unless they are asked for explicitly
+    pub fn colors_enabled(self, is_terminal: bool, no_color: Option<&OsStr>) -> bool {
+        match self {
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+            ColorChoice::Auto => is_terminal && no_color.is_none_or(OsStr::is_empty),
+        }
+    }
+}
+
+impl Display for ColorChoice {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ColorChoice::Auto => "auto",
+            ColorChoice::Always => "always",
+            ColorChoice::Never => "never",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// The style of everything drawn on a board, by the role it plays, so drawing code never picks
+/// colors itself. The default palette styles nothing, drawing exactly what the plain rendering
+/// does
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Palette {
+    /// Cells filled in by the puzzle
+    pub given: ContentStyle,
+    /// Cells filled in by a solver or a player
+    pub solved: ContentStyle,
+    /// Cells that break the sudoku rules, and values taken back by a solver
+    pub conflict: ContentStyle,
+    /// The cell a player has selected
+    pub cursor: ContentStyle,
+    /// Cells singled out for attention, like the one a hint is about
+    pub highlight: ContentStyle,
+}
+
+impl Palette {
+    /// The palette styling nothing
+    pub fn plain() -> Self {
+        Self::default()
+    }
+
+    /// The palette drawing in color
+    pub fn colored() -> Self {
+        Self {
+            given: ContentStyle::new().bold(),
+            solved: ContentStyle::new().with(Color::Cyan),
+            conflict: ContentStyle::new()
+                .with(Color::Red)
+                .attribute(Attribute::Reverse),
+            cursor: ContentStyle::new().attribute(Attribute::Reverse),
+            highlight: ContentStyle::new().black().on(Color::Yellow),
+        }
+    }
+
+    /// The colored palette if `choice` and the `NO_COLOR` variable allow colors on output that
+    /// goes to a terminal when `is_terminal` is true, and the plain one otherwise. Crossterm
+    /// leaves colors out by itself while `NO_COLOR` is set, so [`ColorChoice::Always`] also tells
+    /// it to draw them anyway
+    pub fn for_output(choice: ColorChoice, is_terminal: bool) -> Self {
+        if choice == ColorChoice::Always {
+            style::force_color_output(true);
+        }
+        let no_color = std::env::var_os("NO_COLOR");
+        if choice.colors_enabled(is_terminal, no_color.as_deref()) {
+            Self::colored()
+        } else {
+            Self::plain()
+        }
+    }
+
+    /// Whether the palette styles nothing
+    pub fn is_plain(&self) -> bool {
+        *self == Self::plain()
+    }
+}
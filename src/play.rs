@@ -0,0 +1,88 @@
+use crate::{
+    board::{Board, GivenCellError},
+    technique::Hint,
+};
+
+/// A puzzle being solved by hand: the board as the player has filled it in, the selected cell
+/// and the entries made so far, so they can be taken back one at a time
+#[derive(Debug, Clone)]
+pub struct Game {
+    board: Board,
+    solution: Board,
+    cursor: (usize, usize),
+    /// Every entry as the cell it went into and the value the cell held before, oldest first
+    entries: Vec<((usize, usize), u8)>,
+}
+
+impl Game {
+    /// Starts a game on `puzzle` with the cursor on the top left cell. The solution is only
+    /// consulted to point out mistakes
+    pub fn new(puzzle: Board, solution: Board) -> Self {
+        Self {
+            board: puzzle,
+            solution,
+            cursor: (0, 0),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Row and column of the selected cell
+    pub fn cursor(&self) -> (usize, usize) {
+        self.cursor
+    }
+
+    /// Moves the selection by the given number of rows and columns, wrapping around the edges
+    pub fn move_cursor(&mut self, rows: isize, cols: isize) {
+        let (row, col) = self.cursor;
+        self.cursor = (
+            (row as isize + rows).rem_euclid(9) as usize,
+            (col as isize + cols).rem_euclid(9) as usize,
+        );
+    }
+
+    /// Writes `value` into the selected cell, 0 clearing it. Givens cannot be changed
+    pub fn enter(&mut self, value: u8) -> Result<(), GivenCellError> {
+        let (row, col) = self.cursor;
+        if self.board.is_given(row, col) {
+            return Err(GivenCellError {
+                position: (row, col),
+            });
+        }
+        let previous = self.board.get_cell(row, col);
+        if previous != value {
+            self.entries.push(((row, col), previous));
+            self.board.set_cell(row, col, value);
+        }
+        Ok(())
+    }
+
+    /// Takes back the latest entry and selects its cell. Returns false when there is nothing to
+    /// take back
+    pub fn undo(&mut self) -> bool {
+        let Some(((row, col), previous)) = self.entries.pop() else {
+            return false;
+        };
+        self.board.set_cell(row, col, previous);
+        self.cursor = (row, col);
+        true
+    }
+
+    /// Cells the player filled in with something other than the solution's value
+    pub fn mistakes(&self) -> Vec<(usize, usize)> {
+        self.board.differs_from_solution(&self.solution)
+    }
+
+    /// Whether every cell is filled in with the solution's value
+    pub fn is_solved(&self) -> bool {
+        self.board.matches_solution(&self.solution)
+    }
+
+    /// The next move a human could make from the board as it is, see [`Board::next_hint`]
+    pub fn hint(&self) -> Option<Hint> {
+        self.board.next_hint()
+    }
+}
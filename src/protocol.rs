@@ -0,0 +1,295 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    io::{self, BufRead, Write},
+};
+
+use crate::{
+    board::{Board, SolveError},
+    generator::{
+        generate_unique_puzzle, generate_with_difficulty, DIFFICULTY_ATTEMPTS, MINIMUM_CLUES,
+        UNIQUE_PUZZLE_CLUES,
+    },
+    grade::Difficulty,
+    json::JsonValue,
+    technique::{Hint, HintAction},
+};
+
+/// Every command a request can name in its `cmd` member
+pub const COMMANDS: [&str; 4] = ["solve", "generate", "hint", "grade"];
+
+/// Why a request could not be answered, sent back as the `error` of its response
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProtocolError {
+    /// The line is not JSON, or not a JSON object
+    InvalidJson(String),
+    /// A member is missing, or does not hold what the command expects
+    BadRequest(String),
+    UnknownCommand(String),
+    /// The givens of the puzzle break the sudoku rules
+    InvalidPuzzle(String),
+    Unsolvable(String),
+    /// None of the puzzles generated had the difficulty asked for
+    DifficultyNotReached(String),
+}
+
+impl ProtocolError {
+    /// Name of the variant, for programs to match on rather than the message
+    pub fn code(&self) -> &'static str {
+        match self {
+            ProtocolError::InvalidJson(_) => "invalid_json",
+            ProtocolError::BadRequest(_) => "bad_request",
+            ProtocolError::UnknownCommand(_) => "unknown_command",
+            ProtocolError::InvalidPuzzle(_) => "invalid_puzzle",
+            ProtocolError::Unsolvable(_) => "unsolvable",
+            ProtocolError::DifficultyNotReached(_) => "difficulty_not_reached",
+        }
+    }
+}
+
+impl Display for ProtocolError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProtocolError::UnknownCommand(command) => write!(
+                f,
+                "Unknown command \"{command}\", expected one of {}",
+                COMMANDS.join(", ")
+            ),
+            ProtocolError::InvalidJson(message)
+            | ProtocolError::BadRequest(message)
+            | ProtocolError::InvalidPuzzle(message)
+            | ProtocolError::Unsolvable(message)
+            | ProtocolError::DifficultyNotReached(message) => write!(f, "{message}"),
+        }
+    }
+}
+
+impl Error for ProtocolError {}
+
+impl From<SolveError> for ProtocolError {
+    fn from(error: SolveError) -> Self {
+        match error {
+            SolveError::InvalidGivens(_) => ProtocolError::InvalidPuzzle(error.to_string()),
+            error => ProtocolError::Unsolvable(error.to_string()),
+        }
+    }
+}
+
+/// Answers a request line with a response line. Every line gets an answer, a failed request
+/// getting `{"id":...,"ok":false,"error":{"code":...,"message":...}}` and any other
+/// `{"id":...,"ok":true,"result":{...}}`. The `id` of the request is echoed as it was written,
+/// and is null when the request has none or is not JSON
+pub fn respond(line: &str) -> String {
+    let request = line.parse::<JsonValue>();
+    let id = request
+        .as_ref()
+        .ok()
+        .and_then(|request| request.get("id"))
+        .cloned()
+        .unwrap_or(JsonValue::Null);
+    let result = request
+        .map_err(|error| ProtocolError::InvalidJson(error.to_string()))
+        .and_then(|request| handle(&request));
+    let response = match result {
+        Ok(result) => JsonValue::object([("id", id), ("ok", true.into()), ("result", result)]),
+        Err(error) => JsonValue::object([
+            ("id", id),
+            ("ok", false.into()),
+            (
+                "error",
+                JsonValue::object([
+                    ("code", error.code().into()),
+                    ("message", error.to_string().into()),
+                ]),
+            ),
+        ]),
+    };
+    response.to_string()
+}
+
+/// Answers every request line read from `input` with a response line on `output`, flushed right
+/// away so the program on the other end can wait for it. Blank lines are skipped. Stops when
+/// `input` ends or `output` can no longer be written to, failing only if `input` cannot be read
+pub fn serve(input: impl BufRead, mut output: impl Write) -> io::Result<()> {
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let written = writeln!(output, "{}", respond(&line)).and_then(|()| output.flush());
+        if written.is_err() {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// Carries out the request, returning the `result` of its response
+pub fn handle(request: &JsonValue) -> Result<JsonValue, ProtocolError> {
+    if !matches!(request, JsonValue::Object(_)) {
+        return Err(ProtocolError::InvalidJson(
+            "Expected a request object like {\"cmd\":\"solve\",\"puzzle\":\"...\"}".to_string(),
+        ));
+    }
+    match string_member(request, "cmd")? {
+        "solve" => solve(request),
+        "generate" => generate(request),
+        "hint" => hint(request),
+        "grade" => grade(request),
+        command => Err(ProtocolError::UnknownCommand(command.to_string())),
+    }
+}
+
+/// `{"cmd":"solve","puzzle":"..."}`, answered with the `solution` and whether it is `unique`
+fn solve(request: &JsonValue) -> Result<JsonValue, ProtocolError> {
+    let puzzle = puzzle_member(request)?;
+    let mut solution = puzzle.clone();
+    solution.solve_dlx()?;
+    Ok(JsonValue::object([
+        ("solution", solution.to_string().into()),
+        ("unique", (puzzle.count_solutions_dlx(2) == 1).into()),
+    ]))
+}
+
+/// `{"cmd":"generate"}` with either the `clues` to dig down to, [`UNIQUE_PUZZLE_CLUES`] unless
+/// given, or the `difficulty` to look for, and a `seed` to reproduce a puzzle with. Answered with the unique `puzzle`, its `solution`,
+/// the `seed` it came from, how many `clues` it kept and its grade
+fn generate(request: &JsonValue) -> Result<JsonValue, ProtocolError> {
+    let seed = number_member(request, "seed")?.unwrap_or_else(rand::random);
+    let difficulty = match request.get("difficulty") {
+        None | Some(JsonValue::Null) => None,
+        Some(name) => {
+            let found = Difficulty::ALL.into_iter().find(|difficulty| {
+                name.as_str()
+                    .is_some_and(|name| difficulty.to_string().eq_ignore_ascii_case(name))
+            });
+            let names: Vec<String> = Difficulty::ALL.iter().map(ToString::to_string).collect();
+            Some(found.ok_or_else(|| {
+                ProtocolError::BadRequest(format!(
+                    "\"difficulty\" must be one of {}",
+                    names.join(", ")
+                ))
+            })?)
+        }
+    };
+    let generated = match (number_member(request, "clues")?, difficulty) {
+        (Some(_), Some(_)) => {
+            return Err(ProtocolError::BadRequest(
+                "\"clues\" and \"difficulty\" cannot be asked for together".to_string(),
+            ))
+        }
+        (None, Some(difficulty)) => generate_with_difficulty(seed, difficulty, DIFFICULTY_ATTEMPTS)
+            .map_err(|error| ProtocolError::DifficultyNotReached(error.to_string()))?,
+        (clues, None) => {
+            let clues = clues.map_or(UNIQUE_PUZZLE_CLUES, |clues| clues as usize);
+            if !(MINIMUM_CLUES..=81).contains(&clues) {
+                return Err(ProtocolError::BadRequest(format!(
+                    "\"clues\" must be from {MINIMUM_CLUES} to 81"
+                )));
+            }
+            generate_unique_puzzle(seed, clues).graded()
+        }
+    };
+    Ok(JsonValue::object([
+        ("puzzle", generated.puzzle.to_string().into()),
+        ("solution", generated.solution.to_string().into()),
+        ("seed", seed.into()),
+        ("clues", generated.clue_count.into()),
+        (
+            "difficulty",
+            generated.difficulty.map(|grade| grade.to_string()).into(),
+        ),
+        ("hardest_technique", generated.hardest_technique.into()),
+    ]))
+}
+
+/// `{"cmd":"hint","puzzle":"..."}`, answered with the next move a human could make, null when no
+/// technique applies, and whether the board is `solved`
+fn hint(request: &JsonValue) -> Result<JsonValue, ProtocolError> {
+    let board = puzzle_member(request)?;
+    let hint = board.next_hint();
+    let solved = board.empty_cells().next().is_none();
+    Ok(JsonValue::object([
+        ("hint", hint.as_ref().map(hint_object).into()),
+        ("solved", solved.into()),
+    ]))
+}
+
+/// The hint as its `technique`, `explanation`, the value it would `place` if any and the
+/// candidates it would `eliminate`, cells counted from 1 like r4c7
+fn hint_object(hint: &Hint) -> JsonValue {
+    let cell = |(row, col): (usize, usize), value: u8| {
+        JsonValue::object([
+            ("row", (row + 1).into()),
+            ("col", (col + 1).into()),
+            ("value", (value as usize).into()),
+        ])
+    };
+    let (place, eliminate) = match &hint.action {
+        HintAction::Place(found) => (cell(found.position, found.value), Vec::new()),
+        HintAction::Eliminate(elimination) => (
+            JsonValue::Null,
+            elimination
+                .removed
+                .iter()
+                .map(|&(position, value)| cell(position, value))
+                .collect(),
+        ),
+    };
+    JsonValue::object([
+        ("technique", hint.technique.into()),
+        ("explanation", hint.explanation.clone().into()),
+        ("place", place),
+        ("eliminate", JsonValue::Array(eliminate)),
+    ])
+}
+
+/// `{"cmd":"grade","puzzle":"..."}`, answered with the puzzle's grade and how many times the
+/// logical solve used each technique
+fn grade(request: &JsonValue) -> Result<JsonValue, ProtocolError> {
+    let report = puzzle_member(request)?.grade();
+    let techniques = report
+        .technique_counts
+        .iter()
+        .map(|&(technique, count)| (technique.to_string(), count.into()))
+        .collect();
+    Ok(JsonValue::object([
+        ("difficulty", report.difficulty.to_string().into()),
+        ("hardest_technique", report.hardest_technique.into()),
+        ("requires_guessing", report.requires_guessing.into()),
+        ("techniques", JsonValue::Object(techniques)),
+    ]))
+}
+
+/// The string member of the request under `key`, which must be there
+fn string_member<'a>(request: &'a JsonValue, key: &str) -> Result<&'a str, ProtocolError> {
+    match request.get(key) {
+        Some(JsonValue::String(value)) => Ok(value),
+        None => Err(ProtocolError::BadRequest(format!("Missing \"{key}\""))),
+        Some(_) => Err(ProtocolError::BadRequest(format!(
+            "\"{key}\" must be a string"
+        ))),
+    }
+}
+
+/// The number member of the request under `key`, `None` if it is missing or null
+fn number_member(request: &JsonValue, key: &str) -> Result<Option<u64>, ProtocolError> {
+    match request.get(key) {
+        None | Some(JsonValue::Null) => Ok(None),
+        Some(value) => value.as_u64().map(Some).ok_or_else(|| {
+            ProtocolError::BadRequest(format!("\"{key}\" must be a non negative integer"))
+        }),
+    }
+}
+
+/// The board in the `puzzle` member of the request, which must keep to the sudoku rules
+fn puzzle_member(request: &JsonValue) -> Result<Board, ProtocolError> {
+    let board: Board = string_member(request, "puzzle")?.parse().map_err(|error| {
+        ProtocolError::BadRequest(format!("\"puzzle\" is not a board: {error}"))
+    })?;
+    let conflicts = board.conflicts();
+    if !conflicts.is_empty() {
+        return Err(SolveError::InvalidGivens(conflicts).into());
+    }
+    Ok(board)
+}
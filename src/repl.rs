@@ -0,0 +1,338 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    io::{self, BufRead, Write},
+    str::FromStr,
+};
+
+use crate::board::{cell_name, Board, DrawOptions};
+
+/// Solutions `count-solutions` stops counting at
+pub const SOLUTION_COUNT_LIMIT: usize = 1000;
+
+/// A command of a [`ReplSession`], parsed from a line like `set r4c7 5`. Cells are named by
+/// their 1 based row and column
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplCommand {
+    Show,
+    Set { cell: (usize, usize), value: u8 },
+    Clear { cell: (usize, usize) },
+    Candidates { cell: (usize, usize) },
+    Hint,
+    Solve,
+    Undo,
+    CountSolutions,
+    Save { path: String },
+    Help,
+    Quit,
+}
+
+impl ReplCommand {
+    /// Every command as it is typed, with what it does, as `help` lists them
+    pub const USAGE: [(&'static str, &'static str); 11] = [
+        ("show", "Draw the board"),
+        ("set r4c7 5", "Fill in a cell"),
+        ("clear r4c7", "Empty a cell"),
+        ("candidates r4c7", "List the values a cell can still take"),
+        ("hint", "Explain the next move a human could make"),
+        ("solve", "Fill in the rest of the board"),
+        ("undo", "Take back the last set, clear or solve"),
+        ("count-solutions", "Count the solutions the board has left"),
+        ("save out.txt", "Write the board to a file, on one line"),
+        ("help", "List the commands"),
+        ("quit", "Stop, as the end of the input does"),
+    ];
+}
+
+/// Why a line is not a command, or a command could not be carried out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplError {
+    UnknownCommand(String),
+    /// A known command was given the wrong arguments, holding how it is used
+    Usage(&'static str),
+    BadCell(String),
+    BadValue(String),
+    CellIsGiven((usize, usize)),
+    CellIsEmpty((usize, usize)),
+    NothingToUndo,
+    Unsolvable(String),
+    Save {
+        path: String,
+        reason: String,
+    },
+}
+
+impl Display for ReplError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplError::UnknownCommand(name) => {
+                write!(f, "Unknown command \"{name}\", help lists the commands")
+            }
+            ReplError::Usage(usage) => write!(f, "Usage: {usage}"),
+            ReplError::BadCell(token) => write!(f, "Expected a cell like r4c7, found \"{token}\""),
+            ReplError::BadValue(token) => {
+                write!(f, "Expected a value from 1 to 9, found \"{token}\"")
+            }
+            ReplError::CellIsGiven(cell) => write!(f, "{} is a given", cell_name(*cell)),
+            ReplError::CellIsEmpty(cell) => write!(f, "{} is already empty", cell_name(*cell)),
+            ReplError::NothingToUndo => write!(f, "Nothing to undo"),
+            ReplError::Unsolvable(reason) => write!(f, "Cannot solve the board: {reason}"),
+            ReplError::Save { path, reason } => write!(f, "Could not save to {path}: {reason}"),
+        }
+    }
+}
+
+impl Error for ReplError {}
+
+/// Parses a cell named like r4c7, in either case
+fn parse_cell(token: &str) -> Result<(usize, usize), ReplError> {
+    let bad_cell = || ReplError::BadCell(token.to_string());
+    let lowercase = token.to_ascii_lowercase();
+    let (row, col) = lowercase
+        .strip_prefix('r')
+        .and_then(|rest| rest.split_once('c'))
+        .ok_or_else(bad_cell)?;
+    match (row.parse::<usize>(), col.parse::<usize>()) {
+        (Ok(row @ 1..=9), Ok(col @ 1..=9)) => Ok((row - 1, col - 1)),
+        _ => Err(bad_cell()),
+    }
+}
+
+impl FromStr for ReplCommand {
+    type Err = ReplError;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let (name, args) = words.split_first().unwrap_or((&"", &[]));
+        let name = name.to_ascii_lowercase();
+        let command = match (name.as_str(), args) {
+            ("show", []) => ReplCommand::Show,
+            ("set", [cell, value]) => ReplCommand::Set {
+                cell: parse_cell(cell)?,
+                value: match value.parse() {
+                    Ok(value @ 1..=9) => value,
+                    _ => return Err(ReplError::BadValue(value.to_string())),
+                },
+            },
+            ("clear", [cell]) => ReplCommand::Clear {
+                cell: parse_cell(cell)?,
+            },
+            ("candidates", [cell]) => ReplCommand::Candidates {
+                cell: parse_cell(cell)?,
+            },
+            ("hint", []) => ReplCommand::Hint,
+            ("solve", []) => ReplCommand::Solve,
+            ("undo", []) => ReplCommand::Undo,
+            ("count-solutions", []) => ReplCommand::CountSolutions,
+            ("save", [path]) => ReplCommand::Save {
+                path: path.to_string(),
+            },
+            ("help", []) => ReplCommand::Help,
+            ("quit" | "exit", []) => ReplCommand::Quit,
+            (name, _) => {
+                let usage = Self::USAGE
+                    .iter()
+                    .find(|(usage, _)| usage.split(' ').next() == Some(name));
+                return Err(match usage {
+                    Some((usage, _)) => ReplError::Usage(usage),
+                    None => ReplError::UnknownCommand(name.to_string()),
+                });
+            }
+        };
+        Ok(command)
+    }
+}
+
+/// A board being poked at one command at a time, remembering how it was before every change so
+/// the changes can be undone one at a time
+#[derive(Debug, Clone)]
+pub struct ReplSession {
+    board: Board,
+    /// The board before each change not undone yet, oldest first
+    undo: Vec<Board>,
+    /// How `show` and `solve` draw the board
+    options: DrawOptions,
+}
+
+impl ReplSession {
+    pub fn new(board: Board, options: DrawOptions) -> Self {
+        Self {
+            board,
+            undo: Vec::new(),
+            options,
+        }
+    }
+
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Carries out the command, returning what to print. A command that fails leaves the board
+    /// as it was. [`ReplCommand::Quit`] is left to whoever reads the commands and does nothing
+    pub fn execute(&mut self, command: &ReplCommand) -> Result<String, ReplError> {
+        match command {
+            ReplCommand::Show => Ok(self.drawing()),
+            &ReplCommand::Set { cell, value } => {
+                let (row, col) = cell;
+                if self.board.is_given(row, col) {
+                    return Err(ReplError::CellIsGiven(cell));
+                }
+                if self.board.get_cell(row, col) == value {
+                    return Ok(format!("{} already holds {value}", cell_name(cell)));
+                }
+                self.undo.push(self.board.clone());
+                self.board.set_cell(row, col, value);
+                let clashes: Vec<String> = self
+                    .board
+                    .conflicts()
+                    .iter()
+                    .filter_map(|conflict| match cell {
+                        cell if cell == conflict.first => Some(conflict.second),
+                        cell if cell == conflict.second => Some(conflict.first),
+                        _ => None,
+                    })
+                    .map(cell_name)
+                    .collect();
+                if clashes.is_empty() {
+                    return Ok(format!("{} = {value}", cell_name(cell)));
+                }
+                Ok(format!(
+                    "{} = {value}, which clashes with {}",
+                    cell_name(cell),
+                    clashes.join(", ")
+                ))
+            }
+            &ReplCommand::Clear { cell } => {
+                let (row, col) = cell;
+                if self.board.get_cell(row, col) == 0 {
+                    return Err(ReplError::CellIsEmpty(cell));
+                }
+                let mut cleared = self.board.clone();
+                cleared
+                    .clear_cell(row, col)
+                    .map_err(|_| ReplError::CellIsGiven(cell))?;
+                self.undo.push(std::mem::replace(&mut self.board, cleared));
+                Ok(format!("{} cleared", cell_name(cell)))
+            }
+            &ReplCommand::Candidates { cell } => {
+                let (row, col) = cell;
+                Ok(match self.board.candidates_at(row, col) {
+                    None => format!(
+                        "{} holds {}",
+                        cell_name(cell),
+                        self.board.get_cell(row, col)
+                    ),
+                    Some(candidates) if candidates.is_empty() => {
+                        format!("{} has no candidates left", cell_name(cell))
+                    }
+                    Some(candidates) => {
+                        let values: Vec<String> =
+                            candidates.iter().map(|value| value.to_string()).collect();
+                        format!("{}: {}", cell_name(cell), values.join(" "))
+                    }
+                })
+            }
+            ReplCommand::Hint => Ok(match self.board.next_hint() {
+                Some(hint) => hint.to_string(),
+                None if self.board.empty_cells().next().is_none() => {
+                    "The board is filled in".to_string()
+                }
+                None => "No technique applies, it takes guessing from here".to_string(),
+            }),
+            ReplCommand::Solve => {
+                let mut solved = self.board.clone();
+                solved
+                    .solve_dlx()
+                    .map_err(|error| ReplError::Unsolvable(error.to_string()))?;
+                self.undo.push(std::mem::replace(&mut self.board, solved));
+                Ok(self.drawing())
+            }
+            ReplCommand::Undo => {
+                self.board = self.undo.pop().ok_or(ReplError::NothingToUndo)?;
+                Ok("Took back the last change".to_string())
+            }
+            ReplCommand::CountSolutions => {
+                Ok(match self.board.count_solutions(SOLUTION_COUNT_LIMIT) {
+                    0 => "No solution".to_string(),
+                    1 => "1 solution".to_string(),
+                    SOLUTION_COUNT_LIMIT => format!("At least {SOLUTION_COUNT_LIMIT} solutions"),
+                    count => format!("{count} solutions"),
+                })
+            }
+            ReplCommand::Save { path } => {
+                std::fs::write(path, format!("{}\n", self.board)).map_err(|error| {
+                    ReplError::Save {
+                        path: path.clone(),
+                        reason: error.to_string(),
+                    }
+                })?;
+                Ok(format!("Saved the board to {path}"))
+            }
+            ReplCommand::Help => {
+                let width = ReplCommand::USAGE
+                    .iter()
+                    .map(|(usage, _)| usage.len())
+                    .max()
+                    .unwrap_or_default();
+                let lines: Vec<String> = ReplCommand::USAGE
+                    .iter()
+                    .map(|(usage, description)| format!("{usage:<width$}  {description}"))
+                    .collect();
+                Ok(lines.join("\n"))
+            }
+            ReplCommand::Quit => Ok(String::new()),
+        }
+    }
+
+    /// Carries out the commands read from `input` until it ends or a quit, writing what each one
+    /// answers to `output` and why any that failed did to `errors`. Blank lines and lines
+    /// starting with `#` are skipped, and `prompt` shows a prompt before every line. Returns how
+    /// many commands failed
+    pub fn run(
+        &mut self,
+        input: impl BufRead,
+        mut output: impl Write,
+        mut errors: impl Write,
+        prompt: bool,
+    ) -> io::Result<usize> {
+        let mut failed = 0;
+        let mut lines = input.lines();
+        loop {
+            if prompt {
+                write!(output, "> ")?;
+                output.flush()?;
+            }
+            let Some(line) = lines.next().transpose()? else {
+                break;
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let answer = line
+                .parse::<ReplCommand>()
+                .and_then(|command| match command {
+                    ReplCommand::Quit => Ok(None),
+                    command => self.execute(&command).map(Some),
+                });
+            match answer {
+                Ok(Some(answer)) => writeln!(output, "{answer}")?,
+                Ok(None) => break,
+                Err(error) => {
+                    failed += 1;
+                    writeln!(errors, "{error}")?;
+                }
+            }
+        }
+        output.flush()?;
+        Ok(failed)
+    }
+
+    /// The board as drawn with the session's options, without the trailing newline
+    fn drawing(&self) -> String {
+        self.board
+            .render_board(&self.options)
+            .trim_end()
+            .to_string()
+    }
+}
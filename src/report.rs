@@ -0,0 +1,288 @@
+use std::{
+    fmt::Display,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+use crate::history::MoveHistory;
+
+/// What a successful solve did to get to the solution, used to judge how hard a puzzle was
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SolveReport {
+    /// The moves that make up the solution, oldest first
+    pub history: MoveHistory,
+    pub elapsed: Duration,
+    /// Every value written to the board, including the ones later taken back
+    pub placements: usize,
+    /// Values placed because their cell had a single option left
+    pub forced_singles: usize,
+    /// Values picked for a cell with several options, either fresh or while backtracking
+    pub guesses: usize,
+    /// Dead ends the solver had to backtrack out of
+    pub backtracks: u64,
+    /// Dead ends caught by propagation right after a placement, see [`PropagationLevel`]. The
+    /// rest of the backtracks were only found while looking for the next cell to guess on
+    pub propagation_failures: u64,
+    /// Most moves taken back while recovering from a single dead end
+    pub max_backtrack_depth: usize,
+    /// Times the whole board was scanned for the cell with the least entropy
+    pub entropy_scans: usize,
+    /// Times the solver wiped its guesses and started over, see [`RestartPolicy`]
+    pub restarts: usize,
+}
+
+impl Display for SolveReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Solved in {}ms: {} placements ({} forced, {} guesses), {} backtracks ({} caught by \
+             propagation, deepest {}), {} entropy scans, {} restarts",
+            self.elapsed.as_millis(),
+            self.placements,
+            self.forced_singles,
+            self.guesses,
+            self.backtracks,
+            self.propagation_failures,
+            self.max_backtrack_depth,
+            self.entropy_scans,
+            self.restarts
+        )
+    }
+}
+
+/// Flag shared between a running solve and whoever may want to stop it, possibly from another
+/// thread. Clones share the same flag
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<AtomicBool>);
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Asks every solve holding this token to stop at its next check
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Caps on how much work a solve may do before giving up, along with a way to stop it from
+/// outside. None are set by default
+#[derive(Debug, Clone, Default)]
+pub struct SolveLimits {
+    pub max_duration: Option<Duration>,
+    pub max_placements: Option<u64>,
+    pub max_backtracks: Option<u64>,
+    pub cancellation: Option<CancellationToken>,
+}
+
+impl SolveLimits {
+    /// Limits that only cap how long the solve may take
+    pub fn with_timeout(max_duration: Duration) -> Self {
+        Self {
+            max_duration: Some(max_duration),
+            ..Self::default()
+        }
+    }
+
+    /// Limits that only stop the solve once the token is cancelled
+    pub fn with_cancellation(token: CancellationToken) -> Self {
+        Self {
+            cancellation: Some(token),
+            ..Self::default()
+        }
+    }
+
+    /// Whether the solve has been cancelled through its token
+    pub fn is_cancelled(&self) -> bool {
+        self.cancellation
+            .as_ref()
+            .is_some_and(CancellationToken::is_cancelled)
+    }
+
+    /// The first limit a solve that has been running for `elapsed` has gone past, if any.
+    /// Cancellation comes first
+    pub fn exceeded(&self, report: &SolveReport, elapsed: Duration) -> Option<Limit> {
+        if self.is_cancelled() {
+            Some(Limit::Cancelled)
+        } else if self.max_duration.is_some_and(|max| elapsed > max) {
+            Some(Limit::Duration)
+        } else if self
+            .max_placements
+            .is_some_and(|max| report.placements as u64 > max)
+        {
+            Some(Limit::Placements)
+        } else if self
+            .max_backtracks
+            .is_some_and(|max| report.backtracks > max)
+        {
+            Some(Limit::Backtracks)
+        } else {
+            None
+        }
+    }
+}
+
+/// When the entropy solver gives up on its current line of guesses and starts over from the
+/// puzzle, so one bad early guess cannot keep it backtracking for ages
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RestartPolicy {
+    /// Backtracks since the last restart past which the solver restarts
+    pub backtrack_threshold: u64,
+    /// Restarts allowed before the board is handed over to the exhaustive solver instead
+    pub max_restarts: usize,
+}
+
+impl RestartPolicy {
+    /// A policy that never restarts
+    pub const NEVER: Self = Self {
+        backtrack_threshold: u64::MAX,
+        max_restarts: 0,
+    };
+}
+
+impl Default for RestartPolicy {
+    fn default() -> Self {
+        Self {
+            backtrack_threshold: 500,
+            max_restarts: 10,
+        }
+    }
+}
+
+/// How the entropy solver picks between the cells tied for the fewest candidates
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CellSelection {
+    /// The first of them, left to right, top to bottom
+    #[default]
+    First,
+    /// Any of them, drawn from the solve's generator
+    Random,
+    /// The one with the most empty peers, since filling it in narrows down the most other cells.
+    /// Cells tied on that too are drawn from the solve's generator
+    Degree,
+}
+
+impl CellSelection {
+    pub const ALL: [CellSelection; 3] = [
+        CellSelection::First,
+        CellSelection::Random,
+        CellSelection::Degree,
+    ];
+}
+
+impl Display for CellSelection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            CellSelection::First => "first",
+            CellSelection::Random => "random",
+            CellSelection::Degree => "degree",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Order in which the entropy solver tries the values of a cell it has to guess on
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ValueOrdering {
+    /// The value leaving the fewest options at the next cell first, then the others in an order
+    /// drawn from the solve's generator
+    #[default]
+    FewestOptions,
+    /// The values ruling out the fewest candidates of the cell's peers first
+    LeastConstraining,
+    /// Every value in an order drawn from the solve's generator, trading the guidance for
+    /// variety in the solutions found, as when filling in an empty board
+    Random,
+}
+
+impl ValueOrdering {
+    pub const ALL: [ValueOrdering; 3] = [
+        ValueOrdering::FewestOptions,
+        ValueOrdering::LeastConstraining,
+        ValueOrdering::Random,
+    ];
+}
+
+impl Display for ValueOrdering {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            ValueOrdering::FewestOptions => "fewest",
+            ValueOrdering::LeastConstraining => "lcv",
+            ValueOrdering::Random => "random",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// How much the entropy solver deduces after every placement before making its next guess
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PropagationLevel {
+    /// Nothing, every cell is guessed on, and a dead end only shows up once the cell left
+    /// without options is picked
+    None,
+    /// Fill in the cells left with a single option, stopping as soon as any cell has none. The
+    /// options of every cell are cached, so this costs nothing beyond the placements
+    #[default]
+    ForwardCheck,
+    /// Also place every digit left with a single place in one of its units, stopping as soon as
+    /// a digit has no place left in a unit
+    Ac3,
+}
+
+impl PropagationLevel {
+    pub const ALL: [PropagationLevel; 3] = [
+        PropagationLevel::None,
+        PropagationLevel::ForwardCheck,
+        PropagationLevel::Ac3,
+    ];
+}
+
+impl Display for PropagationLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            PropagationLevel::None => "none",
+            PropagationLevel::ForwardCheck => "forward",
+            PropagationLevel::Ac3 => "ac3",
+        };
+        write!(f, "{name}")
+    }
+}
+
+/// Choices the entropy solver makes along the way, see [`crate::Board::solve_board_with_options`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolveOptions {
+    pub restarts: RestartPolicy,
+    pub cell_selection: CellSelection,
+    pub value_ordering: ValueOrdering,
+    pub propagation: PropagationLevel,
+}
+
+/// Which of the [`SolveLimits`] a solve ran into
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Limit {
+    Duration,
+    Placements,
+    Backtracks,
+    /// The token was cancelled, which ends the solve with [`crate::SolveError::Cancelled`]
+    Cancelled,
+}
+
+impl Display for Limit {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Limit::Duration => "time",
+            Limit::Placements => "placement",
+            Limit::Backtracks => "backtrack",
+            Limit::Cancelled => "cancellation",
+        };
+        write!(f, "{name}")
+    }
+}
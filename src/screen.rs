@@ -0,0 +1,110 @@
+use std::{
+    io::{self, stdout, Stdout, Write},
+    sync::atomic::{AtomicBool, Ordering},
+};
+
+use crossterm::{
+    cursor,
+    terminal::{self, EnterAlternateScreen, LeaveAlternateScreen},
+    QueueableCommand,
+};
+
+/// Whether a [`TerminalGuard`] on stdout currently holds the terminal, so the panic hook knows
+/// there is something to restore
+static TERMINAL_TAKEN: AtomicBool = AtomicBool::new(false);
+
+/// Switches raw mode on or off, see [`TerminalGuard::with_raw_mode`]
+pub type RawModeSwitch = fn(bool) -> io::Result<()>;
+
+/// Switches the real terminal's raw mode
+fn crossterm_raw_mode(enabled: bool) -> io::Result<()> {
+    if enabled {
+        terminal::enable_raw_mode()
+    } else {
+        terminal::disable_raw_mode()
+    }
+}
+
+/// Holds the terminal in raw mode on the alternate screen for as long as it lives. Dropping it
+/// leaves the alternate screen and then raw mode, so the shell gets its terminal back even when
+/// a panic or an early return unwinds past the code drawing on it. Everything written to the
+/// guard goes to the writer it took over
+pub struct TerminalGuard<W: Write> {
+    out: W,
+    set_raw_mode: RawModeSwitch,
+    /// Whether this guard holds stdout, and so has to tell the panic hook it let go
+    owns_stdout: bool,
+}
+
+impl TerminalGuard<Stdout> {
+    /// Takes over the terminal through stdout
+    pub fn new() -> io::Result<Self> {
+        let mut guard = Self::with_raw_mode(stdout(), crossterm_raw_mode)?;
+        guard.owns_stdout = true;
+        TERMINAL_TAKEN.store(true, Ordering::SeqCst);
+        Ok(guard)
+    }
+}
+
+impl<W: Write> TerminalGuard<W> {
+    /// Takes over the terminal `out` writes to, switching raw mode with `set_raw_mode`. Raw
+    /// mode is enabled before the alternate screen is entered, and disabled after it is left.
+    /// If entering the alternate screen fails, raw mode is disabled again before returning
+    pub fn with_raw_mode(out: W, set_raw_mode: RawModeSwitch) -> io::Result<Self> {
+        set_raw_mode(true)?;
+        let mut guard = Self {
+            out,
+            set_raw_mode,
+            owns_stdout: false,
+        };
+        guard
+            .out
+            .queue(EnterAlternateScreen)?
+            .queue(cursor::Hide)?
+            .flush()?;
+        Ok(guard)
+    }
+}
+
+impl<W: Write> Write for TerminalGuard<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.out.write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.out.flush()
+    }
+}
+
+impl<W: Write> Drop for TerminalGuard<W> {
+    fn drop(&mut self) {
+        // Nothing is left to report a failure to, so restoring carries on whatever fails
+        let _ = self
+            .out
+            .queue(cursor::Show)
+            .and_then(|out| out.queue(LeaveAlternateScreen))
+            .and_then(|out| out.flush());
+        let _ = (self.set_raw_mode)(false);
+        if self.owns_stdout {
+            TERMINAL_TAKEN.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Restores the terminal held by a [`TerminalGuard`] on stdout, if any, before the panic message
+/// is printed, so the message is readable and survives leaving the alternate screen. The guard
+/// itself only gets dropped after the message, while unwinding
+pub fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if TERMINAL_TAKEN.swap(false, Ordering::SeqCst) {
+            let mut out = stdout();
+            let _ = out
+                .queue(cursor::Show)
+                .and_then(|out| out.queue(LeaveAlternateScreen))
+                .and_then(|out| out.flush());
+            let _ = terminal::disable_raw_mode();
+        }
+        previous(info);
+    }));
+}
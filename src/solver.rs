@@ -0,0 +1,173 @@
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+
+use crate::{
+    board::{Board, SolveError},
+    observer::SolveObserver,
+    report::{
+        CellSelection, PropagationLevel, RestartPolicy, SolveLimits, SolveOptions, SolveReport,
+        ValueOrdering,
+    },
+};
+
+/// A way of solving a board, so callers can pick one at runtime or run several side by side
+pub trait SudokuSolver {
+    /// Fills in the board, reporting progress to the observer as far as the solver supports it
+    fn solve(
+        &mut self,
+        board: &mut Board,
+        observer: &mut dyn SolveObserver,
+    ) -> Result<SolveReport, SolveError> {
+        self.solve_with_limits(board, observer, &SolveLimits::default())
+    }
+
+    /// Same as [`SudokuSolver::solve`], giving up with [`SolveError::LimitReached`] once the
+    /// solve goes past any of the limits
+    fn solve_with_limits(
+        &mut self,
+        board: &mut Board,
+        observer: &mut dyn SolveObserver,
+        limits: &SolveLimits,
+    ) -> Result<SolveReport, SolveError>;
+
+    /// Short name the solver is selected by, e.g. on the command line
+    fn name(&self) -> &str;
+}
+
+/// The entropy based solver, see [`Board::solve_board`]. Backtracking choices come from a
+/// generator seeded at creation, so every solve with the same seed picks the same way
+pub struct WaveFunctionCollapse {
+    rng: ChaCha8Rng,
+    options: SolveOptions,
+}
+
+impl WaveFunctionCollapse {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            rng: ChaCha8Rng::seed_from_u64(seed),
+            options: SolveOptions::default(),
+        }
+    }
+
+    /// Restarts as `policy` says, see [`Board::solve_board_with_options`]
+    pub fn with_restart_policy(mut self, policy: RestartPolicy) -> Self {
+        self.options.restarts = policy;
+        self
+    }
+
+    /// Picks between cells tied for the fewest candidates as `selection` says
+    pub fn with_cell_selection(mut self, selection: CellSelection) -> Self {
+        self.options.cell_selection = selection;
+        self
+    }
+
+    /// Tries the values of a guessed cell in the order `ordering` says
+    pub fn with_value_ordering(mut self, ordering: ValueOrdering) -> Self {
+        self.options.value_ordering = ordering;
+        self
+    }
+
+    /// Deduces as much as `level` says after every placement
+    pub fn with_propagation(mut self, level: PropagationLevel) -> Self {
+        self.options.propagation = level;
+        self
+    }
+}
+
+impl SudokuSolver for WaveFunctionCollapse {
+    fn solve_with_limits(
+        &mut self,
+        board: &mut Board,
+        observer: &mut dyn SolveObserver,
+        limits: &SolveLimits,
+    ) -> Result<SolveReport, SolveError> {
+        board.solve_board_with_options(&mut self.rng, observer, limits, self.options)
+    }
+
+    fn name(&self) -> &str {
+        "wfc"
+    }
+}
+
+/// The exhaustive depth first solver, see [`Board::solve_dfs`]
+pub struct DepthFirst;
+
+impl SudokuSolver for DepthFirst {
+    fn solve_with_limits(
+        &mut self,
+        board: &mut Board,
+        observer: &mut dyn SolveObserver,
+        limits: &SolveLimits,
+    ) -> Result<SolveReport, SolveError> {
+        board.solve_dfs_with_limits(observer, limits)
+    }
+
+    fn name(&self) -> &str {
+        "dfs"
+    }
+}
+
+/// The dancing links solver, see [`Board::solve_dlx`]. The search runs on its own matrix rather
+/// than the board, so the observer only hears about the placements of the solution, once found.
+/// Limits are ignored, since the search has no partial board to give back and even the hardest
+/// puzzles take it well under a second
+pub struct DancingLinksSolver;
+
+impl SudokuSolver for DancingLinksSolver {
+    fn solve_with_limits(
+        &mut self,
+        board: &mut Board,
+        observer: &mut dyn SolveObserver,
+        _limits: &SolveLimits,
+    ) -> Result<SolveReport, SolveError> {
+        let report = board.solve_dlx()?;
+        for board_move in report.history.iter() {
+            let [row, col] = board_move.position();
+            observer.on_place(board, (row, col), board_move.new_value());
+        }
+        Ok(report)
+    }
+
+    fn name(&self) -> &str {
+        "dlx"
+    }
+}
+
+/// One of every solver, with the entropy based one seeded by `seed`
+pub fn registered_solvers(seed: u64) -> Vec<Box<dyn SudokuSolver>> {
+    vec![
+        Box::new(WaveFunctionCollapse::new(seed)),
+        Box::new(DepthFirst),
+        Box::new(DancingLinksSolver),
+    ]
+}
+
+/// The registered solver called `name`, see [`registered_solvers`]
+pub fn solver_named(name: &str, seed: u64) -> Option<Box<dyn SudokuSolver>> {
+    registered_solvers(seed)
+        .into_iter()
+        .find(|solver| solver.name() == name)
+}
+
+/// Every registered solver followed by the entropy based one with each of its cell selections,
+/// value orderings and propagation levels, named like `wfc/ac3`, all seeded by `seed`. These are
+/// the solvers a benchmark compares
+pub fn comparison_solvers(seed: u64) -> Vec<(String, Box<dyn SudokuSolver>)> {
+    let mut solvers: Vec<(String, Box<dyn SudokuSolver>)> = registered_solvers(seed)
+        .into_iter()
+        .map(|solver| (solver.name().to_string(), solver))
+        .collect();
+    for selection in CellSelection::ALL {
+        let solver = WaveFunctionCollapse::new(seed).with_cell_selection(selection);
+        solvers.push((format!("wfc/{selection}"), Box::new(solver)));
+    }
+    for ordering in ValueOrdering::ALL {
+        let solver = WaveFunctionCollapse::new(seed).with_value_ordering(ordering);
+        solvers.push((format!("wfc/{ordering}"), Box::new(solver)));
+    }
+    for level in PropagationLevel::ALL {
+        let solver = WaveFunctionCollapse::new(seed).with_propagation(level);
+        solvers.push((format!("wfc/{level}"), Box::new(solver)));
+    }
+    solvers
+}
@@ -0,0 +1,378 @@
+use std::{
+    error::Error,
+    fmt::Display,
+    io::ErrorKind,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use crate::{daily::Date, grade::Difficulty};
+
+/// First line of a stats file, telling it apart from any other file at its path
+pub const STATS_HEADER: &str = "# sudoku_solver stats";
+
+/// What has been kept of the games played at one difficulty
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct DifficultyStats {
+    pub played: usize,
+    pub solved: usize,
+    /// Fastest solve, `None` until a game is solved
+    pub best: Option<Duration>,
+    /// Time of every solve added up, for the average
+    pub total: Duration,
+}
+
+impl DifficultyStats {
+    /// Mean time of the solved games, `None` until a game is solved
+    pub fn average(&self) -> Option<Duration> {
+        (self.solved > 0).then(|| self.total / self.solved as u32)
+    }
+}
+
+/// A game once it has ended, as it is recorded in the stats
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameRecord {
+    pub difficulty: Difficulty,
+    /// Whether the board was filled in rather than given up on
+    pub solved: bool,
+    pub elapsed: Duration,
+    /// The day of the puzzle, for puzzles of the day
+    pub daily: Option<Date>,
+}
+
+/// Solving statistics kept across runs: games played and solved with their best and average
+/// times for each difficulty, and the streak of puzzles of the day solved on consecutive days.
+/// Written one line per difficulty played and a line for the streak, see [`Stats::load`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Stats {
+    /// Indexed like [`Difficulty::ALL`]
+    difficulties: [DifficultyStats; 6],
+    /// Day of the latest puzzle of the day solved
+    last_daily: Option<Date>,
+    /// Puzzles of the day solved on consecutive days, ending with `last_daily`
+    daily_streak: usize,
+    best_streak: usize,
+}
+
+impl Stats {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn difficulty(&self, difficulty: Difficulty) -> &DifficultyStats {
+        &self.difficulties[difficulty_index(difficulty)]
+    }
+
+    pub fn played(&self) -> usize {
+        self.difficulties.iter().map(|stats| stats.played).sum()
+    }
+
+    pub fn solved(&self) -> usize {
+        self.difficulties.iter().map(|stats| stats.solved).sum()
+    }
+
+    pub fn last_daily(&self) -> Option<Date> {
+        self.last_daily
+    }
+
+    /// The daily streak as of `today`, broken once a whole day goes by without solving the
+    /// puzzle of the day
+    pub fn current_streak(&self, today: Date) -> usize {
+        match self.last_daily {
+            Some(last) if today.days_since_epoch() - last.days_since_epoch() <= 1 => {
+                self.daily_streak
+            }
+            _ => 0,
+        }
+    }
+
+    pub fn best_streak(&self) -> usize {
+        self.best_streak
+    }
+
+    /// Adds the game to the stats. Solving the puzzle of the day after the previous day's
+    /// extends the streak, solving it again leaves it be, and solving any later day's starts a
+    /// new streak. Puzzles of days before the latest one solved count as ordinary games
+    pub fn record(&mut self, game: &GameRecord) {
+        let stats = &mut self.difficulties[difficulty_index(game.difficulty)];
+        stats.played += 1;
+        if !game.solved {
+            return;
+        }
+        stats.solved += 1;
+        stats.total += game.elapsed;
+        stats.best = Some(
+            stats
+                .best
+                .map_or(game.elapsed, |best| best.min(game.elapsed)),
+        );
+        let Some(day) = game.daily else {
+            return;
+        };
+        match self.last_daily {
+            Some(last) if last >= day => return,
+            Some(last) if day.days_since_epoch() - last.days_since_epoch() == 1 => {
+                self.daily_streak += 1;
+            }
+            _ => self.daily_streak = 1,
+        }
+        self.last_daily = Some(day);
+        self.best_streak = self.best_streak.max(self.daily_streak);
+    }
+
+    /// Reads the stats file at `path`. Stats never stop a game from being played, so a missing
+    /// file gives empty stats, and one that cannot be read or parsed gives empty stats along with
+    /// a warning saying why
+    pub fn load(path: &Path) -> (Stats, Option<String>) {
+        match std::fs::read_to_string(path) {
+            Ok(text) => match text.parse() {
+                Ok(stats) => (stats, None),
+                Err(error) => (
+                    Stats::new(),
+                    Some(format!(
+                        "{} is not a stats file ({error}), starting afresh",
+                        path.display()
+                    )),
+                ),
+            },
+            Err(error) if error.kind() == ErrorKind::NotFound => (Stats::new(), None),
+            Err(error) => (
+                Stats::new(),
+                Some(format!(
+                    "Could not read {} ({error}), starting afresh",
+                    path.display()
+                )),
+            ),
+        }
+    }
+
+    /// Writes the stats to `path`, creating its directory if needed. The stats go to a file
+    /// next to it first and are renamed over it, so an interrupted write leaves the old stats
+    pub fn save(&self, path: &Path) -> std::io::Result<()> {
+        if let Some(directory) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        {
+            std::fs::create_dir_all(directory)?;
+        }
+        let mut temporary = path.as_os_str().to_owned();
+        temporary.push(".tmp");
+        std::fs::write(&temporary, self.to_string())?;
+        std::fs::rename(&temporary, path)
+    }
+
+    /// Loads the stats at `path`, adds the game and saves them back, returning the updated stats
+    /// and the warning of [`Stats::load`], if any
+    pub fn record_in_file(
+        path: &Path,
+        game: &GameRecord,
+    ) -> std::io::Result<(Stats, Option<String>)> {
+        let (mut stats, warning) = Stats::load(path);
+        stats.record(game);
+        stats.save(path)?;
+        Ok((stats, warning))
+    }
+
+    /// The stats as a table of the difficulties played, with the daily streak as of `today`
+    /// below it
+    pub fn table(&self, today: Date) -> String {
+        let time = |duration: Option<Duration>| duration.map_or("-".to_string(), clock_time);
+        let mut table = format!(
+            "{:<10}{:>8}{:>8}{:>10}{:>10}\n",
+            "grade", "played", "solved", "best", "average"
+        );
+        for difficulty in Difficulty::ALL {
+            let stats = self.difficulty(difficulty);
+            if stats.played > 0 {
+                table.push_str(&format!(
+                    "{:<10}{:>8}{:>8}{:>10}{:>10}\n",
+                    difficulty.to_string(),
+                    stats.played,
+                    stats.solved,
+                    time(stats.best),
+                    time(stats.average())
+                ));
+            }
+        }
+        table.push_str(&format!(
+            "{:<10}{:>8}{:>8}\n",
+            "total",
+            self.played(),
+            self.solved()
+        ));
+        table.push_str(&format!(
+            "daily streak {} day(s), best {}",
+            self.current_streak(today),
+            self.best_streak
+        ));
+        if let Some(last) = self.last_daily {
+            table.push_str(&format!(", last solved {last}"));
+        }
+        table
+    }
+}
+
+fn difficulty_index(difficulty: Difficulty) -> usize {
+    Difficulty::ALL
+        .iter()
+        .position(|&other| other == difficulty)
+        .expect("every difficulty is in ALL")
+}
+
+/// The duration as minutes and seconds, like 12:05
+pub fn clock_time(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    format!("{}:{:02}", seconds / 60, seconds % 60)
+}
+
+/// The stats file: the header, then `DIFFICULTY played N solved N best_ms N total_ms N` for each
+/// difficulty played and `daily last YYYY-MM-DD streak N best N` once a puzzle of the day is
+/// solved. `best_ms` is left out until a game is solved
+impl Display for Stats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{STATS_HEADER}")?;
+        for difficulty in Difficulty::ALL {
+            let stats = self.difficulty(difficulty);
+            if stats.played == 0 {
+                continue;
+            }
+            write!(
+                f,
+                "{difficulty} played {} solved {}",
+                stats.played, stats.solved
+            )?;
+            if let Some(best) = stats.best {
+                write!(f, " best_ms {}", best.as_millis())?;
+            }
+            writeln!(f, " total_ms {}", stats.total.as_millis())?;
+        }
+        if let Some(last) = self.last_daily {
+            writeln!(
+                f,
+                "daily last {last} streak {} best {}",
+                self.daily_streak, self.best_streak
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Returned when text is not a stats file
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseStatsError {
+    /// The text does not start with [`STATS_HEADER`]
+    MissingHeader,
+    /// A line that is not a difficulty or the daily streak, numbered from 1
+    BadLine { line: usize, reason: String },
+}
+
+impl Display for ParseStatsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseStatsError::MissingHeader => {
+                write!(f, "expected the first line to be \"{STATS_HEADER}\"")
+            }
+            ParseStatsError::BadLine { line, reason } => write!(f, "line {line}: {reason}"),
+        }
+    }
+}
+
+impl Error for ParseStatsError {}
+
+impl FromStr for Stats {
+    type Err = ParseStatsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut lines = s.lines();
+        if lines.next().map(str::trim) != Some(STATS_HEADER) {
+            return Err(ParseStatsError::MissingHeader);
+        }
+        let mut stats = Stats::new();
+        for (i, line) in lines.enumerate() {
+            let bad_line = |reason: String| ParseStatsError::BadLine {
+                line: i + 2,
+                reason,
+            };
+            let words: Vec<&str> = line.split_whitespace().collect();
+            let Some((&name, fields)) = words.split_first() else {
+                continue;
+            };
+            if name.starts_with('#') {
+                continue;
+            }
+            let fields = Fields::new(fields).map_err(bad_line)?;
+            if name == "daily" {
+                stats.last_daily = Some(fields.get("last").map_err(bad_line)?);
+                stats.daily_streak = fields.get("streak").map_err(bad_line)?;
+                stats.best_streak = fields.get("best").map_err(bad_line)?;
+                continue;
+            }
+            let difficulty = Difficulty::ALL
+                .into_iter()
+                .find(|difficulty| difficulty.to_string() == name)
+                .ok_or_else(|| bad_line(format!("unknown grade \"{name}\"")))?;
+            let played = fields.get("played").map_err(bad_line)?;
+            let solved = fields.get("solved").map_err(bad_line)?;
+            if solved > played {
+                return Err(bad_line("more games solved than played".to_string()));
+            }
+            let best = match fields.get_optional("best_ms").map_err(bad_line)? {
+                Some(_) if solved == 0 => {
+                    return Err(bad_line("a best time without a solved game".to_string()))
+                }
+                best => best.map(Duration::from_millis),
+            };
+            stats.difficulties[difficulty_index(difficulty)] = DifficultyStats {
+                played,
+                solved,
+                best,
+                total: Duration::from_millis(fields.get("total_ms").map_err(bad_line)?),
+            };
+        }
+        Ok(stats)
+    }
+}
+
+/// The `key value` pairs following the name of a line
+struct Fields<'a>(Vec<(&'a str, &'a str)>);
+
+impl<'a> Fields<'a> {
+    fn new(words: &[&'a str]) -> Result<Self, String> {
+        if !words.len().is_multiple_of(2) {
+            return Err("expected pairs of a key and a value".to_string());
+        }
+        Ok(Fields(
+            words.chunks(2).map(|pair| (pair[0], pair[1])).collect(),
+        ))
+    }
+
+    fn get<T: FromStr>(&self, key: &str) -> Result<T, String> {
+        self.get_optional(key)?
+            .ok_or_else(|| format!("missing \"{key}\""))
+    }
+
+    fn get_optional<T: FromStr>(&self, key: &str) -> Result<Option<T>, String> {
+        self.0
+            .iter()
+            .find(|(name, _)| *name == key)
+            .map(|(_, value)| {
+                value
+                    .parse()
+                    .map_err(|_| format!("\"{value}\" is not a valid {key}"))
+            })
+            .transpose()
+    }
+}
+
+/// Where the stats are kept unless `--stats-file` says otherwise: `sudoku_solver/stats.txt` in
+/// the user's data directory, `$XDG_DATA_HOME`, `~/.local/share` or `%APPDATA%`. `None` when
+/// none of them is set
+pub fn default_stats_path() -> Option<PathBuf> {
+    let non_empty = |name: &str| std::env::var_os(name).filter(|value| !value.is_empty());
+    let data_directory = non_empty("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .or_else(|| non_empty("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .or_else(|| non_empty("APPDATA").map(PathBuf::from))?;
+    Some(data_directory.join("sudoku_solver").join("stats.txt"))
+}
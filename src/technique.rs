@@ -0,0 +1,970 @@
+use std::fmt::Display;
+
+use crate::{
+    board::{cell_name, Board, UnitKind},
+    candidates::CandidateSet,
+};
+
+/// A placement found by a technique, along with the unit that forced it and why
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TechniqueMove {
+    /// Name of the technique that found the placement
+    pub technique: &'static str,
+    pub position: (usize, usize),
+    pub value: u8,
+    /// Unit the value was the only place for, and its index, tiles being indexed left to right,
+    /// top to bottom. `None` for naked singles, which are forced by the cell alone
+    pub unit: Option<(UnitKind, usize)>,
+    /// Human readable justification, e.g. "7 can only go in r2c5 within column 5"
+    pub reason: String,
+    /// Eliminations made since the previous placement, which this one relies on, along with the
+    /// names of the techniques that made them
+    pub eliminations: Vec<(&'static str, Elimination)>,
+}
+
+impl Display for TechniqueMove {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+/// Finds every naked single in the grid: an empty cell with a single candidate left
+pub fn find_naked_singles(grid: &CandidatesGrid) -> Vec<TechniqueMove> {
+    grid.open_cells()
+        .filter(|(_, candidates)| candidates.len() == 1)
+        .map(|((row, col), candidates)| {
+            let value = candidates.iter().next().unwrap();
+            TechniqueMove {
+                technique: "Naked single",
+                position: (row, col),
+                value,
+                unit: None,
+                reason: format!("{} can only be {value}", cell_name((row, col))),
+                eliminations: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// Finds every hidden single on the board: a digit that fits in only one cell of a row, column
+/// or tile, even though that cell has other candidates. A placement forced by several units is
+/// reported once, for the first unit found in row, column, tile order
+pub fn find_hidden_singles(board: &Board) -> Vec<TechniqueMove> {
+    find_hidden_singles_in(&CandidatesGrid::from_board(board))
+}
+
+/// [`find_hidden_singles`] over a grid that may have had candidates eliminated
+pub fn find_hidden_singles_in(grid: &CandidatesGrid) -> Vec<TechniqueMove> {
+    let mut moves: Vec<TechniqueMove> = Vec::new();
+    for unit in UnitKind::ALL {
+        for unit_index in 0..9 {
+            let positions = unit.positions(unit_index);
+            for value in 1..=9 {
+                let mut cells = positions.iter().filter(|(row, col)| {
+                    grid.get(*row, *col)
+                        .is_some_and(|candidates| candidates.contains(value))
+                });
+                let (Some(&(row, col)), None) = (cells.next(), cells.next()) else {
+                    continue;
+                };
+                let is_naked = grid
+                    .get(row, col)
+                    .is_some_and(|candidates| candidates.len() == 1);
+                let known = moves
+                    .iter()
+                    .any(|found| found.position == (row, col) && found.value == value);
+                if is_naked || known {
+                    continue;
+                }
+                moves.push(TechniqueMove {
+                    technique: "Hidden single",
+                    position: (row, col),
+                    value,
+                    unit: Some((unit, unit_index)),
+                    reason: format!(
+                        "{value} can only go in {} within {unit} {}",
+                        cell_name((row, col)),
+                        unit_index + 1
+                    ),
+                    eliminations: Vec::new(),
+                });
+            }
+        }
+    }
+    moves
+}
+
+/// Pencil marks for every cell, `None` marking cells that are filled in. Starts out as the
+/// candidates the board allows, and is narrowed down by eliminations that the board itself can't
+/// express
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CandidatesGrid {
+    cells: [[Option<CandidateSet>; 9]; 9],
+}
+
+impl CandidatesGrid {
+    /// Creates the grid from the candidates each empty cell of the board allows
+    pub fn from_board(board: &Board) -> Self {
+        Self {
+            cells: board.candidates_grid(),
+        }
+    }
+
+    /// Candidates left at a cell, `None` if the cell is filled in
+    pub fn get(&self, row: usize, col: usize) -> Option<CandidateSet> {
+        self.cells[row][col]
+    }
+
+    /// Iterates over every empty cell along with its candidates, left to right, top to bottom
+    pub fn open_cells(&self) -> impl Iterator<Item = ((usize, usize), CandidateSet)> + '_ {
+        (0..81).filter_map(|i| Some(((i / 9, i % 9), self.cells[i / 9][i % 9]?)))
+    }
+
+    /// Fills in a cell, removing the value from the candidates of its peers
+    pub fn place(&mut self, row: usize, col: usize, value: u8) {
+        self.cells[row][col] = None;
+        for (peer_row, peer_col) in Board::peers(row, col) {
+            if let Some(candidates) = &mut self.cells[peer_row][peer_col] {
+                candidates.remove(value);
+            }
+        }
+    }
+
+    /// Removes every candidate the elimination names, returning how many were still present
+    pub fn apply_elimination(&mut self, elimination: &Elimination) -> usize {
+        elimination
+            .removed
+            .iter()
+            .filter(|((row, col), value)| {
+                self.cells[*row][*col].is_some_and(|mut candidates| {
+                    let present = candidates.remove(*value);
+                    self.cells[*row][*col] = Some(candidates);
+                    present
+                })
+            })
+            .count()
+    }
+}
+
+/// Candidates ruled out by a technique, along with the cells and digits that rule them out
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Elimination {
+    /// Cells that make up the pattern the technique found
+    pub cells: Vec<(usize, usize)>,
+    /// Digits the pattern is made of
+    pub digits: CandidateSet,
+    /// Units the pattern was found in, empty for patterns of cells that see each other across
+    /// units, like wings
+    pub base: Vec<(UnitKind, usize)>,
+    /// Units the candidates are removed from, empty when they are removed from the pattern's own
+    /// cells
+    pub cover: Vec<(UnitKind, usize)>,
+    /// Every candidate removed, with the cell it is removed from
+    pub removed: Vec<((usize, usize), u8)>,
+    /// Human readable justification
+    pub reason: String,
+}
+
+impl Display for Elimination {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.reason)
+    }
+}
+
+/// Every way to pick `size` of the items, keeping their order
+fn combinations<T: Copy>(items: &[T], size: usize) -> Vec<Vec<T>> {
+    if size == 0 {
+        return vec![Vec::new()];
+    }
+    let mut picked = Vec::new();
+    for (i, &first) in items.iter().enumerate() {
+        for mut rest in combinations(&items[i + 1..], size - 1) {
+            rest.insert(0, first);
+            picked.push(rest);
+        }
+    }
+    picked
+}
+
+/// Formats cells as `r1c2, r3c4`
+fn cell_list(cells: &[(usize, usize)]) -> String {
+    cells
+        .iter()
+        .map(|&position| cell_name(position))
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Finds naked subsets of `size` cells: cells of a unit whose candidates, taken together, are
+/// exactly `size` digits. Those digits have to go in those cells, so they are eliminated from the
+/// rest of the unit. Only subsets that eliminate something are returned
+pub fn find_naked_subsets(grid: &CandidatesGrid, size: usize) -> Vec<Elimination> {
+    let mut eliminations = Vec::new();
+    for unit in UnitKind::ALL {
+        for unit_index in 0..9 {
+            let positions = unit.positions(unit_index);
+            let open: Vec<(usize, usize)> = positions
+                .into_iter()
+                .filter(|&(row, col)| {
+                    grid.get(row, col)
+                        .is_some_and(|candidates| (2..=size).contains(&candidates.len()))
+                })
+                .collect();
+            for cells in combinations(&open, size) {
+                let digits = cells
+                    .iter()
+                    .filter_map(|&(row, col)| grid.get(row, col))
+                    .fold(CandidateSet::empty(), |digits, candidates| {
+                        digits.union(candidates)
+                    });
+                if digits.len() != size {
+                    continue;
+                }
+                let removed: Vec<((usize, usize), u8)> = positions
+                    .into_iter()
+                    .filter(|position| !cells.contains(position))
+                    .flat_map(|(row, col)| {
+                        let candidates = grid.get(row, col).unwrap_or_default();
+                        digits
+                            .iter()
+                            .filter(move |value| candidates.contains(*value))
+                            .map(move |value| ((row, col), value))
+                    })
+                    .collect();
+                if removed.is_empty() {
+                    continue;
+                }
+                let digit_list: Vec<String> = digits.iter().map(|d| d.to_string()).collect();
+                eliminations.push(Elimination {
+                    reason: format!(
+                        "{} only hold {} within {unit} {}, so those digits are removed from the \
+                         rest of it",
+                        cell_list(&cells),
+                        digit_list.join("/"),
+                        unit_index + 1
+                    ),
+                    cells,
+                    digits,
+                    base: vec![(unit, unit_index)],
+                    cover: vec![(unit, unit_index)],
+                    removed,
+                });
+            }
+        }
+    }
+    eliminations
+}
+
+/// Finds hidden subsets of `size` digits: digits that, within a unit, only fit in the same `size`
+/// cells. Those cells have to hold those digits, so every other candidate is eliminated from
+/// them. A hidden subset whose cells hold nothing else is also a naked subset and eliminates
+/// nothing here, so it is not returned
+pub fn find_hidden_subsets(grid: &CandidatesGrid, size: usize) -> Vec<Elimination> {
+    let mut eliminations = Vec::new();
+    for unit in UnitKind::ALL {
+        for unit_index in 0..9 {
+            let positions = unit.positions(unit_index);
+            // Bit `i` of a digit's mask is set when it fits in the `i`th cell of the unit
+            let spread = |value: u8| {
+                positions
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (row, col))| {
+                        grid.get(*row, *col)
+                            .is_some_and(|candidates| candidates.contains(value))
+                    })
+                    .fold(0u16, |mask, (i, _)| mask | 1 << i)
+            };
+            let open: Vec<(u8, u16)> = (1..=9)
+                .map(|value| (value, spread(value)))
+                .filter(|(_, mask)| (2..=size).contains(&(mask.count_ones() as usize)))
+                .collect();
+            for picked in combinations(&open, size) {
+                let mask = picked.iter().fold(0u16, |mask, (_, spread)| mask | spread);
+                if mask.count_ones() as usize != size {
+                    continue;
+                }
+                let digits: CandidateSet = picked.iter().map(|(value, _)| *value).collect();
+                let cells: Vec<(usize, usize)> = (0..9)
+                    .filter(|i| mask & 1 << i != 0)
+                    .map(|i| positions[i])
+                    .collect();
+                let removed: Vec<((usize, usize), u8)> = cells
+                    .iter()
+                    .flat_map(|&(row, col)| {
+                        let candidates = grid.get(row, col).unwrap_or_default();
+                        candidates
+                            .difference(digits)
+                            .iter()
+                            .map(move |value| ((row, col), value))
+                    })
+                    .collect();
+                if removed.is_empty() {
+                    continue;
+                }
+                let digit_list: Vec<String> = digits.iter().map(|d| d.to_string()).collect();
+                eliminations.push(Elimination {
+                    reason: format!(
+                        "{} only fit in {} within {unit} {}, so every other candidate is \
+                         removed from those cells",
+                        digit_list.join("/"),
+                        cell_list(&cells),
+                        unit_index + 1
+                    ),
+                    cells,
+                    digits,
+                    base: vec![(unit, unit_index)],
+                    cover: Vec::new(),
+                    removed,
+                });
+            }
+        }
+    }
+    eliminations
+}
+
+/// Naked and hidden subsets of `size` together, naked ones first. A hidden subset in a unit is
+/// always matched by a naked subset of the unit's remaining cells that removes the same
+/// candidates, so any subset that removes nothing new is left out
+pub fn find_subsets(grid: &CandidatesGrid, size: usize) -> Vec<Elimination> {
+    let mut eliminations: Vec<Elimination> = Vec::new();
+    for found in find_naked_subsets(grid, size)
+        .into_iter()
+        .chain(find_hidden_subsets(grid, size))
+    {
+        let is_new = found.removed.iter().any(|removal| {
+            !eliminations
+                .iter()
+                .any(|known| known.removed.contains(removal))
+        });
+        if is_new {
+            eliminations.push(found);
+        }
+    }
+    eliminations
+}
+
+/// Finds locked candidates in both directions. Pointing: when a digit's candidates in a tile all
+/// lie in one row or column, the digit is eliminated from the rest of that line. Claiming: when a
+/// digit's candidates in a row or column all lie in one tile, the digit is eliminated from the
+/// rest of that tile
+pub fn find_locked_candidates(grid: &CandidatesGrid) -> Vec<Elimination> {
+    let mut eliminations = Vec::new();
+    let tile_of = |(row, col): (usize, usize)| (row / 3) * 3 + col / 3;
+    for unit in UnitKind::ALL {
+        for unit_index in 0..9 {
+            for value in 1..=9 {
+                let cells: Vec<(usize, usize)> = unit
+                    .positions(unit_index)
+                    .into_iter()
+                    .filter(|&(row, col)| {
+                        grid.get(row, col)
+                            .is_some_and(|candidates| candidates.contains(value))
+                    })
+                    .collect();
+                if cells.len() < 2 {
+                    continue;
+                }
+                // The other unit every cell of the pattern shares, if there is one
+                let shared = match unit {
+                    UnitKind::Tile => {
+                        if cells.iter().all(|cell| cell.0 == cells[0].0) {
+                            Some((UnitKind::Row, cells[0].0))
+                        } else if cells.iter().all(|cell| cell.1 == cells[0].1) {
+                            Some((UnitKind::Column, cells[0].1))
+                        } else {
+                            None
+                        }
+                    }
+                    UnitKind::Row | UnitKind::Column => {
+                        let tile = tile_of(cells[0]);
+                        cells
+                            .iter()
+                            .all(|&cell| tile_of(cell) == tile)
+                            .then_some((UnitKind::Tile, tile))
+                    }
+                };
+                let Some((other, other_index)) = shared else {
+                    continue;
+                };
+                let removed: Vec<((usize, usize), u8)> = other
+                    .positions(other_index)
+                    .into_iter()
+                    .filter(|&(row, col)| {
+                        !cells.contains(&(row, col))
+                            && grid
+                                .get(row, col)
+                                .is_some_and(|candidates| candidates.contains(value))
+                    })
+                    .map(|position| (position, value))
+                    .collect();
+                if removed.is_empty() {
+                    continue;
+                }
+                eliminations.push(Elimination {
+                    reason: format!(
+                        "{value} is locked to {} within {unit} {}, so it is removed from the rest \
+                         of {other} {}",
+                        cell_list(&cells),
+                        unit_index + 1,
+                        other_index + 1
+                    ),
+                    cells,
+                    digits: CandidateSet::from_iter([value]),
+                    base: vec![(unit, unit_index)],
+                    cover: vec![(other, other_index)],
+                    removed,
+                });
+            }
+        }
+    }
+    eliminations
+}
+
+/// Formats units as `rows 1/5` or `column 3`
+fn unit_list(kind: UnitKind, indices: &[usize]) -> String {
+    let numbers: Vec<String> = indices.iter().map(|i| (i + 1).to_string()).collect();
+    let plural = if indices.len() > 1 { "s" } else { "" };
+    format!("{kind}{plural} {}", numbers.join("/"))
+}
+
+/// Finds fish of `size` lines, X-Wings being 2 and Swordfish 3: a digit whose candidates in
+/// `size` rows all fall in the same `size` columns, or the other way around. The digit has to
+/// take one cell of each of those columns from within the rows, so it is eliminated from the
+/// rest of the columns
+pub fn find_fish(grid: &CandidatesGrid, size: usize) -> Vec<Elimination> {
+    let mut eliminations = Vec::new();
+    for (base_kind, cover_kind) in [
+        (UnitKind::Row, UnitKind::Column),
+        (UnitKind::Column, UnitKind::Row),
+    ] {
+        for value in 1..=9 {
+            // Bit `i` of a line's mask is set when the digit fits in its `i`th cell
+            let lines: Vec<(usize, u16)> = (0..9)
+                .map(|line| {
+                    let mask = base_kind
+                        .positions(line)
+                        .iter()
+                        .enumerate()
+                        .filter(|(_, (row, col))| {
+                            grid.get(*row, *col)
+                                .is_some_and(|candidates| candidates.contains(value))
+                        })
+                        .fold(0u16, |mask, (i, _)| mask | 1 << i);
+                    (line, mask)
+                })
+                .filter(|(_, mask)| (2..=size).contains(&(mask.count_ones() as usize)))
+                .collect();
+            for picked in combinations(&lines, size) {
+                let mask = picked.iter().fold(0u16, |mask, (_, line)| mask | line);
+                if mask.count_ones() as usize != size {
+                    continue;
+                }
+                let base: Vec<usize> = picked.iter().map(|(line, _)| *line).collect();
+                let cover: Vec<usize> = (0..9).filter(|i| mask & 1 << i != 0).collect();
+                let removed: Vec<((usize, usize), u8)> = cover
+                    .iter()
+                    .flat_map(|&line| cover_kind.positions(line))
+                    .filter(|&(row, col)| {
+                        let line = if base_kind == UnitKind::Row { row } else { col };
+                        !base.contains(&line)
+                            && grid
+                                .get(row, col)
+                                .is_some_and(|candidates| candidates.contains(value))
+                    })
+                    .map(|position| (position, value))
+                    .collect();
+                if removed.is_empty() {
+                    continue;
+                }
+                let cells: Vec<(usize, usize)> = base
+                    .iter()
+                    .flat_map(|&line| base_kind.positions(line))
+                    .filter(|&(row, col)| {
+                        grid.get(row, col)
+                            .is_some_and(|candidates| candidates.contains(value))
+                    })
+                    .collect();
+                eliminations.push(Elimination {
+                    reason: format!(
+                        "within {} {value} only fits in {}, so it is removed from the rest of \
+                         those {cover_kind}s",
+                        unit_list(base_kind, &base),
+                        unit_list(cover_kind, &cover)
+                    ),
+                    cells,
+                    digits: CandidateSet::from_iter([value]),
+                    base: base.iter().map(|&line| (base_kind, line)).collect(),
+                    cover: cover.iter().map(|&line| (cover_kind, line)).collect(),
+                    removed,
+                });
+            }
+        }
+    }
+    eliminations
+}
+
+/// Removals of `value` from every open cell that sees all of `cells`, leaving out the cells
+/// themselves
+fn seen_by_all(
+    grid: &CandidatesGrid,
+    cells: &[(usize, usize)],
+    value: u8,
+) -> Vec<((usize, usize), u8)> {
+    grid.open_cells()
+        .filter(|(position, candidates)| {
+            candidates.contains(value) && cells.iter().all(|&cell| Board::sees(cell, *position))
+        })
+        .map(|(position, _)| (position, value))
+        .collect()
+}
+
+/// The single digit two sets have in common, if they share exactly one
+fn single_common(a: CandidateSet, b: CandidateSet) -> Option<u8> {
+    let common = CandidateSet::from_bits(a.bits() & b.bits());
+    (common.len() == 1).then(|| common.iter().next().unwrap())
+}
+
+/// Finds XY-Wings: a pivot with candidates XY that sees two pincers with candidates XZ and YZ.
+/// Whichever of X or Y the pivot takes, one of the pincers is Z, so Z is eliminated from every
+/// cell that sees both pincers
+pub fn find_xy_wing(grid: &CandidatesGrid) -> Vec<Elimination> {
+    find_wings(grid, 2)
+}
+
+/// Finds XYZ-Wings: a pivot with candidates XYZ that sees two pincers with candidates XZ and YZ.
+/// One of the three cells has to be Z, so Z is eliminated from every cell that sees all three
+pub fn find_xyz_wing(grid: &CandidatesGrid) -> Vec<Elimination> {
+    find_wings(grid, 3)
+}
+
+/// Wings around a pivot with `pivot_size` candidates and two bivalue pincers it sees
+fn find_wings(grid: &CandidatesGrid, pivot_size: usize) -> Vec<Elimination> {
+    let mut eliminations = Vec::new();
+    for (pivot, pivot_candidates) in grid.open_cells() {
+        if pivot_candidates.len() != pivot_size {
+            continue;
+        }
+        // Bivalue peers sharing exactly one digit with the pivot, or two for an XYZ-Wing, in
+        // which case they have to be a subset of it
+        let pincers: Vec<((usize, usize), CandidateSet)> = Board::peers(pivot.0, pivot.1)
+            .into_iter()
+            .filter_map(|(row, col)| {
+                let candidates = grid.get(row, col)?;
+                let shared = CandidateSet::from_bits(candidates.bits() & pivot_candidates.bits());
+                (candidates.len() == 2 && shared.len() == pivot_size - 1)
+                    .then_some(((row, col), candidates))
+            })
+            .collect();
+        for pair in combinations(&pincers, 2) {
+            let [(first, first_candidates), (second, second_candidates)] = [pair[0], pair[1]];
+            let Some(value) = single_common(first_candidates, second_candidates) else {
+                continue;
+            };
+            let wing = first_candidates.union(second_candidates);
+            let is_wing = if pivot_size == 2 {
+                !pivot_candidates.contains(value)
+                    && wing.difference(pivot_candidates) == CandidateSet::from_iter([value])
+            } else {
+                pivot_candidates.contains(value) && wing == pivot_candidates
+            };
+            if !is_wing {
+                continue;
+            }
+            let cells = if pivot_size == 2 {
+                vec![first, second]
+            } else {
+                vec![pivot, first, second]
+            };
+            let removed = seen_by_all(grid, &cells, value);
+            if removed.is_empty() {
+                continue;
+            }
+            eliminations.push(Elimination {
+                reason: format!(
+                    "pivot {} sees pincers {}, so {value} is removed from every cell that sees \
+                     {}",
+                    cell_list(&[pivot]),
+                    cell_list(&[first, second]),
+                    if pivot_size == 2 {
+                        "both pincers"
+                    } else {
+                        "all three"
+                    }
+                ),
+                cells: vec![pivot, first, second],
+                digits: pivot_candidates.union(wing),
+                base: Vec::new(),
+                cover: Vec::new(),
+                removed,
+            });
+        }
+    }
+    eliminations
+}
+
+/// Two cells that are the only candidates for a digit in a unit, along with that unit
+type ConjugatePair = ((usize, usize), (usize, usize), (UnitKind, usize));
+
+/// Finds simple coloring eliminations for one digit. Units where the digit has exactly two
+/// candidate cells link those cells as a conjugate pair, one of which has to hold the digit. Each
+/// connected chain of pairs is colored with two alternating colors, one of which holds the digit
+/// in every cell and the other in none. When two cells of the same color see each other, that
+/// color can't be the true one and the digit is removed from all of its cells. Otherwise the
+/// digit is removed from every cell outside the chain that sees both colors
+pub fn find_simple_coloring(grid: &CandidatesGrid, digit: u8) -> Vec<Elimination> {
+    let mut links: Vec<ConjugatePair> = Vec::new();
+    for unit in UnitKind::ALL {
+        for unit_index in 0..9 {
+            let cells: Vec<(usize, usize)> = unit
+                .positions(unit_index)
+                .into_iter()
+                .filter(|&(row, col)| {
+                    grid.get(row, col)
+                        .is_some_and(|candidates| candidates.contains(digit))
+                })
+                .collect();
+            if let [first, second] = cells[..] {
+                links.push((first, second, (unit, unit_index)));
+            }
+        }
+    }
+
+    // Chain and color of every linked cell, by cell index
+    let mut colors: [Option<(usize, bool)>; 81] = [None; 81];
+    let mut chains: Vec<Vec<((usize, usize), bool)>> = Vec::new();
+    for &(start, _, _) in &links {
+        if colors[start.0 * 9 + start.1].is_some() {
+            continue;
+        }
+        let chain = chains.len();
+        colors[start.0 * 9 + start.1] = Some((chain, true));
+        let mut members = vec![(start, true)];
+        let mut next = 0;
+        while let Some(&(cell, color)) = members.get(next) {
+            next += 1;
+            for &(first, second, _) in &links {
+                let other = if first == cell {
+                    second
+                } else if second == cell {
+                    first
+                } else {
+                    continue;
+                };
+                if colors[other.0 * 9 + other.1].is_none() {
+                    colors[other.0 * 9 + other.1] = Some((chain, !color));
+                    members.push((other, !color));
+                }
+            }
+        }
+        chains.push(members);
+    }
+
+    let mut eliminations = Vec::new();
+    for (chain, members) in chains.iter().enumerate() {
+        let cells: Vec<(usize, usize)> = members.iter().map(|(cell, _)| *cell).collect();
+        let base: Vec<(UnitKind, usize)> = links
+            .iter()
+            .filter(|(first, _, _)| colors[first.0 * 9 + first.1].is_some_and(|c| c.0 == chain))
+            .map(|(_, _, unit)| *unit)
+            .collect();
+        let of_color = |color: bool| {
+            members
+                .iter()
+                .filter(move |(_, c)| *c == color)
+                .map(|(cell, _)| *cell)
+        };
+        let wrapped = [true, false]
+            .into_iter()
+            .find(|&color| of_color(color).any(|a| of_color(color).any(|b| Board::sees(a, b))));
+        let (removed, reason) = if let Some(color) = wrapped {
+            let removed: Vec<((usize, usize), u8)> =
+                of_color(color).map(|cell| (cell, digit)).collect();
+            let reason = format!(
+                "{digit} alternates along {} and two cells of one color share a unit, so it is \
+                 removed from {}",
+                cell_list(&cells),
+                cell_list(&of_color(color).collect::<Vec<_>>())
+            );
+            (removed, reason)
+        } else {
+            let removed: Vec<((usize, usize), u8)> = grid
+                .open_cells()
+                .filter(|(position, candidates)| {
+                    candidates.contains(digit)
+                        && !cells.contains(position)
+                        && of_color(true).any(|cell| Board::sees(cell, *position))
+                        && of_color(false).any(|cell| Board::sees(cell, *position))
+                })
+                .map(|(position, _)| (position, digit))
+                .collect();
+            let reason = format!(
+                "{digit} alternates along {}, so it is removed from the cells that see both \
+                 colors: {}",
+                cell_list(&cells),
+                cell_list(&removed.iter().map(|(cell, _)| *cell).collect::<Vec<_>>())
+            );
+            (removed, reason)
+        };
+        if removed.is_empty() {
+            continue;
+        }
+        eliminations.push(Elimination {
+            reason,
+            cells,
+            digits: CandidateSet::from_iter([digit]),
+            base,
+            cover: Vec::new(),
+            removed,
+        });
+    }
+    eliminations
+}
+
+/// [`find_simple_coloring`] for every digit
+pub fn find_simple_colorings(grid: &CandidatesGrid) -> Vec<Elimination> {
+    (1..=9)
+        .flat_map(|digit| find_simple_coloring(grid, digit))
+        .collect()
+}
+
+/// A technique that only rules out candidates, named for reports
+pub type EliminationTechnique = (&'static str, fn(&CandidatesGrid) -> Vec<Elimination>);
+
+/// A technique that places values, named for reports like the moves it finds
+pub type PlacementTechnique = (&'static str, fn(&CandidatesGrid) -> Vec<TechniqueMove>);
+
+/// Every elimination technique, cheapest first. Runners try them in this order and go back to
+/// the start after each elimination, so a harder technique is only used once every cheaper one
+/// has stopped finding anything
+pub const ELIMINATION_TECHNIQUES: [EliminationTechnique; 10] = [
+    ("Locked candidates", find_locked_candidates),
+    ("Naked pair", |grid| find_naked_subsets(grid, 2)),
+    ("Hidden pair", |grid| find_hidden_subsets(grid, 2)),
+    ("Naked triple", |grid| find_naked_subsets(grid, 3)),
+    ("Hidden triple", |grid| find_hidden_subsets(grid, 3)),
+    ("X-Wing", |grid| find_fish(grid, 2)),
+    ("Swordfish", |grid| find_fish(grid, 3)),
+    ("XY-Wing", find_xy_wing),
+    ("XYZ-Wing", find_xyz_wing),
+    ("Simple coloring", find_simple_colorings),
+];
+
+/// The first elimination found by the cheapest technique that finds one, with its name
+pub fn find_next_elimination(grid: &CandidatesGrid) -> Option<(&'static str, Elimination)> {
+    TechniqueSet::all().find_next_elimination(grid)
+}
+
+/// Every technique in the order runners try them, from singles up
+pub fn technique_names() -> impl Iterator<Item = &'static str> {
+    ["Naked single", "Hidden single"]
+        .into_iter()
+        .chain(ELIMINATION_TECHNIQUES.iter().map(|(name, _)| *name))
+}
+
+/// Which techniques a logical solve may use. Naked singles are always used, since nothing gets
+/// placed without them. Techniques of the caller's own can be added to the known ones, and are
+/// tried once the known ones of their kind find nothing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TechniqueSet {
+    enabled: Vec<&'static str>,
+    placements: Vec<PlacementTechnique>,
+    eliminations: Vec<EliminationTechnique>,
+}
+
+impl TechniqueSet {
+    /// Every known technique
+    pub fn all() -> Self {
+        Self {
+            enabled: technique_names().collect(),
+            placements: Vec::new(),
+            eliminations: Vec::new(),
+        }
+    }
+
+    /// Naked and hidden singles only
+    pub fn singles() -> Self {
+        Self::up_to("Hidden single").unwrap()
+    }
+
+    /// Every technique up to and including the named one, in the order of [`technique_names`].
+    /// Returns `None` for an unknown name
+    pub fn up_to(name: &str) -> Option<Self> {
+        let last = technique_names().position(|known| known == name)?;
+        Some(Self {
+            enabled: technique_names().take(last + 1).collect(),
+            placements: Vec::new(),
+            eliminations: Vec::new(),
+        })
+    }
+
+    /// Adds a technique that places values, tried after hidden singles and before any
+    /// elimination, in the order added. The moves it finds should name it as their technique
+    pub fn with_placement_technique(mut self, technique: PlacementTechnique) -> Self {
+        self.placements.push(technique);
+        self
+    }
+
+    /// Adds a technique that rules out candidates, tried after every known one, in the order
+    /// added
+    pub fn with_elimination_technique(mut self, technique: EliminationTechnique) -> Self {
+        self.eliminations.push(technique);
+        self
+    }
+
+    /// Whether the named technique may be used
+    pub fn contains(&self, name: &str) -> bool {
+        name == "Naked single"
+            || self.enabled.contains(&name)
+            || self.placements.iter().any(|(added, _)| *added == name)
+            || self.eliminations.iter().any(|(added, _)| *added == name)
+    }
+
+    /// The first placement found by the cheapest enabled technique that finds one: naked
+    /// singles, then hidden singles, then the added placement techniques
+    pub fn find_next_placement(&self, grid: &CandidatesGrid) -> Option<TechniqueMove> {
+        find_naked_singles(grid)
+            .into_iter()
+            .next()
+            .or_else(|| {
+                self.contains("Hidden single")
+                    .then(|| find_hidden_singles_in(grid).into_iter().next())
+                    .flatten()
+            })
+            .or_else(|| {
+                self.placements
+                    .iter()
+                    .find_map(|(_, find)| find(grid).into_iter().next())
+            })
+    }
+
+    /// The first elimination found by the cheapest enabled technique that finds one, the added
+    /// elimination techniques coming after the known ones
+    pub fn find_next_elimination(
+        &self,
+        grid: &CandidatesGrid,
+    ) -> Option<(&'static str, Elimination)> {
+        ELIMINATION_TECHNIQUES
+            .iter()
+            .filter(|(name, _)| self.contains(name))
+            .chain(&self.eliminations)
+            .find_map(|(name, find)| Some((*name, find(grid).into_iter().next()?)))
+    }
+}
+
+/// How far a logical solve got
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LogicalOutcome {
+    /// The board was filled in by the moves, in order
+    Solved(Vec<TechniqueMove>),
+    /// None of the techniques could make progress before the board was filled in
+    Stuck {
+        /// Moves made before getting stuck
+        moves: Vec<TechniqueMove>,
+        board_state: Box<Board>,
+        /// Candidates left, including every elimination made after the last placement
+        remaining_candidates: Box<CandidatesGrid>,
+    },
+}
+
+impl LogicalOutcome {
+    /// The moves made, whether or not the board was solved
+    pub fn moves(&self) -> &[TechniqueMove] {
+        match self {
+            LogicalOutcome::Solved(moves) | LogicalOutcome::Stuck { moves, .. } => moves,
+        }
+    }
+
+    /// The latest technique in [`technique_names`] order that any move or elimination needed.
+    /// Techniques added to a [`TechniqueSet`] are only tried once the known ones give up, so
+    /// they come after all of them
+    pub fn hardest_technique(&self) -> Option<&'static str> {
+        self.techniques_used().max_by_key(|name| {
+            technique_names()
+                .position(|known| known == *name)
+                .unwrap_or(usize::MAX)
+        })
+    }
+
+    /// Names of the techniques behind every move and elimination, in the order they were used
+    pub fn techniques_used(&self) -> impl Iterator<Item = &'static str> + '_ {
+        self.moves().iter().flat_map(|found| {
+            std::iter::once(found.technique).chain(found.eliminations.iter().map(|(name, _)| *name))
+        })
+    }
+}
+
+/// What a hint suggests doing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintAction {
+    Place(TechniqueMove),
+    Eliminate(Elimination),
+}
+
+/// The next move a human could make, with the technique behind it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hint {
+    pub technique: &'static str,
+    pub action: HintAction,
+    /// The technique name followed by its reasoning, e.g. "Hidden single: 7 can only go in r4c6
+    /// within tile 5"
+    pub explanation: String,
+}
+
+impl Hint {
+    /// Finds the move the simplest technique can make on the grid: singles first, then
+    /// eliminations in [`ELIMINATION_TECHNIQUES`] order
+    pub fn find(grid: &CandidatesGrid) -> Option<Hint> {
+        Self::find_with(grid, &TechniqueSet::all())
+    }
+
+    /// Same as [`Hint::find`], with the techniques of `techniques` in the order it tries them
+    pub fn find_with(grid: &CandidatesGrid, techniques: &TechniqueSet) -> Option<Hint> {
+        let (technique, reason, action) = match techniques.find_next_placement(grid) {
+            Some(found) => (
+                found.technique,
+                found.reason.clone(),
+                HintAction::Place(found),
+            ),
+            None => {
+                let (technique, elimination) = techniques.find_next_elimination(grid)?;
+                let reason = elimination.reason.clone();
+                (technique, reason, HintAction::Eliminate(elimination))
+            }
+        };
+        Some(Hint {
+            technique,
+            action,
+            explanation: format!("{technique}: {reason}"),
+        })
+    }
+}
+
+impl Display for Hint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.explanation)
+    }
+}
+
+/// One step of a walkthrough of a whole solve
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExplainedStep {
+    /// A move found by a technique
+    Logical(Hint),
+    /// A value taken from the solution because no technique applied, placed in the cell with the
+    /// fewest candidates
+    TrialAndError { position: (usize, usize), value: u8 },
+}
+
+impl Display for ExplainedStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ExplainedStep::Logical(hint) => write!(f, "{hint}"),
+            ExplainedStep::TrialAndError { position, value } => write!(
+                f,
+                "Trial and error: no technique applies, so {} is set to {value}",
+                cell_name(*position)
+            ),
+        }
+    }
+}
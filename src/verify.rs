@@ -0,0 +1,98 @@
+use std::{error::Error, fmt::Display};
+
+use crate::board::{cell_name, Board, UnitKind};
+
+/// How a solution failed to check out against its puzzle, see [`verify_solution`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerificationFailure {
+    /// A given of the puzzle holds another value in the solution
+    GivenChanged {
+        cell: (usize, usize),
+        given: u8,
+        found: u8,
+    },
+    /// A unit of the solution has an empty cell or a value twice. Tiles are indexed left to
+    /// right, top to bottom
+    BrokenUnit { kind: UnitKind, index: usize },
+    /// The puzzle has a single solution and dancing links found another one
+    DisagreesWithReference { reference: Box<Board> },
+}
+
+impl Display for VerificationFailure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerificationFailure::GivenChanged { cell, given, found } => write!(
+                f,
+                "{} was given as {given} but the solution holds {found}",
+                cell_name(*cell)
+            ),
+            VerificationFailure::BrokenUnit { kind, index } => write!(
+                f,
+                "{kind} {} of the solution is not the digits 1 to 9",
+                index + 1
+            ),
+            VerificationFailure::DisagreesWithReference { .. } => write!(
+                f,
+                "the puzzle has a single solution and dancing links found another one"
+            ),
+        }
+    }
+}
+
+impl Error for VerificationFailure {}
+
+/// Checks `solved` against `puzzle` without trusting the solver that produced it: every given
+/// must be kept, every unit must hold the digits 1 to 9 as counted from the cells alone, and
+/// when the puzzle has a single solution it must be the one [`Board::solve_dlx`] finds
+pub fn verify_solution(puzzle: &Board, solved: &Board) -> Result<(), VerificationFailure> {
+    for (row, col) in (0..81).map(|i| (i / 9, i % 9)) {
+        let (given, found) = (puzzle.get_cell(row, col), solved.get_cell(row, col));
+        if given != 0 && given != found {
+            return Err(VerificationFailure::GivenChanged {
+                cell: (row, col),
+                given,
+                found,
+            });
+        }
+    }
+    for kind in UnitKind::ALL {
+        for index in 0..9 {
+            let mut seen = [false; 10];
+            for (row, col) in kind.positions(index) {
+                let value = solved.get_cell(row, col) as usize;
+                if value == 0 || value > 9 || std::mem::replace(&mut seen[value], true) {
+                    return Err(VerificationFailure::BrokenUnit { kind, index });
+                }
+            }
+        }
+    }
+    let mut reference = puzzle.clone();
+    if reference.solve_dlx().is_ok()
+        && reference.to_string() != solved.to_string()
+        && puzzle.count_solutions_dlx(2) == 1
+    {
+        return Err(VerificationFailure::DisagreesWithReference {
+            reference: Box::new(reference),
+        });
+    }
+    Ok(())
+}
+
+/// A report of the failure to attach to a bug, with where the puzzle came from, like its seeds,
+/// and the puzzle, the solution that failed and the one dancing links finds, one per line
+pub fn bug_report(
+    failure: &VerificationFailure,
+    origin: &str,
+    puzzle: &Board,
+    solved: &Board,
+) -> String {
+    let mut reference = puzzle.clone();
+    let reference = match reference.solve_dlx() {
+        Ok(_) => reference.to_string(),
+        Err(error) => format!("none, {error}"),
+    };
+    format!(
+        "# Verification failed: {failure}\n# {origin}\npuzzle    {puzzle}\nsolved    {solved}\n\
+         reference {reference}\n"
+    )
+}
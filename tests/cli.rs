@@ -0,0 +1,538 @@
+use std::{
+    io::{BufRead, BufReader, ErrorKind, Write},
+    process::{Command, Output, Stdio},
+};
+
+const PUZZLE: &str =
+    "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+const SOLUTION: &str =
+    "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+/// Runs the binary with `args`, piping `input` into it. The binary may stop reading and exit
+/// before all of the input is written, which is fine
+fn run(args: &[&str], input: &str) -> Output {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sudoku_solver"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let written = child.stdin.take().unwrap().write_all(input.as_bytes());
+    if let Err(error) = written {
+        assert_eq!(error.kind(), ErrorKind::BrokenPipe, "{error}");
+    }
+    child.wait_with_output().unwrap()
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8(output.stdout.clone()).unwrap()
+}
+
+#[test]
+fn exit_codes_follow_what_happened_to_the_puzzle() {
+    let solved = run(&["solve", "-"], &format!("{PUZZLE}\n"));
+    assert_eq!(solved.status.code(), Some(0));
+    assert_eq!(stdout(&solved), format!("{SOLUTION}\n"));
+
+    let unparsable = run(&["solve", "-"], "53007000060019500009800006\n");
+    assert_eq!(unparsable.status.code(), Some(3));
+    assert_eq!(
+        stdout(&unparsable),
+        "INVALID A board needs exactly 81 cells, found 26\n"
+    );
+    assert!(!unparsable.stderr.is_empty());
+
+    let conflicting = format!("55{}", &PUZZLE[2..]);
+    let invalid = run(&["solve", "-"], &format!("{conflicting}\n"));
+    assert_eq!(invalid.status.code(), Some(2));
+    assert_eq!(stdout(&invalid), "INVALID\n");
+
+    // The last cell of the first row can only be 9, which its column already holds
+    let contradictory = format!("123456780000000009{}", "0".repeat(63));
+    let unsolvable = run(&["solve", "-"], &format!("{contradictory}\n"));
+    assert_eq!(unsolvable.status.code(), Some(1));
+    assert_eq!(stdout(&unsolvable), "UNSOLVABLE\n");
+
+    // Out of time is not the same as out of solutions, on stdout as well as in the exit code
+    let timed_out = run(&["solve", "-", "--timeout-ms", "0"], &format!("{PUZZLE}\n"));
+    assert_eq!(timed_out.status.code(), Some(4));
+    assert_eq!(stdout(&timed_out), "TIMEOUT\n");
+}
+
+#[test]
+fn batches_fail_on_any_puzzle_unless_told_to_keep_going() {
+    let input = format!("# a comment\n{PUZZLE}\nnot a puzzle\n{PUZZLE}\n");
+    let stopped = run(&["solve", "-"], &input);
+    assert_eq!(stopped.status.code(), Some(3));
+    assert_eq!(
+        stdout(&stopped),
+        format!("{SOLUTION}\nINVALID Unexpected character 'n' at index 0\n{SOLUTION}\n")
+    );
+
+    let kept_going = run(&["solve", "-", "--keep-going"], &input);
+    assert_eq!(kept_going.status.code(), Some(0));
+    assert_eq!(stdout(&kept_going), stdout(&stopped));
+}
+
+#[test]
+fn bad_arguments_are_parse_errors() {
+    let unknown_command = run(&["unsolve"], "");
+    assert_eq!(unknown_command.status.code(), Some(3));
+
+    let unknown_format = run(&["solve", "-", "--format", "yaml"], PUZZLE);
+    assert_eq!(unknown_format.status.code(), Some(3));
+    assert!(unknown_format.stdout.is_empty());
+
+    let help = run(&["solve", "--help"], "");
+    assert_eq!(help.status.code(), Some(0));
+    assert!(stdout(&help).starts_with("Usage: sudoku_solver solve"));
+}
+
+#[test]
+fn json_output_stays_valid_for_failures() {
+    let input = format!("{PUZZLE}\nnot a \"puzzle\"\n");
+    let output = run(&["solve", "-", "--format", "json", "--keep-going"], &input);
+    assert_eq!(output.status.code(), Some(0));
+    let stdout = stdout(&output);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with(&format!(
+        "{{\"puzzle\":\"{PUZZLE}\",\"solution\":\"{SOLUTION}\",\"seeds\":null,\"time_us\":"
+    )));
+    assert!(lines[0].ends_with(",\"difficulty\":null,\"error\":null}"));
+    assert_eq!(
+        lines[1],
+        "{\"puzzle\":null,\"solution\":null,\"seeds\":null,\"time_us\":null,\"difficulty\":null,\
+         \"error\":\"Line 2 of - is not a valid board: Unexpected character 'n' at index 0\"}"
+    );
+}
+
+#[test]
+fn csv_output_has_a_single_header() {
+    let input = format!("{PUZZLE}\n{PUZZLE}\n");
+    let output = run(&["solve", "-", "--format", "csv"], &input);
+    let stdout = stdout(&output);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "puzzle,solution,seeds,time_us,difficulty,error");
+    assert_eq!(lines.len(), 3);
+    assert!(lines[1..]
+        .iter()
+        .all(|line| line.starts_with(&format!("{PUZZLE},{SOLUTION},,")) && line.ends_with(",,")));
+}
+
+#[test]
+fn check_reports_what_is_wrong_with_each_solution() {
+    let wrong = format!("99{}0", &SOLUTION[2..80]);
+    let input = format!("puzzle,solution\n{PUZZLE},{SOLUTION}\n{PUZZLE},{wrong}\n");
+    let output = run(&["check", "-"], &input);
+    assert_eq!(output.status.code(), Some(1));
+    let stdout = stdout(&output);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], "1: OK");
+    assert_eq!(lines[1], "2: r1c1 holds 9 instead of the given 5");
+    assert!(lines.contains(&"2: 9 appears more than once in row 1"));
+    assert_eq!(lines.last(), Some(&"2: r9c9 is empty"));
+
+    let kept_going = run(&["check", "-", "--keep-going"], &input);
+    assert_eq!(kept_going.status.code(), Some(0));
+}
+
+#[test]
+fn piped_puzzles_are_answered_line_by_line_as_they_arrive() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_sudoku_solver"))
+        .args(["solve", "--quiet"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+    let mut input = child.stdin.take().unwrap();
+    let mut output = BufReader::new(child.stdout.take().unwrap());
+    let mut answer = |line: &str| {
+        writeln!(input, "{line}").unwrap();
+        input.flush().unwrap();
+        let mut answer = String::new();
+        output.read_line(&mut answer).unwrap();
+        answer
+    };
+    // Each answer is read before the next line is written, so none waits for the end of input
+    assert_eq!(answer(PUZZLE), format!("{SOLUTION}\n"));
+    assert_eq!(
+        answer("12345"),
+        "INVALID A board needs exactly 81 cells, found 5\n"
+    );
+    assert_eq!(answer(PUZZLE), format!("{SOLUTION}\n"));
+    drop(input);
+    let finished = child.wait_with_output().unwrap();
+    assert_eq!(finished.status.code(), Some(3));
+    let stderr = String::from_utf8(finished.stderr).unwrap();
+    assert!(stderr.contains("Line 2 of - is not a valid board"));
+}
+
+#[test]
+fn ambiguous_puzzles_list_their_solutions() {
+    let ambiguous =
+        "534678912672195348198342567859760420426850790713924856961537284287419635345286179";
+    let other = "534678912672195348198342567859763421426851793713924856961537284287419635345286179";
+    let listed = run(
+        &["solve", "-", "--solutions", "5"],
+        &format!("{ambiguous}\n"),
+    );
+    assert_eq!(listed.status.code(), Some(0));
+    assert_eq!(
+        stdout(&listed),
+        format!("{SOLUTION}\n{other}\nAll 2 solutions\n")
+    );
+
+    let first = run(
+        &["solve", "-", "--solutions", "1"],
+        &format!("{ambiguous}\n"),
+    );
+    assert_eq!(
+        stdout(&first),
+        format!("{SOLUTION}\nShowing 1, more solutions exist\n")
+    );
+
+    let counted = run(
+        &["solve", "-", "--solutions", "0"],
+        &format!("{ambiguous}\n"),
+    );
+    assert_eq!(stdout(&counted), "2 solutions\n");
+}
+
+#[test]
+fn bench_runs_the_chosen_solvers_over_seeded_puzzles() {
+    let single = run(
+        &["bench", "-n", "3", "--seed", "7", "--solver", "dlx", "-q"],
+        "",
+    );
+    assert_eq!(single.status.code(), Some(0));
+    let stdout = stdout(&single);
+    assert!(stdout.starts_with("dlx: Solved 3/3 puzzles"));
+    assert!(stdout.contains("completion   3/3 (100.0%)"));
+    assert!(single.stderr.is_empty());
+
+    let compared = run(&["bench", "-n", "2", "--seed", "7", "--compare", "-q"], "");
+    let table = self::stdout(&compared);
+    for solver in ["wfc ", "dfs ", "dlx ", "wfc/ac3 "] {
+        assert!(
+            table.lines().any(|line| line.starts_with(solver)),
+            "{table}"
+        );
+    }
+
+    let unknown = run(&["bench", "--solver", "sat"], "");
+    assert_eq!(unknown.status.code(), Some(3));
+}
+
+#[test]
+fn generate_filters_and_prints_grades() {
+    let generate = |clues: &str, extra: &[&str]| {
+        let args = ["generate", "-n", "2", "--seed", "1", "--clues", clues, "-q"];
+        run(&[&args[..], extra].concat(), "")
+    };
+    let graded = generate("34..=36", &["--difficulty", "easy", "--print-grade"]);
+    assert_eq!(graded.status.code(), Some(0));
+    let lines = stdout(&graded);
+    assert_eq!(lines.lines().count(), 2);
+    for line in lines.lines() {
+        let (puzzle, grade) = line.split_once(' ').unwrap();
+        assert_eq!(puzzle.len(), 81);
+        assert!(grade.starts_with("Easy "), "{line}");
+    }
+
+    // Without a difficulty the grade is printed but nothing is filtered
+    let printed = stdout(&generate("34..=36", &["--print-grade"]));
+    let puzzles: Vec<&str> = printed.lines().map(|line| &line[..81]).collect();
+    let ungraded = stdout(&generate("34..=36", &[]));
+    assert_eq!(puzzles, ungraded.lines().collect::<Vec<_>>());
+
+    let hopeless = generate("50..=52", &["--difficulty", "expert"]);
+    assert_eq!(hopeless.status.code(), Some(4));
+    let error = String::from_utf8(hopeless.stderr).unwrap();
+    assert!(
+        error.contains("Generated 200 candidates in a row, none graded Expert; try fewer clues")
+    );
+}
+
+#[test]
+fn generate_digs_x_sudoku_puzzles_out_of_grids_with_both_diagonals() {
+    let args = ["generate", "-n", "3", "--seed", "5", "--variant", "x", "-q"];
+    let unique = run(&[&args[..], &["--unique", "--format", "grid"]].concat(), "");
+    assert_eq!(unique.status.code(), Some(0));
+    let fast = stdout(&run(&args, ""));
+    assert_eq!(fast.lines().count(), 3);
+    assert!(fast.lines().all(|line| line.len() == 81));
+    let classic = run(&["generate", "-n", "3", "--seed", "5", "-q"], "");
+    assert_ne!(fast, stdout(&classic));
+
+    for flag in [&["--difficulty", "easy"][..], &["--print-grade"]] {
+        let graded = run(&[&args[..], flag].concat(), "");
+        assert_eq!(graded.status.code(), Some(3));
+        assert!(String::from_utf8_lossy(&graded.stderr).contains("--variant x"));
+    }
+    let unknown = run(&["generate", "--variant", "killer"], "");
+    assert_eq!(unknown.status.code(), Some(3));
+}
+
+#[test]
+fn hints_and_walkthroughs_pick_up_from_the_given_moves() {
+    let input = format!("{PUZZLE}\n");
+    let hint = stdout(&run(&["hint", "-"], &input));
+    let lines: Vec<&str> = hint.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains("r5c5"), "{hint}");
+    assert_eq!(lines[1], format!("{}5{}", &PUZZLE[..40], &PUZZLE[41..]));
+
+    // With the hinted move already made, the hint moves on to the next one
+    let moved = stdout(&run(&["hint", "-", "--given-moves", "r5c5=5"], &input));
+    assert!(!moved.lines().next().unwrap().contains("r5c5"), "{moved}");
+
+    let explained = run(&["explain", "-", "--max-steps", "3"], &input);
+    let explained = stdout(&explained);
+    let steps: Vec<&str> = explained.lines().collect();
+    assert_eq!(steps.len(), 3);
+    assert_eq!(steps[0], format!("1. {}", lines[0]));
+    assert!(steps[2].starts_with("3. "));
+
+    let wrong = run(&["explain", "-", "--given-moves", "r1c3=1"], &input);
+    assert_eq!(wrong.status.code(), Some(1));
+    let over_given = run(&["hint", "-", "--given-moves", "r1c2=4"], &input);
+    assert_eq!(over_given.status.code(), Some(3));
+    assert!(String::from_utf8(over_given.stderr)
+        .unwrap()
+        .contains("r1c2 is a given"));
+}
+
+#[test]
+fn grade_prints_details_and_the_distribution_of_a_pack() {
+    let pack = format!("{PUZZLE}\nnot a puzzle\n{PUZZLE}\n");
+    let failed = run(&["grade", "-", "--jobs", "2"], &pack);
+    assert_eq!(failed.status.code(), Some(3));
+
+    let graded = run(
+        &["grade", "-", "--jobs", "2", "--details", "--keep-going"],
+        &pack,
+    );
+    assert_eq!(graded.status.code(), Some(0));
+    let output = stdout(&graded);
+    let lines: Vec<&str> = output.lines().collect();
+    assert!(lines[0].ends_with("logical"));
+    assert_eq!(
+        lines[1].split_whitespace().collect::<Vec<_>>(),
+        ["1", "30", "Trivial", "Naked", "single", "yes"]
+    );
+    assert!(lines[2].starts_with("    2  INVALID "));
+    assert!(lines[3].starts_with("    3     30  Trivial"));
+    assert!(output.contains("Trivial             2  100.0%"), "{output}");
+    assert!(
+        output.ends_with("1 of 3 lines are not puzzles\n"),
+        "{output}"
+    );
+}
+
+#[test]
+fn boards_are_colored_only_when_asked_for_or_on_a_terminal() {
+    let input = format!("{PUZZLE}\n");
+    let grid = |color: &str| {
+        stdout(&run(
+            &["solve", "-", "--format", "grid", "--color", color],
+            &input,
+        ))
+    };
+    // Piped output is not a terminal, so auto leaves it plain
+    assert_eq!(grid("auto"), grid("never"));
+    assert!(!grid("never").contains('\x1b'));
+    assert!(grid("always").contains("\x1b[1m5\x1b[0m"));
+
+    // An explicit --color always wins over NO_COLOR
+    let colored = Command::new(env!("CARGO_BIN_EXE_sudoku_solver"))
+        .args(["solve", "-", "--format", "grid", "--color", "always"])
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+    colored
+        .stdin
+        .as_ref()
+        .unwrap()
+        .write_all(input.as_bytes())
+        .unwrap();
+    let colored = colored.wait_with_output().unwrap();
+    assert_eq!(stdout(&colored), grid("always"));
+
+    let unknown = run(&["solve", "-", "--color", "sometimes"], &input);
+    assert_eq!(unknown.status.code(), Some(3));
+}
+
+#[test]
+fn repl_sessions_are_scriptable_through_stdin() {
+    let directory = std::env::temp_dir().join(format!("repl-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let puzzle = directory.join("puzzle.txt");
+    let saved = directory.join("saved.txt");
+    std::fs::write(&puzzle, format!("{PUZZLE}\n")).unwrap();
+    let script = format!(
+        "# Fill in a cell, then think better of it
+set r1c3 4
+undo
+set r1c1 9
+bogus
+count-solutions
+solve
+save {}
+quit
+clear r1c3
+",
+        saved.display()
+    );
+
+    let session = run(
+        &["repl", puzzle.to_str().unwrap(), "--color", "never"],
+        &script,
+    );
+    assert_eq!(session.status.code(), Some(3));
+    let output = stdout(&session);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines[..3],
+        ["r1c3 = 4", "Took back the last change", "1 solution"]
+    );
+    assert!(lines[3].starts_with('┌'), "{output}");
+    assert!(lines.last().unwrap().starts_with("Saved the board to "));
+    let errors = String::from_utf8(session.stderr).unwrap();
+    let errors: Vec<&str> = errors.lines().collect();
+    assert_eq!(
+        errors,
+        [
+            "r1c1 is a given",
+            "Unknown command \"bogus\", help lists the commands",
+            "2 commands failed"
+        ]
+    );
+    assert_eq!(
+        std::fs::read_to_string(&saved).unwrap(),
+        format!("{SOLUTION}\n")
+    );
+    std::fs::remove_dir_all(&directory).unwrap();
+
+    let from_stdin = run(&["repl", "-"], "show\n");
+    assert_eq!(from_stdin.status.code(), Some(3));
+}
+
+#[test]
+fn serve_stdio_answers_every_line_and_nothing_else() {
+    let requests = format!(
+        "{{\"id\":1,\"cmd\":\"solve\",\"puzzle\":\"{PUZZLE}\"}}\n\nnot json\n\
+         {{\"id\":3,\"cmd\":\"hint\",\"puzzle\":\"{PUZZLE}\"}}\n"
+    );
+    let served = run(&["serve-stdio"], &requests);
+    assert_eq!(served.status.code(), Some(0));
+    assert!(served.stderr.is_empty());
+    let output = stdout(&served);
+    let responses: Vec<&str> = output.lines().collect();
+    assert_eq!(responses.len(), 3);
+    assert!(responses[0].starts_with("{\"id\":1,\"ok\":true,"));
+    assert!(responses[0].contains(SOLUTION));
+    assert!(responses[1].starts_with("{\"id\":null,\"ok\":false,"));
+    assert!(responses[2].starts_with("{\"id\":3,\"ok\":true,"));
+    assert!(!output.contains('\x1b'));
+}
+
+#[test]
+fn verified_solves_print_the_same_answers() {
+    let input = format!("{PUZZLE}\n{}\n", "0".repeat(81));
+    let plain = run(&["solve", "-", "--jobs", "2"], &input);
+    let verified = run(&["solve", "-", "--jobs", "2", "--verify"], &input);
+    assert_eq!(verified.status.code(), Some(0));
+    assert_eq!(stdout(&verified), stdout(&plain));
+    assert!(verified.stderr.is_empty());
+
+    let generated = run(&["solve", "--seeds", "B1-R2-S3", "--verify", "-q"], "");
+    assert_eq!(generated.status.code(), Some(0));
+}
+
+#[test]
+fn stats_are_printed_from_the_stats_file() {
+    let directory = std::env::temp_dir().join(format!("stats-cli-{}", std::process::id()));
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("stats.txt");
+    let stats_file = path.to_str().unwrap();
+
+    let empty = run(&["stats", "--stats-file", stats_file], "");
+    assert!(empty.status.success());
+    assert!(stdout(&empty).starts_with("No games played yet"));
+
+    std::fs::write(
+        &path,
+        "# sudoku_solver stats\nEasy played 3 solved 2 best_ms 65000 total_ms 200000\n\
+         daily last 2001-01-01 streak 2 best 5\n",
+    )
+    .unwrap();
+    let kept = run(&["stats", "--stats-file", stats_file], "");
+    assert!(kept.status.success());
+    let output = stdout(&kept);
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(
+        lines[1].split_whitespace().collect::<Vec<_>>(),
+        ["Easy", "3", "2", "1:05", "1:40"]
+    );
+    assert_eq!(
+        lines.last(),
+        Some(&"daily streak 0 day(s), best 5, last solved 2001-01-01")
+    );
+
+    std::fs::write(&path, "not stats\n").unwrap();
+    let corrupt = run(&["stats", "--stats-file", stats_file], "");
+    assert!(corrupt.status.success());
+    assert!(String::from_utf8_lossy(&corrupt.stderr).contains("is not a stats file"));
+    assert!(stdout(&corrupt).starts_with("No games played yet"));
+    std::fs::remove_dir_all(&directory).unwrap();
+}
+
+#[test]
+fn solve_uses_the_solver_it_is_given() {
+    let input = format!("{PUZZLE}\n");
+    let generated = ["solve", "--seeds", "B1-R2-S3", "--solver"];
+    for args in [&generated[..], &["solve", "-", "--solver"]] {
+        let unknown = run(&[args, &["bogus"]].concat(), &input);
+        assert_eq!(unknown.status.code(), Some(3));
+        assert!(String::from_utf8_lossy(&unknown.stderr).contains("Unknown solver bogus"));
+    }
+    let timings = std::env::temp_dir().join(format!("timings-{}.csv", std::process::id()));
+    for solver in ["wfc", "dfs", "dlx"] {
+        let args = ["solve", "-", "--solver", solver, "--timings"];
+        let piped = run(&[&args[..], &[timings.to_str().unwrap()]].concat(), &input);
+        assert_eq!(stdout(&piped), format!("{SOLUTION}\n"));
+        let rows = std::fs::read_to_string(&timings).unwrap();
+        let row: Vec<&str> = rows.lines().nth(1).unwrap().split(',').collect();
+        assert_eq!(row[3..5], [solver, "solved"]);
+    }
+    std::fs::remove_file(&timings).unwrap();
+
+    let dancing = run(&[&generated[..], &["dlx", "-q"]].concat(), "");
+    let default = run(&["solve", "--seeds", "B1-R2-S3", "-q"], "");
+    assert!(dancing.status.success());
+    assert_eq!(stdout(&dancing), stdout(&default));
+}
+
+#[test]
+fn fast_puzzles_keep_the_clues_asked_for() {
+    let generated = run(
+        &[
+            "solve", "--fast", "--clues", "40", "--seeds", "B1-R2-S3", "--format", "json",
+        ],
+        "",
+    );
+    let output = stdout(&generated);
+    let puzzle = output.split('"').nth(3).unwrap();
+    assert_eq!(puzzle.bytes().filter(|&cell| cell != b'0').count(), 40);
+
+    let too_few = run(
+        &["solve", "--fast", "--clues", "10", "--seeds", "B1-R2-S3"],
+        "",
+    );
+    assert_eq!(too_few.status.code(), Some(3));
+}
@@ -0,0 +1,1648 @@
+use std::{
+    collections::HashSet,
+    fs,
+    io::{self, Write},
+    ops::RangeInclusive,
+    sync::Mutex,
+    time::Duration,
+};
+
+use rand::SeedableRng;
+use rand_chacha::ChaCha8Rng;
+use sudoku_solver::{
+    board::{ClueClass, DrawOptions, GivenCellError, OverlayError, UnitKind, Variant},
+    daily::{daily_seed, ParseDateError, DAILY_DIFFICULTY},
+    generate_board, generate_daily, generate_full_grid, generate_pack_puzzle, generate_puzzle,
+    generate_puzzle_with_grid, generate_symmetric_puzzle, generate_unique_puzzle,
+    generate_with_difficulty,
+    generator::{
+        generate_pack_variant_puzzle, generate_variant_grid, pack_seed, validate_clue_range,
+        ParseSeedBundleError, EFFORT_SOLVER_SEED, MINIMUM_CLUES, PACK_CANDIDATES,
+        PATTERN_SEED_VERSION,
+    },
+    history::ReplayError,
+    hunt_grid, minimize_puzzle,
+    output::RecordError,
+    parse_mask,
+    repl::ReplError,
+    stats::{ParseStatsError, STATS_HEADER},
+    Board, ClueRangeError, ColorChoice, Contradiction, Date, Difficulty, Discrepancy, Game,
+    GameRecord, GeneratedPuzzle, GridMethod, MaskError, NoopObserver, OutputFormat, PackGenerator,
+    Palette, ParseMaskError, Record, RemovalSymmetry, ReplCommand, ReplSession, ResultWriter,
+    SatAssignmentError, SeedBundle, SolveError, Stats, TerminalGuard,
+};
+
+#[test]
+fn generate_remove_and_solve() {
+    let solution: Board = generate_board(42).parse().unwrap();
+    let mut puzzle = solution.clone();
+    let removed = puzzle.remove_clues(&mut ChaCha8Rng::seed_from_u64(43), 45);
+    assert!(solution.validate_board());
+    assert_eq!(removed, 36);
+    assert_eq!(puzzle.clue_count(), 45);
+    assert_eq!(puzzle.count_filled(), 45);
+    assert!(puzzle
+        .empty_cells()
+        .all(|(row, col)| !puzzle.is_given(row, col)));
+    assert_eq!(
+        puzzle.remove_clues(&mut ChaCha8Rng::seed_from_u64(43), 50),
+        0
+    );
+
+    let mut board = puzzle.clone();
+    board
+        .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut NoopObserver)
+        .unwrap();
+    assert!(board.validate_board());
+    assert!(puzzle.differs_from_solution(&board).is_empty());
+}
+
+/// Boards generated from a seed under the current `PATTERN_SEED_VERSION`. These change only
+/// together with the version
+#[test]
+fn pattern_boards_are_pinned_per_seed() {
+    assert_eq!(PATTERN_SEED_VERSION, 2);
+    assert_eq!(
+        generate_board(0),
+        "831257649964381275527694318153972864486513927792846531618735492249168753375429186"
+    );
+    assert_eq!(
+        generate_board(42),
+        "637582491825419673194637852258194367376825149941376285582941736419763528763258914"
+    );
+    for seed in 0..20 {
+        let board: Board = generate_board(seed).parse().unwrap();
+        assert!(board.validate_board(), "seed {seed}: {board}");
+        assert_eq!(generate_board(seed), board.to_string());
+    }
+}
+
+/// The string version returns the number of clues it left, drawn from the range it was given
+#[test]
+#[allow(deprecated)]
+fn string_removal_counts_the_clues_left() {
+    let mut puzzle_string = generate_board(42);
+    let clues = sudoku_solver::remove_board_cells(&mut puzzle_string, 43, 45..=50).unwrap();
+    let puzzle: Board = puzzle_string.parse().unwrap();
+    assert_eq!(puzzle.count_filled(), clues);
+    assert!((45..=50).contains(&clues));
+
+    let mut puzzle_string = generate_board(42);
+    assert_eq!(
+        sudoku_solver::remove_board_cells(&mut puzzle_string, 43, RangeInclusive::new(30, 20)),
+        Err(ClueRangeError::Empty(RangeInclusive::new(30, 20)))
+    );
+    assert_eq!(puzzle_string, generate_board(42));
+}
+
+#[test]
+fn clue_ranges_are_inclusive() {
+    for (start, end) in [(20, 30), (17, 17), (30, 30), (81, 81), (24, 25)] {
+        let mut seen = Vec::new();
+        for seed in 0..20 {
+            let clues = generate_puzzle(seed, seed, start..=end).puzzle.clue_count();
+            assert!(
+                (start..=end).contains(&clues),
+                "{clues} outside {start}..={end}"
+            );
+            seen.push(clues);
+        }
+        // Both ends of a narrow range turn up, so the end is not left out
+        assert!(
+            end - start > 1 || seen.contains(&start) && seen.contains(&end),
+            "{start}..={end}: {seen:?}"
+        );
+    }
+    assert_eq!(
+        validate_clue_range(&(16..=30)),
+        Err(ClueRangeError::OutOfBounds(16..=30))
+    );
+    assert_eq!(
+        validate_clue_range(&(20..=82)),
+        Err(ClueRangeError::OutOfBounds(20..=82))
+    );
+    assert!(validate_clue_range(&(17..=81)).is_ok());
+    assert_eq!(
+        ClueRangeError::Empty(RangeInclusive::new(30, 20)).to_string(),
+        "The clue range 30..=20 is empty, its start must not be above its end"
+    );
+}
+
+#[test]
+#[should_panic(expected = "The clue range 10..=20 must lie within 17..=81")]
+fn generating_with_too_few_clues_panics_clearly() {
+    generate_puzzle(0, 0, 10..=20);
+}
+
+/// The distinct sets of digits the three-cell rows of a band hold. A band that is cyclic shifts
+/// of its first row only ever has three of them
+fn band_digit_patterns(board: &Board, band: usize) -> HashSet<[bool; 10]> {
+    let mut patterns = HashSet::new();
+    for row in band * 3..band * 3 + 3 {
+        for stack in 0..3 {
+            let mut digits = [false; 10];
+            for col in stack * 3..stack * 3 + 3 {
+                digits[board.get_cell(row, col) as usize] = true;
+            }
+            patterns.insert(digits);
+        }
+    }
+    patterns
+}
+
+#[test]
+fn full_grids_are_not_all_shifted_patterns() {
+    for seed in 0..20 {
+        let grid = GridMethod::Pattern.grid(seed);
+        assert!((0..3).all(|band| band_digit_patterns(&grid, band).len() == 3));
+    }
+
+    let mut grids = HashSet::new();
+    let mut unshifted = 0;
+    for seed in 0..1000 {
+        let grid = generate_full_grid(seed);
+        assert!(grid.validate_board(), "seed {seed}: {grid}");
+        assert_eq!(grid.clue_count(), 81);
+        if (0..3).any(|band| band_digit_patterns(&grid, band).len() > 3) {
+            unshifted += 1;
+        }
+        grids.insert(grid.to_string());
+    }
+    assert_eq!(grids.len(), 1000);
+    assert!(
+        unshifted > 900,
+        "only {unshifted} grids are not shifted patterns"
+    );
+    assert_eq!(generate_full_grid(7), GridMethod::default().grid(7));
+
+    let generated = generate_puzzle_with_grid(GridMethod::Pattern, 42, 43, 30..=30);
+    assert_eq!(generated.solution.to_string(), generate_board(42));
+    assert_eq!(
+        generate_puzzle(42, 43, 30..=30).solution,
+        generate_full_grid(42)
+    );
+}
+
+#[test]
+fn generated_puzzles_have_a_unique_solution() {
+    for seed in 0..8 {
+        let generated = generate_unique_puzzle(seed, 24);
+        let puzzle = &generated.puzzle;
+        assert_eq!(puzzle.count_solutions_dlx(2), 1, "seed {seed}: {puzzle}");
+        assert!(puzzle.clue_count() >= 24);
+        assert!(
+            puzzle.clue_count() < 40,
+            "seed {seed} kept {} clues",
+            puzzle.clue_count()
+        );
+        assert!(puzzle.differs_from_solution(&generated.solution).is_empty());
+        assert!(generated.solution.validate_board());
+        assert_eq!(generate_unique_puzzle(seed, 24), generated);
+    }
+    // Digging stops at the target even when more cells could go
+    assert_eq!(generate_unique_puzzle(3, 50).puzzle.clue_count(), 50);
+}
+
+/// Every check either digs cells out or proves they have to stay, and neither is ever checked
+/// again, so digging takes at most one check per cell
+#[test]
+fn digging_checks_every_cell_at_most_once() {
+    for seed in 0..4 {
+        let generated = generate_unique_puzzle(seed, 22);
+        let checks = generated.uniqueness_checks;
+        assert!(checks > 0);
+        assert!(checks <= 81, "seed {seed} took {checks} checks");
+    }
+    let symmetric = generate_symmetric_puzzle(1, 22, RemovalSymmetry::Rotational180);
+    assert!(symmetric.uniqueness_checks <= 41);
+    assert_eq!(generate_puzzle(1, 1, 22..=22).uniqueness_checks, 0);
+}
+
+#[test]
+fn symmetric_removal_keeps_the_clue_pattern_symmetric() {
+    for symmetry in RemovalSymmetry::ALL.into_iter().skip(1) {
+        for seed in 0..3 {
+            let generated = generate_symmetric_puzzle(seed, 26, symmetry);
+            let puzzle = &generated.puzzle;
+            assert_eq!(
+                puzzle.clue_symmetry(),
+                symmetry.kind(),
+                "{symmetry}, seed {seed}"
+            );
+            assert_eq!(puzzle.count_solutions_dlx(2), 1, "{symmetry}, seed {seed}");
+            assert!(puzzle.clue_count() >= 26);
+            for (row, col) in (0..81).map(|i| (i / 9, i % 9)) {
+                let (partner_row, partner_col) = symmetry.partner(row, col);
+                assert_eq!(
+                    puzzle.get_cell(row, col) == 0,
+                    puzzle.get_cell(partner_row, partner_col) == 0
+                );
+            }
+        }
+    }
+    // Without a symmetry the digging is the same as for any unique puzzle
+    assert_eq!(
+        generate_symmetric_puzzle(5, 24, RemovalSymmetry::None),
+        generate_unique_puzzle(5, 24)
+    );
+}
+
+#[test]
+fn generated_puzzles_carry_their_metadata() {
+    let generated = generate_symmetric_puzzle(4, 26, RemovalSymmetry::Rotational180);
+    assert_eq!(generated.clue_count, generated.puzzle.clue_count());
+    assert_eq!(generated.solver_seed, 4);
+    assert_eq!(generated.difficulty, None);
+    assert_eq!(generated.symmetry, RemovalSymmetry::Rotational180);
+    assert!(generated
+        .to_string()
+        .ends_with("grade: ungraded, symmetry: rotational"));
+
+    let generated = generated.with_solver_seed(99).graded();
+    let difficulty = generated.puzzle.grade().difficulty;
+    assert_eq!(generated.difficulty, Some(difficulty));
+    assert_eq!(
+        generated.to_string(),
+        format!(
+            "Board seed: 4, removal seed: 4, solve seed: 99, clues: {}, grade: {difficulty}, \
+             symmetry: rotational",
+            generated.clue_count
+        )
+    );
+
+    let sdk = generated.to_sdk();
+    let lines: Vec<&str> = sdk.lines().collect();
+    assert_eq!(lines.len(), 17);
+    assert!(lines[..8].iter().all(|line| line.starts_with("#C ")));
+    assert!(lines.contains(&"#C solver seed: 99"));
+    assert!(lines.contains(&"#C variant: classic"));
+    assert!(lines.contains(&format!("#C solution: {}", generated.solution).as_str()));
+    let rows: String = lines[8..].concat();
+    assert_eq!(rows.parse::<Board>().unwrap(), generated.puzzle);
+    assert_eq!(rows.matches('.').count(), 81 - generated.clue_count);
+
+    assert_eq!(
+        generate_puzzle(1, 2, 30..=30).symmetry,
+        RemovalSymmetry::None
+    );
+    let difficulty = generate_with_difficulty(1, Difficulty::Easy, 40).unwrap();
+    assert_eq!(difficulty.difficulty, Some(Difficulty::Easy));
+}
+
+#[test]
+fn x_sudoku_puzzles_keep_both_diagonals_and_a_single_solution() {
+    let grids: Vec<Board> = (0..10)
+        .map(|seed| generate_variant_grid(Variant::X, seed))
+        .collect();
+    for grid in &grids {
+        assert!(grid.validate_variant(Variant::X));
+    }
+    assert_eq!(grids.iter().collect::<HashSet<_>>().len(), grids.len());
+    assert_eq!(generate_variant_grid(Variant::X, 3), grids[3]);
+    assert_eq!(
+        generate_variant_grid(Variant::Classic, 3),
+        generate_full_grid(3)
+    );
+
+    // The main diagonal of this grid holds 5 twice
+    let classic: Board = SOLUTION.parse().unwrap();
+    assert!(classic.validate_variant(Variant::Classic));
+    assert!(!classic.validate_variant(Variant::X));
+    assert_eq!(classic.count_variant_solutions(Variant::X, 2), 0);
+
+    for index in 0..3 {
+        let generated = generate_pack_variant_puzzle(Variant::X, 7, index, 24..=28, true);
+        assert_eq!(generated.variant, Variant::X);
+        assert!(generated.solution.validate_variant(Variant::X));
+        assert_eq!(generated.puzzle.count_variant_solutions(Variant::X, 2), 1);
+        assert!(generated.clue_count >= 24);
+        assert!(generated.uniqueness_checks > 0);
+        assert!(generated
+            .puzzle
+            .filled_cells()
+            .all(|((row, col), value)| { generated.solution.get_cell(row, col) == value }));
+        assert!(generated.to_sdk().contains("#C variant: x\n"));
+    }
+    let fast = generate_pack_variant_puzzle(Variant::X, 7, 0, 30..=30, false);
+    assert_eq!(fast.clue_count, 30);
+    assert!(fast.solution.validate_variant(Variant::X));
+    assert_eq!(
+        generate_pack_variant_puzzle(Variant::Classic, 7, 0, 24..=28, true),
+        generate_pack_puzzle(7, 0, 24..=28, true)
+    );
+}
+
+#[test]
+fn packs_are_reproducible_from_their_master_seed() {
+    assert_eq!(pack_seed(10, 3), 13);
+    assert_eq!(pack_seed(u64::MAX, 1), 0);
+
+    for index in 0..4 {
+        let generated = generate_pack_puzzle(10, index, 24..=28, true);
+        assert_eq!(generated.board_seed, 10 + index);
+        assert_eq!(generated.puzzle.count_solutions_dlx(2), 1);
+        assert!(generated.clue_count >= 24);
+        assert_eq!(generate_pack_puzzle(10, index, 24..=28, true), generated);
+    }
+    let generated = generate_pack_puzzle(10, 2, 24..=28, false);
+    assert_eq!(generated, generate_puzzle(12, 12, 24..=28));
+    assert!((24..=28).contains(&generated.clue_count));
+}
+
+#[test]
+fn pack_generators_keep_only_what_they_are_asked_for() {
+    let mut pack = PackGenerator::new(10, 24..=28).with_unique();
+    for index in 0..2 {
+        let generated = pack.next().unwrap().unwrap();
+        let expected = generate_pack_puzzle(10, index, 24..=28, true).graded();
+        assert_eq!(generated.puzzle, expected.puzzle);
+        assert_eq!(generated.difficulty, expected.difficulty);
+        assert_eq!(
+            generated.effort.map(|effort| effort.backtracks),
+            expected
+                .with_effort()
+                .effort
+                .map(|effort| effort.backtracks)
+        );
+    }
+    assert_eq!(pack.discarded(), 0);
+
+    let mut effortless = PackGenerator::new(10, 24..=28).with_max_effort(0);
+    for generated in effortless.by_ref().take(3) {
+        assert_eq!(generated.unwrap().effort.unwrap().backtracks, 0);
+    }
+
+    let mut too_easy = PackGenerator::new(10, 60..=60).with_difficulty(Difficulty::Diabolical);
+    let exhausted = too_easy.next().unwrap().unwrap_err();
+    assert_eq!(exhausted.too_easy, PACK_CANDIDATES);
+    assert_eq!(
+        exhausted.to_string(),
+        "Generated 200 candidates in a row, none graded Diabolical; try fewer clues"
+    );
+    assert_eq!(too_easy.discarded(), PACK_CANDIDATES as u64);
+    assert_eq!(too_easy.next(), None);
+}
+
+#[test]
+fn minimized_puzzles_lose_uniqueness_without_any_clue() {
+    let solution = generate_full_grid(3);
+    let puzzle = minimize_puzzle(&solution, 4);
+    assert_eq!(puzzle.count_solutions_dlx(2), 1);
+    assert!(puzzle.differs_from_solution(&solution).is_empty());
+    for (row, col) in (0..81).map(|i| (i / 9, i % 9)) {
+        if puzzle.get_cell(row, col) != 0 {
+            let mut fewer = puzzle.clone();
+            fewer.set_cell(row, col, 0);
+            assert_eq!(fewer.count_solutions_dlx(2), 2, "clue at {row}, {col}");
+        }
+    }
+
+    let finds = hunt_grid(3, 3);
+    assert_eq!(finds.len(), 3);
+    for find in &finds {
+        assert_eq!(find.grid_seed, 3);
+        assert_eq!(find.clues, find.puzzle.clue_count());
+        assert_eq!(find.puzzle, minimize_puzzle(&solution, find.order_seed));
+        assert!(find
+            .to_string()
+            .starts_with(&format!("{} clues: {}", find.puzzle, find.clues)));
+    }
+    assert_eq!(hunt_grid(3, 3), finds);
+}
+
+/// The mask of the cells a board has filled in
+fn mask_of(board: &Board) -> [bool; 81] {
+    std::array::from_fn(|index| board.get_cell(index / 9, index % 9) != 0)
+}
+
+#[test]
+fn masks_parse_from_a_line_or_a_grid() {
+    let line = format!("{}{}", "#.".repeat(40), "#");
+    let grid: Vec<String> = line
+        .as_bytes()
+        .chunks(9)
+        .map(|row| String::from_utf8(row.to_vec()).unwrap())
+        .collect();
+    let mask = parse_mask(&line).unwrap();
+    assert_eq!(mask.iter().filter(|keep| **keep).count(), 41);
+    assert_eq!(parse_mask(&(grid.join("\n") + "\n")), Ok(mask));
+    assert_eq!(parse_mask("#.#"), Err(ParseMaskError::WrongLength(3)));
+    assert_eq!(
+        parse_mask(&line.replacen('.', "x", 1)),
+        Err(ParseMaskError::InvalidCharacter {
+            index: 1,
+            character: 'x'
+        })
+    );
+}
+
+#[test]
+fn masks_keep_exactly_their_cells_or_report_the_extra_ones() {
+    let solution = generate_full_grid(3);
+    let minimal = minimize_puzzle(&solution, 3);
+    let mut board = solution.clone();
+    assert_eq!(board.remove_clues_by_mask(&mask_of(&minimal), true), Ok(()));
+    assert_eq!(board, minimal);
+
+    // Without one of its clues the minimal puzzle needs a cell put back
+    let mut mask = mask_of(&minimal);
+    let dropped = mask.iter().position(|keep| *keep).unwrap();
+    mask[dropped] = false;
+    let mut board = solution.clone();
+    assert_eq!(
+        board.remove_clues_by_mask(&mask, true),
+        Err(MaskError::ExtraCellsKept(vec![(0, 2)]))
+    );
+    assert_eq!(board.count_solutions_dlx(2), 1);
+    assert_eq!(board.clue_count(), minimal.clue_count());
+    assert!((0..81).all(|index| mask[index] <= (board.get_cell(index / 9, index % 9) != 0)));
+
+    let few: [bool; 81] = std::array::from_fn(|index| index < 16);
+    let mut board = solution.clone();
+    assert_eq!(
+        board.remove_clues_by_mask(&few, true),
+        Err(MaskError::TooFewCells(16))
+    );
+    assert_eq!(board.clue_count(), 81);
+    assert_eq!(board.remove_clues_by_mask(&few, false), Ok(()));
+    assert_eq!(board.clue_count(), 16);
+    assert_eq!(
+        board.remove_clues_by_mask(&[true; 81], false),
+        Err(MaskError::EmptyCell { position: (1, 7) })
+    );
+
+    let ambiguous = generate_puzzle(1, 1, 20..=20).puzzle;
+    let mut board = ambiguous.clone();
+    assert_eq!(
+        board.remove_clues_by_mask(&mask_of(&ambiguous), true),
+        Err(MaskError::NotUnique)
+    );
+    assert_eq!(board, ambiguous);
+}
+
+#[test]
+fn dates_parse_and_count_from_the_epoch() {
+    let leap_day: Date = "2024-02-29".parse().unwrap();
+    assert_eq!(leap_day, Date::new(2024, 2, 29).unwrap());
+    assert_eq!(leap_day.to_string(), "2024-02-29");
+    assert_eq!(Date::new(2023, 2, 29), None);
+    assert_eq!(Date::new(2023, 13, 1), None);
+    assert_eq!(
+        "2023-02-29".parse::<Date>(),
+        Err(ParseDateError::NoSuchDay("2023-02-29".to_string()))
+    );
+    for malformed in [
+        "2024-2-29",
+        "24-02-29",
+        "2024/02/29",
+        "2024-02-29-01",
+        "+024-02-29",
+    ] {
+        assert_eq!(
+            malformed.parse::<Date>(),
+            Err(ParseDateError::Format(malformed.to_string()))
+        );
+    }
+
+    assert_eq!(
+        Date::from_days_since_epoch(0),
+        Date::new(1970, 1, 1).unwrap()
+    );
+    assert_eq!(
+        Date::from_days_since_epoch(-1),
+        Date::new(1969, 12, 31).unwrap()
+    );
+    assert_eq!(
+        Date::from_days_since_epoch(11_016),
+        Date::new(2000, 2, 29).unwrap()
+    );
+    assert_eq!(
+        Date::from_days_since_epoch(19_723),
+        Date::new(2024, 1, 1).unwrap()
+    );
+    for days in [-719_468, -1, 0, 11_016, 19_723, 2_932_896] {
+        assert_eq!(Date::from_days_since_epoch(days).days_since_epoch(), days);
+    }
+}
+
+/// The puzzle of a day is part of the promise that everyone gets the same one, so these only
+/// change along with `DAILY_SALT` or the generator
+#[test]
+fn daily_puzzles_are_pinned_to_their_dates() {
+    let pinned = [
+        (
+            "2026-10-15",
+            17245062853576522242,
+            "090000500200083000500000080000402098000931007000600000000306000039000071001000004",
+        ),
+        (
+            "2024-02-29",
+            6771998597100644222,
+            "000003010040000000007060000830000096005030040004009080002050700100006200070020060",
+        ),
+    ];
+    for (date, seed, puzzle) in pinned {
+        let date: Date = date.parse().unwrap();
+        assert_eq!(daily_seed(date), seed);
+        let generated = generate_daily(date);
+        assert_eq!(generated.puzzle.to_string(), puzzle, "{date}");
+        assert_eq!(generated.difficulty, Some(DAILY_DIFFICULTY));
+        assert_eq!(generated.puzzle.count_solutions_dlx(2), 1);
+    }
+}
+
+#[test]
+fn seed_bundles_reproduce_a_run_exactly() {
+    let seeds: SeedBundle = "B12499731774094038275-R8137985501619016255-S42"
+        .parse()
+        .unwrap();
+    assert_eq!(
+        seeds,
+        SeedBundle {
+            board: 12499731774094038275,
+            removal: 8137985501619016255,
+            solver: 42
+        }
+    );
+    assert_eq!(seeds.to_string().parse(), Ok(seeds));
+    for malformed in ["B1-R2", "B1-R2-S3-T4", "R1-B2-S3", "B1-R2-Sx", "B-1-R2-S3"] {
+        assert_eq!(
+            malformed.parse::<SeedBundle>(),
+            Err(ParseSeedBundleError(malformed.to_string()))
+        );
+    }
+
+    let run = |seeds: SeedBundle| {
+        let generated =
+            generate_puzzle(seeds.board, seeds.removal, 17..=24).with_solver_seed(seeds.solver);
+        assert_eq!(generated.seeds(), seeds);
+        let mut board = generated.puzzle.clone();
+        let report = board
+            .solve_board(
+                &mut ChaCha8Rng::seed_from_u64(generated.solver_seed),
+                &mut NoopObserver,
+            )
+            .unwrap();
+        (generated.puzzle, report.history, report.backtracks)
+    };
+    let first = run(seeds);
+    assert!(!first.1.is_empty());
+    assert_eq!(run(seeds.to_string().parse().unwrap()), first);
+}
+
+#[test]
+fn solve_effort_is_measured_with_a_fixed_seed() {
+    let generated = generate_puzzle(183, 184, 17..=24);
+    assert_eq!(generated.effort, None);
+    assert_eq!(generated.csv_row(), "183,184,184,18,,,");
+
+    let measured = generated.clone().graded().with_effort();
+    let effort = measured.effort.unwrap();
+    let mut board = generated.puzzle.clone();
+    let report = board
+        .solve_board(
+            &mut ChaCha8Rng::seed_from_u64(EFFORT_SOLVER_SEED),
+            &mut NoopObserver,
+        )
+        .unwrap();
+    assert_eq!(effort.backtracks, report.backtracks);
+    assert!(effort.backtracks > 0);
+    assert_eq!(
+        generated.with_effort().effort.unwrap().backtracks,
+        effort.backtracks
+    );
+
+    let row = measured.csv_row();
+    assert_eq!(
+        row.split(',').count(),
+        GeneratedPuzzle::CSV_HEADER.split(',').count()
+    );
+    assert!(row.starts_with(&format!(
+        "183,184,184,18,{},{},",
+        measured.difficulty.unwrap(),
+        effort.backtracks
+    )));
+    assert!(measured.to_sdk().contains(&format!(
+        "#C effort: {} backtracks in {}us\n",
+        effort.backtracks,
+        effort.elapsed.as_micros()
+    )));
+}
+
+#[test]
+fn puzzles_are_generated_at_the_asked_difficulty() {
+    for target in [Difficulty::Easy, Difficulty::Hard] {
+        let generated = generate_with_difficulty(1, target, 40).unwrap();
+        assert_eq!(generated.puzzle.grade().difficulty, target);
+        assert_eq!(generated.puzzle.count_solutions_dlx(2), 1);
+    }
+
+    let error = generate_with_difficulty(1, Difficulty::Medium, 2).unwrap_err();
+    assert_eq!(error.attempts, 2);
+    assert_eq!(
+        error.closest.puzzle.grade().difficulty,
+        error.closest_difficulty
+    );
+    assert_ne!(error.closest_difficulty, Difficulty::Medium);
+    assert!(error
+        .to_string()
+        .starts_with("No Medium puzzle in 2 attempts"));
+}
+
+#[test]
+fn parse_errors() {
+    assert!("123".parse::<Board>().is_err());
+    assert!("x".repeat(81).parse::<Board>().is_err());
+    assert!(".".repeat(81).parse::<Board>().is_ok());
+}
+
+#[test]
+fn candidates_are_the_missing_digits() {
+    let board: Board =
+        "123456700000000000000000000000000000000000000000000000000000000000000000000000000"
+            .parse()
+            .unwrap();
+
+    assert_eq!(board.calculate_entropy_at_cell(0, 7), Some(vec![8, 9]));
+    assert_eq!(board.calculate_entropy_at_cell(0, 8), Some(vec![8, 9]));
+    assert_eq!(board.calculate_entropy_at_cell(0, 0), None);
+    let grid = board.candidates_grid();
+    assert_eq!(grid[0][8].unwrap().iter().collect::<Vec<_>>(), vec![8, 9]);
+    assert!(!grid[5][5].unwrap().contains(0));
+}
+
+#[test]
+fn candidates_follow_every_write() {
+    let mut board: Board = "0".repeat(81).parse().unwrap();
+    board.set_cell(0, 0, 5);
+    board.set_cell(0, 8, 5);
+    assert!(!board.candidates_at(0, 4).unwrap().contains(5));
+
+    // Row 1 holds 5 twice, so clearing one of them must not free the digit
+    board.set_cell(0, 0, 0);
+    assert!(!board.candidates_at(0, 4).unwrap().contains(5));
+    assert!(board.candidates_at(4, 0).unwrap().contains(5));
+    board.set_cell(0, 8, 0);
+    assert!(board.candidates_at(0, 4).unwrap().contains(5));
+
+    let mut puzzle: Board =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    puzzle.relabel_digits([9, 8, 7, 6, 5, 4, 3, 2, 1]);
+    let rebuilt: Board = puzzle.to_string().parse().unwrap();
+    assert_eq!(puzzle.candidates_grid(), rebuilt.candidates_grid());
+    let rotated = puzzle.rotated_90();
+    let rebuilt: Board = rotated.to_string().parse().unwrap();
+    assert_eq!(rotated.candidates_grid(), rebuilt.candidates_grid());
+}
+
+/// Writes and clears cells in a scrambled order, checking the cached least entropy cell against
+/// one found from the candidates grid after every change
+#[test]
+fn least_entropy_follows_writes_and_undos() {
+    let solution: Board = generate_board(7).parse().unwrap();
+    let mut board: Board = "0".repeat(81).parse().unwrap();
+    let order: Vec<usize> = (0..81).map(|i| (i * 37) % 81).collect();
+
+    let check = |board: &Board| {
+        let grid = board.candidates_grid();
+        let expected = board
+            .empty_cells()
+            .map(|(row, col)| ((row, col), grid[row][col].unwrap()))
+            .min_by_key(|(_, candidates)| candidates.len())
+            .map(|(position, candidates)| (position, candidates.iter().collect::<Vec<_>>()));
+        assert_eq!(board.find_least_entropy(), expected);
+    };
+
+    for &index in &order {
+        board.set_cell(
+            index / 9,
+            index % 9,
+            solution.get_cell(index / 9, index % 9),
+        );
+        check(&board);
+    }
+    for &index in order.iter().rev().step_by(2) {
+        board.set_cell(index / 9, index % 9, 0);
+        check(&board);
+    }
+}
+
+const PUZZLE: &str =
+    "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+const SOLUTION: &str =
+    "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+/// Every variable of the CNF, true exactly when the solution places that digit in that cell
+fn solution_assignment() -> Vec<i32> {
+    let solution: Vec<u8> = SOLUTION.bytes().map(|byte| byte - b'0').collect();
+    (1..=729)
+        .map(|variable: i32| {
+            let index = variable as usize - 1;
+            if solution[index / 9] as usize == index % 9 + 1 {
+                variable
+            } else {
+                -variable
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn dimacs_holds_the_rules_and_the_givens() {
+    let empty: Board = ".".repeat(81).parse().unwrap();
+    let puzzle: Board = PUZZLE.parse().unwrap();
+    // 81 cells with at least one digit, then 36 pairs for each cell and each digit of each unit
+    let rules = 81 + 4 * 81 * 36;
+    for (board, clauses) in [(&empty, rules), (&puzzle, rules + 30)] {
+        let cnf = board.to_dimacs();
+        let header = cnf.lines().find(|line| line.starts_with("p ")).unwrap();
+        assert_eq!(header, format!("p cnf 729 {clauses}"));
+        let body = cnf.lines().filter(|line| !line.starts_with(['c', 'p']));
+        assert_eq!(body.count(), clauses);
+    }
+
+    // The known solution satisfies every clause of the puzzle
+    let assignment = solution_assignment();
+    let cnf = puzzle.to_dimacs();
+    for clause in cnf.lines().filter(|line| !line.starts_with(['c', 'p'])) {
+        let literals: Vec<i32> = clause
+            .split_whitespace()
+            .map(|l| l.parse().unwrap())
+            .collect();
+        assert_eq!(literals.last(), Some(&0));
+        assert!(
+            literals.iter().any(|literal| assignment.contains(literal)),
+            "{clause}"
+        );
+    }
+}
+
+#[test]
+fn sat_assignments_read_back_into_boards() {
+    let mut assignment = solution_assignment();
+    let board = Board::from_sat_assignment(&assignment).unwrap();
+    assert_eq!(board.to_string(), SOLUTION);
+    assert!(board.validate_board());
+
+    // The terminating zero of a `v` line is skipped, and so are the false literals
+    let positive: Vec<i32> = assignment
+        .iter()
+        .copied()
+        .filter(|&l| l > 0)
+        .chain([0])
+        .collect();
+    assert_eq!(Board::from_sat_assignment(&positive).unwrap(), board);
+
+    assert_eq!(
+        Board::from_sat_assignment(&[730]),
+        Err(SatAssignmentError::VariableOutOfRange(730))
+    );
+    // Row 0, column 0 holds a 5, so its variable is 5 and 1 is false
+    assignment[0] = 1;
+    assert_eq!(
+        Board::from_sat_assignment(&assignment),
+        Err(SatAssignmentError::Ambiguous {
+            cell: (0, 0),
+            values: (1, 5)
+        })
+    );
+    assignment[0] = -1;
+    assignment[4] = -5;
+    assert_eq!(
+        Board::from_sat_assignment(&assignment),
+        Err(SatAssignmentError::Unassigned { cell: (0, 0) })
+    );
+}
+
+#[test]
+fn conflicts_follow_every_write() {
+    let mut board: Board = SOLUTION.parse().unwrap();
+    assert!(board.validate_board());
+    assert!(board.conflicts().is_empty());
+
+    // The 3 at r1c2 becomes a second 1 in its row, its column and its tile
+    board.set_cell(0, 1, 1);
+    assert!(!board.validate_board());
+    let conflicts = board.conflicts();
+    assert_eq!(conflicts.len(), 3);
+    assert!(conflicts.iter().all(|conflict| conflict.value == 1));
+
+    // A third 1 in the row and tile makes three pairs in each, plus one in column 0
+    board.set_cell(0, 0, 1);
+    assert_eq!(board.conflicts().len(), 8);
+    board.set_cell(0, 0, 5);
+    board.set_cell(0, 1, 0);
+    assert!(board.conflicts().is_empty());
+    assert!(!board.validate_board());
+    board.set_cell(0, 1, 3);
+    assert!(board.validate_board());
+}
+
+#[test]
+fn games_track_entries_mistakes_and_undos() {
+    let generated = generate_unique_puzzle(3, 30);
+    let mut game = Game::new(generated.puzzle.clone(), generated.solution.clone());
+    let given = generated.puzzle.filled_cells().next().unwrap().0;
+    let (row, col) = generated.puzzle.empty_cells().next().unwrap();
+
+    game.move_cursor(-1, -1);
+    assert_eq!(game.cursor(), (8, 8));
+    game.move_cursor(given.0 as isize + 1, given.1 as isize + 1);
+    assert_eq!(game.cursor(), given);
+    assert!(game.enter(0).is_err());
+
+    game.move_cursor(
+        row as isize - given.0 as isize,
+        col as isize - given.1 as isize,
+    );
+    let wrong = generated.solution.get_cell(row, col) % 9 + 1;
+    game.enter(wrong).unwrap();
+    assert_eq!(game.mistakes(), vec![(row, col)]);
+    game.move_cursor(4, 4);
+    assert!(game.undo());
+    assert_eq!(game.cursor(), (row, col));
+    assert_eq!(game.board(), &generated.puzzle);
+    assert!(!game.undo());
+
+    for (row, col) in generated.puzzle.empty_cells() {
+        let (current_row, current_col) = game.cursor();
+        game.move_cursor(
+            row as isize - current_row as isize,
+            col as isize - current_col as isize,
+        );
+        assert!(!game.is_solved());
+        game.enter(generated.solution.get_cell(row, col)).unwrap();
+    }
+    assert!(game.is_solved());
+    assert!(game.mistakes().is_empty());
+}
+
+#[test]
+fn checking_a_solution_lists_every_discrepancy() {
+    let generated = generate_unique_puzzle(5, 30);
+    assert!(generated
+        .puzzle
+        .check_solution(&generated.solution)
+        .is_empty());
+
+    let ((row, col), given) = generated.puzzle.filled_cells().next().unwrap();
+    let (empty_row, empty_col) = generated.puzzle.empty_cells().next().unwrap();
+    let mut solution = generated.solution.clone();
+    let changed = given % 9 + 1;
+    solution.set_cell(row, col, changed);
+    solution.set_cell(empty_row, empty_col, 0);
+    let discrepancies = generated.puzzle.check_solution(&solution);
+    assert_eq!(
+        discrepancies.first(),
+        Some(&Discrepancy::ChangedGiven {
+            position: (row, col),
+            given,
+            value: changed,
+        })
+    );
+    assert!(discrepancies.contains(&Discrepancy::Duplicate {
+        unit: UnitKind::Row,
+        index: row,
+        value: changed,
+    }));
+    assert_eq!(
+        discrepancies.last(),
+        Some(&Discrepancy::EmptyCell {
+            position: (empty_row, empty_col),
+        })
+    );
+    assert_eq!(
+        discrepancies.last().unwrap().to_string(),
+        format!("r{}c{} is empty", empty_row + 1, empty_col + 1)
+    );
+}
+
+/// What a simulated terminal went through, in order
+static TERMINAL_LOG: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+fn log_raw_mode(enabled: bool) -> io::Result<()> {
+    TERMINAL_LOG
+        .lock()
+        .unwrap()
+        .push(format!("raw mode {}", if enabled { "on" } else { "off" }));
+    Ok(())
+}
+
+/// Logs every write, with the escape character dropped so the commands can be compared
+struct LoggingTerminal;
+
+impl Write for LoggingTerminal {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let text = String::from_utf8_lossy(buf).replace('\x1b', "");
+        TERMINAL_LOG.lock().unwrap().push(text);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn terminal_guards_restore_in_reverse_order_even_when_unwinding() {
+    let result = std::panic::catch_unwind(|| {
+        let mut guard = TerminalGuard::with_raw_mode(LoggingTerminal, log_raw_mode).unwrap();
+        write!(guard, "frame").unwrap();
+        panic!("partway through drawing");
+    });
+    assert!(result.is_err());
+    assert_eq!(
+        *TERMINAL_LOG.lock().unwrap(),
+        [
+            "raw mode on",
+            "[?1049h",
+            "[?25l",
+            "frame",
+            "[?25h",
+            "[?1049l",
+            "raw mode off"
+        ]
+    );
+}
+
+#[test]
+fn ambiguous_puzzles_list_their_solutions_in_a_stable_order() {
+    let solution: Board =
+        "534678912672195348198342567859761423426853791713924856961537284287419635345286179"
+            .parse()
+            .unwrap();
+    // Clearing a rectangle of two values swapped between two rows of a band leaves two ways
+    // to fill it back in
+    let mut puzzle = solution.clone();
+    for (row, col) in [(3, 5), (3, 8), (4, 5), (4, 8)] {
+        puzzle.set_cell(row, col, 0);
+    }
+    let puzzle: Board = puzzle.to_string().parse().unwrap();
+    let solutions = puzzle.solutions(5);
+    assert_eq!(solutions.len(), 2);
+    assert_eq!(solutions[0].to_string(), solution.to_string());
+    assert_eq!(
+        solutions[1].to_string(),
+        "534678912672195348198342567859763421426851793713924856961537284287419635345286179"
+    );
+    assert!(solutions.iter().all(Board::validate_board));
+    assert_eq!(puzzle.solutions(5), solutions);
+    assert_eq!(puzzle.solutions(1), solutions[..1]);
+}
+
+/// The text left once every escape sequence setting a style is taken out
+fn strip_styles(styled: &str) -> String {
+    let mut plain = String::new();
+    let mut rest = styled;
+    while let Some(start) = rest.find("\x1b[") {
+        plain += &rest[..start];
+        let end = rest[start..].find('m').unwrap();
+        rest = &rest[start + end + 1..];
+    }
+    plain + rest
+}
+
+#[test]
+fn boards_drawn_without_colors_match_the_plain_rendering() {
+    let mut board: Board =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    board.set_cell(0, 2, 4);
+    board.set_cell(0, 3, 5);
+    let plain = board.render_board(&DrawOptions::default());
+    let uncolored = DrawOptions {
+        palette: Palette::plain(),
+        ..DrawOptions::default()
+    };
+    assert_eq!(board.render_board(&uncolored), plain);
+    assert!(!plain.contains('\x1b'));
+    for row in 0..9 {
+        assert!(plain.contains(&board.get_row(row).to_string()));
+    }
+
+    let palette = Palette::colored();
+    let colored = board.render_board(&DrawOptions {
+        palette,
+        ..DrawOptions::default()
+    });
+    assert_ne!(colored, plain);
+    assert_eq!(strip_styles(&colored), plain);
+    assert!(colored.contains(&palette.given.apply(3).to_string()));
+    assert!(colored.contains(&palette.solved.apply(4).to_string()));
+    // The 5 entered in r1c4 clashes with the given 5 in r1c1
+    assert!(colored.contains(&palette.conflict.apply(5).to_string()));
+}
+
+#[test]
+fn colors_follow_the_choice_the_terminal_and_no_color() {
+    let set = Some(std::ffi::OsStr::new("1"));
+    let empty = Some(std::ffi::OsStr::new(""));
+    assert!(ColorChoice::Auto.colors_enabled(true, None));
+    assert!(ColorChoice::Auto.colors_enabled(true, empty));
+    assert!(!ColorChoice::Auto.colors_enabled(true, set));
+    assert!(!ColorChoice::Auto.colors_enabled(false, None));
+    assert!(ColorChoice::Always.colors_enabled(false, set));
+    assert!(!ColorChoice::Never.colors_enabled(true, None));
+    assert_eq!(ColorChoice::default(), ColorChoice::Auto);
+    assert_eq!(
+        Palette::for_output(ColorChoice::Never, true),
+        Palette::plain()
+    );
+    assert_eq!(
+        Palette::for_output(ColorChoice::Always, false),
+        Palette::colored()
+    );
+    assert!(Palette::plain().is_plain() && !Palette::colored().is_plain());
+}
+
+#[test]
+fn repl_commands_parse_with_helpful_errors() {
+    assert_eq!(
+        "set R4c7 5".parse(),
+        Ok(ReplCommand::Set {
+            cell: (3, 6),
+            value: 5
+        })
+    );
+    assert_eq!(
+        "  count-solutions ".parse(),
+        Ok(ReplCommand::CountSolutions)
+    );
+    assert_eq!(
+        "set r4c7".parse::<ReplCommand>(),
+        Err(ReplError::Usage("set r4c7 5"))
+    );
+    assert_eq!(
+        "clear r10c1".parse::<ReplCommand>(),
+        Err(ReplError::BadCell("r10c1".to_string()))
+    );
+    assert_eq!(
+        "set r1c1 0".parse::<ReplCommand>(),
+        Err(ReplError::BadValue("0".to_string()))
+    );
+    let unknown = "place r1c1 4".parse::<ReplCommand>().unwrap_err();
+    assert_eq!(
+        unknown.to_string(),
+        "Unknown command \"place\", help lists the commands"
+    );
+}
+
+#[test]
+fn repl_sessions_undo_one_change_at_a_time() {
+    let puzzle: Board = PUZZLE.parse().unwrap();
+    let mut session = ReplSession::new(puzzle.clone(), DrawOptions::default());
+    let mut run = |line: &str| session.execute(&line.parse().unwrap());
+
+    assert_eq!(run("candidates r1c3"), Ok("r1c3: 1 2 4".to_string()));
+    assert_eq!(run("set r1c1 4"), Err(ReplError::CellIsGiven((0, 0))));
+    assert_eq!(run("clear r1c3"), Err(ReplError::CellIsEmpty((0, 2))));
+    assert_eq!(run("set r1c3 4"), Ok("r1c3 = 4".to_string()));
+    assert_eq!(
+        run("set r1c4 5"),
+        Ok("r1c4 = 5, which clashes with r1c1, r2c6".to_string())
+    );
+    assert_eq!(run("count-solutions"), Ok("No solution".to_string()));
+    assert!(matches!(run("solve"), Err(ReplError::Unsolvable(_))));
+    assert!(run("undo").is_ok());
+    assert!(run("solve").is_ok());
+    assert_eq!(run("candidates r1c3"), Ok("r1c3 holds 4".to_string()));
+
+    assert!(run("undo").is_ok());
+    assert!(run("undo").is_ok());
+    assert_eq!(run("undo"), Err(ReplError::NothingToUndo));
+    assert_eq!(session.board(), &puzzle);
+}
+
+#[test]
+fn repl_sessions_run_the_commands_of_a_reader() {
+    let puzzle: Board = PUZZLE.parse().unwrap();
+    let mut session = ReplSession::new(puzzle, DrawOptions::default());
+    let input = "# a comment\n\nset r1c3 4\nfly\nset r1c1\nquit\nclear r1c3\n";
+    let (mut output, mut errors) = (Vec::new(), Vec::new());
+    let failed = session
+        .run(input.as_bytes(), &mut output, &mut errors, false)
+        .unwrap();
+    assert_eq!(failed, 2);
+    assert_eq!(String::from_utf8(output).unwrap(), "r1c3 = 4\n");
+    let errors = String::from_utf8(errors).unwrap();
+    assert_eq!(
+        errors.lines().collect::<Vec<_>>(),
+        [
+            "Unknown command \"fly\", help lists the commands",
+            "Usage: set r4c7 5"
+        ]
+    );
+    assert_eq!(session.board().get_cell(0, 2), 4);
+}
+
+/// A solved game of the difficulty taking `seconds`, of the puzzle of the day if given one
+fn solved_game(difficulty: Difficulty, seconds: u64, daily: Option<Date>) -> GameRecord {
+    GameRecord {
+        difficulty,
+        solved: true,
+        elapsed: Duration::from_secs(seconds),
+        daily,
+    }
+}
+
+#[test]
+fn stats_keep_times_and_the_daily_streak() {
+    let day = |day| Date::new(2026, 10, day).unwrap();
+    let mut stats = Stats::new();
+    stats.record(&solved_game(Difficulty::Easy, 300, None));
+    stats.record(&solved_game(Difficulty::Easy, 100, None));
+    stats.record(&GameRecord {
+        solved: false,
+        ..solved_game(Difficulty::Easy, 50, None)
+    });
+    let easy = stats.difficulty(Difficulty::Easy);
+    assert_eq!((easy.played, easy.solved), (3, 2));
+    assert_eq!(easy.best, Some(Duration::from_secs(100)));
+    assert_eq!(easy.average(), Some(Duration::from_secs(200)));
+    assert_eq!(stats.difficulty(Difficulty::Hard).average(), None);
+
+    for (date, streak) in [(10, 1), (11, 2), (11, 2), (9, 2), (12, 3), (14, 1), (15, 2)] {
+        stats.record(&solved_game(Difficulty::Medium, 400, Some(day(date))));
+        assert_eq!(stats.current_streak(day(date)), streak, "2026-10-{date}");
+    }
+    stats.record(&GameRecord {
+        solved: false,
+        ..solved_game(Difficulty::Medium, 400, Some(day(16)))
+    });
+    assert_eq!(stats.current_streak(day(16)), 2);
+    assert_eq!(stats.current_streak(day(17)), 0);
+    assert_eq!(stats.best_streak(), 3);
+    assert_eq!(stats.last_daily(), Some(day(15)));
+    assert_eq!((stats.played(), stats.solved()), (11, 9));
+}
+
+#[test]
+fn stats_files_survive_being_missing_or_corrupt() {
+    let directory = std::env::temp_dir().join(format!("stats-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&directory);
+    let path = directory.join("nested").join("stats.txt");
+
+    let (stats, warning) = Stats::load(&path);
+    assert_eq!((stats, warning), (Stats::new(), None));
+    let date = Some(Date::new(2026, 10, 15).unwrap());
+    Stats::record_in_file(&path, &solved_game(Difficulty::Hard, 61, date)).unwrap();
+    let (updated, warning) =
+        Stats::record_in_file(&path, &solved_game(Difficulty::Hard, 59, None)).unwrap();
+    assert_eq!(warning, None);
+    assert_eq!(Stats::load(&path), (updated.clone(), None));
+    let text = fs::read_to_string(&path).unwrap();
+    assert_eq!(text.parse::<Stats>(), Ok(updated));
+    assert_eq!(
+        text,
+        format!(
+            "{STATS_HEADER}\nHard played 2 solved 2 best_ms 59000 total_ms 120000\n\
+             daily last 2026-10-15 streak 1 best 1\n"
+        )
+    );
+
+    assert_eq!(
+        "Hard played 1".parse::<Stats>(),
+        Err(ParseStatsError::MissingHeader)
+    );
+    for (line, reason) in [
+        ("Hard played 1 solved 2 total_ms 0", "more games solved"),
+        ("Hard played 1 solved 0 best_ms 5 total_ms 0", "a best time"),
+        ("Hard played one solved 0 total_ms 0", "\"one\""),
+        ("Hard played 1 solved 0", "missing \"total_ms\""),
+        ("Impossible played 1 solved 0 total_ms 0", "unknown grade"),
+        ("daily last yesterday streak 1 best 1", "\"yesterday\""),
+        ("daily last", "pairs"),
+    ] {
+        let error = format!("{STATS_HEADER}\n\n{line}\n")
+            .parse::<Stats>()
+            .unwrap_err();
+        let ParseStatsError::BadLine {
+            line: 3,
+            reason: found,
+        } = &error
+        else {
+            panic!("{line}: {error}");
+        };
+        assert!(found.contains(reason), "{line}: {error}");
+    }
+
+    fs::write(&path, "played: lots\n").unwrap();
+    let (stats, warning) = Stats::load(&path);
+    assert_eq!(stats, Stats::new());
+    assert!(warning.unwrap().contains("is not a stats file"));
+    let (stats, warning) =
+        Stats::record_in_file(&path, &solved_game(Difficulty::Easy, 30, None)).unwrap();
+    assert!(warning.is_some());
+    assert_eq!((stats.played(), stats.solved()), (1, 1));
+    assert_eq!(Stats::load(&path), (stats, None));
+    fs::remove_dir_all(&directory).unwrap();
+}
+
+/// A transform of the board, named for the failure messages
+type Transform = (&'static str, fn(&Board) -> Board);
+
+const TRANSFORMS: [Transform; 6] = [
+    ("transposed", Board::transposed),
+    ("rotated_90", Board::rotated_90),
+    ("rotated_180", Board::rotated_180),
+    ("rotated_270", Board::rotated_270),
+    ("mirrored_horizontal", Board::mirrored_horizontal),
+    ("mirrored_vertical", Board::mirrored_vertical),
+];
+
+#[test]
+fn transforms_turn_solved_boards_into_solved_boards() {
+    let puzzle: Board = PUZZLE.parse().unwrap();
+    for seed in 0..20 {
+        let solved: Board = generate_board(seed).parse().unwrap();
+        for (name, transform) in TRANSFORMS {
+            let transformed = transform(&solved);
+            assert!(transformed.validate_board(), "{name} of seed {seed}");
+            assert_ne!(transformed, solved, "{name} of seed {seed}");
+        }
+    }
+    for (name, transform) in TRANSFORMS {
+        let transformed = transform(&puzzle);
+        assert_eq!(transformed.clue_count(), puzzle.clue_count(), "{name}");
+        let givens = (0..81)
+            .filter(|&i| transformed.is_given(i / 9, i % 9))
+            .count();
+        assert_eq!(givens, puzzle.clue_count(), "{name}");
+        let mut solved = transformed.clone();
+        solved.solve_dlx().unwrap();
+        assert_eq!(solved, transform(&SOLUTION.parse().unwrap()), "{name}");
+    }
+}
+
+#[test]
+fn transforms_undo_themselves_in_the_expected_number_of_steps() {
+    let board: Board = PUZZLE.parse().unwrap();
+    let turned = (0..4).fold(board.clone(), |turned, _| turned.rotated_90());
+    assert_eq!(turned, board);
+    assert_eq!(board.rotated_90().rotated_90(), board.rotated_180());
+    assert_eq!(board.rotated_180().rotated_90(), board.rotated_270());
+    assert_eq!(board.rotated_270().rotated_90(), board);
+    for (name, transform) in [TRANSFORMS[0], TRANSFORMS[2], TRANSFORMS[4], TRANSFORMS[5]] {
+        assert_eq!(transform(&transform(&board)), board, "{name}");
+    }
+    assert_eq!(
+        board.mirrored_horizontal().mirrored_vertical(),
+        board.rotated_180()
+    );
+}
+
+#[test]
+fn swaps_keep_solved_boards_valid() {
+    let solved: Board = SOLUTION.parse().unwrap();
+    let puzzle: Board = PUZZLE.parse().unwrap();
+    let swaps: [fn(&mut Board, usize, usize, usize); 2] =
+        [Board::swap_rows_in_band, Board::swap_columns_in_stack];
+    for swap in swaps {
+        for group in 0..3 {
+            for (a, b) in [(0, 1), (0, 2), (1, 2), (1, 1)] {
+                let mut swapped = solved.clone();
+                swap(&mut swapped, group, a, b);
+                assert!(swapped.validate_board(), "{group}: {a} <-> {b}");
+                assert_eq!(swapped == solved, a == b, "{group}: {a} <-> {b}");
+                swap(&mut swapped, group, a, b);
+                assert_eq!(swapped, solved);
+            }
+        }
+    }
+    let whole_swaps: [fn(&mut Board, usize, usize); 2] = [Board::swap_bands, Board::swap_stacks];
+    for swap in whole_swaps {
+        for (a, b) in [(0, 1), (0, 2), (1, 2), (2, 2)] {
+            let mut swapped = solved.clone();
+            swap(&mut swapped, a, b);
+            assert!(swapped.validate_board(), "{a} <-> {b}");
+            assert_eq!(swapped == solved, a == b, "{a} <-> {b}");
+
+            // Givens travel with their values, so the swapped puzzle still solves to the
+            // swapped solution
+            let mut moved = puzzle.clone();
+            swap(&mut moved, a, b);
+            assert!(moved
+                .filled_cells()
+                .all(|((row, col), _)| moved.is_given(row, col)));
+            moved.solve_dlx().unwrap();
+            assert_eq!(moved, swapped, "{a} <-> {b}");
+        }
+    }
+
+    let mut relabeled = solved.clone();
+    relabeled.relabel_digits([9, 8, 7, 6, 5, 4, 3, 2, 1]);
+    assert!(relabeled.validate_board());
+    assert_eq!(relabeled.get_cell(0, 0), 10 - solved.get_cell(0, 0));
+}
+
+#[test]
+#[should_panic(expected = "Row indices must be below 3")]
+fn swapping_rows_outside_their_band_panics() {
+    let mut board: Board = SOLUTION.parse().unwrap();
+    board.swap_rows_in_band(0, 2, 3);
+}
+
+#[test]
+#[should_panic(expected = "Band index must be below 3")]
+fn swapping_a_band_past_the_last_panics() {
+    let mut board: Board = SOLUTION.parse().unwrap();
+    board.swap_bands(0, 3);
+}
+
+#[test]
+#[should_panic(expected = "Stack index must be below 3")]
+fn swapping_columns_of_a_stack_past_the_last_panics() {
+    let mut board: Board = SOLUTION.parse().unwrap();
+    board.swap_columns_in_stack(3, 0, 1);
+}
+
+#[test]
+fn one_wrong_digit_conflicts_in_its_row_column_and_tile() {
+    let mut board: Board = SOLUTION.parse().unwrap();
+    // r1c2 holds a 3, a 1 there meets the 1s at r1c8, r6c2 and r3c1
+    board.set_cell(0, 1, 1);
+    let conflicts: Vec<_> = board
+        .conflicts()
+        .into_iter()
+        .map(|conflict| {
+            (
+                conflict.unit,
+                conflict.first,
+                conflict.second,
+                conflict.value,
+            )
+        })
+        .collect();
+    assert_eq!(
+        conflicts,
+        [
+            (UnitKind::Row, (0, 1), (0, 7), 1),
+            (UnitKind::Column, (0, 1), (5, 1), 1),
+            (UnitKind::Tile, (0, 1), (2, 0), 1),
+        ]
+    );
+}
+
+#[test]
+fn setting_a_band_or_stack_to_itself_changes_nothing() {
+    for board in [PUZZLE, SOLUTION].map(|board| board.parse::<Board>().unwrap()) {
+        for n in 0..3 {
+            let mut round_trip = board.clone();
+            round_trip.set_band(n, &board.get_band(n));
+            assert_eq!(round_trip, board);
+            assert_eq!(round_trip.zobrist_hash(), board.zobrist_hash());
+            round_trip.set_stack(n, &board.get_stack(n));
+            assert_eq!(round_trip, board);
+            assert_eq!(round_trip.zobrist_hash(), board.zobrist_hash());
+            assert_eq!(round_trip.conflicts(), board.conflicts());
+
+            let rows = board.get_band(n);
+            let columns = board.get_stack(n);
+            for i in 0..3 {
+                assert_eq!(rows[i].cells, board.get_row(n * 3 + i).cells);
+                assert_eq!(columns[i].cells, board.get_column(n * 3 + i).cells);
+            }
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "A band is made up of exactly 3 rows")]
+fn setting_a_band_from_two_rows_panics() {
+    let mut board: Board = SOLUTION.parse().unwrap();
+    let rows = board.get_band(0);
+    board.set_band(0, &rows[..2]);
+}
+
+#[test]
+#[should_panic(expected = "Stack index must be below 3")]
+fn getting_a_stack_past_the_last_panics() {
+    let board: Board = SOLUTION.parse().unwrap();
+    board.get_stack(3);
+}
+
+/// An empty board with only the given cells filled in
+fn partial(cells: &[((usize, usize), u8)]) -> Board {
+    let mut board: Board = ".".repeat(81).parse().unwrap();
+    for &((row, col), value) in cells {
+        board.set_cell(row, col, value);
+    }
+    board
+}
+
+#[test]
+fn overlays_fill_empty_cells_and_keep_to_the_givens() {
+    let puzzle: Board = PUZZLE.parse().unwrap();
+
+    // r1c1 is given as 5
+    let mut board = puzzle.clone();
+    assert_eq!(
+        board.overlay(&partial(&[((0, 2), 4), ((0, 0), 4)])),
+        Err(OverlayError::DisagreesWithGiven {
+            position: (0, 0),
+            given: 5,
+            value: 4,
+        })
+    );
+    assert_eq!(board, puzzle);
+
+    // A 5 at r1c3 repeats the given 5 of its row
+    let conflicting = board.overlay(&partial(&[((0, 2), 5)]));
+    assert!(
+        matches!(conflicting, Err(OverlayError::Conflict(conflict)) if conflict.value == 5),
+        "{conflicting:?}"
+    );
+    assert_eq!(board, puzzle);
+
+    // Values matching the givens are fine, and the rest of the solution fills the board
+    board.overlay(&SOLUTION.parse().unwrap()).unwrap();
+    assert_eq!(board.to_string(), SOLUTION);
+    assert!(!board.is_given(0, 2));
+    assert!(board.is_given(0, 0));
+}
+
+#[test]
+fn clearing_refuses_givens() {
+    let mut board: Board = PUZZLE.parse().unwrap();
+    board.overlay(&partial(&[((0, 2), 4)])).unwrap();
+    assert_eq!(
+        board.clear_cell(0, 0),
+        Err(GivenCellError { position: (0, 0) })
+    );
+    assert_eq!(board.get_cell(0, 0), 5);
+    assert_eq!(board.clear_cell(0, 2), Ok(()));
+    assert_eq!(board.get_cell(0, 2), 0);
+    assert_eq!(board.clear_cell(0, 2), Ok(()));
+    assert_eq!(board, PUZZLE.parse().unwrap());
+}
+
+#[test]
+fn clue_counts_come_from_the_board_itself() {
+    let full: Board = generate_board(7).parse().unwrap();
+    assert_eq!(full.clue_count(), 81);
+    assert_eq!(full.clue_class(), ClueClass::Easy);
+
+    // One of the known puzzles with the fewest clues a unique sudoku can have
+    let minimal_puzzle =
+        "000000010400000000020000000000050407008000300001090000300400200050100000000806000";
+    let minimal: Board = minimal_puzzle.parse().unwrap();
+    assert_eq!(minimal.clue_count(), MINIMUM_CLUES);
+    assert_eq!(minimal.clue_class(), ClueClass::Sparse);
+    assert_eq!(minimal.count_solutions_dlx(2), 1);
+
+    let below: Board = minimal_puzzle.replacen('1', "0", 1).parse().unwrap();
+    assert_eq!(below.clue_count(), 16);
+    assert_eq!(below.clue_class(), ClueClass::BelowMinimum);
+
+    let classes = [
+        (0, "below minimum"),
+        (25, "sparse"),
+        (26, "typical"),
+        (32, "typical"),
+    ];
+    for (count, class) in classes {
+        assert_eq!(ClueClass::from_count(count).to_string(), class, "{count}");
+    }
+    assert_eq!(ClueClass::from_count(33), ClueClass::Easy);
+}
+
+#[test]
+fn messages_name_cells_counting_from_1() {
+    let mut board: Board = SOLUTION.parse().unwrap();
+    board.set_cell(0, 1, 1);
+    let conflicts = board.conflicts();
+    assert_eq!(
+        conflicts[0].to_string(),
+        "1 appears at both r1c2 and r1c8 in the same row"
+    );
+    assert_eq!(
+        SolveError::InvalidGivens(conflicts.clone()).to_string(),
+        "The givens break the sudoku rules, 1 appears at both r1c2 and r1c8 in the same row and \
+         2 more conflicts"
+    );
+    assert_eq!(
+        OverlayError::Conflict(conflicts[2]).to_string(),
+        "The overlay puts 1 at both r1c2 and r3c1 in the same tile"
+    );
+
+    let messages = [
+        (
+            SolveError::Contradiction { cell: (0, 8) }.to_string(),
+            "The board has a contradiction at r1c9",
+        ),
+        (
+            SolveError::HistoryMismatch { cell: (8, 0) }.to_string(),
+            "The history does not match the board at r9c1",
+        ),
+        (
+            Contradiction {
+                cell: (3, 4),
+                missing: None,
+            }
+            .to_string(),
+            "r4c5 has no candidates left",
+        ),
+        (
+            Contradiction {
+                cell: (3, 4),
+                missing: Some((7, UnitKind::Tile, 4)),
+            }
+            .to_string(),
+            "The digit 7 has no place left in tile 5",
+        ),
+        (
+            ReplayError::CellIsGiven { position: (1, 2) }.to_string(),
+            "r2c3 is a given",
+        ),
+        (
+            ReplayError::CellOccupied {
+                position: (1, 2),
+                value: 6,
+            }
+            .to_string(),
+            "r2c3 already holds 6",
+        ),
+        (
+            OverlayError::DisagreesWithGiven {
+                position: (0, 0),
+                given: 5,
+                value: 4,
+            }
+            .to_string(),
+            "r1c1 is given as 5 but the overlay has 4",
+        ),
+        (
+            GivenCellError { position: (0, 0) }.to_string(),
+            "r1c1 is a given and cannot be changed",
+        ),
+        (
+            MaskError::EmptyCell { position: (2, 6) }.to_string(),
+            "The mask keeps r3c7, which is empty",
+        ),
+        (
+            MaskError::ExtraCellsKept(vec![(0, 0), (8, 8)]).to_string(),
+            "The mask leaves several solutions, r1c1, r9c9 had to be kept as well",
+        ),
+        (
+            SatAssignmentError::Unassigned { cell: (0, 0) }.to_string(),
+            "No digit is assigned to r1c1",
+        ),
+    ];
+    for (message, expected) in messages {
+        assert_eq!(message, expected);
+    }
+}
+
+#[test]
+fn result_writers_print_records_in_the_chosen_format() {
+    let puzzle: Board = PUZZLE.parse().unwrap();
+    let mut solution = puzzle.clone();
+    solution.solve_dlx().unwrap();
+    let solved = Record {
+        puzzle: Some(&puzzle),
+        solution: Some(&solution),
+        elapsed: Some(Duration::from_micros(42)),
+        ..Record::default()
+    };
+    let failed = Record {
+        error: Some(RecordError {
+            token: "INVALID",
+            message: "Line 2, of puzzles.txt".to_string(),
+        }),
+        note: Some("too short".to_string()),
+        ..Record::default()
+    };
+    let written = |format| {
+        let mut out = Vec::new();
+        let mut writer = ResultWriter::new(&mut out, format, Palette::plain());
+        writer.write(&solved).unwrap();
+        writer.write(&failed).unwrap();
+        String::from_utf8(out).unwrap()
+    };
+
+    assert_eq!(
+        written(OutputFormat::Line),
+        format!("{solution}\nINVALID too short\n")
+    );
+    assert_eq!(
+        written(OutputFormat::Csv),
+        format!(
+            "{}\n{puzzle},{solution},,42,,\n,,,,,\"Line 2, of puzzles.txt\"\n",
+            ResultWriter::<Vec<u8>>::CSV_HEADER
+        )
+    );
+    let json = written(OutputFormat::Json);
+    let lines: Vec<&str> = json.lines().collect();
+    assert_eq!(
+        lines[0],
+        format!(
+            "{{\"puzzle\":\"{puzzle}\",\"solution\":\"{solution}\",\"seeds\":null,\
+             \"time_us\":42,\"difficulty\":null,\"error\":null}}"
+        )
+    );
+    assert_eq!(lines[1], failed.to_json().to_string());
+    assert!(lines[1].ends_with("\"error\":\"Line 2, of puzzles.txt\"}"));
+}
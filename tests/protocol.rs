@@ -0,0 +1,214 @@
+use sudoku_solver::{
+    json::{JsonValue, ParseJsonError},
+    protocol::{respond, serve},
+    Board,
+};
+
+const PUZZLE: &str =
+    "530070000600195000098000060800060003400803001700020006060000280000419005000080079";
+const SOLUTION: &str =
+    "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+/// The response to the request line, parsed back so its members can be checked
+fn response(line: &str) -> JsonValue {
+    respond(line).parse().unwrap()
+}
+
+/// The `code` of the error a response holds
+fn error_code(response: &JsonValue) -> &str {
+    assert_eq!(
+        response.get("ok"),
+        Some(&JsonValue::Bool(false)),
+        "{response}"
+    );
+    response
+        .get("error")
+        .and_then(|error| error.get("code"))
+        .and_then(JsonValue::as_str)
+        .unwrap()
+}
+
+#[test]
+fn json_values_read_back_as_they_are_written() {
+    let text =
+        r#"{"id":18446744073709551615,"list":[true,false,null,-1.5e3],"text":"a\"b\\c\né😀"}"#;
+    let value: JsonValue = text.parse().unwrap();
+    assert_eq!(value.get("id").and_then(JsonValue::as_u64), Some(u64::MAX));
+    assert_eq!(
+        value.get("text").and_then(JsonValue::as_str),
+        Some("a\"b\\c\né😀")
+    );
+    assert_eq!(value.to_string().parse::<JsonValue>(), Ok(value));
+
+    let spaced: JsonValue = " { \"a\" : [ 1 , 2 ] } ".parse().unwrap();
+    assert_eq!(spaced.to_string(), r#"{"a":[1,2]}"#);
+}
+
+#[test]
+fn malformed_json_says_where_it_went_wrong() {
+    let errors = [
+        ("", 0),
+        ("{\"a\":1,}", 7),
+        ("[1 2]", 3),
+        ("\"unterminated", 13),
+        ("01", 1),
+        ("{} {}", 3),
+    ];
+    for (text, position) in errors {
+        let error: ParseJsonError = text.parse::<JsonValue>().unwrap_err();
+        assert_eq!(error.position, position, "{text}: {error}");
+    }
+    let nested = format!("{}{}", "[".repeat(1000), "]".repeat(1000));
+    assert!(nested.parse::<JsonValue>().is_err());
+}
+
+#[test]
+fn solve_requests_echo_their_id() {
+    let solved = response(&format!(
+        r#"{{"id":"a-1","cmd":"solve","puzzle":"{PUZZLE}"}}"#
+    ));
+    assert_eq!(
+        solved.to_string(),
+        format!(r#"{{"id":"a-1","ok":true,"result":{{"solution":"{SOLUTION}","unique":true}}}}"#)
+    );
+
+    let open = "0".repeat(81);
+    let ambiguous = response(&format!(r#"{{"id":7,"cmd":"solve","puzzle":"{open}"}}"#));
+    assert_eq!(
+        ambiguous.get("id"),
+        Some(&JsonValue::Number("7".to_string()))
+    );
+    let result = ambiguous.get("result").unwrap();
+    assert_eq!(result.get("unique"), Some(&JsonValue::Bool(false)));
+    let solution: Board = result
+        .get("solution")
+        .and_then(JsonValue::as_str)
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert!(solution.validate_board());
+}
+
+#[test]
+fn generate_requests_are_reproducible_from_their_seed() {
+    let request = r#"{"cmd":"generate","clues":26,"seed":5}"#;
+    let generated = response(request);
+    assert_eq!(respond(request), generated.to_string());
+    let result = generated.get("result").unwrap();
+    assert_eq!(result.get("seed").and_then(JsonValue::as_u64), Some(5));
+    let puzzle: Board = result
+        .get("puzzle")
+        .and_then(JsonValue::as_str)
+        .unwrap()
+        .parse()
+        .unwrap();
+    assert_eq!(puzzle.count_solutions_dlx(2), 1);
+    let clues = result.get("clues").and_then(JsonValue::as_u64).unwrap();
+    assert_eq!(
+        puzzle.to_string().matches(|c| c != '0').count() as u64,
+        clues
+    );
+    assert!(result
+        .get("difficulty")
+        .and_then(JsonValue::as_str)
+        .is_some());
+
+    let easy = response(r#"{"cmd":"generate","difficulty":"easy","seed":3}"#);
+    let grade = easy
+        .get("result")
+        .and_then(|result| result.get("difficulty"));
+    assert_eq!(grade.and_then(JsonValue::as_str), Some("Easy"));
+}
+
+#[test]
+fn hint_and_grade_requests_describe_the_next_move() {
+    let hinted = response(&format!(r#"{{"cmd":"hint","puzzle":"{PUZZLE}"}}"#));
+    let result = hinted.get("result").unwrap();
+    assert_eq!(result.get("solved"), Some(&JsonValue::Bool(false)));
+    let hint = result.get("hint").unwrap();
+    assert_eq!(
+        hint.get("place").map(ToString::to_string).as_deref(),
+        Some(r#"{"row":5,"col":5,"value":5}"#)
+    );
+    assert!(hint
+        .get("explanation")
+        .and_then(JsonValue::as_str)
+        .unwrap()
+        .contains("r5c5"));
+
+    let finished = response(&format!(r#"{{"cmd":"hint","puzzle":"{SOLUTION}"}}"#));
+    let result = finished.get("result").unwrap();
+    assert_eq!(result.get("hint"), Some(&JsonValue::Null));
+    assert_eq!(result.get("solved"), Some(&JsonValue::Bool(true)));
+
+    let graded = response(&format!(r#"{{"cmd":"grade","puzzle":"{PUZZLE}"}}"#));
+    let result = graded.get("result").unwrap();
+    assert_eq!(
+        result.get("requires_guessing"),
+        Some(&JsonValue::Bool(false))
+    );
+    assert!(
+        matches!(result.get("techniques"), Some(JsonValue::Object(counts)) if !counts.is_empty())
+    );
+}
+
+#[test]
+fn failed_requests_get_structured_errors() {
+    let conflicting = format!("55{}", &PUZZLE[2..]);
+    let unsolvable = format!("{}1{}", &PUZZLE[..2], &PUZZLE[3..]);
+    let requests = [
+        ("not json", "invalid_json"),
+        ("[1,2]", "invalid_json"),
+        (r#"{"id":1}"#, "bad_request"),
+        (r#"{"cmd":"fly"}"#, "unknown_command"),
+        (r#"{"cmd":"solve"}"#, "bad_request"),
+        (r#"{"cmd":"solve","puzzle":42}"#, "bad_request"),
+        (r#"{"cmd":"solve","puzzle":"123"}"#, "bad_request"),
+        (r#"{"cmd":"generate","clues":5}"#, "bad_request"),
+        (r#"{"cmd":"generate","clues":-1}"#, "bad_request"),
+        (
+            r#"{"cmd":"generate","difficulty":"impossible"}"#,
+            "bad_request",
+        ),
+        (
+            r#"{"cmd":"generate","clues":30,"difficulty":"easy"}"#,
+            "bad_request",
+        ),
+    ];
+    for (request, code) in requests {
+        assert_eq!(error_code(&response(request)), code, "{request}");
+    }
+    let request = format!(r#"{{"id":2,"cmd":"hint","puzzle":"{conflicting}"}}"#);
+    let invalid = response(&request);
+    assert_eq!(error_code(&invalid), "invalid_puzzle");
+    assert_eq!(invalid.get("id").and_then(JsonValue::as_u64), Some(2));
+    let request = format!(r#"{{"cmd":"solve","puzzle":"{unsolvable}"}}"#);
+    assert_eq!(error_code(&response(&request)), "unsolvable");
+
+    let unknown = response(r#"{"id":null,"cmd":"fly"}"#);
+    let message = unknown.get("error").and_then(|error| error.get("message"));
+    assert_eq!(
+        message.and_then(JsonValue::as_str),
+        Some("Unknown command \"fly\", expected one of solve, generate, hint, grade")
+    );
+}
+
+#[test]
+fn serving_answers_every_line_in_order() {
+    let input = format!("{{\"id\":1,\"cmd\":\"solve\",\"puzzle\":\"{PUZZLE}\"}}\n\n  \nnot json\n");
+    let mut output = Vec::new();
+    serve(input.as_bytes(), &mut output).unwrap();
+    let output = String::from_utf8(output).unwrap();
+    let lines: Vec<&str> = output.lines().collect();
+    assert_eq!(lines.len(), 2);
+    let solved: JsonValue = lines[0].parse().unwrap();
+    assert_eq!(solved.get("id").and_then(JsonValue::as_u64), Some(1));
+    assert_eq!(
+        solved
+            .get("result")
+            .and_then(|result| result.get("solution"))
+            .and_then(JsonValue::as_str),
+        Some(SOLUTION)
+    );
+    assert_eq!(error_code(&lines[1].parse().unwrap()), "invalid_json");
+}
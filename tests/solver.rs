@@ -0,0 +1,1347 @@
+use std::{
+    collections::HashSet,
+    time::{Duration, Instant},
+};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use sudoku_solver::{
+    batch::{
+        entropy_solver, solve_batch_parallel_with_limits, solve_batch_sequential,
+        solve_batch_streaming, BatchEntry, BatchReport, TimingsWriter,
+    },
+    board::UnitKind,
+    generate_puzzle,
+    history::{MoveHistory, ParseHistoryError, ReplayError},
+    report::Limit,
+    solver::{comparison_solvers, registered_solvers, solver_named},
+    verify::{bug_report, verify_solution, VerificationFailure},
+    AnimatingObserver, Board, BoardMove, CancellationToken, Cascade, CellSelection, Contradiction,
+    NoopObserver, Palette, PropagationLevel, RestartPolicy, SolveError, SolveLimits, SolveObserver,
+    SolveOptions, SolveReport, UniquenessResult, ValueOrdering,
+};
+
+/// The "problem seeds" that used to make the solver report success on an unfinished board now
+/// solve properly
+#[test]
+fn problem_seeds_solve() {
+    let generated = generate_puzzle(12499731774094038275, 8137985501619016255, 20..=30);
+    let mut board = generated.puzzle.clone();
+
+    board
+        .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut NoopObserver)
+        .unwrap();
+    assert!(board.validate_board());
+}
+
+/// Givens that already break the rules are reported before any solving happens
+#[test]
+fn conflicting_givens_are_rejected() {
+    let puzzle: Board =
+        "550070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    let mut board = puzzle.clone();
+
+    let error = board
+        .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut NoopObserver)
+        .unwrap_err();
+    let SolveError::InvalidGivens(conflicts) = error else {
+        panic!("expected invalid givens, got {error:?}");
+    };
+    assert!(!conflicts.is_empty());
+    assert!(conflicts.iter().all(|conflict| conflict.value == 5));
+    assert_eq!(board, puzzle);
+}
+
+/// Fails as soon as the solver reports a placement the board does not actually hold
+struct PlacementChecker;
+
+impl SolveObserver for PlacementChecker {
+    fn on_place(&mut self, board: &Board, position: (usize, usize), value: u8) {
+        assert_eq!(board.get_cell(position.0, position.1), value);
+    }
+
+    fn on_cascade(&mut self, board: &Board, position: (usize, usize), value: u8) {
+        assert_eq!(board.get_cell(position.0, position.1), value);
+    }
+}
+
+/// Seeds whose puzzles need several guesses but never run into a dead end, so the solve is
+/// deterministic
+#[test]
+fn guesses_are_written_to_the_board() {
+    for seed in [0, 4, 5, 7, 10] {
+        let mut board = generate_puzzle(seed, seed + 1, 24..=30).puzzle;
+        board
+            .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut PlacementChecker)
+            .unwrap();
+    }
+}
+
+/// Counts the frames of an animation by their status lines, returning the last one
+fn animation_frames(output: &[u8]) -> (usize, String) {
+    let output = String::from_utf8_lossy(output);
+    let statuses: Vec<&str> = output
+        .split('\x1b')
+        .filter_map(|command| command.split_once('H').map(|(_, text)| text))
+        .filter(|text| text.contains(" placements, "))
+        .collect();
+    (statuses.len(), statuses.last().unwrap().to_string())
+}
+
+#[test]
+fn animation_frames_are_rate_limited_but_backtracks_always_drawn() {
+    let mut board: Board = FIXTURES[1].parse().unwrap();
+    let mut output = Vec::new();
+    let mut observer = AnimatingObserver::new(&mut output, (0, 1)).with_fps(0);
+    board
+        .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut observer)
+        .unwrap();
+    observer.finish(&board);
+    let (frames, status) = animation_frames(&output);
+    let counts: Vec<usize> = status
+        .split(|c: char| !c.is_ascii_digit())
+        .filter_map(|number| number.parse().ok())
+        .collect();
+    let (placements, backtracks) = (counts[0], counts[1]);
+    assert!(backtracks > 0);
+    // Without a limit every event gets a frame, plus the one drawn when finishing
+    assert_eq!(frames, placements + backtracks + 1);
+    // Values taken back by a backtrack are drawn reversed until the next frame
+    assert!(String::from_utf8_lossy(&output).contains("\x1b[7m"));
+
+    // Without colors the same frames are drawn, just without any style
+    let mut board: Board = FIXTURES[1].parse().unwrap();
+    let mut plain = Vec::new();
+    let mut observer = AnimatingObserver::new(&mut plain, (0, 1))
+        .with_fps(0)
+        .with_palette(Palette::plain());
+    board
+        .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut observer)
+        .unwrap();
+    observer.finish(&board);
+    assert_eq!(animation_frames(&plain).0, frames);
+    let plain = String::from_utf8_lossy(&plain);
+    let mut sequences = plain.split("\x1b[").skip(1);
+    assert!(!sequences.any(|sequence| sequence
+        .trim_start_matches(|c: char| c.is_ascii_digit() || c == ';')
+        .starts_with('m')));
+
+    let mut board: Board = FIXTURES[1].parse().unwrap();
+    let mut output = Vec::new();
+    let mut observer = AnimatingObserver::new(&mut output, (0, 1)).with_fps(1);
+    board
+        .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut observer)
+        .unwrap();
+    observer.finish(&board);
+    let (frames, last) = animation_frames(&output);
+    assert!(
+        frames >= backtracks + 2 && frames < placements,
+        "{frames} frames"
+    );
+    assert!(last.starts_with(&format!("{placements} placements, {backtracks} backtracks")));
+}
+
+#[test]
+fn animation_delays_are_cut_short_once_cancelled() {
+    let mut board: Board = FIXTURES[0].parse().unwrap();
+    let mut output = Vec::new();
+    let token = CancellationToken::new();
+    let mut observer = AnimatingObserver::new(&mut output, (0, 1))
+        .with_delay(Duration::from_millis(2))
+        .with_cancellation(token.clone());
+    let start = Instant::now();
+    board
+        .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut observer)
+        .unwrap();
+    observer.finish(&board);
+    // A delay draws every placement and pauses after it
+    let (frames, status) = animation_frames(&output);
+    let placements: usize = status.split(' ').next().unwrap().parse().unwrap();
+    assert_eq!(frames, placements + 1);
+    assert!(start.elapsed() >= Duration::from_millis(2) * placements as u32);
+
+    let mut board: Board = FIXTURES[0].parse().unwrap();
+    token.cancel();
+    let mut observer = AnimatingObserver::new(Vec::new(), (0, 1))
+        .with_delay(Duration::from_secs(60))
+        .with_cancellation(token);
+    let start = Instant::now();
+    board
+        .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut observer)
+        .unwrap();
+    assert!(start.elapsed() < Duration::from_secs(10));
+}
+
+/// Mirrors every value the solver reports placing or taking back, checking after every event
+/// that the board holds exactly the givens plus the recorded moves
+struct MirrorChecker {
+    mirror: Board,
+    backtracks: usize,
+}
+
+impl SolveObserver for MirrorChecker {
+    fn on_place(&mut self, board: &Board, position: (usize, usize), value: u8) {
+        self.mirror.set_cell(position.0, position.1, value);
+        assert_eq!(*board, self.mirror);
+    }
+
+    fn on_cascade(&mut self, board: &Board, position: (usize, usize), value: u8) {
+        self.mirror.set_cell(position.0, position.1, value);
+        assert_eq!(*board, self.mirror);
+    }
+
+    fn on_dead_end(&mut self, board: &Board, _position: (usize, usize)) {
+        assert_eq!(*board, self.mirror);
+    }
+
+    fn on_backtrack(&mut self, board: &Board, undone: &BoardMove) {
+        self.mirror.undo_move(undone);
+        self.backtracks += 1;
+        assert_eq!(*board, self.mirror);
+    }
+}
+
+/// Puzzles whose solve backtracks through substitutes that fail, which used to leave the last
+/// probed value on the board without any move recording it
+#[test]
+fn failed_substitutes_leave_no_phantom_values() {
+    for seed in [3, 9] {
+        let puzzle = generate_puzzle(seed, seed + 1, 17..=24).puzzle;
+        let mut board = puzzle.clone();
+        let mut checker = MirrorChecker {
+            mirror: puzzle,
+            backtracks: 0,
+        };
+
+        board
+            .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut checker)
+            .unwrap();
+        assert!(checker.backtracks > 1);
+        assert!(board.validate_board());
+    }
+}
+
+/// A puzzle without a solution whose solve starts with forced singles. Undoing every move,
+/// including the root holding those singles, leaves only the givens
+#[test]
+fn exhausted_search_restores_the_board() {
+    let puzzle: Board =
+        "530070000600190000008000060890060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    let mut board = puzzle.clone();
+    let mut checker = MirrorChecker {
+        mirror: puzzle.clone(),
+        backtracks: 0,
+    };
+
+    let result = board.solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut checker);
+    assert!(matches!(result, Err(SolveError::Exhausted { backtracks }) if backtracks > 0));
+    assert!(checker.backtracks > 0);
+    assert_eq!(board, checker.mirror);
+    assert_eq!(board, puzzle);
+}
+
+/// Singles forced before the first guess are part of the history, so replaying it onto the
+/// puzzle reproduces the whole solution
+#[test]
+fn history_covers_singles_before_the_first_guess() {
+    for seed in [0, 1, 5] {
+        let puzzle = generate_puzzle(seed, seed + 1, 24..=30).puzzle;
+        let mut board = puzzle.clone();
+        let history = board
+            .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut NoopObserver)
+            .unwrap()
+            .history;
+
+        let first = history.iter().next().unwrap();
+        assert!(!first.cascades().is_empty());
+        let mut replayed = puzzle.clone();
+        history.replay_onto(&mut replayed).unwrap();
+        assert_eq!(replayed, board);
+        assert!(replayed.validate_board());
+    }
+}
+
+/// Backtracking draws its substitutes from the supplied generator, so the same seed always
+/// produces the same sequence of moves
+#[test]
+fn seeded_solves_are_reproducible() {
+    let puzzle = generate_puzzle(183, 184, 17..=24).puzzle;
+    let solve = |seed| {
+        let mut board = puzzle.clone();
+        board
+            .solve_board(&mut ChaCha8Rng::seed_from_u64(seed), &mut NoopObserver)
+            .unwrap()
+    };
+
+    for seed in 0..4 {
+        let (first, second) = (solve(seed), solve(seed));
+        assert_eq!(first.history, second.history);
+        assert_eq!(first.placements, second.placements);
+        assert_eq!(first.backtracks, second.backtracks);
+    }
+}
+
+/// The counts in the report add up for a fixed puzzle that needs backtracking
+#[test]
+fn report_counts_are_plausible() {
+    let puzzle = generate_puzzle(183, 184, 17..=24).puzzle;
+    let mut board = puzzle.clone();
+    let report = board
+        .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut NoopObserver)
+        .unwrap();
+
+    assert_eq!(report.placements, report.forced_singles + report.guesses);
+    assert!(report.placements >= puzzle.count_empty());
+    assert!(report.guesses > 0);
+    assert!(report.backtracks > 0);
+    assert!(report.max_backtrack_depth >= 1);
+    // Every fresh guess needs a scan to find its cell, and one more per option it probes, while
+    // a substitute picked after a dead end needs none
+    let substitutes = report.backtracks as usize;
+    assert!(report.entropy_scans >= 3 * report.guesses.saturating_sub(substitutes));
+    let recorded: usize = report
+        .history
+        .iter()
+        .map(|board_move| 1 + board_move.cascades().len())
+        .sum();
+    assert_eq!(recorded, puzzle.count_empty());
+}
+
+/// A puzzle solved by forced singles alone needs no guesses or backtracking
+#[test]
+fn report_counts_singles_only_solve() {
+    let mut board: Board =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    let empty_cells = board.count_empty();
+    let report = board
+        .solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut NoopObserver)
+        .unwrap();
+
+    assert_eq!(report.forced_singles, empty_cells);
+    assert_eq!(report.guesses, 0);
+    assert_eq!(report.backtracks, 0);
+    assert_eq!(report.max_backtrack_depth, 0);
+    // Propagation fills every single without scanning, leaving one scan to find the board full
+    assert_eq!(report.entropy_scans, 1);
+}
+
+const SOLVED: &str =
+    "534678912672195348198342567859761423426853791713924856961537284287419635345286179";
+
+#[test]
+fn count_solutions_stops_at_the_limit() {
+    let unique: Board =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    assert_eq!(unique.count_solutions(10), 1);
+
+    // Rows 4 and 5 hold 1 and 3 swapped in columns 6 and 9, so the two values can trade places
+    let mut rectangle: Board = SOLVED.parse().unwrap();
+    for (row, col) in [(3, 5), (3, 8), (4, 5), (4, 8)] {
+        rectangle.set_cell(row, col, 0);
+    }
+    let before = rectangle.clone();
+    assert_eq!(rectangle.count_solutions(10), 2);
+    assert_eq!(rectangle.count_solutions(1), 1);
+    assert_eq!(rectangle.count_solutions(0), 0);
+    assert_eq!(rectangle, before);
+
+    let empty: Board = "0".repeat(81).parse().unwrap();
+    assert_eq!(empty.count_solutions(5), 5);
+}
+
+#[test]
+fn count_solutions_of_unsolvable_boards_is_zero() {
+    let conflicting: Board =
+        "550070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    assert_eq!(conflicting.count_solutions(10), 0);
+
+    let unsolvable: Board =
+        "849000307003290001100000020080143500005020400010000000068000000097600000001970004"
+            .parse()
+            .unwrap();
+    assert_eq!(unsolvable.count_solutions(10), 0);
+}
+
+#[test]
+fn threaded_counting_agrees_with_a_single_thread() {
+    let mut rectangle: Board = SOLVED.parse().unwrap();
+    for (row, col) in [(3, 5), (3, 8), (4, 5), (4, 8)] {
+        rectangle.set_cell(row, col, 0);
+    }
+    let boards = [
+        SOLVED.parse().unwrap(),
+        FIXTURES[1].parse().unwrap(),
+        rectangle,
+        "0".repeat(81).parse().unwrap(),
+        "849000307003290001100000020080143500005020400010000000068000000097600000001970004"
+            .parse()
+            .unwrap(),
+        "550070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap(),
+    ];
+    for board in boards {
+        let before = board.clone();
+        for threads in [2, 4] {
+            for limit in [0, 1, 2, 10] {
+                assert_eq!(
+                    board.count_solutions_with_threads(limit, threads),
+                    board.count_solutions(limit),
+                    "{board} with {threads} threads and a limit of {limit}"
+                );
+            }
+        }
+        assert_eq!(board, before);
+    }
+}
+
+#[test]
+fn uniqueness_reports_both_solutions() {
+    let solved: Board = SOLVED.parse().unwrap();
+    let puzzle: Board =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    assert_eq!(
+        puzzle.has_unique_solution(),
+        UniquenessResult::Unique(Box::new(solved.clone()))
+    );
+
+    let mut rectangle = solved.clone();
+    for (row, col) in [(3, 5), (3, 8), (4, 5), (4, 8)] {
+        rectangle.set_cell(row, col, 0);
+    }
+    let result = rectangle.has_unique_solution();
+    let UniquenessResult::Multiple { first, second } = &result else {
+        panic!("expected multiple solutions, got {result:?}");
+    };
+    assert!(first.validate_board() && second.validate_board());
+    assert_ne!(first, second);
+    assert_eq!(
+        result.ambiguous_cells(),
+        vec![(3, 5), (3, 8), (4, 5), (4, 8)]
+    );
+    assert_eq!(
+        result.to_string(),
+        "The puzzle has multiple solutions, cells r4c6/r4c9/r5c6/r5c9 form an unavoidable set"
+    );
+
+    let unsolvable: Board =
+        "849000307003290001100000020080143500005020400010000000068000000097600000001970004"
+            .parse()
+            .unwrap();
+    assert_eq!(unsolvable.has_unique_solution(), UniquenessResult::None);
+    assert!(unsolvable
+        .has_unique_solution()
+        .ambiguous_cells()
+        .is_empty());
+}
+
+/// The depth first solver never fails on a generated puzzle, and agrees with wave function
+/// collapse whenever the puzzle pins down a single solution
+#[test]
+fn dfs_agrees_with_wave_function_collapse() {
+    for seed in 0..200 {
+        let puzzle = generate_puzzle(seed, seed + 1, 20..=30).puzzle;
+        let mut dfs = puzzle.clone();
+        let report = dfs.solve_dfs(&mut NoopObserver).unwrap();
+        assert!(dfs.validate_board());
+        assert!(puzzle.differs_from_solution(&dfs).is_empty());
+
+        let mut replayed = puzzle.clone();
+        report.history.replay_onto(&mut replayed).unwrap();
+        assert_eq!(replayed, dfs);
+
+        let mut wfc = puzzle.clone();
+        let wfc_result = wfc.solve_board(&mut ChaCha8Rng::seed_from_u64(seed), &mut NoopObserver);
+        if wfc_result.is_ok() && puzzle.count_solutions(2) == 1 {
+            assert_eq!(wfc, dfs, "the solvers disagree on seed {seed}");
+        }
+    }
+}
+
+#[test]
+fn dfs_proves_unsolvable_puzzles() {
+    let puzzle: Board =
+        "530070000600190000008000060890060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    let mut board = puzzle.clone();
+
+    let result = board.solve_dfs(&mut NoopObserver);
+    assert!(matches!(result, Err(SolveError::Exhausted { .. })));
+    assert_eq!(board, puzzle);
+}
+
+/// Puzzles with a single solution, from easy to some of the hardest known
+const FIXTURES: [&str; 5] = [
+    "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+    "800000000003600000070090200050007000000045700000100030001000068008500010090000400",
+    "000000012000000003002300400001800005060070800000009000008500000900040500470006000",
+    "000000010400000000020000000000050407008000300001090000300400200050100000000806000",
+    "100007090030020008009600500005300900010080002600004000300000010040000007007000300",
+];
+
+#[test]
+fn all_solvers_agree_on_fixtures() {
+    for fixture in FIXTURES {
+        let puzzle: Board = fixture.parse().unwrap();
+        assert_eq!(puzzle.count_solutions_dlx(2), 1, "{fixture} is not unique");
+
+        let mut wfc = puzzle.clone();
+        wfc.solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut NoopObserver)
+            .unwrap();
+        let mut dfs = puzzle.clone();
+        dfs.solve_dfs(&mut NoopObserver).unwrap();
+        let mut dlx = puzzle.clone();
+        let report = dlx.solve_dlx().unwrap();
+
+        assert!(dlx.validate_board());
+        assert_eq!(wfc, dlx, "{fixture}");
+        assert_eq!(dfs, dlx, "{fixture}");
+        assert_eq!(report.history.len(), puzzle.count_empty());
+        let mut replayed = puzzle.clone();
+        report.history.replay_onto(&mut replayed).unwrap();
+        assert_eq!(replayed, dlx);
+    }
+}
+
+#[test]
+fn registered_solvers_are_interchangeable() {
+    let mut solvers = registered_solvers(0);
+    let names: Vec<&str> = solvers.iter().map(|solver| solver.name()).collect();
+    assert_eq!(names, ["wfc", "dfs", "dlx"]);
+
+    for fixture in FIXTURES {
+        let puzzle: Board = fixture.parse().unwrap();
+        let mut solution = puzzle.clone();
+        solution.solve_dlx().unwrap();
+        for solver in &mut solvers {
+            let mut board = puzzle.clone();
+            solver.solve(&mut board, &mut PlacementChecker).unwrap();
+            assert_eq!(board, solution, "{} on {fixture}", solver.name());
+        }
+    }
+}
+
+#[test]
+fn limits_stop_the_solve_with_a_consistent_board() {
+    let puzzle: Board = FIXTURES[1].parse().unwrap();
+    let limits = SolveLimits {
+        max_placements: Some(30),
+        ..SolveLimits::default()
+    };
+    let results = [
+        puzzle.clone().solve_board_with_limits(
+            &mut ChaCha8Rng::seed_from_u64(0),
+            &mut NoopObserver,
+            &limits,
+        ),
+        puzzle
+            .clone()
+            .solve_dfs_with_limits(&mut NoopObserver, &limits),
+    ];
+    for (result, solve) in results.into_iter().zip(["wfc", "dfs"]) {
+        let Err(SolveError::LimitReached { limit, report }) = result else {
+            panic!("{solve} was not stopped: {result:?}");
+        };
+        assert_eq!(limit, Limit::Placements, "{solve}");
+        assert!(report.placements > 30, "{solve}");
+    }
+
+    let mut board = puzzle.clone();
+    let Err(SolveError::LimitReached { report, .. }) = board.solve_board_with_limits(
+        &mut ChaCha8Rng::seed_from_u64(0),
+        &mut NoopObserver,
+        &limits,
+    ) else {
+        panic!("the solve was not stopped");
+    };
+    let mut replayed = puzzle.clone();
+    report.history.replay_onto(&mut replayed).unwrap();
+    assert_eq!(replayed, board);
+
+    let mut board = puzzle.clone();
+    let limits = SolveLimits {
+        max_backtracks: Some(2),
+        ..SolveLimits::default()
+    };
+    let Err(SolveError::LimitReached { limit, report }) =
+        board.solve_dfs_with_limits(&mut NoopObserver, &limits)
+    else {
+        panic!("the search was not stopped");
+    };
+    assert_eq!(limit, Limit::Backtracks);
+    assert_eq!(report.backtracks, 3);
+    let mut replayed = puzzle.clone();
+    report.history.replay_onto(&mut replayed).unwrap();
+    assert_eq!(replayed, board);
+}
+
+#[test]
+fn resumed_solves_finish_from_their_history() {
+    let puzzle: Board = FIXTURES[1].parse().unwrap();
+    let limits = SolveLimits {
+        max_placements: Some(30),
+        ..SolveLimits::default()
+    };
+    let mut board = puzzle.clone();
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    let Err(SolveError::LimitReached { report, .. }) =
+        board.solve_board_with_limits(&mut rng, &mut NoopObserver, &limits)
+    else {
+        panic!("the solve was not stopped");
+    };
+
+    let [row, col] = report.history.iter().next().unwrap().position();
+    assert!(matches!(
+        puzzle.clone().resume_solve(
+            report.history.clone(),
+            &mut rng,
+            &mut NoopObserver,
+            &SolveLimits::default(),
+        ),
+        Err(SolveError::HistoryMismatch { cell }) if cell == (row, col)
+    ));
+
+    let resumed = board
+        .resume_solve(
+            report.history,
+            &mut rng,
+            &mut NoopObserver,
+            &SolveLimits::default(),
+        )
+        .unwrap();
+    assert!(board.validate_board());
+    let mut replayed = puzzle.clone();
+    resumed.history.replay_onto(&mut replayed).unwrap();
+    assert_eq!(replayed, board);
+}
+
+/// Has no solution, which takes the solver several seconds of backtracking to find out, as
+/// propagation alone does not rule it out
+fn slow_unsolvable() -> Board {
+    "000006000001009003000005000008000020390000065000200000000000000000903000000000000"
+        .parse()
+        .unwrap()
+}
+
+#[test]
+fn contradictions_are_found_before_solving() {
+    // Every place for a 9 in the first row is taken or sees another 9
+    let mut puzzle = Board::from_cells([0; 81]);
+    for ((row, col), value) in [
+        ((1, 0), 9),
+        ((2, 4), 9),
+        ((4, 6), 9),
+        ((7, 7), 9),
+        ((0, 8), 1),
+    ] {
+        puzzle.set_cell(row, col, value);
+    }
+    let contradiction = puzzle.find_contradiction().unwrap();
+    assert_eq!(contradiction.missing, Some((9, UnitKind::Row, 0)));
+    assert_eq!(contradiction.cell, (0, 0));
+
+    let mut board = puzzle.clone();
+    let result = board.solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut NoopObserver);
+    assert_eq!(result, Err(SolveError::Contradiction { cell: (0, 0) }));
+    assert_eq!(board, puzzle);
+    assert_eq!(
+        board.solve_dfs(&mut NoopObserver),
+        Err(SolveError::Contradiction { cell: (0, 0) })
+    );
+
+    for fixture in FIXTURES {
+        let puzzle: Board = fixture.parse().unwrap();
+        assert_eq!(puzzle.find_contradiction(), None);
+    }
+    assert_eq!(slow_unsolvable().find_contradiction(), None);
+}
+
+#[test]
+fn cancelled_solves_return_promptly() {
+    let puzzle = slow_unsolvable();
+    let token = CancellationToken::new();
+    let limits = SolveLimits::with_cancellation(token.clone());
+    let solving = std::thread::spawn({
+        let mut board = puzzle.clone();
+        move || {
+            let result = board.solve_board_with_limits(
+                &mut ChaCha8Rng::seed_from_u64(0),
+                &mut NoopObserver,
+                &limits,
+            );
+            (board, result)
+        }
+    });
+    std::thread::sleep(Duration::from_millis(20));
+    let cancelled_at = Instant::now();
+    token.cancel();
+    let (board, result) = solving.join().unwrap();
+    assert!(cancelled_at.elapsed() < Duration::from_secs(1));
+
+    let Err(SolveError::Cancelled { report }) = result else {
+        panic!("the solve was not cancelled: {result:?}");
+    };
+    assert!(board.conflicts().is_empty());
+    let mut replayed = puzzle.clone();
+    report.history.replay_onto(&mut replayed).unwrap();
+    assert_eq!(replayed, board);
+}
+
+#[test]
+fn dlx_counts_match_the_search() {
+    let mut rectangle: Board = SOLVED.parse().unwrap();
+    for (row, col) in [(3, 5), (3, 8), (4, 5), (4, 8)] {
+        rectangle.set_cell(row, col, 0);
+    }
+    assert_eq!(rectangle.count_solutions_dlx(10), 2);
+    assert_eq!(rectangle.count_solutions_dlx(1), 1);
+
+    let empty: Board = "0".repeat(81).parse().unwrap();
+    assert_eq!(empty.count_solutions_dlx(50), 50);
+
+    let unsolvable: Board =
+        "849000307003290001100000020080143500005020400010000000068000000097600000001970004"
+            .parse()
+            .unwrap();
+    assert_eq!(unsolvable.count_solutions_dlx(10), 0);
+    let mut board = unsolvable.clone();
+    assert!(matches!(
+        board.solve_dlx(),
+        Err(SolveError::Exhausted { .. })
+    ));
+    assert_eq!(board, unsolvable);
+
+    let conflicting: Board =
+        "550070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    assert_eq!(conflicting.count_solutions_dlx(10), 0);
+    assert!(matches!(
+        conflicting.clone().solve_dlx(),
+        Err(SolveError::InvalidGivens(_))
+    ));
+}
+
+#[test]
+fn propagate_singles_fills_forced_cells() {
+    let mut board: Board =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    let empty_cells = board.count_empty();
+
+    let placements = board.propagate_singles().unwrap();
+    assert_eq!(placements.len(), empty_cells);
+    assert!(board.validate_board());
+    for placement in placements {
+        let [row, col] = placement.position();
+        assert_eq!(board.get_cell(row, col), placement.value());
+    }
+
+    // The only cell left in the first row needs a 9, which the last column already has
+    let mut stuck: Board =
+        "123456780000000000000000000000000000000000000000000000000000000000000000000000009"
+            .parse()
+            .unwrap();
+    let before = stuck.clone();
+    assert_eq!(
+        stuck.propagate_singles(),
+        Err(Contradiction {
+            cell: (0, 8),
+            missing: None
+        })
+    );
+    assert_eq!(stuck, before);
+}
+
+#[test]
+fn batches_are_solved_in_order_whatever_the_workers() {
+    let mut puzzles: Vec<Board> = FIXTURES
+        .iter()
+        .map(|fixture| fixture.parse().unwrap())
+        .collect();
+    let unsolvable: Board =
+        "530070000600190000008000060890060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    puzzles.insert(2, unsolvable);
+    puzzles.push(slow_unsolvable());
+
+    let limits = SolveLimits {
+        max_backtracks: Some(5_000),
+        ..SolveLimits::default()
+    };
+    let serial = solve_batch_parallel_with_limits(puzzles.clone(), 1, &limits, entropy_solver);
+    let parallel = solve_batch_parallel_with_limits(puzzles.clone(), 4, &limits, entropy_solver);
+    assert_eq!(parallel.workers, 4);
+    assert_eq!(parallel.entries.len(), puzzles.len());
+    assert_eq!(parallel.solved(), FIXTURES.len());
+    assert_eq!(parallel.timeouts(), 1);
+
+    for (i, (serial, parallel)) in serial.entries.iter().zip(&parallel.entries).enumerate() {
+        assert_eq!(serial.result.is_ok(), parallel.result.is_ok(), "puzzle {i}");
+        if parallel.result.is_ok() {
+            assert!(parallel.board.validate_board(), "puzzle {i}");
+            assert_eq!(serial.board, parallel.board, "puzzle {i}");
+        }
+    }
+    assert!(matches!(
+        parallel.entries[2].result,
+        Err(SolveError::Exhausted { .. })
+    ));
+    assert!(parallel.cpu_time() >= parallel.entries[6].elapsed);
+}
+
+#[test]
+fn batch_statistics_summarize_every_puzzle() {
+    let board: Board = FIXTURES[0].parse().unwrap();
+    let solved = |backtracks| {
+        Ok(SolveReport {
+            backtracks,
+            ..SolveReport::default()
+        })
+    };
+    let results = [
+        (solved(0), 1),
+        (solved(1), 2),
+        (solved(5), 3),
+        (solved(300), 4),
+        (Err(SolveError::Exhausted { backtracks: 12 }), 10),
+        (Err(SolveError::Contradiction { cell: (0, 0) }), 5),
+    ];
+    let report = BatchReport {
+        entries: results
+            .into_iter()
+            .enumerate()
+            .map(|(seed, (result, millis))| BatchEntry {
+                seed: seed as u64,
+                board: board.clone(),
+                result,
+                elapsed: Duration::from_millis(millis),
+                verification: None,
+            })
+            .collect(),
+        workers: 1,
+        wall_time: Duration::from_millis(25),
+    };
+
+    assert_eq!(report.percentile(0.0), Duration::from_millis(1));
+    assert_eq!(report.percentile(50.0), Duration::from_millis(3));
+    assert_eq!(report.percentile(90.0), Duration::from_millis(10));
+    assert_eq!(report.backtrack_histogram(), [1, 1, 0, 1, 0, 0, 0, 0, 0, 1]);
+    assert_eq!(
+        report.failures_by_kind(),
+        [("contradiction", 1), ("exhausted", 1)]
+    );
+    let table = report.statistics_table();
+    assert!(table.contains("0: 1, 1: 1, 4-7: 1, 256-511: 1"), "{table}");
+    assert!(table.contains("contradiction: 1, exhausted: 1"), "{table}");
+    assert!(!table.contains("verified"), "{table}");
+    let benchmark = report.benchmark_table();
+    assert!(
+        benchmark.starts_with("completion   4/6 (66.7%)\n"),
+        "{benchmark}"
+    );
+    assert!(benchmark.contains(&table), "{benchmark}");
+    assert!(
+        benchmark.ends_with("backtracking mean 63.6, max 300\n"),
+        "{benchmark}"
+    );
+    let row = report.comparison_row("wfc");
+    let columns: Vec<&str> = row.split_whitespace().collect();
+    assert_eq!(
+        columns[..7],
+        ["wfc", "4/6", "0", "2", "3000us", "10000us", "10000us"]
+    );
+    assert_eq!(
+        BatchReport::comparison_header().split_whitespace().count(),
+        columns.len()
+    );
+
+    let mut csv = Vec::new();
+    report.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 7);
+    assert_eq!(lines[0], "seed,clues,time_us,backtracks,result");
+    let clues = board.clue_count();
+    assert_eq!(lines[4], format!("3,{clues},4000,300,solved"));
+    assert_eq!(lines[5], format!("4,{clues},10000,12,exhausted"));
+    assert_eq!(lines[6], format!("5,{clues},5000,,contradiction"));
+}
+
+#[test]
+fn sequential_batches_hand_over_every_entry_until_told_to_stop() {
+    let puzzles: Vec<Board> = FIXTURES[..3]
+        .iter()
+        .map(|puzzle| puzzle.parse().unwrap())
+        .collect();
+    let mut solver = solver_named("dlx", 0).unwrap();
+    let mut handed = Vec::new();
+    let report = solve_batch_sequential::<()>(
+        solver.as_mut(),
+        &puzzles,
+        &SolveLimits::default(),
+        |index, entry| {
+            handed.push(index);
+            assert_eq!(entry.seed, index as u64);
+            assert!(entry.board.validate_board());
+            Ok(())
+        },
+    )
+    .unwrap();
+    assert_eq!(handed, [0, 1, 2]);
+    assert_eq!((report.solved(), report.workers), (3, 1));
+
+    let stopped = solve_batch_sequential(
+        solver.as_mut(),
+        &puzzles,
+        &SolveLimits::default(),
+        |index, _| if index == 1 { Err(index) } else { Ok(()) },
+    );
+    assert_eq!(stopped, Err(1));
+
+    let names: Vec<String> = comparison_solvers(0)
+        .into_iter()
+        .map(|(name, _)| name)
+        .collect();
+    assert_eq!(names[..3], ["wfc", "dfs", "dlx"]);
+    assert!(names.contains(&"wfc/ac3".to_string()), "{names:?}");
+    assert_eq!(
+        names.len(),
+        3 + CellSelection::ALL.len() + ValueOrdering::ALL.len() + PropagationLevel::ALL.len()
+    );
+}
+
+#[test]
+fn verification_catches_solutions_that_break_the_rules() {
+    let puzzle: Board = FIXTURES[0].parse().unwrap();
+    let mut solution = puzzle.clone();
+    solution.solve_dlx().unwrap();
+    assert_eq!(verify_solution(&puzzle, &solution), Ok(()));
+
+    // A solve that returns early leaves a unit with an empty cell
+    let mut unfinished = solution.clone();
+    unfinished.set_cell(0, 2, 0);
+    assert_eq!(
+        verify_solution(&puzzle, &unfinished),
+        Err(VerificationFailure::BrokenUnit {
+            kind: UnitKind::Row,
+            index: 0
+        })
+    );
+
+    // Swapping two rows of a band keeps every unit whole but moves the givens
+    let text = solution.to_string();
+    let swapped: Board = format!("{}{}{}", &text[9..18], &text[..9], &text[18..])
+        .parse()
+        .unwrap();
+    let failure = verify_solution(&puzzle, &swapped).unwrap_err();
+    assert!(
+        matches!(failure, VerificationFailure::GivenChanged { .. }),
+        "{failure}"
+    );
+
+    let report = bug_report(&failure, "seeds B1-R2-S3", &puzzle, &swapped);
+    let lines: Vec<&str> = report.lines().collect();
+    assert_eq!(lines[0], format!("# Verification failed: {failure}"));
+    assert_eq!(lines[1], "# seeds B1-R2-S3");
+    assert_eq!(lines[2], format!("puzzle    {puzzle}"));
+    assert_eq!(lines[3], format!("solved    {swapped}"));
+    assert_eq!(lines[4], format!("reference {solution}"));
+}
+
+#[test]
+fn verified_batches_count_their_checks() {
+    let puzzles: Vec<Result<((), Board), ()>> = FIXTURES[..3]
+        .iter()
+        .map(|puzzle| Ok(((), puzzle.parse().unwrap())))
+        .collect();
+    for verify in [false, true] {
+        let mut handed = 0;
+        let limits = SolveLimits::default();
+        let new_solver = |_| solver_named("dlx", 0).unwrap();
+        let puzzles = puzzles.clone().into_iter();
+        let report = solve_batch_streaming(puzzles, 2, &limits, new_solver, verify, |result| {
+            assert!(result.unwrap().1.result.is_ok());
+            handed += 1;
+            true
+        });
+        assert_eq!(handed, 3);
+        assert_eq!(report.entries.len(), 3);
+        assert_eq!(report.workers, 2);
+        assert_eq!(report.verified(), if verify { 3 } else { 0 });
+        assert_eq!(report.verification_failures(), 0);
+        for (entry, puzzle) in report.entries.iter().zip(FIXTURES) {
+            if let Some((verified, result)) = &entry.verification {
+                assert_eq!(verified.to_string(), puzzle);
+                assert_eq!(result, &Ok(()));
+            }
+        }
+    }
+
+    let puzzle: Board = FIXTURES[0].parse().unwrap();
+    let failure = VerificationFailure::BrokenUnit {
+        kind: UnitKind::Tile,
+        index: 4,
+    };
+    let report = BatchReport {
+        entries: vec![BatchEntry {
+            seed: 0,
+            board: puzzle.clone(),
+            result: Ok(SolveReport::default()),
+            elapsed: Duration::from_millis(1),
+            verification: Some((puzzle, Err(failure))),
+        }],
+        workers: 1,
+        wall_time: Duration::from_millis(1),
+    };
+    assert_eq!(report.verification_failures(), 1);
+    let table = report.statistics_table();
+    assert!(
+        table.contains("verified     1 solutions, 1 wrong"),
+        "{table}"
+    );
+}
+
+/// Without restarts the first guesses on this puzzle send the solver through 100,000 backtracks
+#[test]
+fn restarts_recover_from_thrashing() {
+    let puzzle: Board =
+        "040760900001000600000090000010000300400000050000001000000079100150006000000015000"
+            .parse()
+            .unwrap();
+    for max_restarts in [0, 3] {
+        let mut board = puzzle.clone();
+        let mut checker = MirrorChecker {
+            mirror: puzzle.clone(),
+            backtracks: 0,
+        };
+        let policy = RestartPolicy {
+            backtrack_threshold: 50,
+            max_restarts,
+        };
+        let report = board
+            .solve_board_with_options(
+                &mut ChaCha8Rng::seed_from_u64(187),
+                &mut checker,
+                &SolveLimits::default(),
+                SolveOptions {
+                    restarts: policy,
+                    ..SolveOptions::default()
+                },
+            )
+            .unwrap();
+        assert!(board.validate_board());
+        assert_eq!(report.restarts, max_restarts.min(1), "{report}");
+        assert!(report.backtracks > 50);
+        let mut replayed = puzzle.clone();
+        report.history.replay_onto(&mut replayed).unwrap();
+        assert_eq!(replayed, board);
+    }
+}
+
+#[test]
+fn every_cell_selection_solves_reproducibly() {
+    for selection in CellSelection::ALL {
+        let options = SolveOptions {
+            cell_selection: selection,
+            ..SolveOptions::default()
+        };
+        for fixture in FIXTURES {
+            let puzzle: Board = fixture.parse().unwrap();
+            let solve = || {
+                let mut board = puzzle.clone();
+                let report = board
+                    .solve_board_with_options(
+                        &mut ChaCha8Rng::seed_from_u64(7),
+                        &mut NoopObserver,
+                        &SolveLimits::default(),
+                        options,
+                    )
+                    .unwrap();
+                (board, report.history)
+            };
+            let (board, history) = solve();
+            assert!(board.validate_board(), "{selection} on {fixture}");
+            assert_eq!(solve().1, history, "{selection} on {fixture}");
+        }
+    }
+}
+
+#[test]
+fn least_constraining_values_solve_every_fixture() {
+    let options = SolveOptions {
+        value_ordering: ValueOrdering::LeastConstraining,
+        ..SolveOptions::default()
+    };
+    for fixture in FIXTURES {
+        let puzzle: Board = fixture.parse().unwrap();
+        let mut board = puzzle.clone();
+        let mut checker = MirrorChecker {
+            mirror: puzzle.clone(),
+            backtracks: 0,
+        };
+        let report = board
+            .solve_board_with_options(
+                &mut ChaCha8Rng::seed_from_u64(0),
+                &mut checker,
+                &SolveLimits::default(),
+                options,
+            )
+            .unwrap();
+        assert!(board.validate_board(), "{fixture}");
+        for board_move in report.history.iter() {
+            assert!(!board_move
+                .remaining_candidates()
+                .contains(&board_move.new_value()));
+        }
+    }
+}
+
+#[test]
+fn stronger_propagation_catches_more_dead_ends() {
+    let puzzle: Board = FIXTURES[1].parse().unwrap();
+    let solve = |propagation| {
+        let mut board = puzzle.clone();
+        let mut checker = MirrorChecker {
+            mirror: puzzle.clone(),
+            backtracks: 0,
+        };
+        // Without restarts, which would hand the search over to the depth first solver
+        let options = SolveOptions {
+            propagation,
+            restarts: RestartPolicy::NEVER,
+            ..SolveOptions::default()
+        };
+        let report = board
+            .solve_board_with_options(
+                &mut ChaCha8Rng::seed_from_u64(0),
+                &mut checker,
+                &SolveLimits::default(),
+                options,
+            )
+            .unwrap();
+        assert!(board.validate_board(), "{propagation}");
+        assert!(report.propagation_failures <= report.backtracks);
+        let mut replayed = puzzle.clone();
+        report.history.replay_onto(&mut replayed).unwrap();
+        assert_eq!(replayed, board, "{propagation}");
+        report
+    };
+
+    let none = solve(PropagationLevel::None);
+    assert_eq!(none.forced_singles, 0);
+    assert_eq!(none.propagation_failures, 0);
+    let forward = solve(PropagationLevel::ForwardCheck);
+    assert!(forward.propagation_failures > 0);
+    let ac3 = solve(PropagationLevel::Ac3);
+    assert!(ac3.backtracks < forward.backtracks);
+}
+
+#[test]
+fn timings_rows_take_their_numbers_from_the_solve_report() {
+    let mut out = Vec::new();
+    let mut timings = TimingsWriter::new(&mut out).unwrap();
+    let puzzle: Board = FIXTURES[0].parse().unwrap();
+    let report = SolveReport {
+        elapsed: Duration::from_micros(1234),
+        placements: 51,
+        backtracks: 2,
+        ..SolveReport::default()
+    };
+    timings
+        .write_row(
+            0,
+            "B1-R2-S3",
+            &puzzle,
+            "wfc",
+            &Ok(report),
+            Duration::from_secs(9),
+        )
+        .unwrap();
+    let exhausted = Err(SolveError::Exhausted { backtracks: 7 });
+    timings
+        .write_row(
+            1,
+            "puzzles.txt:4",
+            &puzzle,
+            "dfs",
+            &exhausted,
+            Duration::from_micros(500),
+        )
+        .unwrap();
+    assert_eq!(
+        String::from_utf8(out).unwrap(),
+        "index,source,clues,solver,result,ms,placements,backtracks\n\
+         0,B1-R2-S3,30,wfc,solved,1.234,51,2\n\
+         1,puzzles.txt:4,30,dfs,exhausted,0.500,,7\n"
+    );
+}
+
+#[test]
+fn histories_read_back_and_replay_to_the_same_solution() {
+    let generated = generate_puzzle(12499731774094038275, 8137985501619016255, 20..=30);
+    let mut solved = generated.puzzle.clone();
+    let history = solved
+        .solve_board(&mut ChaCha8Rng::seed_from_u64(3), &mut NoopObserver)
+        .unwrap()
+        .history;
+    assert!(history
+        .iter()
+        .any(|board_move| !board_move.cascades().is_empty()));
+
+    let text = history.to_string();
+    assert_eq!(text.lines().count(), history.len());
+    let parsed: MoveHistory = text.parse().unwrap();
+    assert_eq!(parsed, history);
+    let mut replayed = generated.puzzle.clone();
+    parsed.replay_onto(&mut replayed).unwrap();
+    assert_eq!(replayed, solved);
+    assert!(replayed.validate_board());
+}
+
+#[test]
+fn histories_that_do_not_fit_the_board_are_refused() {
+    let puzzle: Board = SOLVED.replacen('4', "0", 1).parse().unwrap();
+    let (row, col) = (0, SOLVED.find('4').unwrap());
+    let value = SOLVED.as_bytes()[col] - b'0';
+    let on_given: MoveHistory = "r1c1=9".parse().unwrap();
+    let mut board = puzzle.clone();
+    assert_eq!(
+        on_given.replay_onto(&mut board),
+        Err(ReplayError::CellIsGiven { position: (0, 0) })
+    );
+    assert_eq!(board, puzzle);
+
+    // The first move fills the empty cell, the second one tries another value there
+    let cell = format!("r{}c{}", row + 1, col + 1);
+    let overwriting: MoveHistory = format!("{cell}={value}\n{cell}={}", value % 9 + 1)
+        .parse()
+        .unwrap();
+    assert_eq!(
+        overwriting.replay_onto(&mut board),
+        Err(ReplayError::CellOccupied {
+            position: (row, col),
+            value,
+        })
+    );
+    assert_eq!(board, puzzle);
+
+    let matching: MoveHistory = format!("{cell}={value} r1c1={}", &SOLVED[..1])
+        .parse()
+        .unwrap();
+    matching.replay_onto(&mut board).unwrap();
+    assert_eq!(board.to_string(), SOLVED);
+
+    assert_eq!(
+        "r1c1=4\n\nr1c2=3 r0c1=3".parse::<MoveHistory>(),
+        Err(ParseHistoryError {
+            line: 3,
+            token: "r0c1=3".to_string(),
+        })
+    );
+}
+
+/// The Zobrist hash of a board built from scratch out of the same cells
+fn fresh_hash(board: &Board) -> u64 {
+    board.to_string().parse::<Board>().unwrap().zobrist_hash()
+}
+
+#[test]
+fn zobrist_hashes_tell_thousands_of_boards_apart() {
+    let mut rng = ChaCha8Rng::seed_from_u64(297);
+    let mut boards = HashSet::new();
+    let mut hashes = HashSet::new();
+    for _ in 0..5000 {
+        let cells: [u8; 81] = std::array::from_fn(|_| {
+            if rng.gen_bool(0.3) {
+                rng.gen_range(1..=9)
+            } else {
+                0
+            }
+        });
+        let board = Board::from_cells(cells);
+        let expected = cells.iter().enumerate().fold(0, |hash, (i, &value)| {
+            hash ^ Board::zobrist_key(i / 9, i % 9, value)
+        });
+        assert_eq!(board.zobrist_hash(), expected);
+        boards.insert(board.to_string());
+        hashes.insert(board.zobrist_hash());
+    }
+    assert_eq!(boards.len(), 5000);
+    assert_eq!(hashes.len(), boards.len());
+    assert_eq!(Board::from_cells([0; 81]).zobrist_hash(), 0);
+}
+
+#[test]
+fn undoing_a_move_restores_the_zobrist_hash() {
+    let puzzle: Board = FIXTURES[0].parse().unwrap();
+    let mut board = puzzle.clone();
+    let empty: Vec<_> = board.empty_cells().take(3).collect();
+    let mut board_move = BoardMove::new([empty[0].0, empty[0].1], 1);
+    for &(row, col) in &empty[1..] {
+        board_move.push_cascade(Cascade::new([row, col], 2));
+    }
+    board.apply_move(&board_move);
+    assert_ne!(board.zobrist_hash(), puzzle.zobrist_hash());
+    assert_eq!(board.zobrist_hash(), fresh_hash(&board));
+    board.undo_move(&board_move);
+    assert_eq!(board.zobrist_hash(), puzzle.zobrist_hash());
+
+    let (row, col) = empty[0];
+    board.set_cell(row, col, 4);
+    board.set_cell(row, col, 7);
+    assert_eq!(board.zobrist_hash(), fresh_hash(&board));
+    board.clear_cell(row, col).unwrap();
+    assert_eq!(board.zobrist_hash(), puzzle.zobrist_hash());
+}
+
+/// Checks the incrementally kept hash against one computed from scratch after every step of a
+/// solve, backtracks included
+struct ZobristChecker {
+    backtracks: usize,
+}
+
+impl SolveObserver for ZobristChecker {
+    fn on_place(&mut self, board: &Board, _position: (usize, usize), _value: u8) {
+        assert_eq!(board.zobrist_hash(), fresh_hash(board));
+    }
+
+    fn on_backtrack(&mut self, board: &Board, _undone: &BoardMove) {
+        self.backtracks += 1;
+        assert_eq!(board.zobrist_hash(), fresh_hash(board));
+    }
+}
+
+#[test]
+fn backtracking_keeps_the_zobrist_hash_in_sync() {
+    let solvable = generate_puzzle(3, 4, 17..=24).puzzle;
+    let unsolvable: Board =
+        "530070000600190000008000060890060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    for puzzle in [solvable, unsolvable] {
+        let mut board = puzzle.clone();
+        let mut checker = ZobristChecker { backtracks: 0 };
+        let result = board.solve_board(&mut ChaCha8Rng::seed_from_u64(0), &mut checker);
+        assert!(checker.backtracks > 0);
+        assert_eq!(board.zobrist_hash(), fresh_hash(&board));
+        if result.is_err() {
+            assert_eq!(board.zobrist_hash(), puzzle.zobrist_hash());
+        }
+    }
+}
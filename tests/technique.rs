@@ -0,0 +1,612 @@
+use sudoku_solver::{
+    board::UnitKind,
+    technique::{
+        find_fish, find_hidden_singles, find_hidden_singles_in, find_hidden_subsets,
+        find_locked_candidates, find_naked_subsets, find_next_elimination, find_simple_coloring,
+        find_subsets, find_xy_wing, find_xyz_wing, CandidatesGrid, ExplainedStep, HintAction,
+        LogicalOutcome, TechniqueMove, TechniqueSet, ELIMINATION_TECHNIQUES,
+    },
+    Board, CandidateSet, Difficulty, GradeDistribution,
+};
+
+/// Has no cell with a single candidate left, but 8 only fits in one cell of column 9
+const HIDDEN_SINGLE: &str =
+    "403706020010800004000000007500000080000500200100000645600081000000600002000040006";
+/// r1c4 and r6c4 can only be 1 or 4, which clears both from r3c4
+const NAKED_PAIR: &str =
+    "970008030300900002000000007030506010040830609000000080000253001000610008000704005";
+/// r1c6, r2c6 and r8c6 share 1, 4 and 6 between them, which clears those from r7c6
+const NAKED_TRIPLE: &str =
+    "000030609080000200000527010700008402060009050009075000902000800003000920000002500";
+/// 6 and 8 only fit in r4c8 and r6c8 within tile 6
+const HIDDEN_PAIR: &str =
+    "009074018000001900000093746000900400600708100000005300200100070000000000000082001";
+/// 2, 4 and 8 only fit in r3c3, r8c3 and r9c3 within column 3
+const HIDDEN_TRIPLE: &str =
+    "009000040067009000230000905010006004045000600820070000001900008000000000000804107";
+/// 1 only fits in row 3 of tile 2 because of the row
+const CLAIMING: &str =
+    "000800200040920010700000400000780901007090000201060000600000103000000000010050082";
+/// 5 only fits in columns 8 and 9 within rows 1 and 7
+const X_WING: &str =
+    "302090000000000000700050062208000506000060038000082970000001600050208100094500007";
+/// 9 only fits in columns 1, 2 and 3 within rows 1, 6 and 9
+const SWORDFISH: &str =
+    "007200300010009080000001697000000974050400000000380150200900708000120009060004200";
+/// Pivot r6c8 (1/9) with pincers r2c8 (1/4) and r6c1 (4/9)
+const XY_WING: &str =
+    "090007030000800000063000470300000000709350861080720500630000720000030080900000050";
+/// Pivot r1c8 (3/4/8) with pincers r1c6 (3/8) and r3c8 (4/8)
+const XYZ_WING: &str =
+    "060510000580040007030976500002800010005620000070000600000180000050062190000000020";
+/// Conjugate pairs of 5 chain r5c3, r5c6, r4c1 and r1c1, and r1c6 sees both colors
+const COLOR_TRAP: &str =
+    "003000001200800370100000040001000060400290000062100500050002703300000000809701050";
+/// Conjugate pairs of 4 chain r1c4, r1c6 and r3c6, whose ends share tile 2 and a color
+const COLOR_WRAP: &str =
+    "600030750020007000000800000254000860000502000910000240100083020040000000000000084";
+/// Unique puzzle whose logical solve needs an XY-Wing
+const NEEDS_XY_WING: &str =
+    "008500640003100002000009000000400008059301000420000070900000006000950400041000300";
+/// Has a hidden pair whose eliminations a naked subset already makes
+const OVERLAPPING_SUBSETS: &str =
+    "503002000004900702201846900000500090000000000400200600830000060000060308000000000";
+
+#[test]
+fn hidden_single_without_naked_singles() {
+    let board: Board = HIDDEN_SINGLE.parse().unwrap();
+    let grid = board.candidates_grid();
+    assert!(board
+        .empty_cells()
+        .all(|(row, col)| grid[row][col].unwrap().len() > 1));
+
+    let moves = find_hidden_singles(&board);
+    assert_eq!(moves.len(), 1);
+    let found = &moves[0];
+    assert_eq!(found.position, (0, 8));
+    assert_eq!(found.value, 8);
+    assert_eq!(found.technique, "Hidden single");
+    assert_eq!(found.unit, Some((UnitKind::Column, 8)));
+    assert_eq!(found.reason, "8 can only go in r1c9 within column 9");
+    assert_eq!(found.to_string(), found.reason);
+}
+
+#[test]
+fn added_placement_techniques_come_after_the_known_singles() {
+    let board: Board = HIDDEN_SINGLE.parse().unwrap();
+    let naked_singles = TechniqueSet::up_to("Naked single").unwrap();
+    assert!(naked_singles.contains("Naked single"));
+    assert!(board
+        .clone()
+        .solve_logical(&naked_singles)
+        .moves()
+        .is_empty());
+
+    let techniques =
+        naked_singles.with_placement_technique(("Any hidden single", |grid: &CandidatesGrid| {
+            find_hidden_singles_in(grid)
+                .into_iter()
+                .map(|found| TechniqueMove {
+                    technique: "Any hidden single",
+                    ..found
+                })
+                .collect()
+        }));
+    assert!(techniques.contains("Any hidden single"));
+    assert!(!techniques.contains("Hidden single"));
+
+    let hint = board.next_hint_with(&techniques).unwrap();
+    assert_eq!(hint.technique, "Any hidden single");
+    let HintAction::Place(found) = &hint.action else {
+        panic!("expected a placement: {hint}");
+    };
+    assert_eq!((found.position, found.value), ((0, 8), 8));
+
+    let mut solved = board.clone();
+    let outcome = solved.solve_logical(&techniques);
+    assert_eq!(outcome.moves()[0].technique, "Any hidden single");
+    assert!(solved.conflicts().is_empty());
+    let grade = board.grade_with(&techniques);
+    assert_eq!(grade.hardest_technique, outcome.hardest_technique());
+    assert!(grade
+        .technique_counts
+        .iter()
+        .any(|(name, _)| *name == "Any hidden single"));
+}
+
+#[test]
+fn added_elimination_techniques_come_after_the_known_ones() {
+    let singles = TechniqueSet::singles();
+    let LogicalOutcome::Stuck { board_state, .. } =
+        CLAIMING.parse::<Board>().unwrap().solve_logical(&singles)
+    else {
+        panic!("singles solved the puzzle");
+    };
+    let board = *board_state;
+    let grid = CandidatesGrid::from_board(&board);
+    assert!(singles.find_next_placement(&grid).is_none());
+    assert!(singles.find_next_elimination(&grid).is_none());
+
+    let techniques =
+        singles.with_elimination_technique(("Pointing or claiming", find_locked_candidates));
+    let (name, elimination) = techniques.find_next_elimination(&grid).unwrap();
+    assert_eq!(name, "Pointing or claiming");
+    assert_eq!(elimination, find_locked_candidates(&grid)[0]);
+
+    let hint = board.next_hint_with(&techniques).unwrap();
+    assert_eq!(hint.technique, "Pointing or claiming");
+    assert_eq!(hint.action, HintAction::Eliminate(elimination));
+    let steps = board.explain_solution_with(&techniques).unwrap();
+    assert_eq!(steps[0], ExplainedStep::Logical(hint));
+    assert!(replay(&board, &steps).validate_board());
+}
+
+#[test]
+fn naked_pair_eliminations() {
+    let board: Board = NAKED_PAIR.parse().unwrap();
+    let mut grid = CandidatesGrid::from_board(&board);
+    let eliminations = find_naked_subsets(&grid, 2);
+    assert_eq!(eliminations.len(), 1);
+    let pair = &eliminations[0];
+    assert_eq!(pair.cells, vec![(0, 3), (5, 3)]);
+    assert_eq!(pair.digits, CandidateSet::from_iter([1, 4]));
+    assert_eq!(pair.removed, vec![((2, 3), 1), ((2, 3), 4)]);
+
+    let before = grid.get(2, 3).unwrap();
+    assert_eq!(grid.apply_elimination(pair), 2);
+    assert_eq!(
+        grid.get(2, 3).unwrap(),
+        before.difference(CandidateSet::from_iter([1, 4]))
+    );
+    assert_eq!(grid.apply_elimination(pair), 0);
+    assert!(find_naked_subsets(&grid, 2).is_empty());
+}
+
+#[test]
+fn naked_triple_eliminations() {
+    let board: Board = NAKED_TRIPLE.parse().unwrap();
+    let grid = CandidatesGrid::from_board(&board);
+    assert!(find_naked_subsets(&grid, 2).is_empty());
+    let eliminations = find_naked_subsets(&grid, 3);
+    assert_eq!(eliminations.len(), 1);
+    let triple = &eliminations[0];
+    assert_eq!(triple.cells, vec![(0, 5), (1, 5), (7, 5)]);
+    assert_eq!(triple.digits, CandidateSet::from_iter([1, 4, 6]));
+    assert_eq!(triple.removed, vec![((6, 5), 1), ((6, 5), 4), ((6, 5), 6)]);
+    assert_eq!(
+        triple.to_string(),
+        "r1c6, r2c6, r8c6 only hold 1/4/6 within column 6, so those digits are removed from the \
+         rest of it"
+    );
+}
+
+#[test]
+fn hidden_pair_eliminations() {
+    let board: Board = HIDDEN_PAIR.parse().unwrap();
+    let grid = CandidatesGrid::from_board(&board);
+    let eliminations = find_hidden_subsets(&grid, 2);
+    assert_eq!(eliminations.len(), 1);
+    let pair = &eliminations[0];
+    assert_eq!(pair.cells, vec![(3, 7), (5, 7)]);
+    assert_eq!(pair.digits, CandidateSet::from_iter([6, 8]));
+    assert_eq!(
+        pair.removed,
+        vec![((3, 7), 2), ((3, 7), 5), ((5, 7), 2), ((5, 7), 9)]
+    );
+}
+
+#[test]
+fn hidden_triple_eliminations() {
+    let board: Board = HIDDEN_TRIPLE.parse().unwrap();
+    let grid = CandidatesGrid::from_board(&board);
+    let eliminations = find_hidden_subsets(&grid, 3);
+    assert_eq!(eliminations.len(), 1);
+    let triple = &eliminations[0];
+    assert_eq!(triple.cells, vec![(2, 2), (7, 2), (8, 2)]);
+    assert_eq!(triple.digits, CandidateSet::from_iter([2, 4, 8]));
+    assert_eq!(
+        triple.removed,
+        vec![((7, 2), 3), ((7, 2), 6), ((8, 2), 3), ((8, 2), 6)]
+    );
+    assert_eq!(
+        triple.to_string(),
+        "2/4/8 only fit in r3c3, r8c3, r9c3 within column 3, so every other candidate is removed \
+         from those cells"
+    );
+}
+
+#[test]
+fn subsets_are_not_reported_twice() {
+    let board: Board = OVERLAPPING_SUBSETS.parse().unwrap();
+    let grid = CandidatesGrid::from_board(&board);
+    let naked = find_naked_subsets(&grid, 2);
+    let hidden = find_hidden_subsets(&grid, 2);
+    assert_eq!(hidden.len(), 1);
+    assert!(hidden[0]
+        .removed
+        .iter()
+        .all(|removal| naked.iter().any(|known| known.removed.contains(removal))));
+    assert_eq!(find_subsets(&grid, 2), naked);
+}
+
+#[test]
+fn pointing_eliminations() {
+    let board: Board = HIDDEN_SINGLE.parse().unwrap();
+    let grid = CandidatesGrid::from_board(&board);
+    let eliminations = find_locked_candidates(&grid);
+    assert_eq!(eliminations.len(), 1);
+    let pointing = &eliminations[0];
+    assert_eq!(pointing.cells, vec![(7, 6), (8, 6)]);
+    assert_eq!(pointing.digits, CandidateSet::from_iter([8]));
+    assert_eq!(pointing.removed, vec![((0, 6), 8), ((2, 6), 8)]);
+    assert_eq!(
+        pointing.to_string(),
+        "8 is locked to r8c7, r9c7 within tile 9, so it is removed from the rest of column 7"
+    );
+}
+
+#[test]
+fn claiming_eliminations() {
+    let board: Board = CLAIMING.parse().unwrap();
+    let mut grid = CandidatesGrid::from_board(&board);
+    let eliminations = find_locked_candidates(&grid);
+    assert_eq!(eliminations.len(), 1);
+    let claiming = &eliminations[0];
+    assert_eq!(claiming.cells, vec![(2, 3), (2, 4), (2, 5)]);
+    assert_eq!(claiming.removed, vec![((0, 4), 1), ((0, 5), 1)]);
+    assert_eq!(grid.apply_elimination(claiming), 2);
+    assert!(find_locked_candidates(&grid).is_empty());
+}
+
+#[test]
+fn x_wing_eliminations() {
+    let board: Board = X_WING.parse().unwrap();
+    let grid = CandidatesGrid::from_board(&board);
+    let eliminations = find_fish(&grid, 2);
+    assert_eq!(eliminations.len(), 1);
+    let x_wing = &eliminations[0];
+    assert_eq!(x_wing.cells, vec![(0, 7), (0, 8), (6, 7), (6, 8)]);
+    assert_eq!(x_wing.base, vec![(UnitKind::Row, 0), (UnitKind::Row, 6)]);
+    assert_eq!(
+        x_wing.cover,
+        vec![(UnitKind::Column, 7), (UnitKind::Column, 8)]
+    );
+    assert_eq!(x_wing.removed, vec![((1, 7), 5), ((1, 8), 5)]);
+    assert_eq!(
+        x_wing.to_string(),
+        "within rows 1/7 5 only fits in columns 8/9, so it is removed from the rest of those \
+         columns"
+    );
+}
+
+#[test]
+fn swordfish_eliminations() {
+    let board: Board = SWORDFISH.parse().unwrap();
+    let grid = CandidatesGrid::from_board(&board);
+    assert!(find_fish(&grid, 2).is_empty());
+    let eliminations = find_fish(&grid, 3);
+    assert_eq!(eliminations.len(), 1);
+    let swordfish = &eliminations[0];
+    assert_eq!(
+        swordfish.base,
+        vec![(UnitKind::Row, 0), (UnitKind::Row, 5), (UnitKind::Row, 8)]
+    );
+    assert_eq!(
+        swordfish.cover,
+        vec![
+            (UnitKind::Column, 0),
+            (UnitKind::Column, 1),
+            (UnitKind::Column, 2)
+        ]
+    );
+    assert_eq!(swordfish.removed, vec![((4, 0), 9), ((4, 2), 9)]);
+}
+
+#[test]
+fn xy_wing_eliminations() {
+    let board: Board = XY_WING.parse().unwrap();
+    let grid = CandidatesGrid::from_board(&board);
+    let eliminations = find_xy_wing(&grid);
+    assert_eq!(eliminations.len(), 1);
+    let wing = &eliminations[0];
+    assert_eq!(wing.cells, vec![(5, 7), (1, 7), (5, 0)]);
+    assert_eq!(wing.digits, CandidateSet::from_iter([1, 4, 9]));
+    assert_eq!(wing.removed, vec![((1, 0), 1)]);
+    assert_eq!(
+        wing.to_string(),
+        "pivot r6c8 sees pincers r2c8, r6c1, so 1 is removed from every cell that sees both \
+         pincers"
+    );
+}
+
+#[test]
+fn xyz_wing_eliminations() {
+    let board: Board = XYZ_WING.parse().unwrap();
+    let grid = CandidatesGrid::from_board(&board);
+    let eliminations = find_xyz_wing(&grid);
+    assert_eq!(eliminations.len(), 1);
+    let wing = &eliminations[0];
+    assert_eq!(wing.cells, vec![(0, 7), (0, 5), (2, 7)]);
+    assert_eq!(wing.digits, CandidateSet::from_iter([3, 4, 8]));
+    assert_eq!(wing.removed, vec![((0, 6), 8), ((0, 8), 8)]);
+}
+
+#[test]
+fn cheaper_eliminations_come_first() {
+    let board: Board = X_WING.parse().unwrap();
+    let grid = CandidatesGrid::from_board(&board);
+    let (name, elimination) = find_next_elimination(&grid).unwrap();
+    let (expected, find) = ELIMINATION_TECHNIQUES
+        .iter()
+        .find(|(_, find)| !find(&grid).is_empty())
+        .unwrap();
+    assert_eq!(name, *expected);
+    assert_ne!(name, "X-Wing");
+    assert_eq!(elimination, find(&grid)[0]);
+}
+
+#[test]
+fn simple_coloring_trap() {
+    let board: Board = COLOR_TRAP.parse().unwrap();
+    let grid = CandidatesGrid::from_board(&board);
+    let eliminations = find_simple_coloring(&grid, 5);
+    assert_eq!(eliminations.len(), 1);
+    let coloring = &eliminations[0];
+    assert_eq!(coloring.cells, vec![(4, 2), (4, 5), (3, 0), (0, 0)]);
+    assert_eq!(
+        coloring.base,
+        vec![
+            (UnitKind::Row, 4),
+            (UnitKind::Column, 0),
+            (UnitKind::Tile, 3)
+        ]
+    );
+    assert_eq!(coloring.removed, vec![((0, 5), 5)]);
+    assert_eq!(
+        coloring.to_string(),
+        "5 alternates along r5c3, r5c6, r4c1, r1c1, so it is removed from the cells that see both \
+         colors: r1c6"
+    );
+}
+
+#[test]
+fn simple_coloring_wrap() {
+    let board: Board = COLOR_WRAP.parse().unwrap();
+    let grid = CandidatesGrid::from_board(&board);
+    let eliminations = find_simple_coloring(&grid, 4);
+    assert_eq!(eliminations.len(), 1);
+    let coloring = &eliminations[0];
+    assert_eq!(coloring.cells, vec![(0, 3), (0, 5), (2, 5)]);
+    assert_eq!(coloring.removed, vec![((0, 3), 4), ((2, 5), 4)]);
+    assert_eq!(
+        coloring.to_string(),
+        "4 alternates along r1c4, r1c6, r3c6 and two cells of one color share a unit, so it is \
+         removed from r1c4, r3c6"
+    );
+}
+
+#[test]
+fn logical_solve_with_singles() {
+    let mut board: Board =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    let empty = board.empty_cells().count();
+    let outcome = board.solve_logical(&TechniqueSet::singles());
+    let LogicalOutcome::Solved(moves) = &outcome else {
+        panic!("got stuck: {outcome:?}");
+    };
+    assert_eq!(moves.len(), empty);
+    assert!(moves.iter().all(|found| found.eliminations.is_empty()));
+    assert!(board.validate_board());
+}
+
+#[test]
+fn logical_solve_records_eliminations() {
+    let puzzle: Board = NEEDS_XY_WING.parse().unwrap();
+    let mut board = puzzle.clone();
+    let outcome = board.solve_logical(&TechniqueSet::all());
+    assert!(matches!(outcome, LogicalOutcome::Solved(_)));
+    assert_eq!(outcome.hardest_technique(), Some("XY-Wing"));
+    assert!(outcome
+        .moves()
+        .iter()
+        .flat_map(|found| &found.eliminations)
+        .any(|(name, _)| *name == "XY-Wing"));
+    assert!(board.validate_board());
+
+    let mut board = puzzle.clone();
+    let outcome = board.solve_logical(&TechniqueSet::up_to("Swordfish").unwrap());
+    let LogicalOutcome::Stuck {
+        moves,
+        board_state,
+        remaining_candidates,
+    } = &outcome
+    else {
+        panic!("solved without an XY-Wing");
+    };
+    assert_eq!(**board_state, board);
+    assert_eq!(
+        board.empty_cells().count() + moves.len(),
+        puzzle.empty_cells().count()
+    );
+    assert!(board.empty_cells().all(|(row, col)| remaining_candidates
+        .get(row, col)
+        .unwrap()
+        .len()
+        > 1));
+}
+
+#[test]
+fn logical_solve_gets_stuck_without_progress() {
+    let puzzle: Board =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .unwrap();
+    let mut board = puzzle.clone();
+    let outcome = board.solve_logical(&TechniqueSet::all());
+    assert!(outcome.moves().is_empty());
+    assert_eq!(outcome.hardest_technique(), None);
+    assert!(matches!(outcome, LogicalOutcome::Stuck { .. }));
+    assert_eq!(board, puzzle);
+}
+
+#[test]
+fn grades_follow_the_hardest_technique() {
+    let board: Board =
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079"
+            .parse()
+            .unwrap();
+    let grade = board.grade();
+    assert_eq!(grade.difficulty, Difficulty::Trivial);
+    assert_eq!(grade.hardest_technique, Some("Naked single"));
+    assert_eq!(
+        grade.technique_counts,
+        vec![("Naked single", board.empty_cells().count())]
+    );
+    assert!(!grade.requires_guessing);
+
+    let board: Board = NEEDS_XY_WING.parse().unwrap();
+    let grade = board.grade();
+    assert_eq!(grade.difficulty, Difficulty::Expert);
+    assert_eq!(grade.hardest_technique, Some("XY-Wing"));
+    assert!(grade
+        .technique_counts
+        .iter()
+        .any(|&(name, count)| name == "XY-Wing" && count > 0));
+    assert!(grade.to_string().starts_with("Expert ("));
+
+    let board: Board =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .unwrap();
+    let grade = board.grade();
+    assert_eq!(grade.difficulty, Difficulty::Diabolical);
+    assert!(grade.requires_guessing);
+    assert_eq!(grade.to_string(), "Diabolical, requires guessing");
+}
+
+#[test]
+fn distributions_count_every_grade() {
+    let boards = [
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+        NEEDS_XY_WING,
+        "530070000600195000098000060800060003400803001700020006060000280000419005000080079",
+    ];
+    let distribution: GradeDistribution = boards
+        .iter()
+        .map(|board| board.parse::<Board>().unwrap().grade())
+        .collect();
+    assert_eq!(distribution.count(Difficulty::Trivial), 2);
+    assert_eq!(distribution.count(Difficulty::Expert), 1);
+    assert_eq!(distribution.count(Difficulty::Hard), 0);
+    assert_eq!(distribution.total(), 3);
+
+    let table = distribution.to_string();
+    assert_eq!(table.lines().count(), Difficulty::ALL.len() + 2);
+    assert!(table.contains("Trivial             2   66.7%"), "{table}");
+    assert!(table.contains("Hard                0    0.0%"), "{table}");
+    assert!(table.ends_with("total               3\n"), "{table}");
+}
+
+#[test]
+fn difficulties_are_ordered() {
+    assert!(Difficulty::ALL.windows(2).all(|pair| pair[0] < pair[1]));
+    assert!(Difficulty::of_technique("Hidden pair") > Difficulty::of_technique("Naked pair"));
+    assert!(
+        Difficulty::of_technique("Locked candidates") > Difficulty::of_technique("Hidden single")
+    );
+}
+
+#[test]
+fn hints_explain_the_simplest_move() {
+    let board: Board = HIDDEN_SINGLE.parse().unwrap();
+    let hint = board.next_hint().unwrap();
+    assert_eq!(hint.technique, "Hidden single");
+    assert_eq!(
+        hint.to_string(),
+        "Hidden single: 8 can only go in r1c9 within column 9"
+    );
+    let HintAction::Place(found) = &hint.action else {
+        panic!("expected a placement, got {hint:?}");
+    };
+    assert_eq!((found.position, found.value), ((0, 8), 8));
+
+    let board: Board = NAKED_PAIR.parse().unwrap();
+    let hint = board.next_hint().unwrap();
+    assert_eq!(hint.to_string(), "Naked single: r4c9 can only be 4");
+
+    // Singles run dry here, leaving an XY-Wing as the simplest move
+    let mut board: Board = NEEDS_XY_WING.parse().unwrap();
+    let LogicalOutcome::Stuck { board_state, .. } = board.solve_logical(&TechniqueSet::singles())
+    else {
+        panic!("solved with singles alone");
+    };
+    let hint = board_state.next_hint().unwrap();
+    assert_eq!(
+        hint.to_string(),
+        "XY-Wing: pivot r3c4 sees pincers r1c6, r3c2, so 7 is removed from every cell that sees \
+         both pincers"
+    );
+    assert!(matches!(hint.action, HintAction::Eliminate(_)));
+
+    let mut solved = board.clone();
+    solved.solve_dlx().unwrap();
+    assert_eq!(solved.next_hint(), None);
+}
+
+/// Fills in a copy of the board from the placements of a walkthrough
+fn replay(board: &Board, steps: &[ExplainedStep]) -> Board {
+    let mut replayed = board.clone();
+    for step in steps {
+        match step {
+            ExplainedStep::Logical(hint) => {
+                if let HintAction::Place(found) = &hint.action {
+                    replayed.set_cell(found.position.0, found.position.1, found.value);
+                }
+            }
+            ExplainedStep::TrialAndError { position, value } => {
+                replayed.set_cell(position.0, position.1, *value)
+            }
+        }
+    }
+    replayed
+}
+
+#[test]
+fn explanations_cover_the_whole_solve() {
+    let puzzle: Board = NEEDS_XY_WING.parse().unwrap();
+    let steps = puzzle.explain_solution().unwrap();
+    assert!(steps
+        .iter()
+        .all(|step| matches!(step, ExplainedStep::Logical(_))));
+    assert!(steps
+        .iter()
+        .any(|step| step.to_string().starts_with("XY-Wing: ")));
+    assert_eq!(puzzle.grade().hardest_technique, Some("XY-Wing"));
+    assert!(replay(&puzzle, &steps).validate_board());
+}
+
+#[test]
+fn explanations_fall_back_to_trial_and_error() {
+    let puzzle: Board =
+        "800000000003600000070090200050007000000045700000100030001000068008500010090000400"
+            .parse()
+            .unwrap();
+    let steps = puzzle.explain_solution().unwrap();
+    let ExplainedStep::TrialAndError { position, value } = steps[0] else {
+        panic!("a technique applied: {}", steps[0]);
+    };
+    assert_eq!(
+        steps[0].to_string(),
+        format!(
+            "Trial and error: no technique applies, so r{}c{} is set to {value}",
+            position.0 + 1,
+            position.1 + 1
+        )
+    );
+
+    let mut solution = puzzle.clone();
+    solution.solve_dlx().unwrap();
+    assert_eq!(replay(&puzzle, &steps), solution);
+}